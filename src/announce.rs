@@ -0,0 +1,501 @@
+//! Release announcements in the chat channels and on the social accounts
+//! set under `[announce]`
+
+use crate::config::{AnnounceConfig, NetworkConfig};
+use crate::error::AnnounceError;
+use crate::forge;
+use crate::http;
+use crate::state;
+use regex::Regex;
+use reqwest::blocking::{Client, Response};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines of the CHANGELOG section an announcement quotes; the rest is on
+/// the record
+const SUMMARY_LINES: usize = 15;
+
+/// Longest toot a Mastodon instance accepts by default
+const MASTODON_LIMIT: usize = 500;
+/// Longest Bluesky post
+const BLUESKY_LIMIT: usize = 300;
+
+/// What an announcement says about a published release
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub title: String,
+    pub version: String,
+    pub doi: String,
+    /// The start of the version's CHANGELOG.md section
+    pub summary: Option<String>,
+}
+
+impl Announcement {
+    /// An announcement of `version`, summarised from CHANGELOG.md
+    pub fn new(project_dir: &Path, title: &str, version: &str, doi: &str) -> Announcement {
+        let summary = forge::changelog_section(project_dir, version).map(|section| {
+            let lines: Vec<&str> = section.lines().collect();
+            if lines.len() > SUMMARY_LINES {
+                format!("{}\n…", lines[..SUMMARY_LINES].join("\n").trim_end())
+            } else {
+                section
+            }
+        });
+        Announcement {
+            title: title.to_string(),
+            version: version.to_string(),
+            doi: doi.to_string(),
+            summary,
+        }
+    }
+
+    /// The DOI as a resolver link
+    pub fn doi_url(&self) -> String {
+        format!("https://doi.org/{}", self.doi)
+    }
+
+    /// `template` with {title}, {version}, {doi} and {doi_url} filled in
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{title}", &self.title)
+            .replace("{version}", &self.version)
+            .replace("{doi_url}", &self.doi_url())
+            .replace("{doi}", &self.doi)
+    }
+
+    /// Markdown, as Mattermost and Matrix clients read it
+    pub fn markdown(&self) -> String {
+        let mut text = format!(
+            "**{} {}** is released\nDOI: [{}]({})",
+            self.title,
+            self.version,
+            self.doi,
+            self.doi_url()
+        );
+        if let Some(summary) = &self.summary {
+            text.push_str("\n\n");
+            text.push_str(summary);
+        }
+        text
+    }
+
+    /// Slack's own markup ("mrkdwn"): single-star bold, `<url|text>` links
+    fn slack(&self) -> String {
+        let mut text = format!(
+            "*{} {}* is released\nDOI: <{}|{}>",
+            escape_slack(&self.title),
+            escape_slack(&self.version),
+            self.doi_url(),
+            self.doi
+        );
+        if let Some(summary) = &self.summary {
+            text.push_str("\n\n");
+            text.push_str(&to_mrkdwn(summary));
+        }
+        text
+    }
+
+    /// The HTML body of a Matrix message
+    fn html(&self) -> String {
+        let mut html = format!(
+            "<p><strong>{} {}</strong> is released<br>DOI: <a href=\"{}\">{}</a></p>",
+            escape_html(&self.title),
+            escape_html(&self.version),
+            self.doi_url(),
+            escape_html(&self.doi)
+        );
+        if let Some(summary) = &self.summary {
+            html.push_str(&format!(
+                "<p>{}</p>",
+                escape_html(summary).replace('\n', "<br>")
+            ));
+        }
+        html
+    }
+}
+
+/// Fill in the `{name}` placeholders of an announcement template from
+/// `values`. Braces around anything but a name are left alone; a name not
+/// in `values` is an error.
+pub fn render_template(template: &str, values: &[(&str, String)]) -> Result<String, AnnounceError> {
+    let placeholder = Regex::new(r"\{([a-z_]+)\}").unwrap();
+    let mut unknown = None;
+    let text = placeholder.replace_all(template, |caps: &regex::Captures| {
+        match values.iter().find(|(name, _)| *name == &caps[1]) {
+            Some((_, value)) => value.clone(),
+            None => {
+                unknown.get_or_insert_with(|| caps[1].to_string());
+                caps[0].to_string()
+            }
+        }
+    });
+    if let Some(name) = unknown {
+        let known: Vec<String> = values
+            .iter()
+            .map(|(name, _)| format!("{{{}}}", name))
+            .collect();
+        return Err(AnnounceError::Placeholder {
+            name,
+            known: known.join(", "),
+        });
+    }
+    Ok(text.into_owned())
+}
+
+/// Post `announcement` to every channel and social account `config` sets
+/// up; returns each one's name and result
+pub fn post_all(
+    config: &AnnounceConfig,
+    network: &NetworkConfig,
+    announcement: &Announcement,
+) -> Vec<(&'static str, Result<(), AnnounceError>)> {
+    if !config.enabled() {
+        return Vec::new();
+    }
+    let client = match http::client(network, None) {
+        Ok(client) => client,
+        Err(e) => return vec![("chat", Err(e.into()))],
+    };
+    let mut results = Vec::new();
+    if config.posts_to("slack") {
+        let result = match config.token("slack") {
+            Some(webhook) => {
+                post_webhook(&client, network, "Slack", &webhook, &announcement.slack())
+            }
+            None => Err(no_token("Slack", "slack")),
+        };
+        results.push(("Slack", result));
+    }
+    if config.posts_to("mattermost") {
+        let result = match config.token("mattermost") {
+            Some(webhook) => post_webhook(
+                &client,
+                network,
+                "Mattermost",
+                &webhook,
+                &announcement.markdown(),
+            ),
+            None => Err(no_token("Mattermost", "mattermost")),
+        };
+        results.push(("Mattermost", result));
+    }
+    if let (Some(homeserver), Some(room)) = (&config.matrix_homeserver, &config.matrix_room) {
+        let result = match config.token("matrix") {
+            Some(token) => post_matrix(&client, network, homeserver, room, &token, announcement),
+            None => Err(no_token("Matrix", "matrix")),
+        };
+        results.push(("Matrix", result));
+    }
+
+    let text = announcement.render(config.social_template());
+    if let Some(instance) = &config.mastodon_instance {
+        let result = match config.token("mastodon") {
+            Some(token) => post_mastodon(&client, network, instance, &token, &text, announcement),
+            None => Err(no_token("Mastodon", "mastodon")),
+        };
+        results.push(("Mastodon", result));
+    }
+    if let Some(handle) = &config.bluesky_handle {
+        let result = match config.token("bluesky") {
+            Some(password) => post_bluesky(
+                &client,
+                network,
+                config.bluesky_service(),
+                handle,
+                &password,
+                &text,
+            ),
+            None => Err(no_token("Bluesky", "bluesky")),
+        };
+        results.push(("Bluesky", result));
+    }
+    results
+}
+
+fn no_token(service: &'static str, account: &'static str) -> AnnounceError {
+    AnnounceError::NoToken {
+        service,
+        account,
+        env_var: format!(
+            "RELEASE_SCHOLAR_ANNOUNCE_{}",
+            AnnounceConfig::secret_key(account).to_uppercase()
+        ),
+    }
+}
+
+/// Fails if `text` is over `limit` characters
+fn within_limit(service: &'static str, text: &str, limit: usize) -> Result<(), AnnounceError> {
+    let length = text.chars().count();
+    if length > limit {
+        return Err(AnnounceError::TooLong {
+            service,
+            length,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Slack and Mattermost incoming webhooks both take `{"text": ...}`. The
+/// webhook URL is itself the credential, so errors leave it out.
+fn post_webhook(
+    client: &Client,
+    network: &NetworkConfig,
+    service: &'static str,
+    webhook: &str,
+    text: &str,
+) -> Result<(), AnnounceError> {
+    http::secret_url(webhook);
+    let body = json!({ "text": text });
+    let resp =
+        http::send_with_retry(network, || client.post(webhook).json(&body)).map_err(|source| {
+            AnnounceError::Http {
+                service,
+                source: source.without_url(),
+            }
+        })?;
+    check(service, resp).map(|_| ())
+}
+
+#[derive(Deserialize)]
+struct RoomAlias {
+    room_id: String,
+}
+
+/// Send an `m.room.message` to `room`, resolving it first if it is an alias
+fn post_matrix(
+    client: &Client,
+    network: &NetworkConfig,
+    homeserver: &str,
+    room: &str,
+    token: &str,
+    announcement: &Announcement,
+) -> Result<(), AnnounceError> {
+    const SERVICE: &str = "Matrix";
+    let auth = format!("Bearer {}", token);
+    let room_id = if room.starts_with('#') {
+        let url = matrix_url(homeserver, &["directory", "room", room])?;
+        let resp = http::send_with_retry(network, || {
+            client.get(url.clone()).header("Authorization", &auth)
+        })
+        .map_err(|source| AnnounceError::Http {
+            service: SERVICE,
+            source: source.without_url(),
+        })?;
+        check(SERVICE, resp)?
+            .json::<RoomAlias>()
+            .map_err(|source| AnnounceError::Parse {
+                what: format!("Matrix room alias {}", room),
+                source: source.without_url(),
+            })?
+            .room_id
+    } else {
+        room.to_string()
+    };
+
+    // The transaction ID makes a retried request idempotent
+    let txn = format!(
+        "release-scholar-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos())
+    );
+    let url = matrix_url(
+        homeserver,
+        &["rooms", &room_id, "send", "m.room.message", &txn],
+    )?;
+    let body = json!({
+        "msgtype": "m.text",
+        "body": announcement.markdown(),
+        "format": "org.matrix.custom.html",
+        "formatted_body": announcement.html(),
+    });
+    let resp = http::send_with_retry(network, || {
+        client
+            .put(url.clone())
+            .header("Authorization", &auth)
+            .json(&body)
+    })
+    .map_err(|source| AnnounceError::Http {
+        service: SERVICE,
+        source: source.without_url(),
+    })?;
+    check(SERVICE, resp).map(|_| ())
+}
+
+/// Toot `text` as the token's account
+fn post_mastodon(
+    client: &Client,
+    network: &NetworkConfig,
+    instance: &str,
+    token: &str,
+    text: &str,
+    announcement: &Announcement,
+) -> Result<(), AnnounceError> {
+    const SERVICE: &str = "Mastodon";
+    within_limit(SERVICE, text, MASTODON_LIMIT)?;
+    let url = format!("{}/api/v1/statuses", instance.trim_end_matches('/'));
+    let resp = http::send_with_retry(network, || {
+        client
+            .post(&url)
+            .bearer_auth(token)
+            // A retried request does not toot twice
+            .header(
+                "Idempotency-Key",
+                format!("release-scholar-{}", announcement.doi),
+            )
+            .form(&[("status", text), ("visibility", "public")])
+    })
+    .map_err(|source| AnnounceError::Http {
+        service: SERVICE,
+        source: source.without_url(),
+    })?;
+    check(SERVICE, resp).map(|_| ())
+}
+
+/// A logged-in Bluesky account
+#[derive(Debug, Deserialize)]
+pub struct BlueskySession {
+    #[serde(rename = "accessJwt")]
+    access_jwt: String,
+    pub did: String,
+    pub handle: String,
+}
+
+/// Log in to `service` as `handle` with an app password
+pub fn bluesky_session(
+    client: &Client,
+    network: &NetworkConfig,
+    service: &str,
+    handle: &str,
+    password: &str,
+) -> Result<BlueskySession, AnnounceError> {
+    let url = format!("{}/xrpc/com.atproto.server.createSession", service);
+    let body = json!({ "identifier": handle.trim_start_matches('@'), "password": password });
+    let resp =
+        http::send_with_retry(network, || client.post(&url).json(&body)).map_err(|source| {
+            AnnounceError::Http {
+                service: "Bluesky",
+                source: source.without_url(),
+            }
+        })?;
+    check("Bluesky", resp)?
+        .json()
+        .map_err(|source| AnnounceError::Parse {
+            what: "Bluesky session".to_string(),
+            source: source.without_url(),
+        })
+}
+
+/// Post `text` as `handle`. Bluesky does not turn URLs into links by
+/// itself, so each one gets a link facet (by its UTF-8 byte range).
+fn post_bluesky(
+    client: &Client,
+    network: &NetworkConfig,
+    service: &str,
+    handle: &str,
+    password: &str,
+    text: &str,
+) -> Result<(), AnnounceError> {
+    const SERVICE: &str = "Bluesky";
+    within_limit(SERVICE, text, BLUESKY_LIMIT)?;
+    let session = bluesky_session(client, network, service, handle, password)?;
+    let url_pattern = Regex::new(r"https?://[^\s)\]>]+").unwrap();
+    let facets: Vec<_> = url_pattern
+        .find_iter(text)
+        .map(|url| {
+            let uri = url
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            json!({
+                "index": { "byteStart": url.start(), "byteEnd": url.start() + uri.len() },
+                "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": uri }],
+            })
+        })
+        .collect();
+    let body = json!({
+        "repo": session.did,
+        "collection": "app.bsky.feed.post",
+        "record": {
+            "$type": "app.bsky.feed.post",
+            "text": text,
+            "facets": facets,
+            "createdAt": state::now_utc(),
+        },
+    });
+    let url = format!("{}/xrpc/com.atproto.repo.createRecord", service);
+    let resp = http::send_with_retry(network, || {
+        client
+            .post(&url)
+            .bearer_auth(&session.access_jwt)
+            .json(&body)
+    })
+    .map_err(|source| AnnounceError::Http {
+        service: SERVICE,
+        source: source.without_url(),
+    })?;
+    check(SERVICE, resp).map(|_| ())
+}
+
+/// `{homeserver}/_matrix/client/v3/{segments...}`, each segment escaped
+/// (room IDs and aliases hold `!`, `#` and `:`)
+pub fn matrix_url(homeserver: &str, segments: &[&str]) -> Result<Url, AnnounceError> {
+    let invalid = || AnnounceError::Url {
+        what: "Matrix homeserver",
+        url: homeserver.to_string(),
+    };
+    let mut url = Url::parse(homeserver).map_err(|_| invalid())?;
+    url.path_segments_mut()
+        .map_err(|_| invalid())?
+        .pop_if_empty()
+        .extend(["_matrix", "client", "v3"])
+        .extend(segments);
+    Ok(url)
+}
+
+fn check(service: &'static str, resp: Response) -> Result<Response, AnnounceError> {
+    let status = resp.status();
+    if status.is_success() {
+        Ok(resp)
+    } else {
+        Err(AnnounceError::Api {
+            service,
+            status,
+            body: resp.text().unwrap_or_default(),
+        })
+    }
+}
+
+/// CHANGELOG Markdown in Slack's markup: headings and `**bold**` become
+/// `*bold*`, `[text](url)` becomes `<url|text>`
+fn to_mrkdwn(markdown: &str) -> String {
+    let link = Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap();
+    let bold = Regex::new(r"\*\*([^*]+)\*\*").unwrap();
+    markdown
+        .lines()
+        .map(|line| {
+            let line = escape_slack(line);
+            let line = match line.trim_start().strip_prefix('#') {
+                Some(heading) => format!("*{}*", heading.trim_start_matches('#').trim()),
+                None => line,
+            };
+            let line = bold.replace_all(&line, "*$1*");
+            link.replace_all(&line, "<$2|$1>").into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Slack reads `&`, `<` and `>` as markup
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_html(text: &str) -> String {
+    escape_slack(text).replace('"', "&quot;")
+}
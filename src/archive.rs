@@ -1,2 +1,6 @@
+/// SHA-256 checksums of the bundle files (`checksums.txt`)
 pub mod checksum;
+pub mod contents;
+pub mod provenance;
+/// The deterministic `.tar.gz` source archive
 pub mod tarball;
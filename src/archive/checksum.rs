@@ -1,10 +1,13 @@
+use crate::error::IoError;
 use sha2::{Digest, Sha256};
+use std::fs::File;
 use std::path::Path;
 
-pub fn sha256_file(path: &Path) -> Result<String, String> {
-    let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+/// Hashes in chunks, so a multi-GB file takes no more memory than a small one
+pub fn sha256_file(path: &Path) -> Result<String, IoError> {
+    let mut file = File::open(path).map_err(IoError::on("open", path))?;
     let mut hasher = Sha256::new();
-    hasher.update(&data);
+    std::io::copy(&mut file, &mut hasher).map_err(IoError::on("read", path))?;
     let result = hasher.finalize();
     Ok(format!("{:x}", result))
 }
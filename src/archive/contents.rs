@@ -0,0 +1,110 @@
+//! The files in a release, read from a built archive or straight from a tag,
+//! and what changed between two of them
+
+use crate::archive::tarball::collect_tree_entries;
+use crate::error::{Error, GitError, IoError};
+use flate2::read::GzDecoder;
+use git2::Repository;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A file in an archive: its size and SHA-256
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Files by path, relative to the archive's top-level directory
+pub type Contents = BTreeMap<String, FileEntry>;
+
+/// How a file differs between two archives
+#[derive(Debug)]
+pub enum Change<'a> {
+    Added(&'a str, &'a FileEntry),
+    Removed(&'a str, &'a FileEntry),
+    Modified {
+        path: &'a str,
+        old: &'a FileEntry,
+        new: &'a FileEntry,
+    },
+}
+
+/// The regular files in a .tar.gz, without its top-level directory
+pub fn from_archive(path: &Path) -> Result<Contents, IoError> {
+    let file = std::fs::File::open(path).map_err(IoError::on("open", path))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut contents = Contents::new();
+    for entry in archive.entries().map_err(IoError::on("read", path))? {
+        let mut entry = entry.map_err(IoError::on("read", path))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map_err(IoError::on("read", path))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let name = name.split_once('/').map_or(name.as_str(), |(_, rest)| rest);
+        // Hashed as it is decompressed, rather than read into memory whole
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut entry, &mut hasher).map_err(IoError::on("read", path))?;
+        let file = FileEntry {
+            size,
+            sha256: format!("{:x}", hasher.finalize()),
+        };
+        contents.insert(name.to_string(), file);
+    }
+    Ok(contents)
+}
+
+/// The files the archive for `tag` would hold
+pub fn from_tag(project_dir: &Path, tag: &str) -> Result<Contents, Error> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    let tree = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .and_then(|obj| obj.peel_to_tree())
+        .map_err(GitError::on(format!("find tag {}", tag)))?;
+    let mut entries = Vec::new();
+    collect_tree_entries(&repo, &tree, "", &mut entries)?;
+    tracing::debug!("read {} file(s) from the tree at {}", entries.len(), tag);
+    let mut contents = Contents::new();
+    for entry in entries {
+        let blob = repo
+            .find_blob(entry.oid)
+            .map_err(GitError::on(format!("read blob {}", entry.path)))?;
+        let data = blob.content();
+        let file = FileEntry {
+            size: data.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(data)),
+        };
+        contents.insert(entry.path, file);
+    }
+    Ok(contents)
+}
+
+/// Added, removed and modified files, in path order
+pub fn compare<'a>(old: &'a Contents, new: &'a Contents) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    for (path, entry) in old {
+        match new.get(path) {
+            None => changes.push(Change::Removed(path, entry)),
+            Some(other) if other.sha256 != entry.sha256 => changes.push(Change::Modified {
+                path,
+                old: entry,
+                new: other,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (path, entry) in new {
+        if !old.contains_key(path) {
+            changes.push(Change::Added(path, entry));
+        }
+    }
+    changes.sort_by_key(|change| match change {
+        Change::Added(path, _) | Change::Removed(path, _) | Change::Modified { path, .. } => *path,
+    });
+    changes
+}
@@ -0,0 +1,146 @@
+//! SLSA provenance for a build: an in-toto statement tying the bundle's
+//! files to the tagged commit they were built from, so a downloaded archive
+//! can be traced back to its source
+
+use crate::archive::checksum;
+use crate::config::{ProvenanceConfig, SignWith};
+use crate::error::{Error, GitError, SignError, ValidationError};
+use crate::forge;
+use git2::Repository;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The attestation's name in the bundle
+pub const FILE_NAME: &str = "provenance.intoto.json";
+
+/// Identifies what the external parameters mean
+const BUILD_TYPE: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "/provenance/build/v1");
+
+/// The statement for `files`, built from `tag` between `started_on` and now
+pub fn statement(
+    project_dir: &Path,
+    tag: &str,
+    started_on: &str,
+    files: &[PathBuf],
+) -> Result<String, Error> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    let commit = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(GitError::on(format!("find tag {}", tag)))?;
+
+    let mut subjects = Vec::new();
+    for file in files {
+        subjects.push(json!({
+            "name": file.file_name().unwrap_or_default().to_string_lossy(),
+            "digest": { "sha256": checksum::sha256_file(file)? },
+        }));
+    }
+
+    let mut source = Map::new();
+    if let Some(remote) = forge::origin(project_dir) {
+        source.insert(
+            "uri".into(),
+            format!(
+                "git+https://{}/{}/{}@refs/tags/{}",
+                remote.host, remote.owner, remote.repo, tag
+            )
+            .into(),
+        );
+    }
+    source.insert(
+        "digest".into(),
+        json!({ "gitCommit": commit.id().to_string() }),
+    );
+
+    let mut metadata = Map::new();
+    if let Some(run) = ci_run() {
+        metadata.insert("invocationId".into(), run.into());
+    }
+    metadata.insert("startedOn".into(), started_on.into());
+    metadata.insert("finishedOn".into(), crate::state::now_utc().into());
+
+    let statement = json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": subjects,
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "source": Value::Object(source.clone()),
+                    "tag": tag,
+                },
+                "resolvedDependencies": [Value::Object(source)],
+            },
+            "runDetails": {
+                "builder": {
+                    "id": concat!(env!("CARGO_PKG_REPOSITORY"), "@v", env!("CARGO_PKG_VERSION")),
+                    "version": { "release-scholar": env!("CARGO_PKG_VERSION") },
+                },
+                "metadata": Value::Object(metadata),
+            },
+        },
+    });
+    serde_json::to_string_pretty(&statement)
+        .map(|s| s + "\n")
+        .map_err(|e| ValidationError::Serialize(e).into())
+}
+
+/// Sign the document at `path` as configured; returns the signature file
+pub fn sign(path: &Path, config: &ProvenanceConfig) -> Result<Option<PathBuf>, SignError> {
+    let (tool, mut command, signature) = match config.sign {
+        SignWith::None => return Ok(None),
+        SignWith::Gpg => {
+            let signature = path.with_extension("json.asc");
+            let mut command = Command::new("gpg");
+            command.args(["--batch", "--yes", "--armor", "--detach-sign"]);
+            if let Some(key) = &config.key {
+                command.args(["--local-user", key]);
+            }
+            command.arg("--output").arg(&signature).arg(path);
+            ("gpg", command, signature)
+        }
+        SignWith::Ssh => {
+            let key = config.key.as_deref().ok_or(SignError::NoSshKey)?;
+            // No shell to expand `~/.ssh/...`
+            let key = match (key.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(key),
+            };
+            let mut command = Command::new("ssh-keygen");
+            // The namespace keeps the signature from being valid for anything else
+            command
+                .args(["-Y", "sign", "-n", "file", "-f"])
+                .arg(&key)
+                .arg(path);
+            ("ssh-keygen", command, path.with_extension("json.sig"))
+        }
+    };
+    let output = command
+        .output()
+        .map_err(|source| SignError::Spawn { tool, source })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SignError::Failed {
+            tool,
+            message: stderr.trim().lines().last().unwrap_or("failed").to_string(),
+        });
+    }
+    Ok(Some(signature))
+}
+
+/// The CI run doing the build, when there is one
+fn ci_run() -> Option<String> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    if let (Some(server), Some(repo), Some(run)) = (
+        var("GITHUB_SERVER_URL"),
+        var("GITHUB_REPOSITORY"),
+        var("GITHUB_RUN_ID"),
+    ) {
+        return Some(format!("{}/{}/actions/runs/{}", server, repo, run));
+    }
+    // GitLab, then Woodpecker (as on Codeberg)
+    var("CI_JOB_URL").or_else(|| var("CI_PIPELINE_URL"))
+}
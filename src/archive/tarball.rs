@@ -1,50 +1,90 @@
+use crate::config::DataConfig;
+use crate::error::{Error, GitError, IoError, ValidationError};
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use git2::Repository;
-use std::path::Path;
+use git2::{Oid, Repository};
+use std::path::{Path, PathBuf};
 use tar::Header;
+use walkdir::WalkDir;
 
-pub fn create_archive(project_dir: &Path, tag: &str, output_path: &Path) -> Result<(), String> {
-    let repo = Repository::open(project_dir).map_err(|e| format!("Cannot open repo: {}", e))?;
+/// Ends the name of the data archive, which is the code archive's with this
+/// in place of `.tar.gz`
+pub const DATA_ARCHIVE_SUFFIX: &str = "-data.tar.gz";
+
+/// The data archive's name, given the code archive's
+pub fn data_archive_name(archive_name: &str) -> String {
+    format!(
+        "{}{}",
+        archive_name.trim_end_matches(".tar.gz"),
+        DATA_ARCHIVE_SUFFIX
+    )
+}
+
+/// Whether `name` is a code archive, not a data archive
+pub fn is_code_archive(name: &str) -> bool {
+    name.ends_with(".tar.gz") && !name.ends_with(DATA_ARCHIVE_SUFFIX)
+}
+
+/// Write the tree at `tag` to `output_path` as a reproducible .tar.gz. The
+/// files sit in a top-level directory named like the archive, minus `.tar.gz`.
+/// Tracked files under the paths of `data`, which has an archive of its own,
+/// are left out.
+pub fn create_archive(
+    project_dir: &Path,
+    tag: &str,
+    output_path: &Path,
+    data: Option<&DataConfig>,
+) -> Result<(), Error> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
 
     // Resolve tag to tree
     let obj = repo
         .revparse_single(&format!("refs/tags/{}", tag))
-        .map_err(|e| format!("Cannot find tag {}: {}", tag, e))?;
+        .map_err(GitError::on(format!("find tag {}", tag)))?;
     let commit = obj
         .peel_to_commit()
-        .map_err(|e| format!("Cannot peel to commit: {}", e))?;
-    let tree = commit
-        .tree()
-        .map_err(|e| format!("Cannot get tree: {}", e))?;
+        .map_err(GitError::on("peel to commit"))?;
+    let tree = commit.tree().map_err(GitError::on("get tree"))?;
 
-    let file =
-        std::fs::File::create(output_path).map_err(|e| format!("Cannot create archive: {}", e))?;
+    let file = std::fs::File::create(output_path).map_err(IoError::on("create", output_path))?;
     let enc = GzEncoder::new(file, Compression::default());
     let mut ar = tar::Builder::new(enc);
 
-    let prefix = format!(
-        "{}-{}",
-        project_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy(),
-        tag
-    );
+    let file_name = output_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let prefix = file_name.trim_end_matches(".tar.gz");
 
-    // Collect all blobs sorted by path for determinism
-    let mut entries: Vec<(String, Vec<u8>, u32)> = Vec::new();
+    // List the files sorted by path for determinism; each blob is read only
+    // when its turn comes, so at most one file is held in memory
+    let mut entries: Vec<TreeFile> = Vec::new();
     collect_tree_entries(&repo, &tree, "", &mut entries)?;
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    if let Some(data) = data {
+        entries.retain(|entry| !data.contains(&entry.path));
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    tracing::debug!(
+        "archiving {} file(s) from {} ({})",
+        entries.len(),
+        tag,
+        commit.id()
+    );
 
     let fixed_mtime = commit.time().seconds() as u64;
 
-    for (path, data, mode) in &entries {
+    for entry in &entries {
+        let path = &entry.path;
+        let blob = repo
+            .find_blob(entry.oid)
+            .map_err(GitError::on(format!("read blob {}", path)))?;
+        let data = blob.content();
+
         let mut header = Header::new_gnu();
         header.set_size(data.len() as u64);
         header.set_mtime(fixed_mtime);
         // Map git mode to tar mode
-        let tar_mode = if *mode == 0o100755 { 0o755 } else { 0o644 };
+        let tar_mode = if entry.mode == 0o100755 { 0o755 } else { 0o644 };
         header.set_mode(tar_mode);
         header.set_uid(0);
         header.set_gid(0);
@@ -53,25 +93,133 @@ pub fn create_archive(project_dir: &Path, tag: &str, output_path: &Path) -> Resu
         header.set_cksum();
 
         let full_path = format!("{}/{}", prefix, path);
-        ar.append_data(&mut header, &full_path, data.as_slice())
-            .map_err(|e| format!("Cannot add {}: {}", path, e))?;
+        ar.append_data(&mut header, &full_path, data)
+            .map_err(IoError::on("add", path))?;
     }
 
     let enc = ar
         .into_inner()
-        .map_err(|e| format!("Cannot finalize tar: {}", e))?;
-    enc.finish()
-        .map_err(|e| format!("Cannot finalize gzip: {}", e))?;
+        .map_err(IoError::on("finalize", output_path))?;
+    enc.finish().map_err(IoError::on("finalize", output_path))?;
 
     Ok(())
 }
 
-fn collect_tree_entries(
+/// Write the files under `paths` (relative to `project_dir`), as they are on
+/// disk, to `output_path` as a .tar.gz laid out like `create_archive`'s. The
+/// files need not be tracked; they get the `tag` commit's time, so unchanged
+/// data gives the same archive. Returns how many files it holds.
+pub fn create_data_archive(
+    project_dir: &Path,
+    tag: &str,
+    paths: &[String],
+    output_path: &Path,
+) -> Result<usize, Error> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    let commit = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .map_err(GitError::on(format!("find tag {}", tag)))?
+        .peel_to_commit()
+        .map_err(GitError::on("peel to commit"))?;
+    let fixed_mtime = commit.time().seconds() as u64;
+
+    // Paths may overlap; symlinks are left out rather than followed
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for data_path in paths {
+        let full_path = project_dir.join(data_path);
+        if !full_path.exists() {
+            return Err(ValidationError::DataPathNotFound(data_path.clone()).into());
+        }
+        for entry in WalkDir::new(&full_path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".git")
+        {
+            let entry = entry.map_err(|source| ValidationError::DataPath {
+                path: data_path.clone(),
+                source,
+            })?;
+            if entry.file_type().is_file() {
+                let rel_path = entry
+                    .path()
+                    .strip_prefix(project_dir)
+                    .unwrap_or(entry.path())
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                files.push((rel_path, entry.into_path()));
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+    tracing::debug!("archiving {} data file(s)", files.len());
+
+    let file = std::fs::File::create(output_path).map_err(IoError::on("create", output_path))?;
+    let enc = GzEncoder::new(file, Compression::default());
+    let mut ar = tar::Builder::new(enc);
+
+    let file_name = output_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let prefix = file_name.trim_end_matches(".tar.gz");
+
+    for (path, full_path) in &files {
+        let data = std::fs::File::open(full_path).map_err(IoError::on("read", full_path))?;
+        let metadata = data.metadata().map_err(IoError::on("read", full_path))?;
+
+        let mut header = Header::new_gnu();
+        header.set_size(metadata.len());
+        header.set_mtime(fixed_mtime);
+        header.set_mode(if is_executable(&metadata) {
+            0o755
+        } else {
+            0o644
+        });
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_username("root").ok();
+        header.set_groupname("root").ok();
+        header.set_cksum();
+
+        let full_name = format!("{}/{}", prefix, path);
+        ar.append_data(&mut header, &full_name, data)
+            .map_err(IoError::on("add", path))?;
+    }
+
+    let enc = ar
+        .into_inner()
+        .map_err(IoError::on("finalize", output_path))?;
+    enc.finish().map_err(IoError::on("finalize", output_path))?;
+
+    Ok(files.len())
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// A file in a git tree, by path, blob and git file mode
+pub(crate) struct TreeFile {
+    pub path: String,
+    pub oid: Oid,
+    pub mode: u32,
+}
+
+/// Every file under `tree`, without reading the blobs
+pub(crate) fn collect_tree_entries(
     repo: &Repository,
     tree: &git2::Tree,
     prefix: &str,
-    entries: &mut Vec<(String, Vec<u8>, u32)>,
-) -> Result<(), String> {
+    entries: &mut Vec<TreeFile>,
+) -> Result<(), GitError> {
     for entry in tree.iter() {
         let name = entry.name().unwrap_or("").to_string();
         let path = if prefix.is_empty() {
@@ -81,16 +229,15 @@ fn collect_tree_entries(
         };
 
         match entry.kind() {
-            Some(git2::ObjectType::Blob) => {
-                let blob = repo
-                    .find_blob(entry.id())
-                    .map_err(|e| format!("Cannot read blob {}: {}", path, e))?;
-                entries.push((path, blob.content().to_vec(), entry.filemode() as u32));
-            }
+            Some(git2::ObjectType::Blob) => entries.push(TreeFile {
+                path,
+                oid: entry.id(),
+                mode: entry.filemode() as u32,
+            }),
             Some(git2::ObjectType::Tree) => {
                 let subtree = repo
                     .find_tree(entry.id())
-                    .map_err(|e| format!("Cannot read tree {}: {}", path, e))?;
+                    .map_err(GitError::on(format!("read tree {}", path)))?;
                 collect_tree_entries(repo, &subtree, &path, entries)?;
             }
             _ => {}
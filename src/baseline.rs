@@ -0,0 +1,102 @@
+use crate::error::{ConfigError, IoError};
+use crate::report::{CheckResult, Report, Status};
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const BASELINE_FILE: &str = ".release-scholar/baseline.toml";
+
+/// Warnings the project has reviewed and accepted; `check` no longer reports
+/// them. Committed alongside the code so the whole team shares it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    pub acknowledged: Vec<Acknowledged>,
+}
+
+/// One acknowledged warning. Warnings that name their rule are known by the
+/// rule and subject, so a file growing from 12 to 13 MB stays acknowledged;
+/// the message is kept for the reader, and is the key for other warnings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledged {
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<String>,
+}
+
+impl Acknowledged {
+    fn matches(&self, result: &CheckResult) -> bool {
+        self.category == result.category
+            && match (&self.rule, result.rule) {
+                (Some(rule), Some(r)) => rule == r && self.subject == result.subject,
+                // Acknowledged before the warning had a rule, or it has none
+                _ => self.message == result.message,
+            }
+    }
+}
+
+impl Baseline {
+    /// Where the baseline is kept in `project_dir`
+    pub fn path(project_dir: &Path) -> PathBuf {
+        project_dir.join(BASELINE_FILE)
+    }
+
+    /// Load the baseline; a missing file acknowledges nothing
+    pub fn load(project_dir: &Path) -> Result<Self, ConfigError> {
+        let path = Self::path(project_dir);
+        if !path.exists() {
+            return Ok(Baseline::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(IoError::on("read", &path))?;
+        toml::from_str(&content).map_err(|source| ConfigError::Parse { path, source })
+    }
+
+    /// Write the baseline, creating `.release-scholar/` if needed
+    pub fn save(&self, project_dir: &Path) -> Result<(), ConfigError> {
+        let path = Self::path(project_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(IoError::on("create", parent))?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|source| ConfigError::Serialize {
+            what: "baseline",
+            source,
+        })?;
+        std::fs::write(&path, content).map_err(IoError::on("write", &path))?;
+        Ok(())
+    }
+
+    /// Whether `result` is an acknowledged warning. Failures are never acknowledged.
+    pub fn contains(&self, result: &CheckResult) -> bool {
+        matches!(result.status, Status::Warn) && self.acknowledged.iter().any(|a| a.matches(result))
+    }
+
+    /// Acknowledge a warning, or withdraw the acknowledgement; returns whether
+    /// it is acknowledged now
+    pub fn toggle(&mut self, result: &CheckResult) -> bool {
+        if self.contains(result) {
+            self.acknowledged.retain(|a| !a.matches(result));
+            false
+        } else {
+            self.acknowledged.push(Acknowledged {
+                category: result.category.clone(),
+                rule: result.rule.map(str::to_string),
+                subject: result.subject.clone(),
+                message: result.message.clone(),
+                acknowledged_at: Some(state::now_utc()),
+            });
+            true
+        }
+    }
+
+    /// Drop acknowledged warnings from `report`; returns how many were dropped
+    pub fn apply(&self, report: &mut Report) -> usize {
+        let before = report.results.len();
+        report.results.retain(|r| !self.contains(r));
+        before - report.results.len()
+    }
+}
@@ -1,5 +1,47 @@
+use clap::Args;
+use release_scholar::config::Config;
+
+pub mod announce;
+pub mod auth;
 pub mod build;
 pub mod check;
+pub mod ci_init;
+pub mod cite;
+pub mod config;
+pub mod diff;
+pub mod discard;
+pub mod hooks;
 pub mod init;
+pub mod list_depositions;
+pub mod metadata;
 pub mod mirror;
+pub mod open;
+pub mod preview;
 pub mod publish;
+pub mod status;
+pub mod verify;
+
+/// `--sandbox` and `--no-sandbox`; without either, the config profile's
+/// `sandbox` setting decides
+#[derive(Debug, Default, Clone, Copy, Args)]
+pub struct Sandbox {
+    /// Use Zenodo sandbox instead of production
+    #[arg(long, overrides_with = "no_sandbox")]
+    sandbox: bool,
+    /// Use production Zenodo, even if the config profile selects the sandbox
+    #[arg(long, overrides_with = "sandbox")]
+    no_sandbox: bool,
+}
+
+impl Sandbox {
+    /// Whether to use the sandbox: as the flags say, or else as `config` does
+    pub fn or_default(self, config: &Config) -> bool {
+        if self.sandbox {
+            true
+        } else if self.no_sandbox {
+            false
+        } else {
+            config.sandbox()
+        }
+    }
+}
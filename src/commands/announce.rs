@@ -0,0 +1,196 @@
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::announce::{self, Announcement};
+use release_scholar::archive::checksum;
+use release_scholar::config::{Artifact, BundleLayout, Config};
+use release_scholar::forge;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::styles;
+use release_scholar::state::ReleaseState;
+use release_scholar::validation::git;
+use std::path::Path;
+
+/// Template file extensions dropped from the name of the file written to
+/// the bundle (`email.txt.tmpl` becomes `email.txt`)
+const TEMPLATE_EXTENSIONS: &[&str] = &[".tmpl", ".template", ".in"];
+
+/// Fill in an announcement template for the release on HEAD, from
+/// CITATION.cff, CHANGELOG.md and the DOIs `publish` recorded; print it, or
+/// write it into the bundle
+pub fn run(
+    project_dir: &Path,
+    template: &Path,
+    bundle: bool,
+    output_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = output_dir {
+        config.set_output_dir(dir)?;
+    }
+    let text = std::fs::read_to_string(template)
+        .map_err(|e| format!("Cannot read template {}: {}", template.display(), e))?;
+    let release = git::release_tag(&project_dir, &config.tag_pattern()?)?;
+
+    let citation_path = project_dir.join("CITATION.cff");
+    if !citation_path.exists() {
+        return Err(Failure::validation(format!(
+            "CITATION.cff not found in {}. Run `release-scholar init` first.",
+            project_dir.display()
+        )));
+    }
+    let mut cff = CitationCff::from_file(&citation_path)?;
+    let state = ReleaseState::load(&project_dir)?;
+    let doi = state
+        .production
+        .releases
+        .get(&release.tag)
+        .and_then(|r| r.doi.clone());
+    let concept_doi = state.production.concept_doi.clone();
+    // The citation is of this release, by its own DOI once it has one
+    cff.version = Some(release.version.clone());
+    if doi.is_some() {
+        cff.doi = doi.clone();
+    }
+
+    let doi_url = |doi: &Option<String>| {
+        doi.as_ref()
+            .map(|d| format!("https://doi.org/{}", d))
+            .unwrap_or_default()
+    };
+    let summary = Announcement::new(
+        &project_dir,
+        &cff.title,
+        &release.version,
+        doi.as_deref().unwrap_or_default(),
+    )
+    .summary;
+    let authors: Vec<String> = cff
+        .authors
+        .iter()
+        .map(|a| match (&a.given_names, &a.family_names) {
+            (Some(given), Some(family)) => format!("{} {}", given, family),
+            _ => a.display_name(),
+        })
+        .collect();
+    let values = [
+        ("title", cff.title.clone()),
+        ("version", release.version.clone()),
+        ("tag", release.tag.clone()),
+        ("date", release.date.clone()),
+        ("doi_url", doi_url(&doi)),
+        ("doi", doi.clone().unwrap_or_default()),
+        ("concept_doi_url", doi_url(&concept_doi)),
+        ("concept_doi", concept_doi.clone().unwrap_or_default()),
+        ("authors", authors.join(", ")),
+        ("abstract", cff.abstract_text.clone().unwrap_or_default()),
+        ("license", cff.license.clone().unwrap_or_default()),
+        (
+            "repository",
+            cff.repository_code.clone().unwrap_or_default(),
+        ),
+        ("keywords", cff.keywords.join(", ")),
+        ("summary", summary.unwrap_or_default()),
+        (
+            "changes",
+            forge::changelog_section(&project_dir, &release.version).unwrap_or_default(),
+        ),
+        ("citation", styles::apa(&cff).trim_end().to_string()),
+    ];
+    // A bundled announcement is published with the release, so it has to
+    // carry the DOI rather than leave it empty
+    if bundle && doi.is_none() {
+        return Err(Failure::validation(format!(
+            "{} has no DOI yet; reserve one with `publish --reserve-doi` or publish first, then write the announcement into the bundle",
+            release.tag
+        )));
+    }
+    let announcement = announce::render_template(&text, &values)?;
+
+    // On stderr, so that a printed announcement can be piped as it is
+    for (name, _) in values
+        .iter()
+        .filter(|(name, value)| value.is_empty() && text.contains(&format!("{{{}}}", name)))
+    {
+        eprintln!(
+            "  {} {{{}}} has no value for {} and was left empty",
+            "!!".yellow().bold(),
+            name,
+            release.tag
+        );
+    }
+
+    if !bundle {
+        print!("{}", announcement);
+        return Ok(());
+    }
+    let release_dir = config.release_dir(&project_dir, &release.tag);
+    if !release_dir.exists() {
+        return Err(format!(
+            "No bundle for {} at {}. Run `release-scholar build` first.",
+            release.tag,
+            release_dir.display()
+        )
+        .into());
+    }
+    let file_name = template
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ANNOUNCEMENT.md".to_string());
+    let file_name = TEMPLATE_EXTENSIONS
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .filter(|name| !name.is_empty())
+        .map_or(file_name.clone(), str::to_string);
+    let layout = config.bundle().layout;
+    let dir = layout.dir(&release_dir, Artifact::Metadata);
+    // checksums.txt names files relative to the archive directory
+    let listed_as = match layout {
+        BundleLayout::Flat => file_name.clone(),
+        BundleLayout::Grouped => format!("../{}/{}", Artifact::Metadata.dir_name(), file_name),
+    };
+    let checksums_path = layout
+        .dir(&release_dir, Artifact::Archive)
+        .join("checksums.txt");
+    let checksums = std::fs::read_to_string(&checksums_path)
+        .map_err(|e| format!("Cannot read {}: {}", checksums_path.display(), e))?;
+    let mut lines: Vec<&str> = checksums.lines().filter(|l| !l.trim().is_empty()).collect();
+    let lists_announcement = |line: &str| {
+        line.split_once("  ")
+            .is_some_and(|(_, name)| name == listed_as)
+    };
+    let announced_before = lines.iter().any(|line| lists_announcement(line));
+
+    // Zenodo keeps the bundle's files side by side, so names must not repeat
+    // across its directories. Only an announcement written before is replaced.
+    let files = layout
+        .files(&release_dir)
+        .map_err(|e| format!("Cannot read {}: {}", release_dir.display(), e))?;
+    let taken = files.iter().any(|f| {
+        f.file_name()
+            .is_some_and(|n| n.to_string_lossy() == file_name)
+    });
+    if taken && !announced_before {
+        return Err(Failure::validation(format!(
+            "The bundle already has a {}; rename the template so the announcement does not replace it",
+            file_name
+        )));
+    }
+
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let path = dir.join(&file_name);
+    std::fs::write(&path, announcement)
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    println!("  {} {}", "Wrote".green(), path.display());
+
+    let hash = checksum::sha256_file(&path)?;
+    let entry = format!("{}  {}", hash, listed_as);
+    lines.retain(|line| !lists_announcement(line));
+    lines.push(&entry);
+    std::fs::write(&checksums_path, format!("{}\n", lines.join("\n")))
+        .map_err(|e| format!("Cannot write {}: {}", checksums_path.display(), e))?;
+    println!("  {} {} to checksums.txt", "Added".green(), file_name);
+    Ok(())
+}
@@ -0,0 +1,448 @@
+use crate::failure::Failure;
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use release_scholar::announce;
+use release_scholar::config::{AnnounceConfig, Config, NetworkConfig};
+use release_scholar::credentials;
+use release_scholar::http;
+use release_scholar::zenodo::{self, ZenodoClient};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Service {
+    Zenodo,
+    ZenodoSandbox,
+    Codeberg,
+    Github,
+    Gitlab,
+    Slack,
+    Mattermost,
+    Matrix,
+    Mastodon,
+    Bluesky,
+}
+
+impl Service {
+    const ALL: [Service; 10] = [
+        Service::Zenodo,
+        Service::ZenodoSandbox,
+        Service::Codeberg,
+        Service::Github,
+        Service::Gitlab,
+        Service::Slack,
+        Service::Mattermost,
+        Service::Matrix,
+        Service::Mastodon,
+        Service::Bluesky,
+    ];
+
+    /// Keyring account name
+    fn account(self) -> &'static str {
+        match self {
+            Service::Zenodo => zenodo::keyring_account(false),
+            Service::ZenodoSandbox => zenodo::keyring_account(true),
+            Service::Codeberg => "codeberg",
+            Service::Github => "github",
+            Service::Gitlab => "gitlab",
+            Service::Slack => "slack",
+            Service::Mattermost => "mattermost",
+            Service::Matrix => "matrix",
+            Service::Mastodon => "mastodon",
+            Service::Bluesky => "bluesky",
+        }
+    }
+
+    /// Keyring account in this project: the Slack and Mattermost webhooks
+    /// are kept per channel, named under `[announce]`
+    fn keyring_account(self, config: &Config) -> Result<String, Failure> {
+        match self {
+            Service::Slack | Service::Mattermost => config
+                .announce()
+                .keyring_account(self.account())
+                .ok_or_else(|| {
+                    Failure::config(format!(
+                        "Name the {} channel with {}_channel under [announce] first",
+                        self.label(),
+                        self.account()
+                    ))
+                }),
+            _ => Ok(self.account().to_string()),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Service::Zenodo => "Zenodo",
+            Service::ZenodoSandbox => "Zenodo sandbox",
+            Service::Codeberg => "Codeberg",
+            Service::Github => "GitHub",
+            Service::Gitlab => "GitLab",
+            Service::Slack => "Slack",
+            Service::Mattermost => "Mattermost",
+            Service::Matrix => "Matrix",
+            Service::Mastodon => "Mastodon",
+            Service::Bluesky => "Bluesky",
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthAction {
+    /// Store a token in the OS keyring after checking that it works
+    Login {
+        #[arg(long, value_enum)]
+        service: Service,
+    },
+    /// Show where each token comes from and whether it is accepted
+    Status {
+        /// Only this service (default: all)
+        #[arg(long, value_enum)]
+        service: Option<Service>,
+    },
+    /// Remove a token from the OS keyring
+    Logout {
+        #[arg(long, value_enum)]
+        service: Service,
+    },
+}
+
+pub fn run(action: AuthAction) -> Result<(), Failure> {
+    let config = Config::load(std::path::Path::new("."), None)?;
+    let network = config.network();
+    match action {
+        AuthAction::Login { service } => login(service, &config, &network),
+        AuthAction::Status { service } => {
+            println!();
+            let services = match service {
+                Some(s) => vec![s],
+                None => Service::ALL.to_vec(),
+            };
+            for service in services {
+                status(service, &config, &network);
+            }
+            println!();
+            Ok(())
+        }
+        AuthAction::Logout { service } => {
+            if credentials::remove(&service.keyring_account(&config)?)? {
+                println!(
+                    "  {} Removed {} token from the OS keyring",
+                    "OK".green().bold(),
+                    service.label()
+                );
+            } else {
+                println!(
+                    "  {} No {} token in the OS keyring",
+                    "—".dimmed(),
+                    service.label()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn login(service: Service, config: &Config, network: &NetworkConfig) -> Result<(), Failure> {
+    let account = service.keyring_account(config)?;
+    // Reading a plaintext token never moves it into the keyring; here the
+    // user can choose to
+    let moved = match plaintext(service, config) {
+        Some((token, source, _)) if io::stdin().is_terminal() => {
+            print!(
+                "  Store the {} token from {} in the OS keyring? [y/N] ",
+                service.label(),
+                source
+            );
+            io::stdout().flush().ok();
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .map_err(|e| format!("Cannot read input: {}", e))?;
+            input
+                .trim()
+                .eq_ignore_ascii_case("y")
+                .then_some((token, source))
+        }
+        _ => None,
+    };
+    let token = match &moved {
+        Some((token, _)) => token.clone(),
+        None => read_token(service)?,
+    };
+    if token.is_empty() {
+        return Err(Failure::config("No token entered"));
+    }
+
+    print!("  Checking token... ");
+    io::stdout().flush().ok();
+    let who = verify(service, &token, config, network)?;
+    println!("{} ({})", "valid".green(), who);
+
+    credentials::store(&account, &token)?;
+    println!(
+        "  {} Stored {} token in the OS keyring",
+        "OK".green().bold(),
+        service.label()
+    );
+    if let Some((_, source)) = moved {
+        println!(
+            "  {}",
+            format!("You can now delete the plaintext copy in {}.", source).dimmed()
+        );
+    }
+    Ok(())
+}
+
+fn status(service: Service, config: &Config, network: &NetworkConfig) {
+    let Some((token, source)) = find(service, config) else {
+        println!(
+            "  {} {:<16} not configured",
+            "—".dimmed(),
+            service.label().bold()
+        );
+        return;
+    };
+    match verify(service, &token, config, network) {
+        Ok(who) => println!(
+            "  {} {:<16} valid ({}) — from {}",
+            "✓".green().bold(),
+            service.label().bold(),
+            who,
+            source
+        ),
+        Err(e) => println!(
+            "  {} {:<16} {} — from {}",
+            "✗".red().bold(),
+            service.label().bold(),
+            e,
+            source
+        ),
+    }
+}
+
+/// The token that commands would use, and where it comes from
+fn find(service: Service, config: &Config) -> Option<(String, String)> {
+    let sandbox = service == Service::ZenodoSandbox;
+    let env_var = match service {
+        Service::Zenodo | Service::ZenodoSandbox => {
+            Some(zenodo::token_env_var(sandbox).to_string())
+        }
+        Service::Slack
+        | Service::Mattermost
+        | Service::Matrix
+        | Service::Mastodon
+        | Service::Bluesky => Some(format!(
+            "RELEASE_SCHOLAR_ANNOUNCE_{}",
+            AnnounceConfig::secret_key(service.account()).to_uppercase()
+        )),
+        _ => None,
+    };
+    if let Some(env_var) = env_var {
+        if let Ok(token) = std::env::var(&env_var) {
+            if !token.trim().is_empty() {
+                return Some((token.trim().to_string(), env_var));
+            }
+        }
+    }
+    // A project's own plaintext token comes before the user's keyring
+    let plaintext = plaintext(service, config);
+    if let Some((token, source, true)) = plaintext {
+        return Some((token, source));
+    }
+    if let Some(token) = service
+        .keyring_account(config)
+        .ok()
+        .and_then(|account| credentials::lookup(&account))
+    {
+        return Some((token, "OS keyring".to_string()));
+    }
+    plaintext.map(|(token, source, _)| (token, source))
+}
+
+/// The token written in a config or token file, where it is written, and
+/// whether the project config itself sets it
+fn plaintext(service: Service, config: &Config) -> Option<(String, String, bool)> {
+    let sandbox = service == Service::ZenodoSandbox;
+    let plaintext = match service {
+        Service::Zenodo | Service::ZenodoSandbox => zenodo::token_file(sandbox).and_then(|p| {
+            std::fs::read_to_string(&p)
+                .ok()
+                .map(|t| (t, p.display().to_string(), false))
+        }),
+        Service::Codeberg | Service::Github | Service::Gitlab => {
+            let mirrors = config.mirrors.clone().unwrap_or_default();
+            let key = format!("{}_token", service.account());
+            let token = match service {
+                Service::Codeberg => mirrors.codeberg_token.clone(),
+                Service::Github => mirrors.github_token.clone(),
+                _ => mirrors.gitlab_token.clone(),
+            };
+            let own = mirrors.is_project_secret(&key);
+            token.map(|t| (t, format!("[mirrors] {}", key), own))
+        }
+        Service::Slack
+        | Service::Mattermost
+        | Service::Matrix
+        | Service::Mastodon
+        | Service::Bluesky => {
+            let announce = config.announce();
+            let key = AnnounceConfig::secret_key(service.account());
+            announce.plaintext(service.account()).map(|t| {
+                (
+                    t,
+                    format!("[announce] {}", key),
+                    announce.is_project_secret(key),
+                )
+            })
+        }
+    };
+    plaintext
+        .map(|(t, source, own)| (t.trim().to_string(), source, own))
+        .filter(|(t, _, _)| !t.is_empty())
+}
+
+/// Make a whoami-style request; returns a description of the account
+fn verify(
+    service: Service,
+    token: &str,
+    config: &Config,
+    network: &NetworkConfig,
+) -> Result<String, String> {
+    let (url, header, value, field) = match service {
+        Service::Zenodo | Service::ZenodoSandbox => {
+            let client = ZenodoClient::with_token(
+                service == Service::ZenodoSandbox,
+                token.to_string(),
+                network,
+            )?;
+            client.verify_token()?;
+            return Ok("deposit access".to_string());
+        }
+        Service::Codeberg => (
+            format!("{}/user", config.gitea_api_url()),
+            "Authorization",
+            format!("token {}", token),
+            "login",
+        ),
+        Service::Github => (
+            "https://api.github.com/user".to_string(),
+            "Authorization",
+            format!("Bearer {}", token),
+            "login",
+        ),
+        Service::Gitlab => (
+            "https://gitlab.com/api/v4/user".to_string(),
+            "PRIVATE-TOKEN",
+            token.to_string(),
+            "username",
+        ),
+        Service::Slack | Service::Mattermost => {
+            // A webhook can only be tested by posting to it
+            let url = reqwest::Url::parse(token)
+                .ok()
+                .filter(|u| u.scheme() == "https")
+                .ok_or("not an https:// webhook URL")?;
+            return Ok(format!(
+                "webhook on {}, not posted to",
+                url.host_str().unwrap_or("?")
+            ));
+        }
+        Service::Matrix => {
+            let homeserver = config
+                .announce()
+                .matrix_homeserver
+                .ok_or("set matrix_homeserver under [announce] to check the token")?;
+            (
+                announce::matrix_url(&homeserver, &["account", "whoami"])?.to_string(),
+                "Authorization",
+                format!("Bearer {}", token),
+                "user_id",
+            )
+        }
+        Service::Mastodon => {
+            let instance = config
+                .announce()
+                .mastodon_instance
+                .ok_or("set mastodon_instance under [announce] to check the token")?;
+            (
+                format!(
+                    "{}/api/v1/accounts/verify_credentials",
+                    instance.trim_end_matches('/')
+                ),
+                "Authorization",
+                format!("Bearer {}", token),
+                "acct",
+            )
+        }
+        Service::Bluesky => {
+            // An app password is checked by logging in with it
+            let announce = config.announce();
+            let handle = announce
+                .bluesky_handle
+                .as_deref()
+                .ok_or("set bluesky_handle under [announce] to check the app password")?;
+            let client = http::client(network, None)?;
+            let session = announce::bluesky_session(
+                &client,
+                network,
+                announce.bluesky_service(),
+                handle,
+                token,
+            )?;
+            return Ok(format!("user {}", session.handle));
+        }
+    };
+
+    let client = http::client(network, None)?;
+    let resp = client
+        .get(&url)
+        .header(header, value)
+        .send()
+        .map_err(|e| format!("HTTP error checking token: {}", e))?;
+    let status = resp.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("token rejected (invalid or expired)".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("{} API error {}", service.label(), status));
+    }
+    let user: serde_json::Value = resp
+        .json()
+        .map_err(|e| format!("Cannot parse user response: {}", e))?;
+    Ok(format!(
+        "user {}",
+        user.get(field).and_then(|v| v.as_str()).unwrap_or("?")
+    ))
+}
+
+/// Read a token from stdin, without echo when it is a terminal
+fn read_token(service: Service) -> Result<String, String> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        let what = match service {
+            Service::Slack | Service::Mattermost => "webhook URL",
+            Service::Bluesky => "app password",
+            _ => "token",
+        };
+        print!("  {} {}: ", service.label(), what);
+        io::stdout().flush().ok();
+        set_echo(false);
+    }
+    let mut input = String::new();
+    let result = stdin.lock().read_line(&mut input);
+    if interactive {
+        set_echo(true);
+        println!();
+    }
+    result.map_err(|e| format!("Cannot read input: {}", e))?;
+    Ok(input.trim().to_string())
+}
+
+fn set_echo(on: bool) {
+    // Best effort; without stty the token is simply echoed
+    let _ = std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status();
+}
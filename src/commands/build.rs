@@ -1,18 +1,46 @@
-use crate::archive::{checksum, tarball};
-use crate::config::Config;
-use crate::metadata::citation::CitationCff;
-use crate::metadata::zenodo::ZenodoDeposit;
+use crate::failure::Failure;
 use colored::Colorize;
-use std::path::Path;
+use release_scholar::archive::{checksum, provenance, tarball};
+use release_scholar::config::{Artifact, Config};
+use release_scholar::error::GitError;
+use release_scholar::hooks::{self, Stage};
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::datacite::DataCiteResource;
+use release_scholar::metadata::datapackage::DataPackage;
+use release_scholar::metadata::jsonld::SoftwareSourceCode;
+use release_scholar::metadata::release_notes;
+use release_scholar::metadata::zenodo::ZenodoDeposit;
+use release_scholar::state::{self, ReleaseState};
+use release_scholar::validation::git;
+use std::path::{Path, PathBuf};
 
-pub fn run(project_dir: &Path) -> Result<(), String> {
+pub fn run(
+    project_dir: &Path,
+    output_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
     let project_dir = std::fs::canonicalize(project_dir)
         .map_err(|e| format!("Invalid project directory: {}", e))?;
-    let config = Config::load(&project_dir);
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = output_dir {
+        config.set_output_dir(dir)?;
+    }
+    let bundle = config.bundle();
+    let started_on = state::now_utc();
 
     // Determine version from git tag
-    let version = get_version_from_tag(&project_dir)?;
-    let tag = format!("v{}", version);
+    let release = match git::release_tag(&project_dir, &config.tag_pattern()?) {
+        Ok(info) => info,
+        Err(e @ GitError::NoReleaseTag { .. }) => {
+            return Err(Failure::validation(format!(
+                "{}. Run `release-scholar check` first.",
+                e
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let version = release.version.clone();
+    let tag = release.tag.clone();
 
     println!(
         "{}",
@@ -20,8 +48,35 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     );
     println!();
 
+    let release_dir = config.release_dir(&project_dir, &tag);
+    let place = |artifact: Artifact, file: &str| -> Result<PathBuf, String> {
+        let dir = bundle.layout.dir(&release_dir, artifact);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        Ok(dir.join(file))
+    };
+
+    // Whatever an earlier build left (under another `archive_name` or
+    // layout) would be uploaded along with this one
+    if release_dir.exists() {
+        std::fs::remove_dir_all(&release_dir)
+            .map_err(|e| format!("Cannot remove previous bundle: {}", e))?;
+    }
+
+    let hook_context = hooks::Context {
+        tag: &tag,
+        version: &version,
+        bundle: Some(&release_dir),
+        ..Default::default()
+    };
+    hooks::run(
+        &project_dir,
+        config.hooks.as_ref(),
+        Stage::PreBuild,
+        &hook_context,
+    )?;
+
     // Create output directory
-    let release_dir = project_dir.join(&config.archive_dir).join(&tag);
     std::fs::create_dir_all(&release_dir)
         .map_err(|e| format!("Cannot create release directory: {}", e))?;
 
@@ -30,46 +85,175 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
         .file_name()
         .unwrap_or_default()
         .to_string_lossy();
-    let archive_name = format!("{}-{}.tar.gz", project_name, tag);
-    let archive_path = release_dir.join(&archive_name);
+    let archive_name = config.archive_file_name(&project_name, &release)?;
+    let archive_path = place(Artifact::Archive, &archive_name)?;
+
+    // The data goes in an archive of its own, from the working tree
+    let data = config.data();
 
     print!("  Creating archive... ");
-    tarball::create_archive(&project_dir, &tag, &archive_path)?;
+    tarball::create_archive(
+        &project_dir,
+        &tag,
+        &archive_path,
+        data.archived_apart().then_some(&data),
+    )?;
     println!("{}", "done".green());
 
-    // Generate checksum
-    print!("  Generating checksum... ");
-    let hash = checksum::sha256_file(&archive_path)?;
-    let checksums_path = release_dir.join("checksums.txt");
-    std::fs::write(&checksums_path, format!("{}  {}\n", hash, archive_name))
-        .map_err(|e| format!("Cannot write checksums: {}", e))?;
-    println!("{}", "done".green());
+    let data_archive = if data.archived_apart() {
+        let name = tarball::data_archive_name(&archive_name);
+        print!("  Creating data archive... ");
+        let count = tarball::create_data_archive(
+            &project_dir,
+            &tag,
+            &data.paths,
+            &place(Artifact::Archive, &name)?,
+        )?;
+        println!("{} ({} files)", "done".green(), count);
+        Some(name)
+    } else {
+        None
+    };
 
     // Generate Zenodo metadata from CITATION.cff
     let citation_path = project_dir.join("CITATION.cff");
     if citation_path.exists() {
         print!("  Generating metadata.json... ");
         let cff = CitationCff::from_file(&citation_path)?;
-        let zenodo = ZenodoDeposit::from_citation(&cff, &config);
-        let metadata_path = release_dir.join("metadata.json");
+        let mut zenodo = ZenodoDeposit::from_citation(&cff, &config);
+        zenodo.merge_zenodo_json(&project_dir)?;
+        let metadata_path = place(Artifact::Metadata, "metadata.json")?;
         std::fs::write(&metadata_path, zenodo.to_json())
             .map_err(|e| format!("Cannot write metadata.json: {}", e))?;
         println!("{}", "done".green());
 
+        if bundle.datacite {
+            print!("  Generating DataCite metadata... ");
+            let datacite = DataCiteResource::from_citation(&cff, &config);
+            std::fs::write(
+                place(Artifact::Metadata, "datacite.json")?,
+                datacite.to_json(),
+            )
+            .map_err(|e| format!("Cannot write datacite.json: {}", e))?;
+            match datacite.to_xml() {
+                Some(xml) => {
+                    std::fs::write(place(Artifact::Metadata, "datacite.xml")?, xml)
+                        .map_err(|e| format!("Cannot write datacite.xml: {}", e))?;
+                    println!("{}", "done".green());
+                }
+                // DataCite XML requires the DOI as its identifier
+                None => println!(
+                    "{}",
+                    "done (no datacite.xml until CITATION.cff has a DOI: reserve one with `publish --reserve-doi`)"
+                        .dimmed()
+                ),
+            }
+        }
+
+        if bundle.jsonld {
+            print!("  Generating scholar.jsonld... ");
+            let jsonld = SoftwareSourceCode::from_citation(&cff);
+            std::fs::write(
+                place(Artifact::Metadata, "scholar.jsonld")?,
+                jsonld.to_json(),
+            )
+            .map_err(|e| format!("Cannot write scholar.jsonld: {}", e))?;
+            println!("{}", "done".green());
+        }
+
         // Copy CITATION.cff into bundle
-        let cff_dest = release_dir.join("CITATION.cff");
-        std::fs::copy(&citation_path, &cff_dest)
-            .map_err(|e| format!("Cannot copy CITATION.cff: {}", e))?;
+        if bundle.citation {
+            std::fs::copy(&citation_path, place(Artifact::Citation, "CITATION.cff")?)
+                .map_err(|e| format!("Cannot copy CITATION.cff: {}", e))?;
+        }
     }
 
     // Copy codemeta.json if it exists
     let codemeta_path = project_dir.join("codemeta.json");
-    if codemeta_path.exists() {
-        std::fs::copy(&codemeta_path, release_dir.join("codemeta.json"))
+    if bundle.codemeta && codemeta_path.exists() {
+        std::fs::copy(&codemeta_path, place(Artifact::Citation, "codemeta.json")?)
             .map_err(|e| format!("Cannot copy codemeta.json: {}", e))?;
         println!("  {} codemeta.json", "Copied".green());
     }
 
+    if bundle.release_notes {
+        print!("  Generating RELEASE_NOTES.md... ");
+        let mut cff = CitationCff::from_file(&citation_path).ok();
+        // Cite this release by its own DOI, once reserved, rather than
+        // CITATION.cff's concept DOI
+        let version_doi = ReleaseState::load(&project_dir)?
+            .production
+            .releases
+            .get(&tag)
+            .and_then(|r| r.doi.clone());
+        if let (Some(cff), Some(doi)) = (cff.as_mut(), version_doi) {
+            cff.doi = Some(doi);
+        }
+        let notes =
+            release_notes::render(&project_dir, &release, &config.tag_pattern()?, cff.as_ref())?;
+        std::fs::write(place(Artifact::Metadata, "RELEASE_NOTES.md")?, notes)
+            .map_err(|e| format!("Cannot write RELEASE_NOTES.md: {}", e))?;
+        println!("{}", "done".green());
+    }
+
+    // Describe bundled data files in datapackage.json
+    if let Some(data) = config.data.as_ref().filter(|d| !d.paths.is_empty()) {
+        print!("  Generating datapackage.json... ");
+        let package = DataPackage::from_config(&project_dir, data, &project_name, &version)?;
+        std::fs::write(
+            place(Artifact::Metadata, "datapackage.json")?,
+            package.to_json(),
+        )
+        .map_err(|e| format!("Cannot write datapackage.json: {}", e))?;
+        println!("{}", "done".green());
+    }
+
+    // Runs once the bundle is otherwise complete, so it can add files (built
+    // docs, figures) that the checksums and provenance then cover
+    hooks::run(
+        &project_dir,
+        config.hooks.as_ref(),
+        Stage::PostBuild,
+        &hook_context,
+    )?;
+
+    // Generate checksums, code archive first
+    print!("  Generating checksum... ");
+    let hash = checksum::sha256_file(&archive_path)?;
+    let mut checksums = format!("{}  {}\n", hash, archive_name);
+    let mut data_hash = None;
+    if let Some(name) = &data_archive {
+        let data_sum = checksum::sha256_file(&place(Artifact::Archive, name)?)?;
+        checksums.push_str(&format!("{}  {}\n", data_sum, name));
+        data_hash = Some(data_sum);
+    }
+    let checksums_path = place(Artifact::Archive, "checksums.txt")?;
+    std::fs::write(&checksums_path, checksums)
+        .map_err(|e| format!("Cannot write checksums: {}", e))?;
+    println!("{}", "done".green());
+
+    // Covers every file in the bundle, the post-build hook's included
+    if bundle.provenance {
+        print!("  Generating provenance... ");
+        let mut files = bundle
+            .layout
+            .files(&release_dir)
+            .map_err(|e| format!("Cannot read {}: {}", release_dir.display(), e))?;
+        files.sort_by_key(|f| !f.to_string_lossy().ends_with(".tar.gz"));
+        let statement = provenance::statement(&project_dir, &tag, &started_on, &files)?;
+        let path = place(Artifact::Archive, provenance::FILE_NAME)?;
+        std::fs::write(&path, statement)
+            .map_err(|e| format!("Cannot write {}: {}", provenance::FILE_NAME, e))?;
+        match provenance::sign(&path, &config.provenance())? {
+            Some(signature) => println!(
+                "{} (signed: {})",
+                "done".green(),
+                signature.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            None => println!("{}", "done".green()),
+        }
+    }
+
     println!();
     println!(
         "  {} Release bundle: {}",
@@ -78,37 +262,11 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     );
     println!("  Archive:   {}", archive_name);
     println!("  SHA256:    {}", hash);
+    if let (Some(name), Some(data_hash)) = (&data_archive, &data_hash) {
+        println!("  Data:      {}", name);
+        println!("  SHA256:    {}", data_hash);
+    }
     println!();
 
     Ok(())
 }
-
-fn get_version_from_tag(project_dir: &Path) -> Result<String, String> {
-    let repo =
-        git2::Repository::open(project_dir).map_err(|e| format!("Cannot open repo: {}", e))?;
-    let head = repo
-        .head()
-        .map_err(|e| format!("Cannot read HEAD: {}", e))?;
-    let head_oid = head.target().ok_or("HEAD has no target")?;
-
-    let tag_names = repo.tag_names(None).map_err(|e| e.to_string())?;
-    let semver_re = regex::Regex::new(r"^v(\d+\.\d+\.\d+)$").unwrap();
-
-    for i in 0..tag_names.len() {
-        let name = match tag_names.get(i) {
-            Some(n) => n,
-            None => continue,
-        };
-        if let Some(caps) = semver_re.captures(name) {
-            let tag_oid = match repo.revparse_single(&format!("refs/tags/{}", name)) {
-                Ok(obj) => obj.peel_to_commit().map(|c| c.id()).unwrap_or(obj.id()),
-                Err(_) => continue,
-            };
-            if tag_oid == head_oid {
-                return Ok(caps[1].to_string());
-            }
-        }
-    }
-
-    Err("HEAD has no semver tag (vX.Y.Z). Run `release-scholar check` first.".to_string())
-}
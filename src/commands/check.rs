@@ -1,35 +1,229 @@
-use crate::config::Config;
-use crate::report::Report;
-use crate::validation;
+mod interactive;
+mod staged;
+mod watch;
+
+use crate::failure::Failure;
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use release_scholar::baseline::Baseline;
+use release_scholar::config::Config;
+use release_scholar::error::ConfigError;
+use release_scholar::report::Report;
+use release_scholar::rules::RuleSet;
+use release_scholar::validation;
+use release_scholar::validation::security;
 use std::path::Path;
 
-pub fn run(project_dir: &Path) -> Result<(), String> {
-    let project_dir = std::fs::canonicalize(project_dir)
-        .map_err(|e| format!("Invalid project directory: {}", e))?;
-    let config = Config::load(&project_dir);
-    let mut report = Report::new();
+pub use staged::{print_finding, print_unscanned};
 
-    // Git validation
-    let git_info = validation::git::validate(&project_dir, &mut report);
+/// Additional checklists and scoring views on top of the standard checks
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CheckProfile {
+    /// FAIR Principles for Research Software
+    Fair4rs,
+    /// Journal of Open Source Software submission checklist
+    Joss,
+}
 
-    // File existence
-    validation::files::validate(&project_dir, &config, &mut report);
+/// What `--rules` selected: any number of checklists and scoring views,
+/// and at most one rule set
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    views: Vec<CheckProfile>,
+    rule_set: Option<String>,
+}
 
-    // Citation validation
-    let version = git_info.as_ref().map(|g| g.version.as_str());
-    validation::citation::validate(&project_dir, version, &mut report);
+impl Profiles {
+    pub fn parse(names: &[String]) -> Result<Profiles, String> {
+        let mut profiles = Profiles::default();
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if let Ok(view) = CheckProfile::from_str(name, true) {
+                profiles.views.push(view);
+                continue;
+            }
+            if let Some(other) = &profiles.rule_set {
+                return Err(format!(
+                    "Only one rule set can apply, not both '{}' and '{}'",
+                    other, name
+                ));
+            }
+            profiles.rule_set = Some(name.to_string());
+        }
+        Ok(profiles)
+    }
 
-    // Security audit
-    validation::security::validate(&project_dir, &mut report);
+    fn has(&self, view: CheckProfile) -> bool {
+        self.views.contains(&view)
+    }
+}
 
-    // Size audit
-    validation::size::validate(&project_dir, &mut report);
+#[derive(Debug, Default, Args)]
+pub struct CheckOptions {
+    /// Also run checks that need network access (DOI/URL resolvability, README links)
+    #[arg(long)]
+    pub online: bool,
+    /// Rule set to grade the results by (default, strict, institutional or
+    /// a [rules.NAME] table), and/or a checklist or score to add (joss,
+    /// fair4rs); comma-separated
+    #[arg(long, value_delimiter = ',')]
+    pub rules: Vec<String>,
+    /// Config profile to use (a [profile.NAME] table in the global config);
+    /// without it, RELEASE_SCHOLAR_PROFILE or `default_profile`
+    #[arg(long, conflicts_with = "staged")]
+    pub profile: Option<String>,
+    /// Browse the findings in a terminal UI: expand details, open files,
+    /// acknowledge warnings, re-run
+    #[arg(long, conflicts_with = "watch")]
+    pub interactive: bool,
+    /// Re-run the checks whenever project files, tags or the config change
+    #[arg(long)]
+    pub watch: bool,
+    /// Only scan the changes staged for the next commit for secrets (as
+    /// the pre-commit hook does)
+    #[arg(long, conflicts_with_all = ["online", "rules", "interactive", "watch"])]
+    pub staged: bool,
+    /// Finish with how long each group of checks took
+    #[arg(long, conflicts_with = "staged")]
+    pub timings: bool,
+    /// Read every tracked file in the secret scan, not only those
+    /// changed since the last run
+    #[arg(long, conflicts_with = "staged")]
+    pub rescan: bool,
+    /// Clear the outputs of notebooks the check flags as large, before
+    /// checking
+    #[arg(long, conflicts_with = "staged")]
+    pub fix: bool,
+}
 
-    report.print();
+pub fn run(project_dir: &Path, options: &CheckOptions) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    if options.staged {
+        return staged::run(&project_dir);
+    }
+    if options.rescan {
+        if let Ok(repo) = git2::Repository::open(&project_dir) {
+            security::clear_scan_cache(&repo)
+                .map_err(|e| format!("Cannot clear the secret scan cache: {}", e))?;
+        }
+    }
+    if options.fix {
+        fix(&project_dir)?;
+    }
+    let profiles = Profiles::parse(&options.rules)?;
+    if options.watch {
+        return watch::run(
+            &project_dir,
+            options.online,
+            &profiles,
+            options.profile.as_deref(),
+            options.timings,
+        );
+    }
+    let (config, problems) = Config::load_lenient(&project_dir, options.profile.as_deref());
+    // The Config checks see the project without the flag, so a profile
+    // named on the command line that does not exist is reported here
+    if let Some(e) = problems
+        .into_iter()
+        .find(|e| matches!(e, ConfigError::UnknownProfile { .. }))
+        .filter(|_| options.profile.is_some())
+    {
+        return Err(e.into());
+    }
 
-    if report.has_failures() {
-        Err("Validation failed".to_string())
+    let report = if options.interactive {
+        interactive::run(&project_dir, &config, options.online)?
+    } else {
+        validation::run_all(&project_dir, &config, options.online).0
+    };
+    if show(&project_dir, &config, report, &profiles, options.timings)? {
+        Err(Failure::validation("Validation failed"))
     } else {
         Ok(())
     }
 }
+
+/// Clear large notebook outputs, saying what was cleared
+fn fix(project_dir: &Path) -> Result<(), Failure> {
+    let notebooks = validation::notebooks::large_outputs(project_dir);
+    if notebooks.is_empty() {
+        return Ok(());
+    }
+    println!("\n{} Clearing notebook outputs", ">>>".bold());
+    for notebook in &notebooks {
+        validation::notebooks::clear_outputs(project_dir, &notebook.path)?;
+        println!(
+            "  {} {} ({:.1} MB of outputs)",
+            "~".yellow().bold(),
+            notebook.path,
+            notebook.bytes as f64 / 1_000_000.0
+        );
+    }
+    println!(
+        "  {}",
+        "Review and commit the cleared notebooks; re-running them restores the outputs".dimmed()
+    );
+    Ok(())
+}
+
+/// Print a report, as the rule set grades it, minus acknowledged warnings,
+/// plus the profiles' checks or scores, and how long the checks took if
+/// asked; returns whether anything failed
+fn show(
+    project_dir: &Path,
+    config: &Config,
+    mut report: Report,
+    profiles: &Profiles,
+    timings: bool,
+) -> Result<bool, String> {
+    if profiles.has(CheckProfile::Joss) {
+        validation::joss::validate(project_dir, &mut report);
+    }
+    let rule_set = match &profiles.rule_set {
+        Some(name) => Some(RuleSet::resolve(config, name).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    if let Some(rule_set) = &rule_set {
+        rule_set.apply(&mut report);
+    }
+
+    // Loaded afterwards: the interactive view may have acknowledged warnings
+    let acknowledged = Baseline::load(project_dir)?.apply(&mut report);
+
+    report.print();
+    if acknowledged > 0 {
+        println!(
+            "  {}\n",
+            format!(
+                "{} acknowledged warning(s) not shown (see {})",
+                acknowledged,
+                Baseline::path(project_dir).display()
+            )
+            .dimmed()
+        );
+    }
+    if let Some(rule_set) = &rule_set {
+        println!(
+            "  {}\n",
+            if config.overridden_rules.contains(&rule_set.name) {
+                format!(
+                    "Graded by the '{}' rule set, as the project redefines it (not the global config's)",
+                    rule_set.name
+                )
+            } else {
+                format!("Graded by the '{}' rule set", rule_set.name)
+            }
+            .dimmed()
+        );
+    }
+
+    if profiles.has(CheckProfile::Fair4rs) {
+        let indicators = validation::fair4rs::assess(project_dir, &report);
+        validation::fair4rs::print(&indicators);
+    }
+    if timings {
+        report.print_timings();
+    }
+
+    Ok(report.has_failures())
+}
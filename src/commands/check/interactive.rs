@@ -0,0 +1,409 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use release_scholar::baseline::Baseline;
+use release_scholar::config::Config;
+use release_scholar::report::{CheckResult, Report, Status};
+use release_scholar::validation;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A line in the findings list
+enum Row {
+    Category(String),
+    Finding(usize),
+}
+
+struct App<'a> {
+    project_dir: &'a Path,
+    config: &'a Config,
+    online: bool,
+    report: Report,
+    baseline: Baseline,
+    rows: Vec<Row>,
+    list: ListState,
+    expanded: bool,
+    show_passed: bool,
+    status: String,
+}
+
+/// Browse the check results until the user quits; returns the last report
+pub fn run(project_dir: &Path, config: &Config, online: bool) -> Result<Report, String> {
+    let baseline = Baseline::load(project_dir)?;
+    let (report, _) = validation::run_all(project_dir, config, online);
+    let mut app = App {
+        project_dir,
+        config,
+        online,
+        report,
+        baseline,
+        rows: Vec::new(),
+        list: ListState::default(),
+        expanded: false,
+        show_passed: false,
+        status: String::new(),
+    };
+    app.rebuild_rows();
+
+    let mut terminal =
+        ratatui::try_init().map_err(|e| format!("Cannot start interactive mode: {}", e))?;
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result?;
+    Ok(app.report)
+}
+
+impl App<'_> {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| format!("Cannot draw: {}", e))?;
+            let Event::Key(key) = event::read().map_err(|e| format!("Cannot read input: {}", e))?
+            else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                KeyCode::Enter | KeyCode::Char(' ') => self.expanded = !self.expanded,
+                KeyCode::Char('a') => self.acknowledge(),
+                KeyCode::Char('p') => {
+                    self.show_passed = !self.show_passed;
+                    self.rebuild_rows();
+                }
+                KeyCode::Char('o') => {
+                    // The editor needs the real terminal
+                    ratatui::restore();
+                    self.open_file();
+                    *terminal = ratatui::try_init()
+                        .map_err(|e| format!("Cannot restart interactive mode: {}", e))?;
+                }
+                KeyCode::Char('r') => {
+                    self.status = "Re-running checks...".to_string();
+                    terminal
+                        .draw(|frame| self.draw(frame))
+                        .map_err(|e| format!("Cannot draw: {}", e))?;
+                    self.rerun();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Group the visible results by category, keeping the selection where possible
+    fn rebuild_rows(&mut self) {
+        let selected = self
+            .selected()
+            .map(|r| (r.category.clone(), r.message.clone()));
+
+        let mut categories: Vec<&str> = Vec::new();
+        for result in &self.report.results {
+            if !categories.contains(&result.category.as_str()) {
+                categories.push(&result.category);
+            }
+        }
+        let mut rows = Vec::new();
+        for category in categories {
+            let findings: Vec<usize> = (0..self.report.results.len())
+                .filter(|&i| {
+                    let r = &self.report.results[i];
+                    r.category == category
+                        && (self.show_passed || !matches!(r.status, Status::Pass))
+                })
+                .collect();
+            if findings.is_empty() {
+                continue;
+            }
+            rows.push(Row::Category(category.to_string()));
+            rows.extend(findings.into_iter().map(Row::Finding));
+        }
+        self.rows = rows;
+
+        let position = selected
+            .and_then(|(category, message)| {
+                self.rows.iter().position(|row| match row {
+                    Row::Finding(i) => {
+                        let r = &self.report.results[*i];
+                        r.category == category && r.message == message
+                    }
+                    Row::Category(_) => false,
+                })
+            })
+            .or_else(|| self.rows.iter().position(|r| matches!(r, Row::Finding(_))));
+        self.list.select(position);
+    }
+
+    fn selected(&self) -> Option<&CheckResult> {
+        match self.rows.get(self.list.selected()?)? {
+            Row::Finding(i) => self.report.results.get(*i),
+            Row::Category(_) => None,
+        }
+    }
+
+    /// Move to the next finding up or down, skipping category headings
+    fn move_selection(&mut self, step: isize) {
+        let Some(mut i) = self.list.selected() else {
+            return;
+        };
+        loop {
+            let next = i as isize + step;
+            if next < 0 || next as usize >= self.rows.len() {
+                return;
+            }
+            i = next as usize;
+            if matches!(self.rows[i], Row::Finding(_)) {
+                self.list.select(Some(i));
+                return;
+            }
+        }
+    }
+
+    fn acknowledge(&mut self) {
+        let Some(result) = self.selected().cloned() else {
+            return;
+        };
+        if !matches!(result.status, Status::Warn) {
+            self.status = "Only warnings can be acknowledged".to_string();
+            return;
+        }
+        let acknowledged = self.baseline.toggle(&result);
+        self.status = match self.baseline.save(self.project_dir) {
+            Ok(()) if acknowledged => format!(
+                "Acknowledged in {}",
+                Baseline::path(self.project_dir).display()
+            ),
+            Ok(()) => "Acknowledgement withdrawn".to_string(),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    fn rerun(&mut self) {
+        let (report, _) = validation::run_all(self.project_dir, self.config, self.online);
+        self.report = report;
+        self.rebuild_rows();
+        self.status = "Checks re-run".to_string();
+    }
+
+    /// Open the first file the finding refers to in $VISUAL/$EDITOR, or the
+    /// system's default application
+    fn open_file(&mut self) {
+        let Some(result) = self.selected() else {
+            return;
+        };
+        let Some((path, line)) = referenced_files(self.project_dir, result)
+            .into_iter()
+            .next()
+        else {
+            self.status = "No file to open for this finding".to_string();
+            return;
+        };
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|e| !e.trim().is_empty());
+        let outcome = match editor {
+            Some(editor) => {
+                let mut parts = editor.split_whitespace();
+                let mut command = Command::new(parts.next().unwrap_or_default());
+                command.args(parts);
+                if let Some(line) = line {
+                    command.arg(format!("+{}", line));
+                }
+                command.arg(&path).status()
+            }
+            None if cfg!(target_os = "macos") => Command::new("open").arg(&path).status(),
+            None if cfg!(windows) => Command::new("cmd")
+                .args(["/C", "start", ""])
+                .arg(&path)
+                .status(),
+            None => Command::new("xdg-open").arg(&path).status(),
+        };
+        self.status = match outcome {
+            Ok(s) if s.success() => format!("Opened {}", path.display()),
+            Ok(s) => format!("Editor exited with {}", s),
+            Err(e) => format!("Cannot open {}: {}", path.display(), e),
+        };
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+
+        let count = |status: fn(&Status) -> bool| {
+            self.report
+                .results
+                .iter()
+                .filter(|r| status(&r.status))
+                .count()
+        };
+        let fails = count(|s| matches!(s, Status::Fail));
+        let warns = count(|s| matches!(s, Status::Warn));
+        let acknowledged = self
+            .report
+            .results
+            .iter()
+            .filter(|r| self.baseline.contains(r))
+            .count();
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(" Release Scholar ").bold(),
+                Span::from(format!("{} failed", fails)).fg(if fails > 0 {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+                Span::from(", "),
+                Span::from(format!("{} warnings", warns - acknowledged)).fg(Color::Yellow),
+                Span::from(format!(" ({} acknowledged)", acknowledged)).dim(),
+            ]),
+            header,
+        );
+
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| match row {
+                Row::Category(name) => ListItem::new(Line::from(name.as_str()).bold()),
+                Row::Finding(i) => {
+                    let r = &self.report.results[*i];
+                    let (label, color) = match r.status {
+                        _ if self.baseline.contains(r) => ("[ACK ]", Color::DarkGray),
+                        Status::Pass => ("[PASS]", Color::Green),
+                        Status::Fail => ("[FAIL]", Color::Red),
+                        Status::Warn => ("[WARN]", Color::Yellow),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::from("  "),
+                        Span::from(label).fg(color).bold(),
+                        Span::from(" "),
+                        Span::from(r.message.as_str()),
+                    ]))
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Findings "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        if self.expanded {
+            let [left, right] =
+                Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                    .areas(body);
+            frame.render_stateful_widget(list, left, &mut self.list);
+            frame.render_widget(self.details(), right);
+        } else {
+            frame.render_stateful_widget(list, body, &mut self.list);
+        }
+
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(format!(" {}", self.status)).dim(),
+                Line::from(
+                    " ↑↓ move  enter details  o open file  a acknowledge  r re-run  p passed  q quit",
+                ),
+            ]),
+            footer,
+        );
+    }
+
+    fn details(&self) -> Paragraph<'_> {
+        let block = Block::default().borders(Borders::ALL).title(" Details ");
+        let Some(result) = self.selected() else {
+            return Paragraph::new("").block(block);
+        };
+        let status = match result.status {
+            Status::Pass => "passed",
+            Status::Fail => "failed",
+            Status::Warn if self.baseline.contains(result) => "warning (acknowledged)",
+            Status::Warn => "warning",
+        };
+        let mut lines = vec![
+            Line::from(vec![
+                Span::from("Category: ").bold(),
+                Span::from(result.category.as_str()),
+            ]),
+            Line::from(vec![Span::from("Status:   ").bold(), Span::from(status)]),
+            Line::from(""),
+            Line::from(result.message.as_str()),
+        ];
+        let files = referenced_files(self.project_dir, result);
+        if !files.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Files:").bold());
+            for (path, line) in files {
+                let path = path.strip_prefix(self.project_dir).unwrap_or(&path);
+                lines.push(Line::from(match line {
+                    Some(line) => format!("  {}:{}", path.display(), line),
+                    None => format!("  {}", path.display()),
+                }));
+            }
+        }
+        if matches!(result.status, Status::Warn) {
+            lines.push(Line::from(""));
+            lines.push(
+                Line::from(if self.baseline.contains(result) {
+                    "Press a to withdraw the acknowledgement."
+                } else {
+                    "Press a to acknowledge; `check` will stop reporting it."
+                })
+                .dim(),
+            );
+        }
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+    }
+}
+
+/// Files a finding points at: paths named in the message (optionally with a
+/// `:line`), or else the file its category is about
+fn referenced_files(project_dir: &Path, result: &CheckResult) -> Vec<(PathBuf, Option<usize>)> {
+    let mut files = Vec::new();
+    for word in result
+        .message
+        .split(|c: char| c.is_whitespace() || c == ',')
+    {
+        let word = word
+            .trim_matches(|c: char| "()'\"`".contains(c))
+            .trim_end_matches(['.', ':', ';']);
+        if word.is_empty() {
+            continue;
+        }
+        let (path, line) = match word.rsplit_once(':') {
+            Some((path, line)) if line.parse::<usize>().is_ok() => (path, line.parse().ok()),
+            _ => (word, None),
+        };
+        let full = project_dir.join(path);
+        if full.is_file() && !files.iter().any(|(p, _)| p == &full) {
+            files.push((full, line));
+        }
+    }
+    if files.is_empty() {
+        let fallback = match result.category.as_str() {
+            "README" => Some("README.md"),
+            "Citation" => Some("CITATION.cff"),
+            "Gitignore" => Some(".gitignore"),
+            _ => None,
+        };
+        if let Some(full) = fallback
+            .map(|f| project_dir.join(f))
+            .filter(|p| p.is_file())
+        {
+            files.push((full, None));
+        }
+    }
+    files
+}
@@ -0,0 +1,80 @@
+use crate::failure::Failure;
+use colored::Colorize;
+use git2::{Repository, RepositoryOpenFlags};
+use release_scholar::validation::security::{self, Finding};
+use std::path::Path;
+use std::time::Instant;
+
+/// Scan only what is staged for the next commit for secrets; quick enough to
+/// run from a pre-commit hook
+pub fn run(project_dir: &Path) -> Result<(), Failure> {
+    let started = Instant::now();
+    // As git itself: `GIT_DIR` and `GIT_INDEX_FILE` from the hook, which
+    // point elsewhere during `git commit -a` or `git commit <paths>`
+    let repo = Repository::open_ext(
+        project_dir,
+        RepositoryOpenFlags::FROM_ENV,
+        &[] as &[&std::ffi::OsStr],
+    )
+    .map_err(|e| format!("Cannot open repo: {}", e))?;
+    let scan = security::scan_staged(&repo)
+        .map_err(|e| format!("Cannot scan the staged changes: {}", e))?;
+    let findings = scan.findings;
+
+    println!("\n{} Scanning staged changes for secrets", ">>>".bold());
+    for finding in &findings {
+        print_finding(finding, None);
+    }
+    for path in &scan.unscanned {
+        print_unscanned(path, None);
+    }
+    if findings.is_empty() {
+        println!("  {} No secrets found", "OK".green().bold());
+    }
+    println!(
+        "\n  {}\n",
+        format!("Finished in {} ms", started.elapsed().as_millis()).dimmed()
+    );
+
+    let blocking = findings.iter().filter(|f| f.certain).count();
+    if blocking > 0 {
+        return Err(Failure::validation(format!(
+            "{} likely secret(s) staged; unstage them with `git restore --staged <file>`",
+            blocking
+        )));
+    }
+    Ok(())
+}
+
+/// A finding with its place in the diff; `commit` names the commit that
+/// adds it, when it is not the staged changes
+pub fn print_finding(finding: &Finding, commit: Option<&str>) {
+    let label = if finding.certain {
+        "[FAIL]".red().bold()
+    } else {
+        "[WARN]".yellow().bold()
+    };
+    let commit = commit
+        .map(|c| format!(" (commit {})", c))
+        .unwrap_or_default();
+    println!(
+        "  {} Possible {} in {}:{}{}",
+        label, finding.kind, finding.path, finding.line, commit
+    );
+    for line in &finding.context {
+        println!("         {}", line.dimmed());
+    }
+}
+
+/// A file too large to diff that could not be streamed or inflated either
+pub fn print_unscanned(path: &str, commit: Option<&str>) {
+    let commit = commit
+        .map(|c| format!(" (commit {})", c))
+        .unwrap_or_default();
+    println!(
+        "  {} {}{} is too large to inflate from a pack; it was not scanned",
+        "[WARN]".yellow().bold(),
+        path,
+        commit
+    );
+}
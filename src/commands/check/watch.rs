@@ -0,0 +1,117 @@
+use super::Profiles;
+use crate::failure::Failure;
+use colored::Colorize;
+use git2::Repository;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use ratatui::crossterm::cursor::MoveTo;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{Clear, ClearType};
+use release_scholar::config::Config;
+use release_scholar::validation;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Editors and `git` touch several files per save/commit; wait for quiet
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Re-run the checks whenever a file that could change the outcome changes,
+/// until interrupted
+pub fn run(
+    project_dir: &Path,
+    online: bool,
+    profiles: &Profiles,
+    config_profile: Option<&str>,
+    timings: bool,
+) -> Result<(), Failure> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("Cannot watch files: {}", e))?;
+    watcher
+        .watch(project_dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Cannot watch {}: {}", project_dir.display(), e))?;
+    let repo = Repository::open(project_dir).ok();
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    loop {
+        // Re-read each time: the config itself may be what changed
+        let config = Config::load_lenient(project_dir, config_profile).0;
+        let _ = execute!(std::io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
+        if !changed.is_empty() {
+            let names: Vec<String> = changed
+                .iter()
+                .map(|p| {
+                    p.strip_prefix(project_dir)
+                        .unwrap_or(p)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            println!("  {}", format!("Changed: {}", names.join(", ")).dimmed());
+        }
+
+        let (report, _) = validation::run_all(project_dir, &config, online);
+        super::show(project_dir, &config, report, profiles, timings)?;
+        println!(
+            "{} Watching {} for changes (Ctrl-C to stop)",
+            ">>>".bold(),
+            project_dir.display()
+        );
+
+        changed = wait_for_changes(&rx, project_dir, &config, repo.as_ref())?;
+    }
+}
+
+/// Block until at least one relevant path changed, then collect whatever
+/// else changes within the debounce window
+fn wait_for_changes(
+    rx: &mpsc::Receiver<notify::Result<Event>>,
+    project_dir: &Path,
+    config: &Config,
+    repo: Option<&Repository>,
+) -> Result<Vec<PathBuf>, String> {
+    let mut changed = Vec::new();
+    let collect = |event: notify::Result<Event>, changed: &mut Vec<PathBuf>| {
+        let event = event.map_err(|e| format!("File watch failed: {}", e))?;
+        if matches!(event.kind, EventKind::Access(_)) {
+            return Ok::<(), String>(());
+        }
+        for path in event.paths {
+            if relevant(&path, project_dir, config, repo) && !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+        Ok(())
+    };
+
+    while changed.is_empty() {
+        let event = rx.recv().map_err(|_| "File watcher stopped".to_string())?;
+        collect(event, &mut changed)?;
+    }
+    while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+        collect(event, &mut changed)?;
+    }
+    Ok(changed)
+}
+
+/// Whether a change to `path` can affect the check results: tracked or
+/// untracked project files, and new tags or commits, but not git internals,
+/// ignored files or the build output
+fn relevant(path: &Path, project_dir: &Path, config: &Config, repo: Option<&Repository>) -> bool {
+    let Ok(rel) = path.strip_prefix(project_dir) else {
+        return false;
+    };
+    if let Ok(git_path) = rel.strip_prefix(".git") {
+        return git_path == Path::new("HEAD")
+            || git_path == Path::new("packed-refs")
+            || git_path.starts_with("refs/tags")
+            || git_path.starts_with("refs/heads");
+    }
+    if rel.starts_with(&config.archive_dir) || rel.starts_with(".release-scholar/state.toml") {
+        return false;
+    }
+    match repo {
+        Some(repo) => !repo.is_path_ignored(rel).unwrap_or(false),
+        None => true,
+    }
+}
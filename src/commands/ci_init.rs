@@ -0,0 +1,155 @@
+use crate::failure::Failure;
+use clap::ValueEnum;
+use colored::Colorize;
+use release_scholar::config::Config;
+use std::path::Path;
+use toml_edit::DocumentMut;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Provider {
+    Github,
+    Gitlab,
+    Woodpecker,
+    Forgejo,
+}
+
+impl Provider {
+    fn path(self) -> &'static str {
+        match self {
+            Provider::Github => ".github/workflows/release-scholar.yml",
+            // GitLab reads only .gitlab-ci.yml, which is likely the project's
+            // own pipeline, so the jobs go in a file for it to include
+            Provider::Gitlab => ".gitlab/release-scholar.yml",
+            Provider::Woodpecker => ".woodpecker/release-scholar.yaml",
+            Provider::Forgejo => ".forgejo/workflows/release-scholar.yml",
+        }
+    }
+
+    fn template(self) -> &'static str {
+        match self {
+            Provider::Github => include_str!("ci_init/github.yml"),
+            Provider::Gitlab => include_str!("ci_init/gitlab.yml"),
+            Provider::Woodpecker => include_str!("ci_init/woodpecker.yaml"),
+            Provider::Forgejo => include_str!("ci_init/forgejo.yml"),
+        }
+    }
+}
+
+/// Rule set the workflows check pull requests with, added to the project
+/// config unless it defines one already
+const CI_RULES: &str = "
+# Pull requests and branch pushes are checked before the release is tagged,
+# so there a missing release tag only warns (used by the CI workflow)
+[rules.ci]
+categories = { Git = \"warn\" }
+";
+
+/// Write a CI workflow that checks pull requests and builds release tags
+pub fn run(
+    project_dir: &Path,
+    provider: Provider,
+    force: bool,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let config = Config::load(&project_dir, profile)?;
+
+    let path = project_dir.join(provider.path());
+    if path.exists() && !force {
+        return Err(Failure::from(format!(
+            "{} already exists; pass --force to replace it",
+            provider.path()
+        )));
+    }
+
+    // A custom tag_pattern can match anything, so every tag runs the build
+    let prefix = match config.tag_pattern {
+        Some(_) => "",
+        None => config.tag_prefix.as_str(),
+    };
+    let install = format!(
+        "cargo install --locked --git {} release-scholar",
+        env!("CARGO_PKG_REPOSITORY")
+    );
+    let workflow = provider
+        .template()
+        .replace("{install}", &install)
+        .replace("{branch}", &default_branch(&project_dir))
+        .replace("{tag_glob}", &format!("{}*", prefix))
+        .replace("{tag_regex}", &regex::escape(prefix))
+        .replace("{archive_dir}", config.archive_dir.trim_end_matches('/'));
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, workflow)
+        .map_err(|e| format!("Cannot write {}: {}", provider.path(), e))?;
+    println!("  {} Created {}", "+".green().bold(), provider.path());
+
+    if add_ci_rules(&project_dir)? {
+        println!(
+            "  {} Added the [rules.ci] rule set to .release-scholar.toml",
+            "~".green().bold()
+        );
+    }
+    println!();
+    if let Provider::Gitlab = provider {
+        println!(
+            "{}",
+            format!(
+                "Include it from .gitlab-ci.yml to run it:\n\n  include:\n    - local: {}\n",
+                provider.path()
+            )
+            .dimmed()
+        );
+    }
+    println!(
+        "{}",
+        "Commit the workflow; the comments at its top list the secrets to set up.".dimmed()
+    );
+    Ok(())
+}
+
+/// Append `[rules.ci]` to the project config; false if it is already there
+fn add_ci_rules(project_dir: &Path) -> Result<bool, String> {
+    let path = project_dir.join(".release-scholar.toml");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Cannot read .release-scholar.toml: {}", e)),
+    };
+    let doc: DocumentMut = content
+        .parse()
+        .map_err(|e| format!("Cannot parse .release-scholar.toml: {}", e))?;
+    let defined = doc.get("rules").and_then(|rules| rules.get("ci")).is_some();
+    if defined {
+        return Ok(false);
+    }
+    let mut content = content;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(if content.is_empty() {
+        CI_RULES.trim_start()
+    } else {
+        CI_RULES
+    });
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Cannot write .release-scholar.toml: {}", e))?;
+    Ok(true)
+}
+
+/// The branch checked out now, taken to be the one releases are made from
+fn default_branch(project_dir: &Path) -> String {
+    git2::Repository::open(project_dir)
+        .ok()
+        .and_then(|repo| {
+            let head = repo.head().ok()?;
+            head.is_branch()
+                .then(|| head.shorthand().map(str::to_string))
+                .flatten()
+        })
+        .unwrap_or_else(|| "main".to_string())
+}
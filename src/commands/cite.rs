@@ -0,0 +1,74 @@
+use crate::failure::Failure;
+use clap::ValueEnum;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::{bibtex, ris, styles};
+use release_scholar::state::ReleaseState;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CiteFormat {
+    Bibtex,
+    Ris,
+    Endnote,
+    Apa,
+    Chicago,
+    /// A `references:` entry for another project's CITATION.cff
+    Cff,
+}
+
+pub fn run(
+    project_dir: &Path,
+    format: CiteFormat,
+    all_versions: bool,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let citation_path = project_dir.join("CITATION.cff");
+    if !citation_path.exists() {
+        return Err(Failure::validation(format!(
+            "CITATION.cff not found in {}. Run `release-scholar init` first.",
+            project_dir.display()
+        )));
+    }
+    let config = Config::load(&project_dir, profile)?;
+    let mut cff = CitationCff::from_file(&citation_path)?;
+    if all_versions {
+        // The software as a whole, not one release of it
+        let state = ReleaseState::load(&project_dir)?;
+        if let Some(concept) = state.production.concept_doi {
+            cff.doi = Some(concept);
+        }
+        cff.version = None;
+    } else if let Some(doi) = minted_doi(&project_dir, &config, &cff) {
+        cff.doi = Some(doi);
+    }
+
+    let output = match format {
+        CiteFormat::Bibtex => bibtex::from_citation(&cff),
+        CiteFormat::Ris => ris::from_citation(&cff),
+        CiteFormat::Endnote => ris::endnote_from_citation(&cff),
+        CiteFormat::Apa => styles::apa(&cff),
+        CiteFormat::Chicago => styles::chicago(&cff),
+        CiteFormat::Cff => styles::cff_reference(&cff),
+    };
+    print!("{}", output);
+
+    Ok(())
+}
+
+/// The version DOI Zenodo minted for the CITATION.cff version, as recorded
+/// by `publish`. It is more specific than CITATION.cff's `doi`, which often
+/// holds the concept DOI, or none until the file is updated.
+fn minted_doi(project_dir: &Path, config: &Config, cff: &CitationCff) -> Option<String> {
+    let version = cff.version.as_deref()?;
+    let tags = config.tag_pattern().ok()?;
+    let state = ReleaseState::load(project_dir).ok()?;
+    state
+        .production
+        .releases
+        .iter()
+        .filter(|(tag, release)| release.published && tags.version(tag) == Some(version))
+        .find_map(|(_, release)| release.doi.clone())
+}
@@ -0,0 +1,311 @@
+use crate::failure::Failure;
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use release_scholar::config::Config;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Print a setting (the effective value, unless --global)
+    Get {
+        /// Dotted key, e.g. author.orcid
+        key: String,
+        #[command(flatten)]
+        scope: Scope,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long, conflicts_with = "global")]
+        profile: Option<String>,
+    },
+    /// Change a setting in the project (or, with --global, the global) config
+    Set {
+        /// Dotted key, e.g. author.orcid
+        key: String,
+        /// A TOML value (3, true, ["a", "b"]); anything else is taken as a string
+        value: String,
+        #[command(flatten)]
+        scope: Scope,
+    },
+    /// Print every setting (the effective values, unless --global)
+    List {
+        #[command(flatten)]
+        scope: Scope,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long, conflicts_with = "global")]
+        profile: Option<String>,
+    },
+    /// Open the config file in $VISUAL/$EDITOR and check it afterwards
+    Edit {
+        #[command(flatten)]
+        scope: Scope,
+    },
+    /// Print the location of the config file
+    Path {
+        #[command(flatten)]
+        scope: Scope,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct Scope {
+    /// Use the global config instead of the project's .release-scholar.toml
+    #[arg(long)]
+    global: bool,
+    /// Path to the project directory
+    #[arg(long, default_value = ".", conflicts_with = "global")]
+    project_dir: PathBuf,
+}
+
+impl Scope {
+    fn path(&self) -> Result<PathBuf, String> {
+        if self.global {
+            return Config::global_config_path()
+                .ok_or_else(|| "Cannot determine config directory".to_string());
+        }
+        Ok(self.project_dir()?.join(".release-scholar.toml"))
+    }
+
+    fn project_dir(&self) -> Result<PathBuf, String> {
+        std::fs::canonicalize(&self.project_dir)
+            .map_err(|e| format!("Invalid project directory: {}", e))
+    }
+
+    /// The settings `get` and `list` show: the global file on its own, or
+    /// what a command run in the project (with `profile`) would use
+    fn settings(&self, profile: Option<&str>) -> Result<toml::Table, String> {
+        let config = if self.global {
+            let path = self.path()?;
+            if path.exists() {
+                Config::from_file(&path)?
+            } else {
+                Config::default()
+            }
+        } else {
+            Config::load(&self.project_dir()?, profile)?
+        };
+        toml::Table::try_from(&config).map_err(|e| format!("Cannot serialize config: {}", e))
+    }
+}
+
+pub fn run(action: ConfigAction) -> Result<(), Failure> {
+    match action {
+        ConfigAction::Get {
+            key,
+            scope,
+            profile,
+        } => {
+            let settings = scope.settings(profile.as_deref())?;
+            let value = lookup(&settings, &key).ok_or_else(|| format!("{} is not set", key))?;
+            match masked(&key, value) {
+                toml::Value::String(s) => println!("{}", s),
+                toml::Value::Table(t) => print!("{}", t),
+                v => println!("{}", v),
+            }
+            Ok(())
+        }
+        ConfigAction::Set { key, value, scope } => {
+            set(&scope.path()?, &key, &value).map_err(Failure::config)
+        }
+        ConfigAction::List { scope, profile } => {
+            let mut lines = Vec::new();
+            flatten("", &scope.settings(profile.as_deref())?, &mut lines);
+            for (key, value) in lines {
+                println!("{} = {}", key, value);
+            }
+            Ok(())
+        }
+        ConfigAction::Edit { scope } => edit(&scope.path()?),
+        ConfigAction::Path { scope } => {
+            println!("{}", scope.path()?.display());
+            Ok(())
+        }
+    }
+}
+
+fn lookup<'a>(settings: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (first, rest) = key.split_once('.').unwrap_or((key, ""));
+    let value = settings.get(first)?;
+    if rest.is_empty() {
+        return Some(value);
+    }
+    lookup(value.as_table()?, rest)
+}
+
+/// `value`, set under `key`, with the credentials in it masked
+fn masked(key: &str, value: &toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Table(t) => toml::Value::Table(
+            t.iter()
+                .map(|(name, v)| (name.clone(), masked(name, v)))
+                .collect(),
+        ),
+        _ if Config::is_secret_key(key) => toml::Value::String("********".to_string()),
+        v => v.clone(),
+    }
+}
+
+/// `key = value` lines for every leaf setting, with credentials masked
+fn flatten(prefix: &str, table: &toml::Table, lines: &mut Vec<(String, String)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            toml::Value::Table(t) => flatten(&key, t, lines),
+            v => lines.push((key.clone(), masked(&key, v).to_string())),
+        }
+    }
+}
+
+/// Set `key` in the file at `path`, keeping its comments and layout. The
+/// result must still be a valid config.
+fn set(path: &Path, key: &str, input: &str) -> Result<(), String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+    };
+    let doc: DocumentMut = content
+        .parse()
+        .map_err(|e| format!("Cannot parse {}: {}", path.display(), e))?;
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|p| p.trim().is_empty()) {
+        return Err(format!("Invalid key: {}", key));
+    }
+
+    // `3`, `true` or `["a"]` as typed; a bare word (or a value of the wrong
+    // type for a string setting) as a string
+    let mut candidates = Vec::new();
+    if let Ok(value) = input.parse::<toml_edit::Value>() {
+        candidates.push(value);
+    }
+    if !candidates.iter().any(|v| v.is_str()) {
+        candidates.push(input.into());
+    }
+
+    let mut error = None;
+    for value in candidates {
+        let mut updated = doc.clone();
+        insert(updated.as_table_mut(), &parts, value);
+        let text = updated.to_string();
+        if let Err(e) = Config::parse(&text, path) {
+            error.get_or_insert(e);
+            continue;
+        }
+        write(path, &text)?;
+        println!("  {} {} in {}", "~".green().bold(), key, path.display());
+        if Config::is_secret_key(key) {
+            println!(
+                "  {} {} is stored in plain text here; `release-scholar auth login` keeps it in the OS keyring.",
+                "!!".yellow().bold(),
+                key
+            );
+        }
+        return Ok(());
+    }
+    match error {
+        Some(e) => Err(format!("Cannot set {}: {}", key, e)),
+        None => Err(format!("Cannot set {}", key)),
+    }
+}
+
+/// Set `path` in `table`, creating the tables on the way. Only the innermost
+/// new table gets a `[header]`; inside inline tables they stay inline.
+fn insert(table: &mut dyn TableLike, path: &[&str], value: toml_edit::Value) {
+    match path {
+        [] => {}
+        [last] => {
+            table.insert(last, Item::Value(value));
+        }
+        [first, rest @ ..] => {
+            if !table.get(first).is_some_and(Item::is_table_like) {
+                let mut new = Table::new();
+                new.set_implicit(rest.len() > 1);
+                table.insert(first, Item::Table(new));
+            }
+            if let Some(inner) = table.get_mut(first).and_then(Item::as_table_like_mut) {
+                insert(inner, rest, value);
+            }
+        }
+    }
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let new = !path.exists();
+    std::fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    // The global config may hold tokens
+    #[cfg(unix)]
+    if new && Some(path) == Config::global_config_path().as_deref() {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Cannot restrict {}: {}", path.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = new;
+    Ok(())
+}
+
+/// Open `path` in the editor until it holds a valid config (or the user
+/// gives up, which restores the previous contents)
+fn edit(path: &Path) -> Result<(), Failure> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| Failure::config("Set $EDITOR (or $VISUAL) to the editor to use"))?;
+    let original = std::fs::read_to_string(path).ok();
+    if original.is_none() {
+        write(path, "")?;
+    }
+
+    loop {
+        let mut parts = editor.split_whitespace();
+        let status = Command::new(parts.next().unwrap_or_default())
+            .args(parts)
+            .arg(path)
+            .status()
+            .map_err(|e| format!("Cannot run {}: {}", editor, e))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}", editor, status).into());
+        }
+
+        let error = match Config::from_file(path) {
+            Ok(_) => {
+                println!("  {} {} is valid.", "OK".green().bold(), path.display());
+                return Ok(());
+            }
+            Err(e) => e.to_string(),
+        };
+        println!("  {} {}", "FAIL".red().bold(), error);
+        if !io::stdin().is_terminal() || !ask("Edit again?")? {
+            match &original {
+                Some(content) => write(path, content)?,
+                None => std::fs::remove_file(path)
+                    .map_err(|e| format!("Cannot remove {}: {}", path.display(), e))?,
+            }
+            return Err(Failure::config(format!(
+                "Invalid config; {} left unchanged",
+                path.display()
+            )));
+        }
+    }
+}
+
+fn ask(question: &str) -> Result<bool, String> {
+    print!("  {} [Y/n] ", question);
+    io::stdout().flush().ok();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Cannot read input: {}", e))?;
+    let input = input.trim();
+    Ok(input.is_empty() || input.eq_ignore_ascii_case("y") || input.eq_ignore_ascii_case("yes"))
+}
@@ -0,0 +1,155 @@
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::archive::contents::{self, Change, Contents};
+use release_scholar::archive::tarball;
+use release_scholar::config::{Artifact, Config};
+use std::path::Path;
+
+/// Compare the files of two releases, each a tag (its built archive, or the
+/// tagged tree when it has not been built) or a path to a .tar.gz
+pub fn run(
+    project_dir: &Path,
+    old: &str,
+    new: &str,
+    output_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = output_dir {
+        config.set_output_dir(dir)?;
+    }
+
+    let (old_source, old_contents) = load(&project_dir, &config, old)?;
+    let (new_source, new_contents) = load(&project_dir, &config, new)?;
+    println!(
+        "\n{} Comparing {} with {}\n",
+        ">>>".bold(),
+        old.bold(),
+        new.bold()
+    );
+    println!("  {:<4} {}", "Old".dimmed(), old_source);
+    println!("  {:<4} {}\n", "New".dimmed(), new_source);
+
+    let changes = contents::compare(&old_contents, &new_contents);
+    let width = changes
+        .iter()
+        .map(|c| match c {
+            Change::Added(path, _) | Change::Removed(path, _) | Change::Modified { path, .. } => {
+                path.chars().count()
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+    for change in &changes {
+        match change {
+            Change::Added(path, entry) => {
+                added += 1;
+                println!(
+                    "  {} {:<width$}  {}",
+                    "+".green().bold(),
+                    path,
+                    size(entry.size)
+                );
+            }
+            Change::Removed(path, entry) => {
+                removed += 1;
+                println!(
+                    "  {} {:<width$}  {}",
+                    "-".red().bold(),
+                    path,
+                    size(entry.size).dimmed()
+                );
+            }
+            Change::Modified { path, old, new } => {
+                modified += 1;
+                println!(
+                    "  {} {:<width$}  {} → {} {}",
+                    "~".yellow().bold(),
+                    path,
+                    size(old.size),
+                    size(new.size),
+                    format!("({})", delta(old.size, new.size)).dimmed()
+                );
+            }
+        }
+    }
+    if changes.is_empty() {
+        println!(
+            "  {} The releases hold identical files",
+            "OK".green().bold()
+        );
+    }
+
+    let total = |c: &Contents| c.values().map(|e| e.size).sum::<u64>();
+    let (old_total, new_total) = (total(&old_contents), total(&new_contents));
+    let unchanged = new_contents.len() - added - modified;
+    println!(
+        "\n  {} added, {} removed, {} modified, {} unchanged; {} → {} ({})\n",
+        added,
+        removed,
+        modified,
+        unchanged,
+        size(old_total),
+        size(new_total),
+        delta(old_total, new_total)
+    );
+    Ok(())
+}
+
+/// The files of `release`, and where they were read from
+fn load(project_dir: &Path, config: &Config, release: &str) -> Result<(String, Contents), String> {
+    let path = Path::new(release);
+    if release.ends_with(".tar.gz") || path.is_file() {
+        let contents = contents::from_archive(path)?;
+        return Ok((path.display().to_string(), contents));
+    }
+
+    let release_dir = config.release_dir(project_dir, release);
+    let archive_dir = config.bundle().layout.dir(&release_dir, Artifact::Archive);
+    let archive = std::fs::read_dir(&archive_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            p.file_name()
+                .is_some_and(|n| tarball::is_code_archive(&n.to_string_lossy()))
+        });
+    match archive {
+        Some(archive) => {
+            let contents = contents::from_archive(&archive)?;
+            let shown = archive.strip_prefix(project_dir).unwrap_or(&archive);
+            Ok((shown.display().to_string(), contents))
+        }
+        None => {
+            let contents = contents::from_tag(project_dir, release).map_err(|e| {
+                format!(
+                    "{} is not a built release, a tag or an archive: {}",
+                    release, e
+                )
+            })?;
+            Ok((format!("tag {} (not built)", release), contents))
+        }
+    }
+}
+
+/// `1.2 kB`, in decimal units as Zenodo shows file sizes
+fn size(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{} B", bytes),
+        1_000..1_000_000 => format!("{:.1} kB", bytes as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1} MB", bytes as f64 / 1e6),
+        _ => format!("{:.1} GB", bytes as f64 / 1e9),
+    }
+}
+
+fn delta(old: u64, new: u64) -> String {
+    if new >= old {
+        format!("+{}", size(new - old))
+    } else {
+        format!("-{}", size(old - new))
+    }
+}
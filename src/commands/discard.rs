@@ -0,0 +1,180 @@
+use crate::commands::Sandbox;
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::state::ReleaseState;
+use release_scholar::zenodo::ZenodoClient;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// A draft that can be discarded, with the tag it was recorded under (if any)
+struct Candidate {
+    id: u64,
+    tag: Option<String>,
+    title: String,
+}
+
+pub fn run(
+    project_dir: &Path,
+    sandbox: Sandbox,
+    id: Option<u64>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let config = Config::load(&project_dir, profile)?;
+    let sandbox = sandbox.or_default(&config);
+    let client = ZenodoClient::new(sandbox, &config.network())?;
+    let mut release_state = ReleaseState::load(&project_dir)?;
+
+    let candidates = match id {
+        Some(id) => vec![Candidate {
+            id,
+            tag: tag_for(&release_state, sandbox, id),
+            title: String::new(),
+        }],
+        None => {
+            let selected = select(drafts(&project_dir, &client, &release_state, sandbox)?)?;
+            if selected.is_empty() {
+                println!("  Nothing discarded.");
+                return Ok(());
+            }
+            selected
+        }
+    };
+
+    println!();
+    let mut forget = Vec::new();
+    for candidate in &candidates {
+        match client.get_deposition(candidate.id)? {
+            None => {
+                println!(
+                    "  {} Deposition {} does not exist — skipping",
+                    "—".dimmed(),
+                    candidate.id
+                );
+                forget.extend(candidate.tag.clone());
+            }
+            Some(d) if d.submitted => println!(
+                "  {} Deposition {} is published and cannot be deleted — skipping",
+                "!!".red().bold(),
+                candidate.id
+            ),
+            Some(_) => {
+                print!("  Discarding draft {}... ", candidate.id);
+                io::stdout().flush().ok();
+                client.delete_deposition(candidate.id)?;
+                println!("{}", "done".green());
+                forget.extend(candidate.tag.clone());
+            }
+        }
+    }
+    if !forget.is_empty() {
+        for tag in &forget {
+            release_state.env_mut(sandbox).releases.remove(tag);
+        }
+        release_state.save(&project_dir)?;
+    }
+
+    println!();
+    Ok(())
+}
+
+/// Drafts recorded in the state file, or, if none, the unpublished
+/// depositions on the account titled as this project's CITATION.cff is
+fn drafts(
+    project_dir: &Path,
+    client: &ZenodoClient,
+    release_state: &ReleaseState,
+    sandbox: bool,
+) -> Result<Vec<Candidate>, Failure> {
+    let recorded: Vec<Candidate> = release_state
+        .env(sandbox)
+        .releases
+        .iter()
+        .filter(|(_, r)| !r.published)
+        .map(|(tag, r)| Candidate {
+            id: r.deposition_id,
+            tag: Some(tag.clone()),
+            title: tag.clone(),
+        })
+        .collect();
+    if !recorded.is_empty() {
+        return Ok(recorded);
+    }
+
+    // Other projects' drafts on the same account are not ours to offer, and
+    // without a CITATION.cff there is no telling which are this project's
+    let citation_path = project_dir.join("CITATION.cff");
+    if !citation_path.exists() {
+        return Ok(Vec::new());
+    }
+    let title = CitationCff::from_file(&citation_path)?.title;
+    Ok(client
+        .list_depositions()?
+        .into_iter()
+        .filter(|d| !d.submitted && d.metadata_title() == Some(title.as_str()))
+        .map(|d| Candidate {
+            id: d.id,
+            tag: None,
+            title: match d.metadata_version() {
+                Some(version) => format!("{} {}", title, version),
+                None => title.clone(),
+            },
+        })
+        .collect())
+}
+
+/// Ask which drafts to discard
+fn select(candidates: Vec<Candidate>) -> Result<Vec<Candidate>, String> {
+    if candidates.is_empty() {
+        println!("\n  No draft depositions found.");
+        return Ok(Vec::new());
+    }
+
+    println!("\n{} Draft depositions:\n", ">>>".bold());
+    for (i, c) in candidates.iter().enumerate() {
+        println!("  [{}] {} (id: {})", i + 1, c.title, c.id);
+    }
+    print!("\n  Discard which? (numbers separated by commas, 'all', or Enter to abort) ");
+    io::stdout().flush().ok();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Cannot read input: {}", e))?;
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+    if input.eq_ignore_ascii_case("all") {
+        return Ok(candidates);
+    }
+    let mut picked = Vec::new();
+    for part in input.split(',') {
+        let n: usize = part
+            .trim()
+            .parse()
+            .map_err(|_| format!("Not a number: {}", part.trim()))?;
+        if n == 0 || n > candidates.len() {
+            return Err(format!("No draft numbered {}", n));
+        }
+        picked.push(n - 1);
+    }
+    Ok(candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| picked.contains(i))
+        .map(|(_, c)| c)
+        .collect())
+}
+
+fn tag_for(release_state: &ReleaseState, sandbox: bool, id: u64) -> Option<String> {
+    release_state
+        .env(sandbox)
+        .releases
+        .iter()
+        .find(|(_, r)| r.deposition_id == id && !r.published)
+        .map(|(tag, _)| tag.clone())
+}
@@ -0,0 +1,285 @@
+use crate::commands::check::{self, print_finding, print_unscanned};
+use crate::failure::Failure;
+use clap::Subcommand;
+use colored::Colorize;
+use git2::{Oid, Repository};
+use release_scholar::config::Config;
+use release_scholar::validation::security::{self, Finding};
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Marks the hooks written here, so they are replaced or removed without
+/// asking and a hook of your own is not
+const MARKER: &str = "# Installed by release-scholar";
+
+/// The hooks `install` writes
+const HOOKS: &[(&str, &str)] = &[("pre-commit", PRE_COMMIT), ("pre-push", PRE_PUSH)];
+
+const PRE_COMMIT: &str = r#"#!/bin/sh
+# Installed by release-scholar (`release-scholar hooks install`): stops a commit
+# whose staged changes add secrets. Skip it once with `git commit --no-verify`.
+if ! command -v release-scholar >/dev/null 2>&1; then
+    echo "release-scholar not found on PATH; pre-commit secret scan skipped" >&2
+    exit 0
+fi
+exec release-scholar check --staged
+"#;
+
+const PRE_PUSH: &str = r#"#!/bin/sh
+# Installed by release-scholar (`release-scholar hooks install`): blocks a push
+# whose commits add secrets, and runs `release-scholar check` on release tags.
+# Skip it once with `git push --no-verify`.
+if ! command -v release-scholar >/dev/null 2>&1; then
+    echo "release-scholar not found on PATH; pre-push checks skipped" >&2
+    exit 0
+fi
+exec release-scholar hooks pre-push "$1" "$2"
+"#;
+
+#[derive(Debug, Subcommand)]
+pub enum HooksAction {
+    /// Install git pre-commit and pre-push hooks that keep secrets and
+    /// failing release tags from leaving this machine
+    Install {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Replace hooks that release-scholar did not write
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hooks `install` wrote
+    Uninstall {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+    },
+    /// Run by the installed pre-push hook, with the refs being pushed on stdin
+    #[command(hide = true)]
+    PrePush {
+        /// Name of the remote being pushed to
+        remote: String,
+        /// Its URL
+        url: Option<String>,
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+    },
+}
+
+pub fn run(action: HooksAction) -> Result<(), Failure> {
+    match action {
+        HooksAction::Install { project_dir, force } => Ok(install(&project_dir, force)?),
+        HooksAction::Uninstall { project_dir } => Ok(uninstall(&project_dir)?),
+        HooksAction::PrePush {
+            remote,
+            project_dir,
+            ..
+        } => pre_push(&project_dir, &remote),
+    }
+}
+
+fn install(project_dir: &Path, force: bool) -> Result<(), String> {
+    let dir = hooks_dir(project_dir)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    // Check them all first, so nothing is half installed
+    for (name, _) in HOOKS {
+        let path = dir.join(name);
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            if !existing.contains(MARKER) && !force {
+                return Err(format!(
+                    "{} already exists and was not written by release-scholar; \
+                     pass --force to replace it",
+                    path.display()
+                ));
+            }
+        }
+    }
+    for (name, script) in HOOKS {
+        let path = dir.join(name);
+        write_executable(&path, script)?;
+        println!("  {} Installed {}", "+".green().bold(), path.display());
+    }
+    println!(
+        "\n{}",
+        "Commits and pushes now stop on secrets, and pushes on release tags that fail `check`."
+            .dimmed()
+    );
+    Ok(())
+}
+
+fn uninstall(project_dir: &Path) -> Result<(), String> {
+    let dir = hooks_dir(project_dir)?;
+    let mut removed = false;
+    for (name, _) in HOOKS {
+        let path = dir.join(name);
+        let ours = std::fs::read_to_string(&path).is_ok_and(|c| c.contains(MARKER));
+        if ours {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Cannot remove {}: {}", path.display(), e))?;
+            println!("  {} Removed {}", "-".green().bold(), path.display());
+            removed = true;
+        }
+    }
+    if !removed {
+        println!(
+            "  {} No release-scholar hooks installed",
+            "OK".green().bold()
+        );
+    }
+    Ok(())
+}
+
+/// `core.hooksPath` if set, else `.git/hooks`
+fn hooks_dir(project_dir: &Path) -> Result<PathBuf, String> {
+    let repo = Repository::open(project_dir).map_err(|e| format!("Cannot open repo: {}", e))?;
+    let configured = repo
+        .config()
+        .and_then(|c| c.get_path("core.hooksPath"))
+        .ok();
+    Ok(match (configured, repo.workdir()) {
+        (Some(path), Some(workdir)) if path.is_relative() => workdir.join(path),
+        (Some(path), _) => path,
+        (None, _) => repo.path().join("hooks"),
+    })
+}
+
+fn write_executable(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Cannot make {} executable: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Scan the commits being pushed for secrets, and check release tags being
+/// pushed. Git writes one `<local ref> <local sha> <remote ref> <remote sha>`
+/// line per ref to stdin.
+fn pre_push(project_dir: &Path, remote: &str) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let repo = Repository::open(&project_dir).map_err(|e| format!("Cannot open repo: {}", e))?;
+    // Git runs the hook without arguments of ours: the profile comes from
+    // RELEASE_SCHOLAR_PROFILE or `default_profile`, here and in the tag check
+    let tags = Config::load_lenient(&project_dir, None)
+        .0
+        .tag_pattern()
+        .unwrap_or_default();
+
+    let mut commits = Vec::new();
+    let mut seen = HashSet::new();
+    let mut release_tags = Vec::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("Cannot read the refs being pushed: {}", e))?;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [local_ref, local_sha, _, remote_sha] = fields[..] else {
+            continue;
+        };
+        // A deleted ref pushes nothing
+        let Ok(local) = Oid::from_str(local_sha).map(|oid| (!oid.is_zero()).then_some(oid)) else {
+            continue;
+        };
+        let Some(tip) = local
+            .and_then(|oid| repo.find_object(oid, None).ok())
+            .and_then(|obj| obj.peel_to_commit().ok())
+        else {
+            continue;
+        };
+        if let Some(tag) = local_ref.strip_prefix("refs/tags/") {
+            if tags.version(tag).is_some() {
+                release_tags.push((tag.to_string(), tip.id()));
+            }
+        }
+
+        let walk = || -> Result<Vec<Oid>, git2::Error> {
+            let mut walk = repo.revwalk()?;
+            walk.push(tip.id())?;
+            // What the remote has already been scanned, or was never ours to scan
+            match Oid::from_str(remote_sha) {
+                Ok(oid) if !oid.is_zero() && repo.find_commit(oid).is_ok() => walk.hide(oid)?,
+                _ => walk.hide_glob(&format!("refs/remotes/{}/*", remote))?,
+            }
+            walk.collect()
+        };
+        for oid in walk().map_err(|e| format!("Cannot list the commits being pushed: {}", e))? {
+            if seen.insert(oid) {
+                commits.push(oid);
+            }
+        }
+    }
+
+    let mut findings: Vec<(Oid, Finding)> = Vec::new();
+    let mut unscanned: Vec<(Oid, String)> = Vec::new();
+    for &oid in &commits {
+        let Ok(commit) = repo.find_commit(oid) else {
+            continue;
+        };
+        // A merge's changes are scanned in the commits it merges
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let found = security::scan_commit(&repo, &commit)
+            .map_err(|e| format!("Cannot scan commit {}: {}", short(oid), e))?;
+        findings.extend(found.findings.into_iter().map(|f| (oid, f)));
+        unscanned.extend(found.unscanned.into_iter().map(|path| (oid, path)));
+    }
+
+    println!(
+        "\n{} Scanning {} commit(s) pushed to {} for secrets",
+        ">>>".bold(),
+        commits.len(),
+        remote
+    );
+    for (oid, finding) in &findings {
+        print_finding(finding, Some(&short(*oid)));
+    }
+    for (oid, path) in &unscanned {
+        print_unscanned(path, Some(&short(*oid)));
+    }
+    let blocking = findings.iter().filter(|(_, f)| f.certain).count();
+    if findings.is_empty() {
+        println!("  {} No secrets found", "OK".green().bold());
+    }
+    println!();
+    if blocking > 0 {
+        return Err(Failure::validation(format!(
+            "Push stopped: {} likely secret(s). Remove them from the commits \
+             (rewriting history), or push with --no-verify if they are not secrets",
+            blocking
+        )));
+    }
+
+    // Git has no hook for creating tags; pushing a release tag is where it
+    // becomes public
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .map(|c| c.id());
+    for (tag, commit) in release_tags {
+        if Some(commit) != head {
+            println!(
+                "  {} {} is not checked out; run `release-scholar check` on it before releasing\n",
+                "!!".yellow().bold(),
+                tag
+            );
+            continue;
+        }
+        println!("{} Checking release tag {}", ">>>".bold(), tag.bold());
+        check::run(&project_dir, &check::CheckOptions::default()).map_err(|e| {
+            Failure::new(
+                e.kind,
+                format!("Push stopped: {} does not pass `check`", tag),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn short(oid: Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
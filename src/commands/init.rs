@@ -1,8 +1,29 @@
-use crate::config::Config;
+mod authors;
+mod changelog;
+mod license;
+mod update;
+
+pub use license::License;
+
+use crate::failure::Failure;
+use authors::Author;
 use colored::Colorize;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::zenodo::ZenodoDeposit;
+use serde_yaml::Mapping;
 use std::path::Path;
 
-pub fn run(project_dir: &Path) -> Result<(), String> {
+const PLACEHOLDER_ORCID: &str = "https://orcid.org/0000-0000-0000-0000";
+
+pub fn run(
+    project_dir: &Path,
+    zenodo_json: bool,
+    license: Option<License>,
+    changelog_from_tags: bool,
+    update: bool,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
     let project_dir = std::fs::canonicalize(project_dir)
         .map_err(|e| format!("Invalid project directory: {}", e))?;
 
@@ -10,34 +31,72 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     println!();
 
     let mut created = Vec::new();
+    let mut updated = Vec::new();
 
     // Load config (merges global + project)
-    let config = Config::load(&project_dir);
+    let config = Config::load(&project_dir, profile)?;
 
     // Resolve author info: config > git > placeholder
     let (git_name, git_email) = get_git_user_info(&project_dir);
-    let author_name = config
-        .author
-        .as_ref()
+    let configured = config.author.as_ref();
+    let configured = configured
         .and_then(|a| a.name.clone())
         .or(git_name)
-        .unwrap_or_else(|| "Your Name".to_string());
-    let author_email = config
-        .author
-        .as_ref()
-        .and_then(|a| a.email.clone())
-        .or(git_email)
-        .unwrap_or_else(|| "your.email@example.com".to_string());
-    let author_orcid = config
-        .author
-        .as_ref()
-        .and_then(|a| a.orcid.clone())
-        .unwrap_or_else(|| "https://orcid.org/0000-0000-0000-0000".to_string());
-    let forge_base = match config.forge {
-        crate::config::Forge::Codeberg => "https://codeberg.org",
-        crate::config::Forge::Github => "https://github.com",
-        crate::config::Forge::Gitlab => "https://gitlab.com",
+        .map(|name| {
+            Author::new(
+                name,
+                configured.and_then(|a| a.email.clone()),
+                configured.and_then(|a| a.orcid.clone()),
+            )
+            .with_git_email(git_email)
+        });
+    let citation_path = project_dir.join("CITATION.cff");
+    let authors = if citation_path.exists() {
+        configured.into_iter().collect()
+    } else {
+        authors::confirm(authors::candidates(
+            configured,
+            authors::from_history(&project_dir),
+        ))?
     };
+    let copyright_holder = match authors.as_slice() {
+        [] => "Your Name".to_string(),
+        authors => authors
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    let forge_base = config.forge_base_url();
+    let project_name = project_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let today = chrono_free_today();
+
+    // An empty LICENSE fails `check` just like a missing one
+    let license_path = project_dir.join("LICENSE");
+    let existing_license = std::fs::read_to_string(&license_path)
+        .ok()
+        .filter(|text| !text.trim().is_empty());
+    let (license, spdx) = match (license, &existing_license) {
+        (Some(license), _) => (Some(license), Some(license.spdx().to_string())),
+        (None, Some(text)) => match License::detect(text) {
+            Some(spdx) => (None, Some(spdx.to_string())),
+            None => (None, license::ask_spdx()?),
+        },
+        (None, None) => {
+            let license = license::choose()?;
+            (Some(license), Some(license.spdx().to_string()))
+        }
+    };
+    if existing_license.is_some() && spdx.is_none() {
+        println!(
+            "  {} The license in LICENSE was not recognized; add its SPDX id as `license` in CITATION.cff",
+            "!!".yellow().bold()
+        );
+    }
 
     // .release-scholar.toml
     let config_path = project_dir.join(".release-scholar.toml");
@@ -49,83 +108,102 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     }
 
     // CITATION.cff
-    let citation_path = project_dir.join("CITATION.cff");
-    if !citation_path.exists() {
-        let project_name = project_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy();
-
-        let (given, family) = split_name(&author_name);
-        let today = chrono_free_today();
-
-        let cff = format!(
-            r#"cff-version: 1.2.0
-title: "{}"
+    let cff = format!(
+        r#"cff-version: 1.2.0
+message: "If you use this software, please cite it using the metadata from this file."
+title: {}
 type: software
 authors:
-  - family-names: "{}"
-    given-names: "{}"
-    email: "{}"
-    orcid: "{}"
-version: "0.1.0"
-license: Apache-2.0
-date-released: "{}"
+{}version: "0.1.0"
+{}date-released: "{}"
 repository-code: "{}/YOUR-ORG/{}"
 abstract: "A brief description of the software."
 keywords:
   - research-software
 "#,
-            project_name,
-            family,
-            given,
-            author_email,
-            author_orcid,
-            today,
-            forge_base,
-            project_name
-        );
+        yaml_scalar(&project_name)?,
+        cff_authors(&authors)?,
+        spdx.map(|spdx| format!("license: {}\n", spdx))
+            .unwrap_or_default(),
+        today,
+        forge_base,
+        project_name
+    );
+    if !citation_path.exists() {
         std::fs::write(&citation_path, cff)
             .map_err(|e| format!("Cannot write CITATION.cff: {}", e))?;
         created.push("CITATION.cff");
+    } else if update {
+        let added = update::citation(&citation_path, &cff)?;
+        if !added.is_empty() {
+            updated.push(format!("CITATION.cff (added {})", added.join(", ")));
+        }
     }
 
     // CHANGELOG.md
     let changelog_path = project_dir.join("CHANGELOG.md");
     if !changelog_path.exists() {
-        let changelog = r#"# Changelog
-
-All notable changes to this project will be documented in this file.
-
-The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.0.0/),
-and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
-
-## [Unreleased]
-
-## [0.1.0] - YYYY-MM-DD
-
-### Added
-- Initial release
-"#;
-        std::fs::write(&changelog_path, changelog)
-            .map_err(|e| format!("Cannot write CHANGELOG.md: {}", e))?;
+        let tags = config.tag_pattern()?;
+        std::fs::write(
+            &changelog_path,
+            changelog::render(&project_dir, changelog_from_tags.then_some(&tags)),
+        )
+        .map_err(|e| format!("Cannot write CHANGELOG.md: {}", e))?;
         created.push("CHANGELOG.md");
     }
 
-    // LICENSE (Apache-2.0 as default)
-    let license_path = project_dir.join("LICENSE");
-    if !license_path.exists() {
-        std::fs::write(&license_path, apache2_license_text())
-            .map_err(|e| format!("Cannot write LICENSE: {}", e))?;
+    // LICENSE
+    if let (None, Some(license)) = (&existing_license, license) {
+        let year = today.get(..4).unwrap_or("YYYY");
+        let text = license.text(year, &copyright_holder, &project_name);
+        std::fs::write(&license_path, text).map_err(|e| format!("Cannot write LICENSE: {}", e))?;
         created.push("LICENSE");
     }
 
-    if created.is_empty() {
-        println!("  {} All metadata files already exist.", "OK".green());
+    // .zenodo.json (optional, for the GitHub–Zenodo integration)
+    let zenodo_json_path = project_dir.join(".zenodo.json");
+    if zenodo_json && !zenodo_json_path.exists() {
+        let cff = CitationCff::from_file(&citation_path)?;
+        let deposit = ZenodoDeposit::from_citation(&cff, &config);
+        std::fs::write(&zenodo_json_path, deposit.to_zenodo_json())
+            .map_err(|e| format!("Cannot write .zenodo.json: {}", e))?;
+        created.push(".zenodo.json");
+    }
+
+    if update {
+        // codemeta.json follows CITATION.cff
+        let codemeta_existed = project_dir.join("codemeta.json").exists();
+        if update::codemeta(&project_dir)? {
+            if codemeta_existed {
+                updated.push("codemeta.json (regenerated from CITATION.cff)".to_string());
+            } else {
+                created.push("codemeta.json");
+            }
+        }
+
+        let gitignore_existed = project_dir.join(".gitignore").exists();
+        let patterns = update::gitignore(&project_dir)?;
+        if !gitignore_existed && !patterns.is_empty() {
+            created.push(".gitignore");
+        } else if !patterns.is_empty() {
+            updated.push(format!(".gitignore (added {})", patterns.join(", ")));
+        }
+    }
+
+    if created.is_empty() && updated.is_empty() {
+        let status = if update {
+            "All metadata files are up to date."
+        } else {
+            "All metadata files already exist."
+        };
+        println!("  {} {}", "OK".green(), status);
     } else {
         for file in &created {
             println!("  {} Created {}", "+".green().bold(), file);
         }
+        for file in &updated {
+            println!("  {} Updated {}", "~".green().bold(), file);
+        }
     }
     println!();
     println!(
@@ -150,6 +228,54 @@ fn get_git_user_info(project_dir: &Path) -> (Option<String>, Option<String>) {
     (name, email)
 }
 
+/// The `authors:` entries; a placeholder to fill in when nobody is known
+/// The `authors` list of CITATION.cff, as YAML
+fn cff_authors(authors: &[Author]) -> Result<String, String> {
+    let entries: Vec<Mapping> = if authors.is_empty() {
+        vec![cff_author(
+            "Your Name",
+            "your.email@example.com",
+            PLACEHOLDER_ORCID,
+        )]
+    } else {
+        // ORCIDs only come from the config; leave a placeholder for the first author
+        authors
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let orcid = a
+                    .orcid
+                    .as_deref()
+                    .or((i == 0).then_some(PLACEHOLDER_ORCID))
+                    .unwrap_or_default();
+                cff_author(&a.name, a.email.as_deref().unwrap_or_default(), orcid)
+            })
+            .collect()
+    };
+    serde_yaml::to_string(&entries).map_err(|e| format!("Cannot write the authors: {}", e))
+}
+
+fn cff_author(name: &str, email: &str, orcid: &str) -> Mapping {
+    let (given, family) = split_name(name);
+    let mut entry = Mapping::new();
+    entry.insert("family-names".into(), family.into());
+    entry.insert("given-names".into(), given.into());
+    if !email.is_empty() {
+        entry.insert("email".into(), email.into());
+    }
+    if !orcid.is_empty() {
+        entry.insert("orcid".into(), orcid.into());
+    }
+    entry
+}
+
+/// `value` as a YAML scalar, quoted where it has to be
+fn yaml_scalar(value: &str) -> Result<String, String> {
+    serde_yaml::to_string(value)
+        .map(|yaml| yaml.trim_end().to_string())
+        .map_err(|e| format!("Cannot write {}: {}", value, e))
+}
+
 fn split_name(name: &str) -> (&str, &str) {
     match name.rsplit_once(' ') {
         Some((given, family)) => (given, family),
@@ -165,198 +291,3 @@ fn chrono_free_today() -> String {
         Err(_) => "YYYY-MM-DD".to_string(),
     }
 }
-
-fn apache2_license_text() -> &'static str {
-    r#"
-                                 Apache License
-                           Version 2.0, January 2004
-                        http://www.apache.org/licenses/
-
-   TERMS AND CONDITIONS FOR USE, REPRODUCTION, AND DISTRIBUTION
-
-   1. Definitions.
-
-      "License" shall mean the terms and conditions for use, reproduction,
-      and distribution as defined by Sections 1 through 9 of this document.
-
-      "Licensor" shall mean the copyright owner or entity authorized by
-      the copyright owner that is granting the License.
-
-      "Legal Entity" shall mean the union of the acting entity and all
-      other entities that control, are controlled by, or are under common
-      control with that entity. For the purposes of this definition,
-      "control" means (i) the power, direct or indirect, to cause the
-      direction or management of such entity, whether by contract or
-      otherwise, or (ii) ownership of fifty percent (50%) or more of the
-      outstanding shares, or (iii) beneficial ownership of such entity.
-
-      "You" (or "Your") shall mean an individual or Legal Entity
-      exercising permissions granted by this License.
-
-      "Source" form shall mean the preferred form for making modifications,
-      including but not limited to software source code, documentation
-      source, and configuration files.
-
-      "Object" form shall mean any form resulting from mechanical
-      transformation or translation of a Source form, including but
-      not limited to compiled object code, generated documentation,
-      and conversions to other media types.
-
-      "Work" shall mean the work of authorship, whether in Source or
-      Object form, made available under the License, as indicated by a
-      copyright notice that is included in or attached to the work
-      (an example is provided in the Appendix below).
-
-      "Derivative Works" shall mean any work, whether in Source or Object
-      form, that is based on (or derived from) the Work and for which the
-      editorial revisions, annotations, elaborations, or other modifications
-      represent, as a whole, an original work of authorship. For the purposes
-      of this License, Derivative Works shall not include works that remain
-      separable from, or merely link (or bind by name) to the interfaces of,
-      the Work and Derivative Works thereof.
-
-      "Contribution" shall mean any work of authorship, including
-      the original version of the Work and any modifications or additions
-      to that Work or Derivative Works thereof, that is intentionally
-      submitted to the Licensor for inclusion in the Work by the copyright owner
-      or by an individual or Legal Entity authorized to submit on behalf of
-      the copyright owner. For the purposes of this definition, "submitted"
-      means any form of electronic, verbal, or written communication sent
-      to the Licensor or its representatives, including but not limited to
-      communication on electronic mailing lists, source code control systems,
-      and issue tracking systems that are managed by, or on behalf of, the
-      Licensor for the purpose of discussing and improving the Work, but
-      excluding communication that is conspicuously marked or otherwise
-      designated in writing by the copyright owner as "Not a Contribution."
-
-      "Contributor" shall mean Licensor and any individual or Legal Entity
-      on behalf of whom a Contribution has been received by the Licensor and
-      subsequently incorporated within the Work.
-
-   2. Grant of Copyright License. Subject to the terms and conditions of
-      this License, each Contributor hereby grants to You a perpetual,
-      worldwide, non-exclusive, no-charge, royalty-free, irrevocable
-      copyright license to reproduce, prepare Derivative Works of,
-      publicly display, publicly perform, sublicense, and distribute the
-      Work and such Derivative Works in Source or Object form.
-
-   3. Grant of Patent License. Subject to the terms and conditions of
-      this License, each Contributor hereby grants to You a perpetual,
-      worldwide, non-exclusive, no-charge, royalty-free, irrevocable
-      (except as stated in this section) patent license to make, have made,
-      use, offer to sell, sell, import, and otherwise transfer the Work,
-      where such license applies only to those patent claims licensable
-      by such Contributor that are necessarily infringed by their
-      Contribution(s) alone or by combination of their Contribution(s)
-      with the Work to which such Contribution(s) was submitted. If You
-      institute patent litigation against any entity (including a
-      cross-claim or counterclaim in a lawsuit) alleging that the Work
-      or a Contribution incorporated within the Work constitutes direct
-      or contributory patent infringement, then any patent licenses
-      granted to You under this License for that Work shall terminate
-      as of the date such litigation is filed.
-
-   4. Redistribution. You may reproduce and distribute copies of the
-      Work or Derivative Works thereof in any medium, with or without
-      modifications, and in Source or Object form, provided that You
-      meet the following conditions:
-
-      (a) You must give any other recipients of the Work or
-          Derivative Works a copy of this License; and
-
-      (b) You must cause any modified files to carry prominent notices
-          stating that You changed the files; and
-
-      (c) You must retain, in the Source form of any Derivative Works
-          that You distribute, all copyright, patent, trademark, and
-          attribution notices from the Source form of the Work,
-          excluding those notices that do not pertain to any part of
-          the Derivative Works; and
-
-      (d) If the Work includes a "NOTICE" text file as part of its
-          distribution, then any Derivative Works that You distribute must
-          include a readable copy of the attribution notices contained
-          within such NOTICE file, excluding any notices that do not
-          pertain to any part of the Derivative Works, in at least one
-          of the following places: within a NOTICE text file distributed
-          as part of the Derivative Works; within the Source form or
-          documentation, if provided along with the Derivative Works; or,
-          within a display generated by the Derivative Works, if and
-          wherever such third-party notices normally appear. The contents
-          of the NOTICE file are for informational purposes only and
-          do not modify the License. You may add Your own attribution
-          notices within Derivative Works that You distribute, alongside
-          or as an addendum to the NOTICE text from the Work, provided
-          that such additional attribution notices cannot be construed
-          as modifying the License.
-
-      You may add Your own copyright statement to Your modifications and
-      may provide additional or different license terms and conditions
-      for use, reproduction, or distribution of Your modifications, or
-      for any such Derivative Works as a whole, provided Your use,
-      reproduction, and distribution of the Work otherwise complies with
-      the conditions stated in this License.
-
-   5. Submission of Contributions. Unless You explicitly state otherwise,
-      any Contribution intentionally submitted for inclusion in the Work
-      by You to the Licensor shall be under the terms and conditions of
-      this License, without any additional terms or conditions.
-      Notwithstanding the above, nothing herein shall supersede or modify
-      the terms of any separate license agreement you may have executed
-      with Licensor regarding such Contributions.
-
-   6. Trademarks. This License does not grant permission to use the trade
-      names, trademarks, service marks, or product names of the Licensor,
-      except as required for reasonable and customary use in describing the
-      origin of the Work and reproducing the content of the NOTICE file.
-
-   7. Disclaimer of Warranty. Unless required by applicable law or
-      agreed to in writing, Licensor provides the Work (and each
-      Contributor provides its Contributions) on an "AS IS" BASIS,
-      WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or
-      implied, including, without limitation, any warranties or conditions
-      of TITLE, NON-INFRINGEMENT, MERCHANTABILITY, or FITNESS FOR A
-      PARTICULAR PURPOSE. You are solely responsible for determining the
-      appropriateness of using or redistributing the Work and assume any
-      risks associated with Your exercise of permissions under this License.
-
-   8. Limitation of Liability. In no event and under no legal theory,
-      whether in tort (including negligence), contract, or otherwise,
-      unless required by applicable law (such as deliberate and grossly
-      negligent acts) or agreed to in writing, shall any Contributor be
-      liable to You for damages, including any direct, indirect, special,
-      incidental, or consequential damages of any character arising as a
-      result of this License or out of the use or inability to use the
-      Work (including but not limited to damages for loss of goodwill,
-      work stoppage, computer failure or malfunction, or any and all
-      other commercial damages or losses), even if such Contributor
-      has been advised of the possibility of such damages.
-
-   9. Accepting Warranty or Additional Liability. While redistributing
-      the Work or Derivative Works thereof, You may choose to offer,
-      and charge a fee for, acceptance of support, warranty, indemnity,
-      or other liability obligations and/or rights consistent with this
-      License. However, in accepting such obligations, You may act only
-      on Your own behalf and on Your sole responsibility, not on behalf
-      of any other Contributor, and only if You agree to indemnify,
-      defend, and hold each Contributor harmless for any liability
-      incurred by, or claims asserted against, such Contributor by reason
-      of your accepting any such warranty or additional liability.
-
-   END OF TERMS AND CONDITIONS
-
-   Copyright [yyyy] [name of copyright owner]
-
-   Licensed under the Apache License, Version 2.0 (the "License");
-   you may not use this file except in compliance with the License.
-   You may obtain a copy of the License at
-
-       http://www.apache.org/licenses/LICENSE-2.0
-
-   Unless required by applicable law or agreed to in writing, software
-   distributed under the License is distributed on an "AS IS" BASIS,
-   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
-   See the License for the specific language governing permissions and
-   limitations under the License.
-"#
-}
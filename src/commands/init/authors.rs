@@ -0,0 +1,202 @@
+use git2::{Repository, Sort};
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// A CITATION.cff author, from the config or from git history
+pub struct Author {
+    pub name: String,
+    /// An address to publish: the configured one, or one from git the user
+    /// agreed to
+    pub email: Option<String>,
+    pub orcid: Option<String>,
+    /// The address git knows, which goes into CITATION.cff only once confirmed
+    git_email: Option<String>,
+    commits: usize,
+}
+
+impl Author {
+    pub fn new(name: String, email: Option<String>, orcid: Option<String>) -> Self {
+        Author {
+            name,
+            email,
+            orcid,
+            git_email: None,
+            commits: 0,
+        }
+    }
+
+    /// `email` from git, held back until `confirm` asks about it
+    pub fn with_git_email(mut self, email: Option<String>) -> Self {
+        // A noreply address is no way to reach anyone
+        self.git_email = email.filter(|e| !e.is_empty() && !e.to_lowercase().contains("noreply"));
+        self
+    }
+
+    fn any_email(&self) -> Option<&str> {
+        self.email.as_deref().or(self.git_email.as_deref())
+    }
+
+    fn same_person(&self, other: &Author) -> bool {
+        let email = |a: &Author| a.any_email().map(str::to_lowercase);
+        self.name.eq_ignore_ascii_case(&other.name)
+            || (email(self).is_some() && email(self) == email(other))
+    }
+}
+
+/// Distinct commit authors reachable from HEAD, most commits first. The
+/// repo's .mailmap is applied, so old names and addresses fold together.
+pub fn from_history(project_dir: &Path) -> Vec<Author> {
+    let Ok(repo) = Repository::open(project_dir) else {
+        return Vec::new();
+    };
+    let mailmap = repo.mailmap().ok();
+    let Ok(mut walk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    // Sorting resets the walk, so it has to come first
+    walk.set_sorting(Sort::TIME).ok();
+    if walk.push_head().is_err() {
+        return Vec::new();
+    }
+
+    let mut authors: Vec<Author> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    for commit in walk.flatten().filter_map(|oid| repo.find_commit(oid).ok()) {
+        let raw = commit.author();
+        let sig = match &mailmap {
+            Some(mailmap) => mailmap.resolve_signature(&raw).unwrap_or(raw.to_owned()),
+            None => raw.to_owned(),
+        };
+        let (Some(name), email) = (sig.name(), sig.email()) else {
+            continue;
+        };
+        if name.trim().is_empty() || name.ends_with("[bot]") {
+            continue;
+        }
+        let name_key = format!("name:{}", name.to_lowercase());
+        let email_key = email.map(|e| format!("email:{}", e.to_lowercase()));
+
+        let known = by_key
+            .get(&name_key)
+            .or_else(|| email_key.as_ref().and_then(|k| by_key.get(k)))
+            .copied();
+        let index = known.unwrap_or_else(|| {
+            authors.push(
+                Author::new(name.to_string(), None, None).with_git_email(email.map(str::to_string)),
+            );
+            authors.len() - 1
+        });
+        authors[index].commits += 1;
+        by_key.insert(name_key, index);
+        if let Some(key) = email_key {
+            by_key.insert(key, index);
+        }
+    }
+
+    // Stable: equal counts keep first-seen (most recent) order
+    authors.sort_by_key(|a| std::cmp::Reverse(a.commits));
+    authors
+}
+
+/// The configured author first, then everyone else from the history
+pub fn candidates(configured: Option<Author>, history: Vec<Author>) -> Vec<Author> {
+    let mut all: Vec<Author> = configured.into_iter().collect();
+    for author in history {
+        match all.iter_mut().find(|a| a.same_person(&author)) {
+            Some(known) => {
+                known.commits += author.commits;
+                if known.git_email.is_none() {
+                    known.git_email = author.git_email;
+                }
+            }
+            None => all.push(author),
+        }
+    }
+    all
+}
+
+/// Let the user pick which candidates become CITATION.cff authors, and
+/// whether their addresses from git are published with them. Not at a
+/// terminal, all of them, without those addresses.
+pub fn confirm(candidates: Vec<Author>) -> Result<Vec<Author>, String> {
+    if !io::stdin().is_terminal() {
+        return Ok(candidates);
+    }
+    let mut authors = pick(candidates)?;
+    if authors
+        .iter()
+        .any(|a| a.email.is_none() && a.git_email.is_some())
+    {
+        print!("  Add their email addresses from git to CITATION.cff? They are published with the release. [y/N] ");
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut input)
+            .map_err(|e| format!("Cannot read input: {}", e))?;
+        if input.trim().eq_ignore_ascii_case("y") {
+            for author in &mut authors {
+                if author.email.is_none() {
+                    author.email = author.git_email.take();
+                }
+            }
+        }
+    }
+    Ok(authors)
+}
+
+fn pick(candidates: Vec<Author>) -> Result<Vec<Author>, String> {
+    if candidates.len() < 2 {
+        return Ok(candidates);
+    }
+
+    println!("  Authors found in the config and git history:");
+    for (i, a) in candidates.iter().enumerate() {
+        let email = a
+            .any_email()
+            .map(|e| format!(" <{}>", e))
+            .unwrap_or_default();
+        println!(
+            "    [{}] {}{} ({} commits)",
+            i + 1,
+            a.name,
+            email,
+            a.commits
+        );
+    }
+    loop {
+        print!("  Include which? (numbers separated by commas, or Enter for all) ");
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut input)
+            .map_err(|e| format!("Cannot read input: {}", e))?;
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(candidates);
+        }
+
+        let picked: Option<Vec<usize>> = input
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| (1..=candidates.len()).contains(n))
+            })
+            .collect();
+        match picked {
+            Some(picked) => {
+                return Ok(candidates
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| picked.contains(&(i + 1)))
+                    .map(|(_, a)| a)
+                    .collect());
+            }
+            None => println!("  Enter numbers between 1 and {}.", candidates.len()),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use git2::{Oid, Repository, Sort};
+use release_scholar::state;
+use release_scholar::validation::git::TagPattern;
+use std::path::Path;
+
+const HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+
+## [Unreleased]
+";
+
+/// A Keep-a-Changelog CHANGELOG.md. With `from_tags`, each existing release
+/// tag gets an entry listing the commits since the previous one, sorted
+/// into Added, Changed, Removed, Fixed and Security.
+pub fn render(project_dir: &Path, from_tags: Option<&TagPattern>) -> String {
+    let mut changelog = HEADER.to_string();
+    if let Some(tags) = from_tags {
+        for entry in tag_entries(project_dir, tags) {
+            changelog.push('\n');
+            changelog.push_str(&entry);
+        }
+    }
+    changelog
+}
+
+struct Release {
+    version: String,
+    numbers: Vec<u64>,
+    commit: Oid,
+    time: i64,
+}
+
+/// Entries for the release tags, newest first
+fn tag_entries(project_dir: &Path, tags: &TagPattern) -> Vec<String> {
+    let Ok(repo) = Repository::open(project_dir) else {
+        return Vec::new();
+    };
+    let Ok(tag_names) = repo.tag_names(None) else {
+        return Vec::new();
+    };
+
+    let mut releases: Vec<Release> = tag_names
+        .iter()
+        .flatten()
+        .filter_map(|name| {
+            let version = tags.version(name)?;
+            let commit = repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .ok()?
+                .peel_to_commit()
+                .ok()?;
+            Some(Release {
+                version: version.to_string(),
+                numbers: version.split('.').filter_map(|n| n.parse().ok()).collect(),
+                commit: commit.id(),
+                time: commit.time().seconds(),
+            })
+        })
+        .collect();
+    releases.sort_by(|a, b| b.numbers.cmp(&a.numbers));
+
+    releases
+        .iter()
+        .enumerate()
+        .map(|(i, release)| {
+            let previous = releases.get(i + 1).map(|r| r.commit);
+            let date = state::format_utc(release.time);
+            let mut entry = format!("## [{}] - {}\n", release.version, &date[..10]);
+            let changes: Vec<(Section, String)> = subjects(&repo, release.commit, previous)
+                .iter()
+                .map(|subject| classify(subject))
+                .collect();
+            for section in Section::ALL {
+                let items: Vec<&String> = changes
+                    .iter()
+                    .filter(|(s, _)| *s == section)
+                    .map(|(_, text)| text)
+                    .collect();
+                if items.is_empty() {
+                    continue;
+                }
+                entry.push_str(&format!("\n### {}\n\n", section.heading()));
+                for item in items {
+                    entry.push_str(&format!("- {}\n", item));
+                }
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Keep a Changelog's sections, in the order an entry lists them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Added,
+    Changed,
+    Removed,
+    Fixed,
+    Security,
+}
+
+impl Section {
+    const ALL: [Section; 5] = [
+        Section::Added,
+        Section::Changed,
+        Section::Removed,
+        Section::Fixed,
+        Section::Security,
+    ];
+
+    fn heading(self) -> &'static str {
+        match self {
+            Section::Added => "Added",
+            Section::Changed => "Changed",
+            Section::Removed => "Removed",
+            Section::Fixed => "Fixed",
+            Section::Security => "Security",
+        }
+    }
+}
+
+/// The section a commit belongs in and the line it gets there. A
+/// Conventional Commits type (`feat(cli)!: ...`) decides and is dropped from
+/// the line, which is capitalized; otherwise the subject's first word does ("Fix ...", "Add ...").
+/// Anything else is a change.
+fn classify(subject: &str) -> (Section, String) {
+    if let Some((kind, description)) = conventional(subject) {
+        let section = match kind.as_str() {
+            "feat" | "feature" => Section::Added,
+            "fix" | "bugfix" => Section::Fixed,
+            "security" => Section::Security,
+            "remove" => Section::Removed,
+            _ => Section::Changed,
+        };
+        let mut chars = description.chars();
+        let line = chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        return (section, line);
+    }
+    let first = subject
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let section = match first.as_str() {
+        "add" | "adds" | "added" | "implement" | "implements" | "implemented" | "introduce"
+        | "introduces" | "introduced" | "support" | "supports" => Section::Added,
+        "fix" | "fixes" | "fixed" | "correct" | "corrects" | "corrected" | "resolve"
+        | "resolves" | "resolved" => Section::Fixed,
+        "remove" | "removes" | "removed" | "drop" | "drops" | "dropped" | "delete" | "deletes"
+        | "deleted" => Section::Removed,
+        _ => Section::Changed,
+    };
+    (section, subject.to_string())
+}
+
+/// The lowercased type and the description of a Conventional Commits
+/// subject, `type(scope)!: description`
+fn conventional(subject: &str) -> Option<(String, &str)> {
+    let (prefix, description) = subject.split_once(": ")?;
+    let kind = prefix.strip_suffix('!').unwrap_or(prefix);
+    let kind = match kind.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => kind,
+    };
+    let description = description.trim();
+    if kind.is_empty() || description.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((kind.to_lowercase(), description))
+}
+
+/// Subjects of the non-merge commits in `previous..commit`
+fn subjects(repo: &Repository, commit: Oid, previous: Option<Oid>) -> Vec<String> {
+    let Ok(mut walk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME).ok();
+    if walk.push(commit).is_err() {
+        return Vec::new();
+    }
+    if let Some(previous) = previous {
+        walk.hide(previous).ok();
+    }
+    walk.flatten()
+        .filter_map(|oid| repo.find_commit(oid).ok())
+        .filter(|c| c.parent_count() <= 1)
+        .filter_map(|c| c.summary().map(str::to_string))
+        .collect()
+}
@@ -0,0 +1,185 @@
+use clap::ValueEnum;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// License templates `init` can write, by SPDX identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum License {
+    #[value(name = "MIT")]
+    Mit,
+    #[value(name = "Apache-2.0")]
+    Apache2,
+    #[value(name = "BSD-3-Clause")]
+    Bsd3Clause,
+    #[value(name = "GPL-3.0-or-later", alias = "GPL-3.0")]
+    Gpl3,
+    #[value(name = "MPL-2.0")]
+    Mpl2,
+    /// For data, documentation and other non-software releases
+    #[value(name = "CC-BY-4.0")]
+    CcBy4,
+}
+
+impl License {
+    const ALL: [License; 6] = [
+        License::Mit,
+        License::Apache2,
+        License::Bsd3Clause,
+        License::Gpl3,
+        License::Mpl2,
+        License::CcBy4,
+    ];
+
+    pub fn spdx(self) -> &'static str {
+        match self {
+            License::Mit => "MIT",
+            License::Apache2 => "Apache-2.0",
+            License::Bsd3Clause => "BSD-3-Clause",
+            License::Gpl3 => "GPL-3.0-or-later",
+            License::Mpl2 => "MPL-2.0",
+            License::CcBy4 => "CC-BY-4.0",
+        }
+    }
+
+    fn template(self) -> &'static str {
+        match self {
+            License::Mit => include_str!("licenses/MIT.txt"),
+            License::Apache2 => include_str!("licenses/Apache-2.0.txt"),
+            License::Bsd3Clause => include_str!("licenses/BSD-3-Clause.txt"),
+            License::Gpl3 => include_str!("licenses/GPL-3.0.txt"),
+            License::Mpl2 => include_str!("licenses/MPL-2.0.txt"),
+            License::CcBy4 => include_str!("licenses/CC-BY-4.0.txt"),
+        }
+    }
+
+    /// Full license text, with the copyright line (where the license has
+    /// one) filled in
+    pub fn text(self, year: &str, author: &str, project: &str) -> String {
+        self.template()
+            .replace("{{year}}", year)
+            .replace("{{author}}", author)
+            .replace("{{project}}", project)
+    }
+
+    /// The SPDX identifier of the license in an existing LICENSE file, if
+    /// it is recognized for certain. The GNU licenses are told apart by
+    /// their version line, MIT-0 from MIT by its missing notice condition.
+    pub fn detect(text: &str) -> Option<&'static str> {
+        // Line breaks and indentation differ between copies
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let gnu = [
+            (
+                "GNU AFFERO GENERAL PUBLIC LICENSE",
+                "Version 3, 19 November 2007",
+                "AGPL-3.0-or-later",
+            ),
+            (
+                "GNU LESSER GENERAL PUBLIC LICENSE",
+                "Version 3, 29 June 2007",
+                "LGPL-3.0-or-later",
+            ),
+            (
+                "GNU LESSER GENERAL PUBLIC LICENSE",
+                "Version 2.1, February 1999",
+                "LGPL-2.1-or-later",
+            ),
+            (
+                "GNU GENERAL PUBLIC LICENSE",
+                "Version 3, 29 June 2007",
+                "GPL-3.0-or-later",
+            ),
+            (
+                "GNU GENERAL PUBLIC LICENSE",
+                "Version 2, June 1991",
+                "GPL-2.0-or-later",
+            ),
+        ];
+        if text.contains("GNU") {
+            return gnu
+                .iter()
+                .find(|(name, version, _)| text.contains(name) && text.contains(version))
+                .map(|(_, _, spdx)| *spdx);
+        }
+        let markers = [
+            ("Apache License Version 2.0", "Apache-2.0"),
+            ("Mozilla Public License Version 2.0", "MPL-2.0"),
+            ("Attribution 4.0 International", "CC-BY-4.0"),
+            ("Neither the name of the copyright holder", "BSD-3-Clause"),
+        ];
+        if let Some((_, spdx)) = markers.iter().find(|(marker, _)| text.contains(marker)) {
+            return Some(spdx);
+        }
+        if text.contains("Permission is hereby granted, free of charge") {
+            let notice = "The above copyright notice and this permission notice shall be included";
+            return Some(if text.contains(notice) {
+                "MIT"
+            } else {
+                "MIT-0"
+            });
+        }
+        None
+    }
+}
+
+/// Ask for the SPDX identifier of a LICENSE file `detect` did not
+/// recognize; `None` on Enter or when not at a terminal, leaving it unset
+pub fn ask_spdx() -> Result<Option<String>, String> {
+    if !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    print!("  LICENSE is not one release-scholar recognizes. Its SPDX id [Enter to leave unset] ");
+    io::stdout().flush().ok();
+    let mut input = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut input)
+        .map_err(|e| format!("Cannot read input: {}", e))?;
+    let input = input.trim();
+    Ok((!input.is_empty()).then(|| input.to_string()))
+}
+
+/// Ask which license to use; Apache-2.0 when not at a terminal or on Enter
+pub fn choose() -> Result<License, String> {
+    let default = License::Apache2;
+    if !io::stdin().is_terminal() {
+        return Ok(default);
+    }
+
+    println!("  License for this project:");
+    for (i, license) in License::ALL.iter().enumerate() {
+        let marker = if *license == default {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("    [{}] {}{}", i + 1, license.spdx(), marker);
+    }
+    loop {
+        print!(
+            "  Choose 1-{} or an SPDX id [Enter for default] ",
+            License::ALL.len()
+        );
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut input)
+            .map_err(|e| format!("Cannot read input: {}", e))?;
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(default);
+        }
+        if let Some(license) = input
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| License::ALL.get(i))
+        {
+            return Ok(*license);
+        }
+        if let Ok(license) = License::from_str(input, true) {
+            return Ok(license);
+        }
+        println!("  Unknown license '{}'.", input);
+    }
+}
@@ -0,0 +1,101 @@
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::codemeta::{read_codemeta, CodeMeta};
+use release_scholar::validation::security::missing_gitignore_patterns;
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// Fields the CFF schema or `check` require
+const REQUIRED_CFF_FIELDS: &[&str] = &[
+    "cff-version",
+    "message",
+    "title",
+    "authors",
+    "version",
+    "license",
+    "date-released",
+];
+
+/// Add the required fields an existing CITATION.cff lacks, taking their
+/// values from `template`. They are appended as text, so comments and the
+/// order of what is already there are left alone. Returns the added fields.
+pub fn citation(path: &Path, template: &str) -> Result<Vec<&'static str>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let existing: Value = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Cannot update CITATION.cff, it is not valid YAML: {}", e))?;
+    let template: Value = serde_yaml::from_str(template)
+        .map_err(|e| format!("Invalid CITATION.cff template: {}", e))?;
+
+    let mut additions = String::new();
+    let mut added = Vec::new();
+    for field in REQUIRED_CFF_FIELDS {
+        if existing.get(field).is_some_and(|v| !v.is_null()) {
+            continue;
+        }
+        let Some(value) = template.get(field) else {
+            continue;
+        };
+        let mut entry = Mapping::new();
+        entry.insert(Value::from(*field), value.clone());
+        additions.push_str(
+            &serde_yaml::to_string(&entry).map_err(|e| format!("Cannot write {}: {}", field, e))?,
+        );
+        added.push(*field);
+    }
+
+    if !added.is_empty() {
+        let mut updated = content;
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(&additions);
+        std::fs::write(path, updated)
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    }
+    Ok(added)
+}
+
+/// Write codemeta.json from the current CITATION.cff, keeping any fields
+/// CFF has nothing to say about. Returns whether the file changed.
+pub fn codemeta(project_dir: &Path) -> Result<bool, String> {
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff"))?;
+    let path = project_dir.join("codemeta.json");
+    let existing = if path.exists() {
+        read_codemeta(&path)?
+    } else {
+        Default::default()
+    };
+
+    let fields = CodeMeta::from_citation(&cff).merged_into(existing.clone());
+    if fields == existing {
+        return Ok(false);
+    }
+    let json = serde_json::to_string_pretty(&fields)
+        .map_err(|e| format!("Cannot serialize codemeta.json: {}", e))?;
+    std::fs::write(&path, json + "\n").map_err(|e| format!("Cannot write codemeta.json: {}", e))?;
+    Ok(true)
+}
+
+/// Append the recommended patterns .gitignore is missing; returns them
+pub fn gitignore(project_dir: &Path) -> Result<Vec<&'static str>, String> {
+    let missing = missing_gitignore_patterns(project_dir);
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    let path = project_dir.join(".gitignore");
+    let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str("# Recommended by release-scholar\n");
+    for pattern in &missing {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    std::fs::write(&path, content).map_err(|e| format!("Cannot write .gitignore: {}", e))?;
+    Ok(missing)
+}
@@ -0,0 +1,78 @@
+use crate::commands::Sandbox;
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::state::ReleaseState;
+use release_scholar::zenodo::{DepositionResponse, ZenodoClient};
+use std::path::Path;
+
+pub fn run(
+    project_dir: &Path,
+    sandbox: Sandbox,
+    all: bool,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let config = Config::load(&project_dir, profile)?;
+    let sandbox = sandbox.or_default(&config);
+    let client = ZenodoClient::new(sandbox, &config.network())?;
+    let depositions = client.list_depositions()?;
+
+    let depositions: Vec<&DepositionResponse> = if all {
+        depositions.iter().collect()
+    } else {
+        let cff = CitationCff::from_file(&project_dir.join("CITATION.cff"))?;
+        let release_state = ReleaseState::load(&project_dir)?;
+        let env = release_state.env(sandbox);
+        depositions
+            .iter()
+            .filter(|d| {
+                d.metadata_title() == Some(cff.title.as_str())
+                    || env.releases.values().any(|r| r.deposition_id == d.id)
+                    || (env.concept_record_id.is_some() && d.conceptrecid == env.concept_record_id)
+            })
+            .collect()
+    };
+
+    if depositions.is_empty() {
+        println!(
+            "\n  No depositions found{}.\n",
+            if all {
+                ""
+            } else {
+                " for this project (use --all to list every deposition)"
+            }
+        );
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {}",
+        format!(
+            "{:<10} {:<10} {:<10} {:<28} {}",
+            "ID", "VERSION", "STATE", "DOI", "TITLE"
+        )
+        .bold()
+    );
+    for d in &depositions {
+        let version = d.metadata_version().unwrap_or("—");
+        let state = if d.submitted {
+            format!("{:<10}", "published").green()
+        } else {
+            format!("{:<10}", "draft").yellow()
+        };
+        println!(
+            "  {:<10} {:<10} {} {:<28} {}",
+            d.id,
+            version,
+            state,
+            d.doi.as_deref().filter(|s| !s.is_empty()).unwrap_or("—"),
+            d.metadata_title().unwrap_or("(untitled)")
+        );
+    }
+    println!("\n  {} deposition(s)\n", depositions.len());
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::failure::Failure;
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use release_scholar::metadata::sync::{self, Source};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum MetadataAction {
+    /// Make CITATION.cff, codemeta.json and the package manifest agree on
+    /// title, version, authors, license, repository and keywords
+    Sync {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Which file wins where they disagree; fields it lacks come from the others
+        #[arg(long, value_enum, default_value = "cff")]
+        from: SyncSource,
+        /// Write the changes (without this, only report them)
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SyncSource {
+    /// CITATION.cff
+    Cff,
+    /// codemeta.json
+    Codemeta,
+    /// Cargo.toml, pyproject.toml or package.json
+    Manifest,
+}
+
+impl SyncSource {
+    fn label(self) -> &'static str {
+        match self {
+            SyncSource::Cff => "CITATION.cff",
+            SyncSource::Codemeta => "codemeta.json",
+            SyncSource::Manifest => "package manifest",
+        }
+    }
+
+    fn matches(self, source: &Source) -> bool {
+        matches!(
+            (self, source),
+            (SyncSource::Cff, Source::Citation)
+                | (SyncSource::Codemeta, Source::CodeMeta)
+                | (SyncSource::Manifest, Source::Manifest(_))
+        )
+    }
+}
+
+pub fn run(action: MetadataAction) -> Result<(), Failure> {
+    match action {
+        MetadataAction::Sync {
+            project_dir,
+            from,
+            apply,
+        } => run_sync(&project_dir, from, apply),
+    }
+}
+
+fn run_sync(project_dir: &Path, from: SyncSource, apply: bool) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+
+    let sources = sync::sources(&project_dir);
+    let names: Vec<&str> = sources.iter().map(Source::file_name).collect();
+    println!("\n{} Syncing {}\n", ">>>".bold(), names.join(", "));
+    if sources.len() < 2 {
+        println!("  {} Nothing to sync with.\n", "—".dimmed());
+        return Ok(());
+    }
+    if !sources.iter().any(|s| from.matches(s)) {
+        println!(
+            "  {}\n",
+            format!(
+                "No {}; taking each field from the first file that has it",
+                from.label()
+            )
+            .dimmed()
+        );
+    }
+
+    let changes = sync::plan(&project_dir, |s| from.matches(s))?;
+    if changes.is_empty() {
+        println!("  {} All files agree.\n", "OK".green().bold());
+        return Ok(());
+    }
+
+    for source in &sources {
+        let mut changes = changes.iter().filter(|c| &c.source == source).peekable();
+        if changes.peek().is_none() {
+            continue;
+        }
+        println!("  {}", source.file_name().bold());
+        for change in changes {
+            let old = change
+                .old
+                .as_ref()
+                .map_or("(none)".to_string(), |v| v.to_string());
+            println!(
+                "    {}: {} {} {}",
+                change.field.name(),
+                old.red(),
+                "->".dimmed(),
+                change.new.to_string().green()
+            );
+            if !change.dropped.is_empty() {
+                println!(
+                    "      {} left out, as crates.io takes at most 5 keywords of up to 20 ASCII letters, digits, `_`, `-` or `+`: {}",
+                    "!!".yellow().bold(),
+                    change.dropped.join(", ")
+                );
+            }
+        }
+    }
+    println!();
+
+    if apply {
+        sync::apply(&project_dir, &changes)?;
+        println!(
+            "  {} Updated {} field(s).\n",
+            "OK".green().bold(),
+            changes.len()
+        );
+    } else {
+        println!(
+            "  {}\n",
+            "Run with --apply to write these changes.".dimmed()
+        );
+    }
+    Ok(())
+}
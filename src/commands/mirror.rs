@@ -1,22 +1,47 @@
-use crate::config::Config;
+use crate::failure::{Failure, Kind};
+use clap::Args;
 use colored::Colorize;
-use reqwest::blocking::Client;
-use serde::Serialize;
+use release_scholar::config::{Config, Forge, MirrorDirection, MirrorsConfig};
+use release_scholar::error::ForgeError;
+use release_scholar::forge;
+use release_scholar::forge::gitea::{GiteaClient, PushMirrorRequest};
+use release_scholar::forge::github::GitHubClient;
+use release_scholar::forge::gitlab::GitLabClient;
+use release_scholar::metadata::citation::CitationCff;
+use std::io::{self, Write};
 use std::path::Path;
 
-#[derive(Debug, Serialize)]
-struct PushMirrorRequest {
-    remote_address: String,
-    remote_username: String,
-    remote_password: String,
-    interval: String,
-    sync_on_commit: bool,
+#[derive(Debug, Args)]
+pub struct MirrorOptions {
+    /// List the repository's push mirrors instead of adding any
+    #[arg(long, group = "action")]
+    pub list: bool,
+    /// Remove the push mirrors to HOST (e.g. github.com), after confirming
+    #[arg(long, value_name = "HOST", group = "action")]
+    pub remove: Option<String>,
+    /// Sync the mirrors now instead of waiting for the interval
+    #[arg(long, group = "action")]
+    pub sync: bool,
+    /// Show when each mirror last synced and whether it failed
+    #[arg(long, group = "action")]
+    pub status: bool,
+    /// Copy the Codeberg description, topics, and website to the mirrors
+    #[arg(long, group = "action")]
+    pub sync_metadata: bool,
+    /// Create GitHub/GitLab repositories that don't exist yet, with the
+    /// Codeberg repository's description and visibility
+    #[arg(long, conflicts_with = "action")]
+    pub create_missing: bool,
 }
 
-pub fn run(project_dir: &Path) -> Result<(), String> {
+pub fn run(
+    project_dir: &Path,
+    options: &MirrorOptions,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
     let project_dir = std::fs::canonicalize(project_dir)
         .map_err(|e| format!("Invalid project directory: {}", e))?;
-    let config = Config::load(&project_dir);
+    let config = Config::load(&project_dir, profile)?;
 
     let mirrors = config.mirrors.as_ref().ok_or(
         "No [mirrors] section in config. Add it to your global config at:\n  \
@@ -24,53 +49,306 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
          ~/.config/release-scholar/config.toml (Linux)",
     )?;
 
-    let codeberg_token = mirrors
-        .codeberg_token
-        .as_deref()
-        .ok_or("codeberg_token not set in [mirrors] config")?;
+    let codeberg_token = mirrors.token("codeberg").ok_or(
+        "No Codeberg token: store one in the OS keyring or set codeberg_token in [mirrors] config",
+    )?;
     let codeberg_user = mirrors
         .codeberg_user
         .as_deref()
         .ok_or("codeberg_user not set in [mirrors] config")?;
 
-    // Determine repo name from directory
-    let repo_name = project_dir
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+    let (codeberg_owner, repo_name) =
+        forge::repository(&project_dir, &config, "codeberg", codeberg_user);
+    let codeberg = GiteaClient::new(
+        &config.gitea_api_url(),
+        &codeberg_owner,
+        &repo_name,
+        codeberg_token,
+        &config.network(),
+    )?;
+
+    if options.list {
+        return list(&codeberg, &codeberg_owner, &repo_name);
+    }
+    if let Some(host) = &options.remove {
+        return remove(&codeberg, host);
+    }
+    let pull = mirrors.direction() == MirrorDirection::Pull;
+    if pull && matches!(config.forge, Forge::Codeberg) {
+        return Err(Failure::config(
+            "direction = \"pull\" in [mirrors] pulls from the primary forge, so it needs forge = \"github\" or \"gitlab\"",
+        ));
+    }
+    if options.sync {
+        print!("\n  Starting mirror sync... ");
+        codeberg.sync_mirrors(pull)?;
+        println!("{}", "done".green());
+        println!(
+            "  {}\n",
+            "Syncing runs in the background; check it with `release-scholar mirror --status`."
+                .dimmed()
+        );
+        return Ok(());
+    }
+    if options.status {
+        return status(&codeberg, pull);
+    }
+    if options.sync_metadata {
+        if pull {
+            return Err(Failure::config(format!(
+                "--sync-metadata copies from Codeberg, but the mirrors pull from {}",
+                config.forge
+            )));
+        }
+        return sync_metadata(&project_dir, &config, &codeberg);
+    }
+
+    if pull {
+        return pull_mirror(&project_dir, &config, &codeberg, mirrors);
+    }
+    push_mirrors(
+        &project_dir,
+        &config,
+        &codeberg,
+        &codeberg_owner,
+        &repo_name,
+        options.create_missing,
+    )
+}
+
+fn list(codeberg: &GiteaClient, codeberg_owner: &str, repo_name: &str) -> Result<(), Failure> {
+    let mirrors = codeberg.push_mirrors()?;
+    if mirrors.is_empty() {
+        println!("\n  No push mirrors on {}/{}.\n", codeberg_owner, repo_name);
+        return Ok(());
+    }
 
     println!(
-        "\n{} Setting up push mirrors for {}/{}...\n",
+        "\n{} Push mirrors of {}/{}:\n",
         ">>>".bold(),
-        codeberg_user,
+        codeberg_owner,
         repo_name.bold()
     );
+    for mirror in &mirrors {
+        println!(
+            "  {}  every {}{}",
+            mirror.remote_address,
+            mirror.interval,
+            if mirror.sync_on_commit {
+                " and on push"
+            } else {
+                ""
+            }
+        );
+    }
+    println!();
+    Ok(())
+}
+
+fn status(codeberg: &GiteaClient, pull: bool) -> Result<(), Failure> {
+    println!();
+    if pull {
+        let repo = codeberg.repository()?;
+        if !repo.mirror {
+            return Err(Failure::config(
+                "The Codeberg repository is not a pull mirror",
+            ));
+        }
+        println!(
+            "  {} Pull mirror, every {} — last synced {}",
+            "OK".green().bold(),
+            repo.mirror_interval,
+            repo.mirror_updated.as_deref().unwrap_or("never")
+        );
+        println!();
+        return Ok(());
+    }
+
+    let mirrors = codeberg.push_mirrors()?;
+    if mirrors.is_empty() {
+        println!("  No push mirrors.\n");
+        return Ok(());
+    }
+    let mut failing = 0;
+    for mirror in &mirrors {
+        let last = mirror.last_update.as_deref().unwrap_or("never");
+        if mirror.last_error.is_empty() {
+            println!(
+                "  {} {} — last synced {}",
+                "OK".green().bold(),
+                mirror.remote_address,
+                last
+            );
+        } else {
+            failing += 1;
+            println!(
+                "  {} {} — last attempt {}",
+                "!!".red().bold(),
+                mirror.remote_address,
+                last
+            );
+            println!("     {}", mirror.last_error.trim().red());
+        }
+    }
+    println!();
+    if failing > 0 {
+        return Err(Failure::new(
+            Kind::Network,
+            format!("{} mirror(s) failed their last sync", failing),
+        ));
+    }
+    Ok(())
+}
+
+/// Make the mirrors' about sections match Codeberg's
+fn sync_metadata(
+    project_dir: &Path,
+    config: &Config,
+    codeberg: &GiteaClient,
+) -> Result<(), Failure> {
+    let mirrors = config.mirrors.clone().unwrap_or_default();
+    let network = config.network();
+    let source = codeberg.repository()?;
+    let topics = codeberg.topics()?;
+
+    println!(
+        "\n{} Copying description, {} topic(s){} to the mirrors...\n",
+        ">>>".bold(),
+        topics.len(),
+        if source.website.is_empty() {
+            ""
+        } else {
+            " and website"
+        }
+    );
+
+    if let (Some(gh_user), Some(gh_token)) = (&mirrors.github_user, mirrors.token("github")) {
+        print!("  Updating GitHub... ");
+        let (gh_owner, gh_repo) = forge::repository(project_dir, config, "github", gh_user);
+        let github = GitHubClient::new(&gh_owner, &gh_repo, gh_token, &network)?;
+        github.set_about(&source.description, &source.website)?;
+        github.set_topics(&topics)?;
+        println!("{}", "done".green());
+    }
+    if let (Some(gl_user), Some(gl_token)) = (&mirrors.gitlab_user, mirrors.token("gitlab")) {
+        print!("  Updating GitLab... ");
+        let (gl_owner, gl_repo) = forge::repository(project_dir, config, "gitlab", gl_user);
+        let gitlab = GitLabClient::new(&gl_owner, &gl_repo, gl_token, &network)?;
+        gitlab.set_about(&source.description, &topics)?;
+        println!("{}", "done".green());
+    }
+    println!();
+    Ok(())
+}
+
+fn remove(codeberg: &GiteaClient, host: &str) -> Result<(), Failure> {
+    let matching: Vec<_> = codeberg
+        .push_mirrors()?
+        .into_iter()
+        .filter(|m| points_to(&m.remote_address, host))
+        .collect();
+    if matching.is_empty() {
+        return Err(Failure::config(format!(
+            "No push mirror points to {}",
+            host
+        )));
+    }
+
+    println!(
+        "
+  Push mirrors to {}:",
+        host
+    );
+    for mirror in &matching {
+        println!("    {}", mirror.remote_address);
+    }
+    print!(
+        "
+  Remove {}? [y/N] ",
+        if matching.len() == 1 { "it" } else { "them" }
+    );
+    io::stdout().flush().ok();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Cannot read input: {}", e))?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        return Err(Failure::aborted());
+    }
 
-    let client = Client::builder()
-        .user_agent(format!("release-scholar/{}", env!("CARGO_PKG_VERSION")))
-        .build()
-        .map_err(|e| format!("Cannot create HTTP client: {}", e))?;
+    println!();
+    for mirror in &matching {
+        print!("  Removing mirror to {}... ", mirror.remote_address);
+        codeberg.delete_push_mirror(&mirror.remote_name)?;
+        println!("{}", "done".green());
+    }
+    println!();
+    Ok(())
+}
+
+/// Whether the mirror `address` is on `host`, compared exactly (so
+/// "github.com" is not "github.com.evil.example" or "mygithub.com")
+fn points_to(address: &str, host: &str) -> bool {
+    let address_host = match reqwest::Url::parse(address) {
+        Ok(url) => url.host_str().map(str::to_string),
+        // scp-like syntax: git@github.com:owner/repo.git
+        Err(_) => address.split_once(':').map(|(user_host, _)| {
+            user_host
+                .rsplit('@')
+                .next()
+                .unwrap_or(user_host)
+                .to_string()
+        }),
+    };
+    address_host.is_some_and(|h| h.eq_ignore_ascii_case(host.trim()))
+}
+
+/// Codeberg is the primary forge: push from it to GitHub and GitLab
+fn push_mirrors(
+    project_dir: &Path,
+    config: &Config,
+    codeberg: &GiteaClient,
+    codeberg_owner: &str,
+    repo_name: &str,
+    create_missing: bool,
+) -> Result<(), Failure> {
+    let mirrors = config.mirrors.clone().unwrap_or_default();
+    let network = config.network();
+    let source = if create_missing {
+        Some(codeberg.repository()?)
+    } else {
+        None
+    };
+
+    println!(
+        "\n{} Setting up push mirrors for {}/{}...\n",
+        ">>>".bold(),
+        codeberg_owner,
+        repo_name.bold()
+    );
 
     // Check existing mirrors first
-    let existing = get_existing_mirrors(&client, codeberg_user, &repo_name, codeberg_token)?;
+    let existing: Vec<String> = codeberg
+        .push_mirrors()?
+        .into_iter()
+        .map(|m| m.remote_address)
+        .collect();
 
     // GitHub mirror
-    if let (Some(gh_user), Some(gh_token)) = (&mirrors.github_user, &mirrors.github_token) {
-        let gh_url = format!("https://github.com/{}/{}.git", gh_user, repo_name);
-        if existing.iter().any(|url| url.contains("github.com")) {
+    if let (Some(gh_user), Some(gh_token)) = (&mirrors.github_user, mirrors.token("github")) {
+        let (gh_owner, gh_repo) = forge::repository(project_dir, config, "github", gh_user);
+        let gh_url = format!("https://github.com/{}/{}.git", gh_owner, gh_repo);
+        if existing.iter().any(|url| points_to(url, "github.com")) {
             println!("  {} GitHub mirror already exists — skipping", "OK".green());
         } else {
+            if let Some(source) = &source {
+                let github = GitHubClient::new(&gh_owner, &gh_repo, gh_token.clone(), &network)?;
+                ensure_exists("GitHub", &gh_owner, &gh_repo, github.repo_exists()?, || {
+                    github.create_repo(&source.description, source.private, &source.website)
+                })?;
+            }
             print!("  Adding GitHub mirror... ");
-            add_push_mirror(
-                &client,
-                codeberg_user,
-                &repo_name,
-                codeberg_token,
-                &gh_url,
-                gh_user,
-                gh_token,
-            )?;
+            codeberg.add_push_mirror(&push_mirror_request(&mirrors, &gh_url, gh_user, gh_token))?;
             println!("{}", "done".green());
             println!("    → {}", gh_url);
         }
@@ -82,21 +360,24 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     }
 
     // GitLab mirror
-    if let (Some(gl_user), Some(gl_token)) = (&mirrors.gitlab_user, &mirrors.gitlab_token) {
-        let gl_url = format!("https://gitlab.com/{}/{}.git", gl_user, repo_name);
-        if existing.iter().any(|url| url.contains("gitlab.com")) {
+    if let (Some(gl_user), Some(gl_token)) = (&mirrors.gitlab_user, mirrors.token("gitlab")) {
+        let (gl_owner, gl_repo) = forge::repository(project_dir, config, "gitlab", gl_user);
+        let gl_url = format!("https://gitlab.com/{}/{}.git", gl_owner, gl_repo);
+        if existing.iter().any(|url| points_to(url, "gitlab.com")) {
             println!("  {} GitLab mirror already exists — skipping", "OK".green());
         } else {
+            if let Some(source) = &source {
+                let gitlab = GitLabClient::new(&gl_owner, &gl_repo, gl_token.clone(), &network)?;
+                ensure_exists(
+                    "GitLab",
+                    &gl_owner,
+                    &gl_repo,
+                    gitlab.project_exists()?,
+                    || gitlab.create_project(&source.description, source.private),
+                )?;
+            }
             print!("  Adding GitLab mirror... ");
-            add_push_mirror(
-                &client,
-                codeberg_user,
-                &repo_name,
-                codeberg_token,
-                &gl_url,
-                gl_user,
-                gl_token,
-            )?;
+            codeberg.add_push_mirror(&push_mirror_request(&mirrors, &gl_url, gl_user, gl_token))?;
             println!("{}", "done".green());
             println!("    → {}", gl_url);
         }
@@ -108,86 +389,114 @@ pub fn run(project_dir: &Path) -> Result<(), String> {
     }
 
     println!(
-        "\n  {} Mirrors will sync every 8 hours and on push.\n",
-        "OK".green().bold()
+        "\n  {} Mirrors will sync every {}{}.\n",
+        "OK".green().bold(),
+        mirrors.interval(),
+        if mirrors.sync_on_commit() {
+            " and on push"
+        } else {
+            ""
+        }
     );
 
     Ok(())
 }
 
-fn get_existing_mirrors(
-    client: &Client,
+/// Create a missing destination repository before a mirror is pointed at it
+fn ensure_exists(
+    forge: &str,
     owner: &str,
-    repo: &str,
-    token: &str,
-) -> Result<Vec<String>, String> {
-    let url = format!(
-        "https://codeberg.org/api/v1/repos/{}/{}/push_mirrors",
-        owner, repo
-    );
-    let resp = client
-        .get(&url)
-        .header("Authorization", format!("token {}", token))
-        .send()
-        .map_err(|e| format!("HTTP error listing mirrors: {}", e))?;
-
-    let status = resp.status();
-    if !status.is_success() {
-        let body = resp.text().unwrap_or_default();
-        return Err(format!(
-            "Codeberg API error {} listing mirrors: {}",
-            status, body
-        ));
+    repo_name: &str,
+    exists: bool,
+    create: impl FnOnce() -> Result<(), ForgeError>,
+) -> Result<(), Failure> {
+    if exists {
+        return Ok(());
     }
-
-    let mirrors: Vec<serde_json::Value> = resp.json().unwrap_or_default();
-    Ok(mirrors
-        .iter()
-        .filter_map(|m| {
-            m.get("remote_address")
-                .and_then(|v| v.as_str())
-                .map(String::from)
-        })
-        .collect())
+    print!(
+        "  Creating {} repository {}/{}... ",
+        forge, owner, repo_name
+    );
+    create()?;
+    println!("{}", "done".green());
+    Ok(())
 }
 
-fn add_push_mirror(
-    client: &Client,
-    owner: &str,
-    repo: &str,
-    codeberg_token: &str,
+fn push_mirror_request(
+    mirrors: &MirrorsConfig,
     remote_url: &str,
     remote_user: &str,
-    remote_token: &str,
-) -> Result<(), String> {
-    let url = format!(
-        "https://codeberg.org/api/v1/repos/{}/{}/push_mirrors",
-        owner, repo
-    );
-
-    let body = PushMirrorRequest {
+    remote_token: String,
+) -> PushMirrorRequest {
+    PushMirrorRequest {
         remote_address: remote_url.to_string(),
         remote_username: remote_user.to_string(),
-        remote_password: remote_token.to_string(),
-        interval: "8h0m0s".to_string(),
-        sync_on_commit: true,
+        remote_password: remote_token,
+        interval: mirrors.interval().to_string(),
+        sync_on_commit: mirrors.sync_on_commit(),
+    }
+}
+
+/// GitHub or GitLab is the primary forge: keep an archive copy on Codeberg
+/// that pulls from it
+fn pull_mirror(
+    project_dir: &Path,
+    config: &Config,
+    codeberg: &GiteaClient,
+    mirrors: &MirrorsConfig,
+) -> Result<(), Failure> {
+    // The upstream token is optional; it is only needed for private repositories
+    let (host, service, user, token) = match config.forge {
+        Forge::Github => (
+            "github.com",
+            "github",
+            mirrors.github_user.as_deref(),
+            mirrors.token("github"),
+        ),
+        _ => (
+            "gitlab.com",
+            "gitlab",
+            mirrors.gitlab_user.as_deref(),
+            mirrors.token("gitlab"),
+        ),
     };
+    let user = user.ok_or(format!("{}_user not set in [mirrors] config", service))?;
+    let (owner, repo) = forge::repository(project_dir, config, service, user);
+    let upstream = format!("https://{}/{}/{}.git", host, owner, repo);
 
-    let resp = client
-        .post(&url)
-        .header("Authorization", format!("token {}", codeberg_token))
-        .json(&body)
-        .send()
-        .map_err(|e| format!("HTTP error adding mirror: {}", e))?;
-
-    let status = resp.status();
-    if !status.is_success() {
-        let body = resp.text().unwrap_or_default();
-        return Err(format!(
-            "Codeberg API error {} adding mirror: {}",
-            status, body
-        ));
-    }
+    println!(
+        "\n{} Setting up a Codeberg pull mirror of {}...\n",
+        ">>>".bold(),
+        upstream.bold()
+    );
+
+    let description = CitationCff::from_file(&project_dir.join("CITATION.cff"))
+        .ok()
+        .and_then(|cff| cff.abstract_text);
 
+    print!("  Creating pull mirror... ");
+    let created = codeberg.create_pull_mirror(
+        &upstream,
+        service,
+        token.as_deref(),
+        mirrors.interval(),
+        description.as_deref(),
+    )?;
+    if created {
+        println!("{}", "done".green());
+        println!(
+            "\n  {} Codeberg will pull from {} every {}.\n",
+            "OK".green().bold(),
+            host,
+            mirrors.interval()
+        );
+    } else {
+        println!("{}", "already exists".dimmed());
+        println!(
+            "\n  {} The Codeberg repository already exists. An existing repository cannot be\n  \
+             turned into a pull mirror; delete it first if it holds nothing else.\n",
+            "NOTE".dimmed()
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,141 @@
+use crate::commands::Sandbox;
+use crate::failure::Failure;
+use clap::Args;
+use colored::Colorize;
+use release_scholar::config::Config;
+use release_scholar::forge;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::state::{self, ReleaseState, TagState};
+use release_scholar::zenodo;
+use std::path::Path;
+use std::process::Command;
+
+/// Which page to open; without one, the DOI once published and the draft
+/// before that
+#[derive(Debug, Default, Args)]
+#[group(multiple = false)]
+pub struct OpenTarget {
+    /// The published record, through its DOI
+    #[arg(long)]
+    pub doi: bool,
+    /// The deposition's page on Zenodo (the draft, before publishing)
+    #[arg(long)]
+    pub deposit: bool,
+    /// The repository on its forge
+    #[arg(long)]
+    pub repo: bool,
+}
+
+pub fn run(
+    project_dir: &Path,
+    sandbox: Sandbox,
+    tag: Option<&str>,
+    target: &OpenTarget,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let config = Config::load(&project_dir, profile)?;
+    let sandbox = sandbox.or_default(&config);
+
+    let url = if target.repo {
+        repo_url(&project_dir)?
+    } else {
+        let release_state = ReleaseState::load(&project_dir)?;
+        let (tag, recorded) = recorded_release(&release_state, sandbox, tag)?;
+        let web = zenodo::web_url(sandbox);
+        let draft = format!("{}/deposit/{}", web, recorded.deposition_id);
+        if target.deposit || (!target.doi && !recorded.published) {
+            draft
+        } else if !recorded.published {
+            return Err(format!("{} is not published yet; its draft is at {}", tag, draft).into());
+        } else {
+            match &recorded.doi {
+                // Sandbox DOIs are not registered, so they do not resolve
+                Some(_) if sandbox => format!("{}/records/{}", web, recorded.deposition_id),
+                Some(doi) => format!("https://doi.org/{}", doi),
+                None => return Err(format!("No DOI is recorded for {}", tag).into()),
+            }
+        }
+    };
+
+    println!("  {} {}", "Opening".green(), url);
+    launch(&url).map_err(|e| format!("Cannot open a browser ({}); the URL is {}", e, url).into())
+}
+
+/// `tag`'s publish record, or else the one whose deposition was created
+/// last, whether or not it is published yet
+fn recorded_release<'a>(
+    release_state: &'a ReleaseState,
+    sandbox: bool,
+    tag: Option<&'a str>,
+) -> Result<(&'a str, &'a TagState), String> {
+    let releases = &release_state.env(sandbox).releases;
+    let where_ = if sandbox {
+        "the Zenodo sandbox"
+    } else {
+        "Zenodo"
+    };
+    match tag {
+        Some(tag) => releases.get(tag).map(|r| (tag, r)).ok_or_else(|| {
+            format!(
+                "{} has no deposition on {} recorded in {}",
+                tag,
+                where_,
+                state::STATE_FILE
+            )
+        }),
+        None => releases
+            .iter()
+            .max_by(|(_, a), (_, b)| a.created_at.cmp(&b.created_at))
+            .map(|(tag, r)| (tag.as_str(), r))
+            .ok_or_else(|| {
+                format!(
+                    "No deposition on {} is recorded in {} — run `release-scholar publish` first",
+                    where_,
+                    state::STATE_FILE
+                )
+            }),
+    }
+}
+
+/// The `origin` remote's web page, else CITATION.cff's `repository-code`
+fn repo_url(project_dir: &Path) -> Result<String, String> {
+    if let Some(remote) = forge::origin(project_dir) {
+        return Ok(format!(
+            "https://{}/{}/{}",
+            remote.host, remote.owner, remote.repo
+        ));
+    }
+    CitationCff::from_file(&project_dir.join("CITATION.cff"))
+        .ok()
+        .and_then(|cff| cff.repository_code)
+        .ok_or_else(|| "No `origin` remote, and no `repository-code` in CITATION.cff".to_string())
+}
+
+/// Open `url` in `$BROWSER`, or else the desktop's default browser
+fn launch(url: &str) -> std::io::Result<()> {
+    let browser = std::env::var("BROWSER")
+        .ok()
+        .filter(|b| !b.trim().is_empty());
+    let status = match browser {
+        Some(browser) => {
+            let mut parts = browser.split_whitespace();
+            Command::new(parts.next().unwrap_or_default())
+                .args(parts)
+                .arg(url)
+                .status()
+        }
+        None if cfg!(target_os = "macos") => Command::new("open").arg(url).status(),
+        None if cfg!(windows) => Command::new("cmd")
+            .args(["/C", "start", ""])
+            .arg(url)
+            .status(),
+        None => Command::new("xdg-open").arg(url).status(),
+    }?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("it exited with {}", status)))
+    }
+}
@@ -0,0 +1,246 @@
+use crate::failure::Failure;
+use colored::Colorize;
+use regex::Regex;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::zenodo::ZenodoDeposit;
+use std::path::Path;
+
+const WIDTH: usize = 76;
+
+/// Show the deposit metadata `publish` would send, as the record will read
+pub fn run(project_dir: &Path, json: bool, profile: Option<&str>) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let config = Config::load(&project_dir, profile)?;
+    let citation_path = project_dir.join("CITATION.cff");
+    if !citation_path.exists() {
+        return Err(Failure::validation(format!(
+            "CITATION.cff not found in {}. Run `release-scholar init` first.",
+            project_dir.display()
+        )));
+    }
+    let cff = CitationCff::from_file(&citation_path)?;
+    let mut deposit = ZenodoDeposit::from_citation(&cff, &config);
+    deposit.merge_zenodo_json(&project_dir)?;
+
+    if json {
+        println!("{}", deposit.to_json());
+        return Ok(());
+    }
+
+    let m = &deposit.metadata;
+    let sources = if project_dir.join(".zenodo.json").exists() {
+        "CITATION.cff, .zenodo.json"
+    } else {
+        "CITATION.cff"
+    };
+    println!(
+        "\n{} Zenodo record preview ({})\n",
+        ">>>".bold(),
+        sources.dimmed()
+    );
+
+    field("Title", &m.title.bold().to_string());
+    field("Version", m.version.as_deref().unwrap_or("-"));
+    field("Published", m.publication_date.as_deref().unwrap_or("-"));
+    field("Type", &m.upload_type);
+    field("License", m.license.as_deref().unwrap_or("-"));
+    field("Language", m.language.as_deref().unwrap_or("-"));
+    field(
+        "Access",
+        m.extra
+            .get("access_right")
+            .and_then(|v| v.as_str())
+            .unwrap_or("open"),
+    );
+
+    let creators: Vec<String> = m
+        .creators
+        .iter()
+        .map(|c| {
+            let mut details = Vec::new();
+            if let Some(orcid) = &c.orcid {
+                details.push(format!("ORCID {}", orcid));
+            }
+            if let Some(affiliation) = &c.affiliation {
+                details.push(affiliation.clone());
+            }
+            if details.is_empty() {
+                c.name.clone()
+            } else {
+                format!("{} ({})", c.name, details.join("; ").dimmed())
+            }
+        })
+        .collect();
+    list("Creators", &creators);
+    if !m.keywords.is_empty() {
+        field("Keywords", &m.keywords.join(", "));
+    }
+    let subjects: Vec<String> = m
+        .subjects
+        .iter()
+        .map(|s| format!("{} {}", s.term, s.identifier.dimmed()))
+        .collect();
+    list("Subjects", &subjects);
+    let related: Vec<String> = m
+        .related_identifiers
+        .iter()
+        .map(|r| {
+            let kind = match &r.resource_type {
+                Some(t) => format!("{}, {}", r.scheme, t),
+                None => r.scheme.clone(),
+            };
+            format!(
+                "{} {} {}",
+                r.relation,
+                r.identifier,
+                format!("({})", kind).dimmed()
+            )
+        })
+        .collect();
+    list("Related", &related);
+    for key in ["communities", "grants"] {
+        let values: Vec<String> = m
+            .extra
+            .get(key)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| {
+                v.get("identifier")
+                    .or_else(|| v.get("id"))
+                    .and_then(|i| i.as_str())
+                    .map(str::to_string)
+            })
+            .collect();
+        let mut label = key.to_string();
+        label[..1].make_ascii_uppercase();
+        list(&label, &values);
+    }
+
+    println!(
+        "\n  {}",
+        "Description (as the record page shows it):".bold()
+    );
+    let description = m.description.as_deref().unwrap_or_default();
+    if description.trim().is_empty() {
+        println!("    {}", "(none)".dimmed());
+    }
+    for paragraph in render_html(description) {
+        for line in wrap(&paragraph, WIDTH - 4) {
+            println!("    {}", line);
+        }
+        println!();
+    }
+
+    // What publishing would reject, and what would look wrong once published
+    let problems: Vec<String> = m
+        .validate()
+        .into_iter()
+        .map(|(field, message)| format!("{}: {}", field, message))
+        .collect();
+    let warnings = formatting_warnings(description);
+    for problem in &problems {
+        println!("  {} {}", "[FAIL]".red().bold(), problem);
+    }
+    for warning in &warnings {
+        println!("  {} {}", "[WARN]".yellow().bold(), warning);
+    }
+    if problems.is_empty() && warnings.is_empty() {
+        println!(
+            "  {} No formatting problems found. `publish --dry-run` also checks with Zenodo itself.",
+            "OK".green().bold()
+        );
+    }
+    println!();
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(Failure::validation(format!(
+        "Zenodo would reject {} field(s) — fix CITATION.cff or .zenodo.json",
+        problems.len()
+    )))
+}
+
+fn field(label: &str, value: &str) {
+    println!("  {:<12} {}", label.dimmed(), value);
+}
+
+/// One line per value, the label on the first
+fn list(label: &str, values: &[String]) {
+    for (i, value) in values.iter().enumerate() {
+        field(if i == 0 { label } else { "" }, value);
+    }
+}
+
+/// The paragraphs a browser would show for an HTML description: tags are
+/// dropped, and whitespace (line breaks included) collapses unless a tag
+/// breaks the line
+fn render_html(html: &str) -> Vec<String> {
+    let breaks =
+        Regex::new(r"(?i)<br\s*/?>|</?(p|div|ul|ol|h[1-6]|blockquote|pre)\b[^>]*>").unwrap();
+    let items = Regex::new(r"(?i)<li\b[^>]*>").unwrap();
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    let text = html.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = breaks.replace_all(&text, "\n");
+    let text = items.replace_all(&text, "\n• ");
+    let text = tags.replace_all(&text, "");
+    text.split('\n')
+        .map(|p| decode_entities(p.trim()))
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Zenodo treats the description as HTML: plain-text line breaks disappear
+/// and Markdown is shown as typed
+fn formatting_warnings(description: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let has_html = Regex::new(r"<[a-zA-Z][^>]*>")
+        .unwrap()
+        .is_match(description);
+    if !has_html && description.trim().contains('\n') {
+        warnings.push(
+            "description has line breaks but no HTML; Zenodo joins it into one paragraph (use <p>...</p>)"
+                .to_string(),
+        );
+    }
+    let markdown =
+        Regex::new(r"(?m)\*\*[^*\n]+\*\*|^\s*#{1,6} |^\s*[-*] |\[[^\]\n]+\]\([^)\n]+\)|`[^`\n]+`")
+            .unwrap();
+    if let Some(found) = markdown.find(description) {
+        warnings.push(format!(
+            "description looks like Markdown ('{}'), which Zenodo shows as typed; use HTML",
+            found.as_str().trim()
+        ));
+    }
+    warnings
+}
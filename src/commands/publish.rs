@@ -1,13 +1,97 @@
-use crate::metadata::citation::CitationCff;
-use crate::metadata::zenodo::ZenodoDeposit;
-use crate::zenodo::ZenodoClient;
+use crate::commands::Sandbox;
+use crate::failure::Failure;
+use clap::Args;
 use colored::Colorize;
+use regex::Regex;
+use release_scholar::announce::{self, Announcement};
+use release_scholar::archive::{checksum, tarball};
+use release_scholar::config::{Artifact, BadgeDoi, BadgesConfig, Config};
+use release_scholar::error::{ForgeError, IoError, ZenodoError};
+use release_scholar::forge::gitea::GiteaClient;
+use release_scholar::forge::github::GitHubClient;
+use release_scholar::forge::gitlab::{self, GitLabClient};
+use release_scholar::forge::{self, Release, ReleaseTarget};
+use release_scholar::hooks::{self, Stage};
+use release_scholar::metadata;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::metadata::doi::MintedDoi;
+use release_scholar::metadata::ledger;
+use release_scholar::metadata::zenodo::ZenodoDeposit;
+use release_scholar::state::{self, ReleaseState, TagState};
+use release_scholar::validation::git;
+use release_scholar::zenodo::{self, DepositionResponse, ZenodoClient};
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 
-pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), String> {
+const MAX_CONCURRENT_UPLOADS: usize = 4;
+
+#[derive(Debug, Args)]
+pub struct PublishOptions {
+    /// Actually publish (without this, creates a draft only)
+    #[arg(long)]
+    pub confirm: bool,
+    /// Re-apply CITATION.cff metadata to the already published record for this tag
+    #[arg(long)]
+    pub update_metadata: bool,
+    /// Upload only the source archive, not the rest of the bundle
+    #[arg(long)]
+    pub archive_only: bool,
+    /// Validate metadata (locally and on a throwaway sandbox draft) without publishing
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Also create a release with the bundle files on a forge (repeatable)
+    #[arg(long = "forge-release", value_enum)]
+    pub forge_releases: Vec<ReleaseTarget>,
+    /// Directory the bundle was built in, if `build --output-dir` was used
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+    /// Commit the README badge and DOI updates after publishing, and push
+    #[arg(long)]
+    pub commit: bool,
+    /// Before tagging: create the draft with a reserved DOI and write it into
+    /// CITATION.cff, codemeta.json and the README badge, so the release's
+    /// files contain it; tag, build and publish afterwards
+    #[arg(long, conflicts_with_all = ["confirm", "update_metadata", "dry_run", "archive_only", "forge_releases"])]
+    pub reserve_doi: bool,
+    /// With --update-metadata: the record to update, when the state file
+    /// does not know it
+    #[arg(long, requires = "update_metadata")]
+    pub id: Option<u64>,
+    /// Do not post the release to the `[announce]` channels
+    #[arg(long)]
+    pub no_announce: bool,
+}
+
+pub fn run(
+    project_dir: &Path,
+    sandbox: Sandbox,
+    options: &PublishOptions,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let confirm = options.confirm;
+    let archive_only = options.archive_only;
+    let forge_releases = options.forge_releases.as_slice();
     let project_dir = std::fs::canonicalize(project_dir)
         .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = &options.output_dir {
+        config.set_output_dir(dir)?;
+    }
+    let sandbox = sandbox.or_default(&config);
+
+    if options.dry_run {
+        return run_dry_run(&project_dir, &config, archive_only);
+    }
+
+    if options.update_metadata {
+        return run_update_metadata(&project_dir, &config, sandbox, confirm, options.id);
+    }
+
+    if options.commit {
+        ensure_clean(&project_dir, &DOI_FILES)?;
+    }
 
     // Safety prompt for production
     if !sandbox && !confirm {
@@ -24,12 +108,15 @@ pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), Strin
             .read_line(&mut input)
             .map_err(|e| format!("Cannot read input: {}", e))?;
         if !input.trim().eq_ignore_ascii_case("y") {
-            println!("  Aborted.");
-            return Ok(());
+            return Err(Failure::aborted());
         }
         println!();
     }
 
+    if options.reserve_doi {
+        return run_reserve_doi(&project_dir, &config, sandbox, options.commit);
+    }
+
     if !sandbox && confirm {
         println!(
             "\n  {} You are about to {} on {}.",
@@ -45,38 +132,32 @@ pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), Strin
             .read_line(&mut input)
             .map_err(|e| format!("Cannot read input: {}", e))?;
         if input.trim() != "publish" {
-            println!("  Aborted.");
-            return Ok(());
+            return Err(Failure::aborted());
         }
         println!();
     }
 
     // Determine version from git tag
-    let version = get_version(&project_dir)?;
-    let tag = format!("v{}", version);
+    let release = git::release_tag(&project_dir, &config.tag_pattern()?)?;
+    let (version, tag) = (release.version, release.tag);
 
-    let config = crate::config::Config::load(&project_dir);
-    let release_dir = project_dir.join(&config.archive_dir).join(&tag);
+    let release_dir = config.release_dir(&project_dir, &tag);
 
     if !release_dir.exists() {
-        return Err(format!(
+        return Err(Failure::from(format!(
             "Release bundle not found at {}. Run `release-scholar build` first.",
             release_dir.display()
-        ));
+        )));
     }
 
     // Find the archive file
-    let archive_path = find_archive(&release_dir)?;
-    let archive_name = archive_path
-        .file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
+    let archive_path = find_archive(&release_dir, &config)?;
 
     // Load citation metadata
     let citation_path = project_dir.join("CITATION.cff");
     let cff = CitationCff::from_file(&citation_path)?;
-    let deposit = ZenodoDeposit::from_citation(&cff, &config);
+    let mut deposit = ZenodoDeposit::from_citation(&cff, &config);
+    deposit.merge_zenodo_json(&project_dir)?;
 
     let env_label = if sandbox {
         "SANDBOX".yellow().bold()
@@ -89,29 +170,120 @@ pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), Strin
         tag.bold(),
         env_label
     );
+    if let Some(profile) = &config.active_profile {
+        println!("  {}\n", format!("Profile: {}", profile).dimmed());
+    }
+
+    hooks::run(
+        &project_dir,
+        config.hooks.as_ref(),
+        Stage::PrePublish,
+        &hooks::Context {
+            tag: &tag,
+            version: &version,
+            bundle: Some(&release_dir),
+            doi: None,
+            sandbox,
+        },
+    )?;
 
     // Connect to Zenodo
-    let client = ZenodoClient::new(sandbox)?;
+    let client = ZenodoClient::new(sandbox, &config.network())?;
+    print!("  Checking token scopes... ");
+    io::stdout().flush().ok();
+    client.check_scopes(confirm)?;
+    println!("{}", "done".green());
+    let mut release_state = ReleaseState::load(&project_dir)?;
 
-    // Step 1: Create deposition
-    print!("  Creating deposition... ");
-    let deposition = client.create_deposition()?;
+    // Step 1: Create deposition, or resume the draft left by an interrupted run
+    let resumed = find_draft(&client, &mut release_state, sandbox, &tag, &cff, &version)?;
+    let deposition = match resumed {
+        Some(draft) => {
+            match draft.reserved_doi() {
+                Some(doi) => {
+                    println!(
+                        "  Resuming draft deposition... {} (id: {}, reserved DOI {})",
+                        "found".green(),
+                        draft.id,
+                        doi
+                    );
+                    // Keep the reservation through the metadata update
+                    deposit
+                        .metadata
+                        .extra
+                        .insert("prereserve_doi".to_string(), true.into());
+                }
+                None => println!(
+                    "  Resuming draft deposition... {} (id: {})",
+                    "found".green(),
+                    draft.id
+                ),
+            }
+            draft
+        }
+        None => {
+            // A new version of the record the earlier releases went to, so
+            // they all share one concept DOI
+            let deposition = match release_state.env(sandbox).concept_record_id.clone() {
+                Some(concept) => {
+                    print!("  Creating new version of record {}... ", concept);
+                    io::stdout().flush().ok();
+                    client.new_version(&concept, &cff.title, &version, false)?
+                }
+                None => {
+                    print!("  Creating deposition... ");
+                    client.create_deposition(&cff.title, &version)?
+                }
+            };
+            println!("{} (id: {})", "done".green(), deposition.id);
+            deposition
+        }
+    };
     let deposition_id = deposition.id;
     let bucket_url = deposition
         .links
         .bucket
         .ok_or("No bucket URL in deposition response")?;
-    println!("{} (id: {})", "done".green(), deposition_id);
+    release_state
+        .env_mut(sandbox)
+        .releases
+        .entry(tag.clone())
+        .or_insert_with(|| TagState {
+            deposition_id,
+            created_at: state::now_utc(),
+            ..Default::default()
+        });
+    release_state.save(&project_dir)?;
 
-    // Step 2: Upload archive
-    print!("  Uploading {}... ", archive_name);
-    let file_resp = client.upload_file(&bucket_url, &archive_path, &archive_name)?;
-    println!(
-        "{} ({} bytes, checksum: {})",
-        "done".green(),
-        file_resp.size,
-        file_resp.checksum
-    );
+    // Step 2: Upload files (skipping any a previous run already uploaded)
+    let files = if archive_only {
+        vec![archive_path.clone()]
+    } else {
+        bundle_files(&release_dir, &archive_path, &config)?
+    };
+    let mut pending = Vec::new();
+    for path in files.iter().cloned() {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let already_uploaded = release_state
+            .env(sandbox)
+            .releases
+            .get(&tag)
+            .is_some_and(|r| r.uploads.contains_key(&name));
+        if already_uploaded {
+            println!("  Uploading {}... {}", name, "already uploaded".dimmed());
+        } else {
+            pending.push((name, path));
+        }
+    }
+    upload_all(
+        &client,
+        &bucket_url,
+        &pending,
+        &mut release_state,
+        sandbox,
+        &tag,
+        &project_dir,
+    )?;
 
     // Step 3: Update metadata
     print!("  Setting metadata... ");
@@ -130,13 +302,183 @@ pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), Strin
         let default_doi_url = format!("https://doi.org/{}", doi);
         let doi_url = published.doi_url.as_deref().unwrap_or(&default_doi_url);
 
+        // The concept is the first release's; later ones are versions of it
+        let env = release_state.env_mut(sandbox);
+        if env.concept_doi.is_none() {
+            env.concept_doi = published.conceptdoi.clone();
+        }
+        if env.concept_record_id.is_none() {
+            env.concept_record_id = published.conceptrecid.clone();
+        }
+        if let Some(entry) = env.releases.get_mut(&tag) {
+            entry.published = true;
+            entry.doi = published.doi.clone();
+            entry.published_at = Some(state::now_utc());
+        }
+        release_state.save(&project_dir)?;
+
         println!("\n  {} Deposit published!", "OK".green().bold());
         println!("  DOI:     {}", doi.bold());
         println!("  URL:     {}", doi_url);
         println!("  View at: {}", web_url);
 
-        // Auto-add DOI badge to README
-        add_doi_badge(&project_dir, doi, doi_url, &tag)?;
+        // Auto-add DOI badges to README, and record the DOIs in the metadata.
+        // Sandbox DOIs do not resolve, so they go nowhere.
+        let concept_doi = release_state.env(sandbox).concept_doi.as_deref();
+        let mut updated = Vec::new();
+        let ours = release_state.env(sandbox).dois();
+        if !sandbox && add_doi_badges(&project_dir, &config.badges(), doi, concept_doi, &ours)? {
+            updated.push("README.md");
+        }
+        if let (Some(doi), false, true) = (
+            published.doi.as_deref(),
+            sandbox,
+            config.doi_links().metadata,
+        ) {
+            let minted = MintedDoi {
+                version: doi,
+                concept: concept_doi,
+                tag: &tag,
+            };
+            match metadata::doi::write_back(&project_dir, &minted) {
+                Ok(files) => {
+                    for file in &files {
+                        println!(
+                            "  {} Recorded DOI {} in {}",
+                            "+".green().bold(),
+                            minted.canonical(),
+                            file
+                        );
+                    }
+                    updated.extend(files);
+                }
+                Err(e) => println!("  {} Could not record the DOI: {}", "!!".yellow().bold(), e),
+            }
+        }
+        if let (Some(doi), false, true) =
+            (published.doi.as_deref(), sandbox, config.doi_links().ledger)
+        {
+            let record_url = format!("{}/records/{}", client.base_web_url(), deposition_id);
+            match add_to_ledger(&project_dir, &tag, doi, &archive_path, &record_url) {
+                Ok(true) => {
+                    println!(
+                        "  {} Added {} to {}",
+                        "+".green().bold(),
+                        tag,
+                        ledger::FILE_NAME
+                    );
+                    updated.push(ledger::FILE_NAME);
+                }
+                Ok(false) => {}
+                Err(e) => println!(
+                    "  {} Could not update {}: {}",
+                    "!!".yellow().bold(),
+                    ledger::FILE_NAME,
+                    e
+                ),
+            }
+        }
+        let message = format!("Add DOI for {}", tag);
+        if !updated.is_empty() && options.commit {
+            if let Err(e) = commit_and_push(&project_dir, &updated, &message) {
+                println!("  {} {}", "!!".yellow().bold(), e);
+            }
+        } else if !updated.is_empty() {
+            println!(
+                "  {}",
+                format!(
+                    "Commit and push to update: git add {} && git commit -m \"{}\" (or use --commit)",
+                    updated.join(" "),
+                    message
+                )
+                .dimmed()
+            );
+        }
+
+        // The deposit is already published, so like linking the DOI below,
+        // a forge that fails only warns and the rest of the release goes on
+        if !forge_releases.is_empty() {
+            println!();
+            let warn = |target: ReleaseTarget, e: &dyn std::fmt::Display| {
+                println!(
+                    "  {} Could not create the {} release: {}\n     {}",
+                    "!!".yellow().bold(),
+                    target.label(),
+                    e,
+                    format!(
+                        "The deposit is published; attach the files in {} to a release by hand.",
+                        release_dir.display()
+                    )
+                    .dimmed()
+                )
+            };
+            match tag_commit(&project_dir, &tag) {
+                Ok(commit) => {
+                    let release = Release {
+                        tag: &tag,
+                        commit,
+                        version: &version,
+                        name: format!("{} {}", cff.title, tag),
+                        description: release_notes(&release_dir, &config)
+                            .or_else(|| forge::changelog_section(&project_dir, &version))
+                            .unwrap_or_else(|| format!("Release {}", tag)),
+                    };
+                    for &target in forge_releases {
+                        if let Err(e) =
+                            create_forge_release(&project_dir, &config, target, &release, &files)
+                        {
+                            println!("{}", "failed".red());
+                            warn(target, &e.message);
+                        }
+                    }
+                }
+                Err(e) => forge_releases.iter().for_each(|&target| warn(target, &e)),
+            }
+        }
+
+        if sandbox {
+            println!(
+                "\n  {}",
+                "Sandbox DOIs don't resolve — not linking or announcing this one.".dimmed()
+            );
+        } else if let Some(doi) = published.doi.as_deref() {
+            let concept_url = release_state
+                .env(sandbox)
+                .concept_doi
+                .as_ref()
+                .map(|d| format!("https://doi.org/{}", d));
+            link_doi(
+                &project_dir,
+                &config,
+                &tag,
+                doi,
+                doi_url,
+                concept_url.as_deref(),
+            );
+            if !options.no_announce {
+                let announcement = Announcement::new(&project_dir, &cff.title, &version, doi);
+                announce(&config, &announcement);
+            }
+        }
+
+        // The record is already public, so a failing hook is reported, not fatal
+        if let Some(doi) = published.doi.as_deref() {
+            let hook_context = hooks::Context {
+                tag: &tag,
+                version: &version,
+                bundle: Some(&release_dir),
+                doi: Some(doi),
+                sandbox,
+            };
+            if let Err(e) = hooks::run(
+                &project_dir,
+                config.hooks.as_ref(),
+                Stage::PostPublish,
+                &hook_context,
+            ) {
+                println!("  {} {}", "!!".yellow().bold(), e);
+            }
+        }
     } else {
         println!(
             "\n  {} Draft deposit created (not yet published).",
@@ -148,104 +490,974 @@ pub fn run(project_dir: &Path, sandbox: bool, confirm: bool) -> Result<(), Strin
             project_dir.display(),
             if sandbox { " --sandbox" } else { "" }
         );
+        if !forge_releases.is_empty() {
+            println!(
+                "  {}",
+                "Forge releases are created once the deposit is published (--confirm).".dimmed()
+            );
+        }
     }
 
+    println!(
+        "\n  {}",
+        format!(
+            "Release state recorded in {}",
+            ReleaseState::path(&project_dir).display()
+        )
+        .dimmed()
+    );
     println!();
     Ok(())
 }
 
-fn add_doi_badge(project_dir: &Path, doi: &str, doi_url: &str, tag: &str) -> Result<(), String> {
-    let readme_path = project_dir.join("README.md");
-    if !readme_path.exists() {
-        return Ok(());
+/// Create the release for `tag` on a forge, with the bundle files attached
+fn create_forge_release(
+    project_dir: &Path,
+    config: &Config,
+    target: ReleaseTarget,
+    release: &Release,
+    files: &[PathBuf],
+) -> Result<(), Failure> {
+    let mirrors = config.mirrors.clone().unwrap_or_default();
+    match target {
+        ReleaseTarget::Codeberg => {
+            let user = mirrors
+                .codeberg_user
+                .as_deref()
+                .ok_or("codeberg_user not set in [mirrors] config")?;
+            let token = mirrors.token("codeberg").ok_or(
+                "No Codeberg token: store one with `release-scholar auth login --service codeberg` \
+                 or set codeberg_token in [mirrors] config",
+            )?;
+            let (owner, repo) = forge::repository(project_dir, config, "codeberg", user);
+            let client = GiteaClient::new(
+                &config.gitea_api_url(),
+                &owner,
+                &repo,
+                token,
+                &config.network(),
+            )?;
+
+            print!("  Creating {} release... ", target.label());
+            io::stdout().flush().ok();
+            // A release an earlier run created may lack the files whose
+            // upload failed then
+            let created = match client.create_release(release)? {
+                Some(created) => {
+                    println!("{}", "done".green());
+                    created
+                }
+                None => {
+                    println!("{}", "already exists".dimmed());
+                    client
+                        .get_release(release.tag)?
+                        .ok_or_else(|| format!("Cannot find the release for {}", release.tag))?
+                }
+            };
+            for path in files {
+                let name = path.file_name().unwrap().to_string_lossy();
+                if created.assets.iter().any(|a| a.name == name) {
+                    continue;
+                }
+                print!("  Attaching {}... ", name);
+                io::stdout().flush().ok();
+                client.upload_asset(created.id, path)?;
+                println!("{}", "done".green());
+            }
+            println!("    → {}", created.html_url);
+        }
+        ReleaseTarget::Gitlab => {
+            let user = mirrors
+                .gitlab_user
+                .as_deref()
+                .ok_or("gitlab_user not set in [mirrors] config")?;
+            let token = mirrors.token("gitlab").ok_or(
+                "No GitLab token: store one with `release-scholar auth login --service gitlab` \
+                 or set gitlab_token in [mirrors] config",
+            )?;
+            let (owner, repo) = forge::repository(project_dir, config, "gitlab", user);
+            let client = GitLabClient::new(&owner, &repo, token, &config.network())?;
+
+            let mut links = Vec::new();
+            for path in files {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                print!("  Uploading {} to GitLab packages... ", name);
+                io::stdout().flush().ok();
+                links.push((name, client.upload_package_file(release.version, path)?));
+                println!("{}", "done".green());
+            }
+
+            print!("  Creating {} release... ", target.label());
+            io::stdout().flush().ok();
+            match client.create_release(release, &links)? {
+                gitlab::Created::New(url) => println!("{}\n    → {}", "done".green(), url),
+                gitlab::Created::Exists(url) => {
+                    println!("{}\n    → {}", "already exists".dimmed(), url)
+                }
+            }
+        }
     }
+    Ok(())
+}
 
-    let content = std::fs::read_to_string(&readme_path)
-        .map_err(|e| format!("Cannot read README.md: {}", e))?;
+/// Point the forges at the new DOI: append it to the tag's release notes and
+/// optionally make the concept DOI the repository website. Failures only warn,
+/// since the deposit is already published.
+fn link_doi(
+    project_dir: &Path,
+    config: &Config,
+    tag: &str,
+    doi: &str,
+    doi_url: &str,
+    concept_url: Option<&str>,
+) {
+    let links = config.doi_links();
+    if !links.release_notes && !links.website {
+        return;
+    }
+    let Some(mirrors) = config.mirrors.as_ref() else {
+        return;
+    };
+    let network = config.network();
+    let website = concept_url.filter(|_| links.website);
+
+    let mut steps: Vec<(&str, Result<Vec<String>, ForgeError>)> = Vec::new();
+    if let (Some(user), Some(token)) = (&mirrors.codeberg_user, mirrors.token("codeberg")) {
+        let (owner, repo) = forge::repository(project_dir, config, "codeberg", user);
+        let result = GiteaClient::new(&config.gitea_api_url(), &owner, &repo, token, &network)
+            .and_then(|client| {
+                let mut done = Vec::new();
+                if links.release_notes {
+                    if let Some(release) = client.get_release(tag)? {
+                        if let Some(body) = forge::with_doi_badge(&release.body, doi, doi_url) {
+                            client.update_release_body(release.id, &body)?;
+                            done.push("release notes".to_string());
+                        }
+                    }
+                }
+                if let Some(url) = website {
+                    client.set_website(url)?;
+                    done.push("website".to_string());
+                }
+                Ok(done)
+            });
+        steps.push(("Codeberg", result));
+    }
+    if let (Some(user), Some(token)) = (&mirrors.github_user, mirrors.token("github")) {
+        let (owner, repo) = forge::repository(project_dir, config, "github", user);
+        let result = GitHubClient::new(&owner, &repo, token, &network).and_then(|client| {
+            let mut done = Vec::new();
+            if links.release_notes {
+                if let Some(release) = client.get_release(tag)? {
+                    let body = release.body.unwrap_or_default();
+                    if let Some(body) = forge::with_doi_badge(&body, doi, doi_url) {
+                        client.update_release_body(release.id, &body)?;
+                        done.push("release notes".to_string());
+                    }
+                }
+            }
+            if let Some(url) = website {
+                client.set_homepage(url)?;
+                done.push("website".to_string());
+            }
+            Ok(done)
+        });
+        steps.push(("GitHub", result));
+    }
+    if let (Some(user), Some(token)) = (&mirrors.gitlab_user, mirrors.token("gitlab")) {
+        // GitLab projects have no website field, so only the release notes
+        let (owner, repo) = forge::repository(project_dir, config, "gitlab", user);
+        let result = GitLabClient::new(&owner, &repo, token, &network).and_then(|client| {
+            let mut done = Vec::new();
+            if links.release_notes {
+                if let Some(description) = client.release_description(tag)? {
+                    if let Some(body) = forge::with_doi_badge(&description, doi, doi_url) {
+                        client.update_release_description(tag, &body)?;
+                        done.push("release notes".to_string());
+                    }
+                }
+            }
+            Ok(done)
+        });
+        steps.push(("GitLab", result));
+    }
+
+    for (forge, result) in steps {
+        match result {
+            Ok(done) if done.is_empty() => {}
+            Ok(done) => println!(
+                "  {} Linked DOI on {} ({})",
+                "+".green().bold(),
+                forge,
+                done.join(", ")
+            ),
+            Err(e) => println!(
+                "  {} Could not link DOI on {}: {}",
+                "!!".yellow().bold(),
+                forge,
+                e
+            ),
+        }
+    }
+}
+
+/// Post the release to the `[announce]` chat channels. Failures only warn,
+/// since the deposit is already published.
+fn announce(config: &Config, announcement: &Announcement) {
+    for (channel, result) in announce::post_all(&config.announce(), &config.network(), announcement)
+    {
+        match result {
+            Ok(()) => println!("  {} Announced on {}", "+".green().bold(), channel),
+            Err(e) => println!(
+                "  {} Could not announce on {}: {}",
+                "!!".yellow().bold(),
+                channel,
+                e
+            ),
+        }
+    }
+}
+
+/// Validate the deposit without publishing anything: locally against Zenodo's
+/// field constraints, then server-side on a throwaway sandbox draft
+fn run_dry_run(project_dir: &Path, config: &Config, archive_only: bool) -> Result<(), Failure> {
+    let release = git::release_tag(project_dir, &config.tag_pattern()?)?;
+    let tag = release.tag;
+
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff"))?;
+    let mut deposit = ZenodoDeposit::from_citation(&cff, config);
+    deposit.merge_zenodo_json(project_dir)?;
+
+    println!(
+        "{} Dry run for {} — nothing will be published\n",
+        ">>>".bold(),
+        tag.bold()
+    );
+
+    let release_dir = config.release_dir(project_dir, &tag);
+    match find_archive(&release_dir, config) {
+        Ok(archive_path) => {
+            let files = if archive_only {
+                vec![archive_path]
+            } else {
+                bundle_files(&release_dir, &archive_path, config)?
+            };
+            println!("  Would upload:");
+            for file in &files {
+                let size = std::fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+                println!(
+                    "    {} ({} bytes)",
+                    file.file_name().unwrap().to_string_lossy(),
+                    size
+                );
+            }
+        }
+        Err(_) => println!(
+            "  {} No bundle at {} — run `release-scholar build` before publishing",
+            "!!".yellow().bold(),
+            release_dir.display()
+        ),
+    }
+
+    print!("\n  Checking metadata against Zenodo's constraints... ");
+    let mut problems = deposit.metadata.validate();
+    if problems.is_empty() {
+        println!("{}", "ok".green());
+    } else {
+        println!("{}", "problems found".red());
+    }
+
+    print!("  Validating on the Zenodo sandbox... ");
+    io::stdout().flush().ok();
+    let remote = ZenodoClient::new(true, &config.network())
+        .and_then(|client| client.validate_remote(&deposit));
+    match remote {
+        Ok(errors) if errors.is_empty() => println!("{}", "accepted".green()),
+        Ok(errors) => {
+            println!("{}", "rejected".red());
+            for error in errors {
+                if !problems.contains(&error) {
+                    problems.push(error);
+                }
+            }
+        }
+        Err(e) => println!("{} ({})", "skipped".yellow(), e),
+    }
 
-    // Check if there's already a DOI badge
-    if content.contains("doi.org") && content.contains("zenodo") {
+    if problems.is_empty() {
         println!(
-            "\n  {} README.md already has a DOI badge — skipping.",
-            "NOTE".dimmed()
+            "\n  {} Metadata would be accepted by Zenodo.\n",
+            "OK".green().bold()
         );
         return Ok(());
     }
+    println!();
+    for (field, message) in &problems {
+        println!("  {} {}: {}", "[FAIL]".red().bold(), field.bold(), message);
+    }
+    println!();
+    Err(Failure::validation(format!(
+        "Zenodo would reject {} field(s) — fix CITATION.cff or .zenodo.json",
+        problems.len()
+    )))
+}
 
-    // Build badge markdown
-    let badge_url = format!("https://zenodo.org/badge/DOI/{}.svg", doi);
-    let badge_md = format!("[![DOI]({})]({})", badge_url, doi_url);
+/// Re-apply metadata from CITATION.cff to the published record for the current tag
+fn run_update_metadata(
+    project_dir: &Path,
+    config: &Config,
+    sandbox: bool,
+    confirm: bool,
+    id: Option<u64>,
+) -> Result<(), Failure> {
+    let release = git::release_tag(project_dir, &config.tag_pattern()?)?;
+    let (version, tag) = (release.version, release.tag);
 
-    // Insert after the first heading, or at the top
-    let new_content = if let Some(pos) = content.find('\n') {
-        let first_line = &content[..pos];
-        if first_line.starts_with('#') {
-            format!("{}\n\n{}\n{}", first_line, badge_md, &content[pos + 1..])
-        } else {
-            format!("{}\n\n{}", badge_md, content)
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff"))?;
+    let mut deposit = ZenodoDeposit::from_citation(&cff, config);
+    deposit.merge_zenodo_json(project_dir)?;
+
+    if !sandbox && !confirm {
+        println!(
+            "\n  {} You are about to change the metadata of a published record on {}.",
+            "WARNING".yellow().bold(),
+            "PRODUCTION Zenodo".red().bold()
+        );
+        println!(
+            "  The DOI stays the same; the previous metadata remains in the record's history.\n"
+        );
+        print!("  Continue? [y/N] ");
+        io::stdout().flush().ok();
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .map_err(|e| format!("Cannot read input: {}", e))?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            return Err(Failure::aborted());
         }
-    } else {
-        format!("{}\n\n{}", badge_md, content)
-    };
+        println!();
+    }
 
-    std::fs::write(&readme_path, new_content)
-        .map_err(|e| format!("Cannot write README.md: {}", e))?;
+    let client = ZenodoClient::new(sandbox, &config.network())?;
+    client.check_scopes(true)?;
+    let mut release_state = ReleaseState::load(project_dir)?;
+
+    // The published record: as given, from the state file, from the version
+    // DOI CITATION.cff records for the tag, else by title and version. The
+    // last fails when the title is what is being corrected.
+    let recorded = id
+        .or_else(|| {
+            release_state
+                .env(sandbox)
+                .releases
+                .get(&tag)
+                .filter(|r| r.published)
+                .map(|r| r.deposition_id)
+        })
+        .or_else(|| {
+            metadata::doi::recorded_version(project_dir, &tag)
+                .as_deref()
+                .and_then(zenodo::record_id)
+        });
+    let deposition_id = match recorded {
+        Some(id) => id,
+        None => {
+            let depositions = client.list_depositions()?;
+            zenodo::find_release(&depositions, &cff.title, &version)
+                .filter(|d| d.submitted)
+                .map(|d| d.id)
+                .ok_or(format!(
+                    "No published Zenodo record found for {} — run `release-scholar publish --confirm` first, or name the record with --id",
+                    tag
+                ))?
+        }
+    };
 
-    println!("\n  {} Added DOI badge to README.md", "+".green().bold());
     println!(
-        "  {}",
-        format!(
-            "Commit and push to update: git add README.md && git commit -m \"Add DOI badge for {}\"",
-            tag
-        )
-        .dimmed()
+        "{} Updating metadata of {} (record {})...\n",
+        ">>>".bold(),
+        tag.bold(),
+        deposition_id
     );
 
+    print!("  Unlocking record for editing... ");
+    client.edit(deposition_id)?;
+    println!("{}", "done".green());
+
+    print!("  Setting metadata... ");
+    if let Err(e) = client.update_metadata(deposition_id, &deposit) {
+        // Leave the record as it was rather than stuck in edit mode
+        client.discard_edit(deposition_id).ok();
+        return Err(e.into());
+    }
+    println!("{}", "done".green());
+
+    print!("  Publishing changes... ");
+    let published = match client.publish(deposition_id) {
+        Ok(published) => published,
+        Err(e) => {
+            // As above: keep the published version rather than a half-done edit
+            client.discard_edit(deposition_id).ok();
+            return Err(e.into());
+        }
+    };
+    println!("{}", "done".green());
+
+    if let Some(entry) = release_state.env_mut(sandbox).releases.get_mut(&tag) {
+        entry.metadata_updated_at = Some(state::now_utc());
+        release_state.save(project_dir)?;
+    }
+
+    println!(
+        "\n  {} Metadata updated (DOI {} unchanged).",
+        "OK".green().bold(),
+        published.doi.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "  View at: {}/records/{}\n",
+        client.base_web_url(),
+        deposition_id
+    );
     Ok(())
 }
 
-fn get_version(project_dir: &Path) -> Result<String, String> {
+/// Reserve the DOI of the release CITATION.cff's version is for, before it is
+/// tagged: create its draft, record it under the tag it will have, and write
+/// the DOI where `publish --confirm` would
+fn run_reserve_doi(
+    project_dir: &Path,
+    config: &Config,
+    sandbox: bool,
+    commit: bool,
+) -> Result<(), Failure> {
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff"))?;
+    let version = cff
+        .version
+        .clone()
+        .ok_or("CITATION.cff has no `version` to reserve a DOI for")?;
+    if config.tag_pattern.is_some() {
+        return Err(Failure::config(
+            "--reserve-doi names the release tag from `tag_prefix` and the version, so it cannot be used with `tag_pattern`",
+        ));
+    }
+    let tag = format!("{}{}", config.tag_prefix, version);
     let repo =
         git2::Repository::open(project_dir).map_err(|e| format!("Cannot open repo: {}", e))?;
-    let head = repo
-        .head()
-        .map_err(|e| format!("Cannot read HEAD: {}", e))?;
-    let head_oid = head.target().ok_or("HEAD has no target")?;
-
-    let tag_names = repo.tag_names(None).map_err(|e| e.to_string())?;
-    let semver_re = regex::Regex::new(r"^v(\d+\.\d+\.\d+)$").unwrap();
-
-    for i in 0..tag_names.len() {
-        let name = match tag_names.get(i) {
-            Some(n) => n,
-            None => continue,
-        };
-        if let Some(caps) = semver_re.captures(name) {
-            let tag_oid = match repo.revparse_single(&format!("refs/tags/{}", name)) {
-                Ok(obj) => obj.peel_to_commit().map(|c| c.id()).unwrap_or(obj.id()),
-                Err(_) => continue,
+    if repo.find_reference(&format!("refs/tags/{}", tag)).is_ok() {
+        return Err(Failure::validation(format!(
+            "{} is already tagged; reserve the DOI before tagging, so the tagged files can contain it",
+            tag
+        )));
+    }
+
+    let client = ZenodoClient::new(sandbox, &config.network())?;
+    client.check_scopes(false)?;
+    let mut release_state = ReleaseState::load(project_dir)?;
+
+    let doi = match release_state.env(sandbox).releases.get(&tag) {
+        Some(recorded) if recorded.published => {
+            return Err(Failure::from(format!(
+                "{} is already published on Zenodo (DOI {})",
+                tag,
+                recorded.doi.as_deref().unwrap_or("unknown")
+            )))
+        }
+        Some(TagState { doi: Some(doi), .. }) => {
+            println!(
+                "  {} A DOI is already reserved for {}: {}",
+                "~".yellow().bold(),
+                tag,
+                doi
+            );
+            doi.clone()
+        }
+        Some(recorded) => {
+            return Err(Failure::from(format!(
+                "Draft {} already exists for {} without a reserved DOI; delete it with `release-scholar discard --id {}` first",
+                recorded.deposition_id, tag, recorded.deposition_id
+            )))
+        }
+        None => {
+            print!("  Reserving a DOI for {}... ", tag);
+            io::stdout().flush().ok();
+            let draft = match release_state.env(sandbox).concept_record_id.clone() {
+                Some(concept) => client.new_version(&concept, &cff.title, &version, true)?,
+                None => client.reserve_doi(&cff.title, &version)?,
             };
-            if tag_oid == head_oid {
-                return Ok(caps[1].to_string());
+            let doi = draft
+                .reserved_doi()
+                .ok_or_else(|| {
+                    ZenodoError::DraftLeftBehind {
+                        draft: draft.id,
+                        source: Box::new(ZenodoError::Reservation),
+                    }
+                })?
+                .to_string();
+            println!("{} (draft {})", "done".green(), draft.id);
+            release_state.env_mut(sandbox).releases.insert(
+                tag.clone(),
+                TagState {
+                    deposition_id: draft.id,
+                    doi: Some(doi.clone()),
+                    created_at: state::now_utc(),
+                    ..Default::default()
+                },
+            );
+            release_state.save(project_dir)?;
+            doi
+        }
+    };
+
+    // A sandbox DOI does not resolve, so it is only recorded in the state file
+    let concept_doi = release_state.env(sandbox).concept_doi.as_deref();
+    let mut updated = Vec::new();
+    let ours = release_state.env(sandbox).dois();
+    if !sandbox && add_doi_badges(project_dir, &config.badges(), &doi, concept_doi, &ours)? {
+        println!("  {} Added the DOI badge to README.md", "+".green().bold());
+        updated.push("README.md");
+    }
+    if !sandbox && config.doi_links().metadata {
+        let minted = MintedDoi {
+            version: &doi,
+            concept: concept_doi,
+            tag: &tag,
+        };
+        let files = metadata::doi::write_back(project_dir, &minted)?;
+        for file in &files {
+            println!(
+                "  {} Recorded DOI {} in {}",
+                "+".green().bold(),
+                minted.canonical(),
+                file
+            );
+        }
+        updated.extend(files);
+    }
+
+    let message = format!("Reserve DOI for {}", tag);
+    if !updated.is_empty() && commit {
+        commit_and_push(project_dir, &updated, &message)?;
+    } else if !updated.is_empty() {
+        println!(
+            "\n  Commit the DOI: git add {} && git commit -m \"{}\" (or use --commit)",
+            updated.join(" "),
+            message
+        );
+    }
+    println!(
+        "  Then tag the release and build it: git tag {} && release-scholar build",
+        tag
+    );
+    println!("  `release-scholar publish` uploads the bundle to the reserved draft.\n");
+    Ok(())
+}
+
+/// An unpublished deposition for this tag, from the state file or, failing
+/// that, from the user's Zenodo drafts. Errors if the tag is already published.
+fn find_draft(
+    client: &ZenodoClient,
+    release_state: &mut ReleaseState,
+    sandbox: bool,
+    tag: &str,
+    cff: &CitationCff,
+    version: &str,
+) -> Result<Option<DepositionResponse>, Failure> {
+    if let Some(recorded) = release_state.env(sandbox).releases.get(tag) {
+        if recorded.published {
+            return Err(Failure::from(format!(
+                "{} is already published on Zenodo (DOI {})",
+                tag,
+                recorded.doi.as_deref().unwrap_or("unknown")
+            )));
+        }
+        match client.get_deposition(recorded.deposition_id)? {
+            Some(d) if !d.submitted => return Ok(Some(d)),
+            Some(d) => {
+                return Err(Failure::from(format!(
+                    "{} is already published on Zenodo (DOI {})",
+                    tag,
+                    d.doi.as_deref().unwrap_or("unknown")
+                )))
+            }
+            // Deleted on Zenodo since the last run: forget it
+            None => {
+                release_state.env_mut(sandbox).releases.remove(tag);
             }
         }
     }
 
-    Err("HEAD has no semver tag (vX.Y.Z)".to_string())
+    let depositions = client.list_depositions()?;
+    match zenodo::find_release(&depositions, &cff.title, version) {
+        Some(d) if d.submitted => Err(Failure::from(format!(
+            "{} is already published on Zenodo (DOI {})",
+            tag,
+            d.doi.as_deref().unwrap_or("unknown")
+        ))),
+        Some(d) => Ok(client.get_deposition(d.id)?),
+        None => Ok(None),
+    }
+}
+
+/// Add the release to RELEASES.md; returns whether it changed
+fn add_to_ledger(
+    project_dir: &Path,
+    tag: &str,
+    doi: &str,
+    archive_path: &Path,
+    record_url: &str,
+) -> Result<bool, IoError> {
+    let sha256 = checksum::sha256_file(archive_path)?;
+    let published_at = state::now_utc();
+    let entry = ledger::Entry {
+        tag,
+        date: &published_at[..10],
+        doi,
+        sha256: &sha256,
+        record_url,
+    };
+    ledger::record(project_dir, &entry)
+}
+
+/// Put the configured DOI badges at the top of README.md: a version badge
+/// replaces the previous release's, a concept badge is added once. Returns
+/// whether README.md changed.
+fn add_doi_badges(
+    project_dir: &Path,
+    badges: &BadgesConfig,
+    doi: &str,
+    concept_doi: Option<&str>,
+    ours: &[&str],
+) -> Result<bool, String> {
+    let readme_path = project_dir.join("README.md");
+    if !readme_path.exists() || badges.doi == BadgeDoi::None {
+        return Ok(false);
+    }
+
+    let mut content = std::fs::read_to_string(&readme_path)
+        .map_err(|e| format!("Cannot read README.md: {}", e))?;
+    let badge = |label: &str, doi: &str| {
+        format!(
+            "[![{}]({})](https://doi.org/{})",
+            label,
+            badges.style.image_url(label, doi),
+            doi
+        )
+    };
+
+    let mut wanted = Vec::new();
+    if matches!(badges.doi, BadgeDoi::Version | BadgeDoi::Both) {
+        wanted.push(("DOI", doi));
+    }
+    if matches!(badges.doi, BadgeDoi::Concept | BadgeDoi::Both) {
+        match concept_doi {
+            Some(concept) => wanted.push(("DOI (latest)", concept)),
+            None if badges.doi == BadgeDoi::Concept => wanted.push(("DOI", doi)),
+            None => {}
+        }
+    }
+
+    // Badges already in the README, by label and the DOI they link to
+    let existing = Regex::new(
+        r"\[!\[([^\]]*)\]\((?:https://zenodo\.org/badge/DOI/|https://img\.shields\.io/badge/)[^)]*\)\]\(https://doi\.org/([^)\s]+)\)",
+    )
+    .unwrap();
+    let mut added = Vec::new();
+    let mut replaced = false;
+    for (label, target) in wanted {
+        // The badge this one takes the place of: the previous release's
+        // version badge, or an all-versions badge from an earlier concept.
+        // With only a concept badge wanted, that replaces the version badge
+        // it stood in for before the concept DOI was known. Only badges for
+        // this record's DOIs are ours to replace; one for a paper stays.
+        let previous = existing
+            .captures_iter(&content)
+            .filter(|c| ours.contains(&&c[2]))
+            .find(|c| {
+                &c[1] == label
+                    || (label != "DOI" && badges.doi == BadgeDoi::Concept && &c[1] == "DOI")
+            })
+            .map(|c| (c[0].to_string(), c[2].to_string()));
+        if previous
+            .as_ref()
+            .is_some_and(|(_, linked)| linked == target)
+        {
+            continue;
+        }
+        let markdown = badge(label, target);
+        match previous.map(|(old, _)| old) {
+            Some(old) => {
+                content = content.replacen(&old, &markdown, 1);
+                replaced = true;
+            }
+            None => added.push(markdown),
+        }
+    }
+    if added.is_empty() && !replaced {
+        println!(
+            "\n  {} README.md already has the DOI badge — skipping.",
+            "NOTE".dimmed()
+        );
+        return Ok(false);
+    }
+
+    // Insert after the first heading, or at the top
+    if !added.is_empty() {
+        let badges_md = added.join(" ");
+        content = match content.split_once('\n') {
+            Some((first_line, rest)) if first_line.starts_with('#') => {
+                format!("{}\n\n{}\n{}", first_line, badges_md, rest)
+            }
+            _ => format!("{}\n\n{}", badges_md, content),
+        };
+    }
+
+    std::fs::write(&readme_path, content).map_err(|e| format!("Cannot write README.md: {}", e))?;
+
+    println!(
+        "\n  {} {} DOI badge in README.md",
+        "+".green().bold(),
+        if replaced { "Updated the" } else { "Added the" }
+    );
+
+    Ok(true)
+}
+
+/// The commit `tag` points at
+fn tag_commit(project_dir: &Path, tag: &str) -> Result<String, String> {
+    git2::Repository::open(project_dir)
+        .and_then(|repo| {
+            repo.revparse_single(&format!("refs/tags/{}", tag))?
+                .peel_to_commit()
+                .map(|c| c.id().to_string())
+        })
+        .map_err(|e| format!("Cannot resolve {}: {}", tag, e))
+}
+
+/// Commit just `files` and push the branch, with the user's own git (so
+/// their hooks, signing and credentials apply)
+/// The files `publish` writes a new DOI into, which `--commit` commits
+const DOI_FILES: [&str; 4] = [
+    "README.md",
+    "CITATION.cff",
+    "codemeta.json",
+    ledger::FILE_NAME,
+];
+
+/// Fail unless `files` are as committed, so that `--commit` commits only
+/// the DOI updates and none of the user's own changes
+fn ensure_clean(project_dir: &Path, files: &[&str]) -> Result<(), Failure> {
+    let repo = git2::Repository::open(project_dir)
+        .map_err(|e| format!("Cannot open repository to commit: {}", e))?;
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(true);
+    for file in files {
+        options.pathspec(file);
+    }
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Cannot read the status of the repository: {}", e))?;
+    let dirty: Vec<String> = statuses
+        .iter()
+        .filter(|entry| !entry.status().is_ignored())
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect();
+    if dirty.is_empty() {
+        return Ok(());
+    }
+    Err(Failure::validation(format!(
+        "--commit would also commit your uncommitted changes to {}; commit or stash them first",
+        dirty.join(", ")
+    )))
+}
+
+fn commit_and_push(project_dir: &Path, files: &[&str], message: &str) -> Result<(), String> {
+    let repo = git2::Repository::open(project_dir)
+        .map_err(|e| format!("Cannot open repository to commit: {}", e))?;
+    if repo.head_detached().unwrap_or(false) {
+        return Err(format!(
+            "HEAD is detached (a tag checkout?), so the DOI updates are not committed; commit {} on a branch",
+            files.join(", ")
+        ));
+    }
+
+    let git = |args: &[&str]| -> Result<(), String> {
+        tracing::debug!("running git {}", args.join(" "));
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(project_dir)
+            .output()
+            .map_err(|e| format!("Cannot run git: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+    let mut add = vec!["add", "--"];
+    add.extend(files);
+    git(&add)?;
+    // Only these paths, whatever else happens to be staged
+    let mut commit = vec!["commit", "-m", message, "--"];
+    commit.extend(files);
+    git(&commit)?;
+    println!(
+        "  {} Committed {}: {}",
+        "+".green().bold(),
+        files.join(", "),
+        message
+    );
+
+    print!("  Pushing... ");
+    io::stdout().flush().ok();
+    git(&["push"])?;
+    println!("{}", "done".green());
+    Ok(())
+}
+
+/// Upload files concurrently (bounded pool), recording each finished upload in the
+/// state file as it completes so a failed run resumes with only the missing files
+fn upload_all(
+    client: &ZenodoClient,
+    bucket_url: &str,
+    pending: &[(String, PathBuf)],
+    release_state: &mut ReleaseState,
+    sandbox: bool,
+    tag: &str,
+    project_dir: &Path,
+) -> Result<(), Failure> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let total_bytes: u64 = pending
+        .iter()
+        .filter_map(|(_, p)| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+    println!(
+        "  Uploading {} file(s), {:.1} MB, {} at a time...",
+        pending.len(),
+        total_bytes as f64 / 1_000_000.0,
+        MAX_CONCURRENT_UPLOADS.min(pending.len())
+    );
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    let mut errors: Vec<Failure> = Vec::new();
+    std::thread::scope(|scope| {
+        for _ in 0..MAX_CONCURRENT_UPLOADS.min(pending.len()) {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some((name, path)) = pending.get(i) else {
+                    break;
+                };
+                let result = client.upload_file(bucket_url, path, name);
+                if tx.send((name, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut done = 0;
+        let mut done_bytes = 0;
+        for (name, result) in rx {
+            done += 1;
+            match result {
+                Ok(file_resp) => {
+                    done_bytes += file_resp.size;
+                    println!(
+                        "    [{}/{}] {} {} ({} bytes, checksum: {}) — {:.0}% of bytes",
+                        done,
+                        pending.len(),
+                        name,
+                        "done".green(),
+                        file_resp.size,
+                        file_resp.checksum,
+                        done_bytes as f64 * 100.0 / total_bytes.max(1) as f64
+                    );
+                    if let Some(entry) = release_state.env_mut(sandbox).releases.get_mut(tag) {
+                        entry.uploads.insert(file_resp.key, file_resp.checksum);
+                    }
+                    if let Err(e) = release_state.save(project_dir) {
+                        errors.push(e.into());
+                    }
+                }
+                Err(e) => {
+                    println!(
+                        "    [{}/{}] {} {}",
+                        done,
+                        pending.len(),
+                        name,
+                        "failed".red()
+                    );
+                    errors.push(e.into());
+                }
+            }
+        }
+    });
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        n => Err(Failure::new(
+            errors[0].kind,
+            format!(
+                "{} uploads failed (rerun publish to retry them):\n  {}",
+                n,
+                errors
+                    .iter()
+                    .map(|e| e.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n  ")
+            ),
+        )),
+    }
+}
+
+/// Every file in the bundle, the code archive first and any data archive
+/// next, so the record is self-describing. Zenodo records are flat, so files
+/// in the grouped layout's directories are uploaded under their own names.
+fn bundle_files(
+    release_dir: &Path,
+    archive_path: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<PathBuf> = config
+        .bundle()
+        .layout
+        .files(release_dir)
+        .map_err(|e| format!("Cannot read {}: {}", release_dir.display(), e))?
+        .into_iter()
+        .filter(|p| p != archive_path)
+        .collect();
+    files.sort_by_key(|f| !f.to_string_lossy().ends_with(tarball::DATA_ARCHIVE_SUFFIX));
+    files.insert(0, archive_path.to_path_buf());
+    Ok(files)
+}
+
+/// The bundle's RELEASE_NOTES.md, if `build` wrote one
+fn release_notes(release_dir: &Path, config: &Config) -> Option<String> {
+    let dir = config.bundle().layout.dir(release_dir, Artifact::Metadata);
+    let notes = std::fs::read_to_string(dir.join("RELEASE_NOTES.md")).ok()?;
+    (!notes.trim().is_empty()).then_some(notes)
 }
 
-fn find_archive(release_dir: &Path) -> Result<std::path::PathBuf, String> {
-    for entry in std::fs::read_dir(release_dir).map_err(|e| e.to_string())? {
+fn find_archive(release_dir: &Path, config: &Config) -> Result<PathBuf, String> {
+    let dir = config.bundle().layout.dir(release_dir, Artifact::Archive);
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
         let entry = entry.map_err(|e| e.to_string())?;
         let path = entry.path();
         if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if name.ends_with(".tar.gz") {
+            if tarball::is_code_archive(name) {
                 return Ok(path);
             }
         }
     }
-    Err(format!(
-        "No .tar.gz archive found in {}",
-        release_dir.display()
-    ))
+    Err(format!("No .tar.gz archive found in {}", dir.display()))
 }
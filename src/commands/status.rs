@@ -0,0 +1,208 @@
+use crate::commands::Sandbox;
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::baseline::Baseline;
+use release_scholar::config::Config;
+use release_scholar::metadata::citation::CitationCff;
+use release_scholar::report::{Report, Status};
+use release_scholar::state::ReleaseState;
+use release_scholar::validation;
+use release_scholar::zenodo::{self, ZenodoClient};
+use std::path::Path;
+
+pub fn run(
+    project_dir: &Path,
+    sandbox: Sandbox,
+    output_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = output_dir {
+        config.set_output_dir(dir)?;
+    }
+    let sandbox = sandbox.or_default(&config);
+
+    let (mut report, git_info) = validation::run_all(&project_dir, &config, false);
+    Baseline::load(&project_dir)?.apply(&mut report);
+    let git_info = git_info.ok_or_else(|| {
+        format!(
+            "HEAD has no release tag ({}) — nothing to report",
+            config.tag_pattern().unwrap_or_default().expected
+        )
+    })?;
+    let tag = &git_info.tag;
+
+    println!("\n{} Release status for {}\n", ">>>".bold(), tag.bold());
+
+    // 1. check
+    let fails = count(&report, |s| matches!(s, Status::Fail));
+    let warns = count(&report, |s| matches!(s, Status::Warn));
+    if fails == 0 {
+        line(
+            Step::Done,
+            "Check",
+            &format!("passed ({} warning(s))", warns),
+        );
+    } else {
+        line(
+            Step::Blocked,
+            "Check",
+            &format!(
+                "{} failure(s) — run `release-scholar check` for details",
+                fails
+            ),
+        );
+    }
+
+    // 2. build
+    let release_dir = config.release_dir(&project_dir, tag);
+    let bundle_files = config
+        .bundle()
+        .layout
+        .files(&release_dir)
+        .map_or(0, |files| files.len());
+    if bundle_files > 0 {
+        let shown = release_dir
+            .strip_prefix(&project_dir)
+            .unwrap_or(&release_dir);
+        line(
+            Step::Done,
+            "Bundle",
+            &format!("{} ({} files)", shown.display(), bundle_files),
+        );
+    } else {
+        line(
+            Step::Todo,
+            "Bundle",
+            "not built — run `release-scholar build`",
+        );
+    }
+
+    // 3. publish
+    let label = if sandbox {
+        "Zenodo (sandbox)"
+    } else {
+        "Zenodo"
+    };
+    let release_state = ReleaseState::load(&project_dir)?;
+    let recorded = release_state.env(sandbox).releases.get(tag.as_str());
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff")).ok();
+    match (ZenodoClient::new(sandbox, &config.network()), cff) {
+        _ if recorded.is_some_and(|r| r.published) => line(
+            Step::Done,
+            label,
+            &format!(
+                "published — DOI {}",
+                recorded.and_then(|r| r.doi.as_deref()).unwrap_or("pending")
+            ),
+        ),
+        _ if recorded.is_some() => line(
+            Step::Todo,
+            label,
+            &format!(
+                "draft {}{} (from {})",
+                recorded.map(|r| r.deposition_id).unwrap_or_default(),
+                recorded
+                    .and_then(|r| r.doi.as_deref())
+                    .map(|doi| format!(", DOI {} reserved", doi))
+                    .unwrap_or_default(),
+                release_scholar::state::STATE_FILE
+            ),
+        ),
+        (Err(e), _) => line(Step::Unknown, label, &format!("unknown — {}", e)),
+        (_, None) => line(Step::Unknown, label, "unknown — CITATION.cff not readable"),
+        (Ok(client), Some(cff)) => match client.list_depositions() {
+            Err(e) => line(Step::Unknown, label, &format!("unknown — {}", e)),
+            Ok(depositions) => {
+                match zenodo::find_release(&depositions, &cff.title, &git_info.version) {
+                    Some(d) if d.submitted => line(
+                        Step::Done,
+                        label,
+                        &format!("published — DOI {}", d.doi.as_deref().unwrap_or("pending")),
+                    ),
+                    Some(d) => line(
+                        Step::Todo,
+                        label,
+                        &format!(
+                            "draft {} — review at {}/deposit/{}",
+                            d.id,
+                            client.base_web_url(),
+                            d.id
+                        ),
+                    ),
+                    None => line(
+                        Step::Todo,
+                        label,
+                        "no deposition — run `release-scholar publish`",
+                    ),
+                }
+            }
+        },
+    }
+
+    // The concept DOI, shared by every version
+    let env = release_state.env(sandbox);
+    if let Some(concept) = &env.concept_doi {
+        let record = env
+            .concept_record_id
+            .as_deref()
+            .map(|id| format!(" (concept record {})", id))
+            .unwrap_or_default();
+        line(
+            Step::Done,
+            "All versions",
+            &format!("DOI {}{}", concept, record),
+        );
+    }
+
+    // 4. mirror
+    let targets: Vec<&str> = config
+        .mirrors
+        .as_ref()
+        .map(|m| {
+            let mut targets = Vec::new();
+            if m.github_user.is_some() && m.token("github").is_some() {
+                targets.push("GitHub");
+            }
+            if m.gitlab_user.is_some() && m.token("gitlab").is_some() {
+                targets.push("GitLab");
+            }
+            targets
+        })
+        .unwrap_or_default();
+    if targets.is_empty() {
+        line(
+            Step::Todo,
+            "Mirrors",
+            "not configured — add a [mirrors] section to the global config",
+        );
+    } else {
+        line(Step::Done, "Mirrors", &targets.join(", "));
+    }
+
+    println!();
+    Ok(())
+}
+
+enum Step {
+    Done,
+    Todo,
+    Blocked,
+    Unknown,
+}
+
+fn line(step: Step, label: &str, detail: &str) {
+    let icon = match step {
+        Step::Done => "✓".green().bold(),
+        Step::Todo => "·".yellow().bold(),
+        Step::Blocked => "✗".red().bold(),
+        Step::Unknown => "?".dimmed(),
+    };
+    println!("  {} {:<18} {}", icon, label.bold(), detail);
+}
+
+fn count(report: &Report, f: impl Fn(&Status) -> bool) -> usize {
+    report.results.iter().filter(|r| f(&r.status)).count()
+}
@@ -0,0 +1,166 @@
+use crate::failure::Failure;
+use colored::Colorize;
+use release_scholar::archive::contents::{self, Change};
+use release_scholar::archive::{checksum, tarball};
+use release_scholar::config::{Artifact, Config};
+use release_scholar::validation::git;
+use std::path::Path;
+
+/// Check the bundle's files against checksums.txt and, with `reproduce`,
+/// that building the archive again from the tag gives the same bytes
+pub fn run(
+    project_dir: &Path,
+    reproduce: bool,
+    output_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), Failure> {
+    let project_dir = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Invalid project directory: {}", e))?;
+    let mut config = Config::load(&project_dir, profile)?;
+    if let Some(dir) = output_dir {
+        config.set_output_dir(dir)?;
+    }
+    let tag = git::release_tag(&project_dir, &config.tag_pattern()?)?.tag;
+    let release_dir = config.release_dir(&project_dir, &tag);
+    let archive_dir = config.bundle().layout.dir(&release_dir, Artifact::Archive);
+    let checksums_path = archive_dir.join("checksums.txt");
+    let checksums = std::fs::read_to_string(&checksums_path).map_err(|e| {
+        format!(
+            "Cannot read {}: {}. Run `release-scholar build` first.",
+            checksums_path.display(),
+            e
+        )
+    })?;
+
+    println!("\n{} Verifying the {} bundle\n", ">>>".bold(), tag.bold());
+
+    let mut failures = 0;
+    let mut archive = None;
+    for line in checksums.lines().filter(|l| !l.trim().is_empty()) {
+        let Some((expected, name)) = line.split_once("  ") else {
+            println!(
+                "  {} Malformed line in checksums.txt: {}",
+                "[FAIL]".red().bold(),
+                line
+            );
+            failures += 1;
+            continue;
+        };
+        let path = archive_dir.join(name);
+        match checksum::sha256_file(&path) {
+            Ok(actual) if actual == expected => {
+                println!("  {} {} matches checksums.txt", "OK".green().bold(), name);
+            }
+            Ok(actual) => {
+                println!(
+                    "  {} {} has SHA-256 {}, checksums.txt says {}",
+                    "[FAIL]".red().bold(),
+                    name,
+                    actual,
+                    expected
+                );
+                failures += 1;
+            }
+            Err(e) => {
+                println!("  {} {}", "[FAIL]".red().bold(), e);
+                failures += 1;
+            }
+        }
+        if tarball::is_code_archive(name) {
+            archive = Some((name.to_string(), expected.to_string()));
+        }
+    }
+
+    if reproduce {
+        let (name, expected) = archive.ok_or("checksums.txt lists no .tar.gz archive")?;
+        if !reproduces(&project_dir, &config, &tag, &archive_dir, &name, &expected)? {
+            failures += 1;
+        }
+    }
+
+    println!();
+    if failures > 0 {
+        return Err(Failure::validation(format!(
+            "{} verification(s) failed",
+            failures
+        )));
+    }
+    Ok(())
+}
+
+/// Rebuild `name` from `tag` in a scratch directory and compare it with
+/// `expected`; on a mismatch, say which files differ
+fn reproduces(
+    project_dir: &Path,
+    config: &Config,
+    tag: &str,
+    archive_dir: &Path,
+    name: &str,
+    expected: &str,
+) -> Result<bool, String> {
+    // The name sets the archive's top-level directory, so it must not change
+    let scratch = std::env::temp_dir().join(format!("release-scholar-{}", std::process::id()));
+    std::fs::create_dir_all(&scratch)
+        .map_err(|e| format!("Cannot create {}: {}", scratch.display(), e))?;
+    let rebuilt = scratch.join(name);
+
+    print!("  Rebuilding {} from {}... ", name, tag);
+    let data = config.data();
+    let result = tarball::create_archive(
+        project_dir,
+        tag,
+        &rebuilt,
+        data.archived_apart().then_some(&data),
+    )
+    .map_err(String::from)
+    .and_then(|()| Ok(checksum::sha256_file(&rebuilt)?));
+    let hash = match result {
+        Ok(hash) => hash,
+        Err(e) => {
+            std::fs::remove_dir_all(&scratch).ok();
+            return Err(e);
+        }
+    };
+    println!("{}", "done".green());
+
+    let same = hash == expected;
+    if same {
+        println!(
+            "  {} The rebuilt archive is identical (SHA-256 {})",
+            "OK".green().bold(),
+            hash
+        );
+    } else {
+        println!(
+            "  {} The rebuilt archive has SHA-256 {}, checksums.txt says {}",
+            "[FAIL]".red().bold(),
+            hash,
+            expected
+        );
+        let original = contents::from_archive(&archive_dir.join(name));
+        let again = contents::from_archive(&rebuilt);
+        match (original, again) {
+            (Ok(original), Ok(again)) => {
+                let changes = contents::compare(&original, &again);
+                if changes.is_empty() {
+                    println!(
+                        "    {}",
+                        "Same files with the same content: the compression or the tar headers differ (another flate2 or zlib build?)"
+                            .dimmed()
+                    );
+                }
+                for change in changes {
+                    let line = match change {
+                        Change::Added(path, _) => format!("only in the rebuild: {}", path),
+                        Change::Removed(path, _) => format!("only in the bundle: {}", path),
+                        Change::Modified { path, .. } => format!("differs: {}", path),
+                    };
+                    println!("    {}", line.dimmed());
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => println!("    {}", e.to_string().dimmed()),
+        }
+    }
+    std::fs::remove_dir_all(&scratch).ok();
+    Ok(same)
+}
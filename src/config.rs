@@ -1,6 +1,16 @@
+mod env;
+
+use crate::archive::tarball;
+use crate::credentials;
+use crate::error::{ConfigError, IoError};
+use crate::validation::git::{GitInfo, TagPattern};
+use colored::Colorize;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+/// The primary forge, where the project is hosted
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[derive(Default)]
@@ -21,7 +31,51 @@ impl std::fmt::Display for Forge {
     }
 }
 
+/// Which way `mirror` copies the repository between Codeberg and the other
+/// forges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MirrorDirection {
+    /// Codeberg pushes to GitHub and GitLab
+    #[default]
+    Push,
+    /// Codeberg pulls from the primary forge, GitHub or GitLab
+    Pull,
+}
+
+/// The form of the version in release tags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionScheme {
+    /// MAJOR.MINOR.PATCH
+    #[default]
+    Semver,
+    /// YYYY.MM, YYYY.MM.MICRO or YY.MM.MICRO (2024.06.1); months and days
+    /// may be zero-padded
+    Calver,
+}
+
+impl VersionScheme {
+    /// Regex for a version in this scheme
+    pub fn regex(self) -> &'static str {
+        match self {
+            VersionScheme::Semver => r"\d+\.\d+\.\d+",
+            VersionScheme::Calver => r"(?:\d{4}|\d{2})\.\d{1,2}(?:\.\d+)?",
+        }
+    }
+
+    /// How a version in this scheme looks, for messages
+    pub fn example(self) -> &'static str {
+        match self {
+            VersionScheme::Semver => "X.Y.Z",
+            VersionScheme::Calver => "YYYY.MM.MICRO",
+        }
+    }
+}
+
+/// The release author: name, ORCID and email
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AuthorConfig {
     pub name: Option<String>,
     pub orcid: Option<String>,
@@ -43,41 +97,1065 @@ impl AuthorConfig {
     }
 }
 
+/// The merged configuration: the project's `.release-scholar.toml` over the
+/// global config, with the active profile and `RELEASE_SCHOLAR_*` variables
+/// applied
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
     pub forge: Forge,
+    /// Base URL of a self-hosted Forgejo/Gitea instance used instead of codeberg.org
     pub forge_url: Option<String>,
     #[serde(default = "default_required_files")]
-    pub required_files: Vec<String>,
+    pub required_files: Vec<RequiredFile>,
+    /// What files must (not) contain, e.g. `[content."README.md"]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<String, ContentRules>,
+    /// README headings to require; `|` separates accepted alternatives
+    #[serde(default = "default_readme_sections")]
+    pub readme_sections: Vec<String>,
     #[serde(default = "default_archive_dir")]
     pub archive_dir: String,
+    /// Archive file name, from `{name}` (the project directory), `{version}`,
+    /// `{tag}` and `{date}` (of the tagged commit)
+    #[serde(default = "default_archive_name")]
+    pub archive_name: String,
+    /// Release tags are this prefix and the version (`v1.2.3`); "" for `1.2.3`
+    #[serde(default = "default_tag_prefix")]
+    pub tag_prefix: String,
+    #[serde(default)]
+    pub versioning: VersionScheme,
+    /// Regex for release tags, instead of `tag_prefix`, with a
+    /// `(?P<version>...)` group
+    pub tag_pattern: Option<String>,
     #[serde(default = "default_language")]
     pub language: String,
+    /// DataCite `publisher`: who holds and publishes the release. Unset,
+    /// it is Zenodo, where release-scholar deposits the release.
+    pub publisher: Option<String>,
     pub author: Option<AuthorConfig>,
     pub mirrors: Option<MirrorsConfig>,
+    pub data: Option<DataConfig>,
+    pub subjects: Option<SubjectsConfig>,
+    pub container: Option<ContainerConfig>,
+    pub size: Option<SizeConfig>,
+    pub network: Option<NetworkConfig>,
+    pub doi_links: Option<DoiLinksConfig>,
+    pub badges: Option<BadgesConfig>,
+    pub announce: Option<AnnounceConfig>,
+    pub hooks: Option<HooksConfig>,
+    pub zenodo: Option<ZenodoConfig>,
+    pub bundle: Option<BundleConfig>,
+    pub provenance: Option<ProvenanceConfig>,
+    /// Profile to apply when a command is run without `--profile`
+    pub default_profile: Option<String>,
+    /// Named identities, e.g. `[profile.university]` and `[profile.personal]`
+    #[serde(
+        default,
+        rename = "profile",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// The profile `load` applied
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+    /// Named check rule sets, e.g. `[rules.institutional]`, selected with
+    /// `check --rules`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, RuleSetConfig>,
+    /// Global rule sets the project config replaced, which `check` reports
+    #[serde(skip)]
+    pub overridden_rules: Vec<String>,
+    /// `RELEASE_SCHOLAR_*` variables that name no config key, which `load`
+    /// and `check` warn about
+    #[serde(skip)]
+    pub ignored_env: Vec<String>,
 }
 
+/// Mirrors between Codeberg and GitHub/GitLab, and the forge tokens
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MirrorsConfig {
+    /// "push" (default) or "pull", for a copy on Codeberg of a project whose
+    /// primary forge is GitHub or GitLab
+    pub direction: Option<MirrorDirection>,
     pub codeberg_user: Option<String>,
     pub codeberg_token: Option<String>,
     pub github_user: Option<String>,
     pub github_token: Option<String>,
     pub gitlab_user: Option<String>,
     pub gitlab_token: Option<String>,
+    /// How often mirrors sync, as a Go duration ("8h0m0s", "30m")
+    pub interval: Option<String>,
+    /// Whether push mirrors also sync on every push to Codeberg
+    pub sync_on_commit: Option<bool>,
+    /// Repository names, when they differ from the project directory name
+    pub codeberg_repo: Option<String>,
+    pub github_repo: Option<String>,
+    pub gitlab_repo: Option<String>,
+    /// Token keys the project config sets itself
+    #[serde(skip)]
+    project_secrets: Vec<&'static str>,
+}
+
+impl MirrorsConfig {
+    /// The token keys this config sets
+    fn secrets(&self) -> Vec<&'static str> {
+        [
+            ("codeberg_token", &self.codeberg_token),
+            ("github_token", &self.github_token),
+            ("gitlab_token", &self.gitlab_token),
+        ]
+        .into_iter()
+        .filter(|(_, token)| token.is_some())
+        .map(|(key, _)| key)
+        .collect()
+    }
+
+    /// Whether the project config itself sets `key`: a token meant for this
+    /// project, such as a bot account's, which is used before the keyring's
+    pub fn is_project_secret(&self, key: &str) -> bool {
+        self.project_secrets.contains(&key)
+    }
+
+    /// Merge: self takes priority, fallback fills in gaps
+    fn merge_with_fallback(&mut self, fallback: &MirrorsConfig) {
+        let MirrorsConfig {
+            direction,
+            codeberg_user,
+            codeberg_token,
+            github_user,
+            github_token,
+            gitlab_user,
+            gitlab_token,
+            interval,
+            sync_on_commit,
+            codeberg_repo,
+            github_repo,
+            gitlab_repo,
+            project_secrets: _,
+        } = fallback.clone();
+        self.direction = self.direction.or(direction);
+        self.codeberg_user = self.codeberg_user.take().or(codeberg_user);
+        self.codeberg_token = self.codeberg_token.take().or(codeberg_token);
+        self.github_user = self.github_user.take().or(github_user);
+        self.github_token = self.github_token.take().or(github_token);
+        self.gitlab_user = self.gitlab_user.take().or(gitlab_user);
+        self.gitlab_token = self.gitlab_token.take().or(gitlab_token);
+        self.interval = self.interval.take().or(interval);
+        self.sync_on_commit = self.sync_on_commit.or(sync_on_commit);
+        self.codeberg_repo = self.codeberg_repo.take().or(codeberg_repo);
+        self.github_repo = self.github_repo.take().or(github_repo);
+        self.gitlab_repo = self.gitlab_repo.take().or(gitlab_repo);
+    }
+
+    /// Which way the mirrors copy; push by default
+    pub fn direction(&self) -> MirrorDirection {
+        self.direction.unwrap_or_default()
+    }
+
+    /// How often the forge syncs a mirror, as a Go duration ("8h0m0s")
+    pub fn interval(&self) -> &str {
+        self.interval.as_deref().unwrap_or("8h0m0s")
+    }
+
+    /// Whether a push mirror also syncs on every commit; true by default
+    pub fn sync_on_commit(&self) -> bool {
+        self.sync_on_commit.unwrap_or(true)
+    }
+
+    /// Repository name on "codeberg", "github", or "gitlab", or `default`
+    pub fn repo_name(&self, forge: &str, default: &str) -> String {
+        let name = match forge {
+            "codeberg" => self.codeberg_repo.as_deref(),
+            "github" => self.github_repo.as_deref(),
+            "gitlab" => self.gitlab_repo.as_deref(),
+            _ => None,
+        };
+        name.unwrap_or(default).to_string()
+    }
+
+    /// Token for "codeberg", "github", or "gitlab": from
+    /// `RELEASE_SCHOLAR_MIRRORS_<FORGE>_TOKEN`, the project config, the OS
+    /// keyring, else the global config
+    pub fn token(&self, forge: &str) -> Option<String> {
+        let variable = format!("{}MIRRORS_{}_TOKEN", env::PREFIX, forge.to_uppercase());
+        if let Some(token) = std::env::var(variable)
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+        {
+            return Some(token);
+        }
+        let plaintext = match forge {
+            "codeberg" => self.codeberg_token.clone(),
+            "github" => self.github_token.clone(),
+            "gitlab" => self.gitlab_token.clone(),
+            _ => None,
+        };
+        let key = format!("{}_token", forge);
+        if self.is_project_secret(&key) {
+            return plaintext;
+        }
+        credentials::resolve(forge, plaintext, &format!("[mirrors] {}", key))
+    }
+}
+
+/// Author, forge accounts and Zenodo defaults for one affiliation. Fields
+/// set in the project config still take priority over the profile's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub author: Option<AuthorConfig>,
+    pub mirrors: Option<MirrorsConfig>,
+    /// Use the Zenodo sandbox even without `--sandbox`
+    #[serde(default)]
+    pub sandbox: bool,
+    /// Zenodo communities new depositions are submitted to
+    #[serde(default)]
+    pub communities: Vec<String>,
+}
+
+/// An entry of `required_files`: a path or glob (`docs/**/*.md`), or a
+/// table requiring it only `when` a condition holds
+/// (`{ path = "CONTRIBUTING.md", when = "multiple-authors" }`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequiredFile {
+    Path(String),
+    When { path: String, when: String },
+}
+
+impl RequiredFile {
+    /// The path or glob of the required file
+    pub fn path(&self) -> &str {
+        match self {
+            RequiredFile::Path(path) | RequiredFile::When { path, .. } => path,
+        }
+    }
+
+    /// The condition the file is required under, if any
+    pub fn when(&self) -> Option<&str> {
+        match self {
+            RequiredFile::Path(_) => None,
+            RequiredFile::When { when, .. } => Some(when),
+        }
+    }
+}
+
+/// Assertions about a file's text. In `contains` and `excludes`, `{title}`
+/// stands for the CITATION.cff title and `{name}` for the project directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContentRules {
+    /// The file must have more than whitespace in it
+    #[serde(default)]
+    pub not_empty: bool,
+    /// Text that must appear, ignoring case
+    #[serde(default)]
+    pub contains: Vec<String>,
+    /// Text that must not appear, ignoring case
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// Regular expressions that must match somewhere
+    #[serde(default)]
+    pub matches: Vec<String>,
+}
+
+impl ContentRules {
+    /// The placeholders `contains` and `excludes` may use
+    pub const PLACEHOLDERS: [&'static str; 2] = ["title", "name"];
+
+    /// Leave out the rules that cannot be checked, each added to `problems`:
+    /// an unknown placeholder, or a regular expression that does not compile
+    fn drop_invalid(&mut self, file: &str, problems: &mut Vec<ConfigError>) {
+        let placeholder = Regex::new(r"\{([^{}]*)\}").unwrap();
+        let mut invalid = |rule: String, problem: String| {
+            problems.push(ConfigError::ContentRule {
+                file: file.to_string(),
+                rule,
+                problem,
+            })
+        };
+        for needles in [&mut self.contains, &mut self.excludes] {
+            needles.retain(|needle| {
+                let unknown = placeholder
+                    .captures_iter(needle)
+                    .find(|caps| !Self::PLACEHOLDERS.contains(&&caps[1]));
+                match unknown {
+                    Some(caps) => {
+                        invalid(
+                            needle.clone(),
+                            format!(
+                                "unknown placeholder {{{}}} (use {{title}} or {{name}})",
+                                &caps[1]
+                            ),
+                        );
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+        self.matches.retain(|pattern| match Regex::new(pattern) {
+            Ok(_) => true,
+            Err(e) => {
+                // The last line of the multi-line parse error says what is wrong
+                let reason = e.to_string();
+                let reason = reason.lines().last().unwrap_or_default().trim().to_string();
+                invalid(format!("/{}/", pattern), reason);
+                false
+            }
+        });
+    }
+}
+
+/// Which check categories count, and how much. Categories the rule set
+/// does not mention keep the severity each check gave.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSetConfig {
+    /// Rule set to start from (built-in or configured)
+    pub extends: Option<String>,
+    /// Severity for every category not in `categories`
+    pub severity: Option<Severity>,
+    /// Per category, e.g. `Security = "fail"`, `Container = "off"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, Severity>,
+}
+
+/// How much a check category's failures count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not reported at all
+    Off,
+    /// Failures are reported as warnings
+    Warn,
+    /// Warnings are reported as failures
+    Fail,
+}
+
+/// Data files shipped alongside the code, described in datapackage.json.
+/// The paths are where data is allowed: the size and security checks hold
+/// files there to this policy rather than to the limits for code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataConfig {
+    /// Files or directories (relative to the project root) holding the data
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub name: Option<String>,
+    pub title: Option<String>,
+    /// SPDX identifier of the data license, if it differs from the code
+    pub license: Option<String>,
+    /// Size limits for the data files; a limit left out is the one under
+    /// `[size]`. They are totalled apart from the code.
+    #[serde(default)]
+    pub size: PathSizeLimits,
+    /// Scan the data files' content for secrets, like the code's
+    #[serde(default = "default_true")]
+    pub scan_secrets: bool,
+    /// Also pack the data paths, as they are on disk (tracked or not), into
+    /// a separate `-data.tar.gz` archive
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        DataConfig {
+            paths: Vec::new(),
+            name: None,
+            title: None,
+            license: None,
+            size: PathSizeLimits::default(),
+            scan_secrets: true,
+            archive: false,
+        }
+    }
+}
+
+impl DataConfig {
+    /// Whether `build` packs the data paths into an archive of their own,
+    /// leaving them out of the code archive
+    pub fn archived_apart(&self) -> bool {
+        self.archive && !self.paths.is_empty()
+    }
+
+    /// Whether `path` (relative to the project root, `/`-separated) is one of
+    /// the data paths or inside one
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.iter().any(|data_path| {
+            let data_path = data_path.trim_start_matches("./").trim_end_matches('/');
+            path == data_path
+                || path
+                    .strip_prefix(data_path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// Mapping of free-text CFF keywords onto controlled subject vocabularies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubjectsConfig {
+    /// Map keywords naming an OECD Fields of Science field (e.g. "physics")
+    #[serde(default)]
+    pub fields_of_science: bool,
+    /// Keyword → subject, for MeSH or any other scheme
+    #[serde(default)]
+    pub aliases: BTreeMap<String, SubjectAlias>,
+}
+
+/// The controlled-vocabulary subject a keyword stands for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubjectAlias {
+    pub term: String,
+    pub identifier: String,
+    #[serde(default = "default_subject_scheme")]
+    pub scheme: String,
+}
+
+/// How API clients (Zenodo, forges) behave on unreliable networks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Extra attempts after a connection error, timeout, 429, or 5xx
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Delay before the first retry; doubled on each further attempt, with jitter
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Limit for a whole request, upload included (0 = no limit)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Limit for connecting to a server (0 = no limit)
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Proxy URL for all requests; without it, HTTP_PROXY/HTTPS_PROXY/ALL_PROXY apply
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy` (defaults to NO_PROXY)
+    pub no_proxy: Option<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            retries: default_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            timeout_secs: default_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            proxy: None,
+            no_proxy: None,
+        }
+    }
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    30
+}
+
+/// Checks for projects that ship a Dockerfile or Apptainer recipe
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerConfig {
+    /// Warn unless a published image is referenced in codemeta.json,
+    /// CITATION.cff (a `software-container` reference) or .zenodo.json
+    #[serde(default)]
+    pub require_image_reference: bool,
+}
+
+/// Limits for the size check, in MB (1 MB = 1,000,000 bytes)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizeConfig {
+    /// Warn about a tracked file this large
+    #[serde(default = "default_file_warn_mb")]
+    pub file_warn_mb: f64,
+    /// Fail on a tracked file this large
+    #[serde(default = "default_file_fail_mb")]
+    pub file_fail_mb: f64,
+    /// Warn when the tracked files total this much
+    #[serde(default = "default_total_warn_mb")]
+    pub total_warn_mb: f64,
+    /// Fail when the tracked files total this much
+    #[serde(default = "default_total_fail_mb")]
+    pub total_fail_mb: f64,
+    /// Other limits for the files a glob matches, e.g. `[size.paths."data/**"]`.
+    /// Those files are also totalled on their own.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub paths: BTreeMap<String, PathSizeLimits>,
+}
+
+impl Default for SizeConfig {
+    fn default() -> Self {
+        SizeConfig {
+            file_warn_mb: default_file_warn_mb(),
+            file_fail_mb: default_file_fail_mb(),
+            total_warn_mb: default_total_warn_mb(),
+            total_fail_mb: default_total_fail_mb(),
+            paths: BTreeMap::new(),
+        }
+    }
+}
+
+/// Limits for the files under one glob; a limit left out is the one under
+/// `[size]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathSizeLimits {
+    pub file_warn_mb: Option<f64>,
+    pub file_fail_mb: Option<f64>,
+    pub total_warn_mb: Option<f64>,
+    pub total_fail_mb: Option<f64>,
+}
+
+impl PathSizeLimits {
+    /// These limits, with those left out taken from `size`
+    pub fn or_global(&self, size: &SizeConfig) -> PathSizeLimits {
+        PathSizeLimits {
+            file_warn_mb: self.file_warn_mb.or(Some(size.file_warn_mb)),
+            file_fail_mb: self.file_fail_mb.or(Some(size.file_fail_mb)),
+            total_warn_mb: self.total_warn_mb.or(Some(size.total_warn_mb)),
+            total_fail_mb: self.total_fail_mb.or(Some(size.total_fail_mb)),
+        }
+    }
+}
+
+fn default_file_warn_mb() -> f64 {
+    1.0
+}
+
+fn default_file_fail_mb() -> f64 {
+    10.0
+}
+
+fn default_total_warn_mb() -> f64 {
+    50.0
+}
+
+fn default_total_fail_mb() -> f64 {
+    200.0
+}
+
+/// Where a freshly minted DOI is linked from: the forges and the project's
+/// metadata files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DoiLinksConfig {
+    /// Append the DOI badge to the tag's release description
+    #[serde(default = "default_true")]
+    pub release_notes: bool,
+    /// Set the concept DOI (always the latest version) as the repository website
+    #[serde(default)]
+    pub website: bool,
+    /// Write the DOIs into CITATION.cff and codemeta.json
+    #[serde(default = "default_true")]
+    pub metadata: bool,
+    /// Add each published release to RELEASES.md
+    #[serde(default = "default_true")]
+    pub ledger: bool,
+}
+
+/// Chat channels and social accounts `publish` announces a published
+/// release in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnnounceConfig {
+    /// `false` turns announcing off, including the channels the global
+    /// config sets up (default: true)
+    pub enabled: Option<bool>,
+    /// Name of the Slack channel to announce in. Nothing is posted to Slack
+    /// unless it (or `slack_webhook`) is set; the webhook is kept in the OS
+    /// keyring under this name
+    pub slack_channel: Option<String>,
+    /// Slack incoming webhook URL. Anyone who has it can post, so it is
+    /// better kept in the OS keyring with `auth login --service slack`
+    pub slack_webhook: Option<String>,
+    /// Name of the Mattermost channel to announce in, as `slack_channel`
+    pub mattermost_channel: Option<String>,
+    /// Mattermost incoming webhook URL; likewise better kept in the OS
+    /// keyring, with `auth login --service mattermost`
+    pub mattermost_webhook: Option<String>,
+    /// Matrix homeserver, e.g. `https://matrix.org`
+    pub matrix_homeserver: Option<String>,
+    /// Room ID ("!abc:matrix.org") or alias ("#releases:matrix.org")
+    pub matrix_room: Option<String>,
+    /// Access token of the Matrix account that posts; better kept in the OS
+    /// keyring with `auth login --service matrix`
+    pub matrix_token: Option<String>,
+    /// Mastodon (or other Mastodon API) instance to toot from, e.g.
+    /// `https://fosstodon.org`
+    pub mastodon_instance: Option<String>,
+    /// Access token with the write:statuses scope; better kept in the OS
+    /// keyring with `auth login --service mastodon`
+    pub mastodon_token: Option<String>,
+    /// Bluesky handle to post as, e.g. "lab.bsky.social"
+    pub bluesky_handle: Option<String>,
+    /// App password of that account; better kept in the OS keyring with
+    /// `auth login --service bluesky`
+    pub bluesky_app_password: Option<String>,
+    /// Server the Bluesky account is hosted on (default: `https://bsky.social`)
+    pub bluesky_service: Option<String>,
+    /// The toot or post, with {title}, {version}, {doi} and {doi_url}
+    /// replaced (default: "{title} {version} is released: {doi_url}")
+    pub social_template: Option<String>,
+    /// Secret keys the project config sets itself
+    #[serde(skip)]
+    project_secrets: Vec<&'static str>,
+}
+
+impl AnnounceConfig {
+    fn merge_with_fallback(&mut self, fallback: &AnnounceConfig) {
+        let AnnounceConfig {
+            enabled,
+            slack_channel,
+            slack_webhook,
+            mattermost_channel,
+            mattermost_webhook,
+            matrix_homeserver,
+            matrix_room,
+            matrix_token,
+            mastodon_instance,
+            mastodon_token,
+            bluesky_handle,
+            bluesky_app_password,
+            bluesky_service,
+            social_template,
+            project_secrets: _,
+        } = fallback.clone();
+        self.enabled = self.enabled.or(enabled);
+        // A webhook belongs to its channel, so the two are taken together
+        if self.slack_channel.is_none() && self.slack_webhook.is_none() {
+            self.slack_channel = slack_channel;
+            self.slack_webhook = slack_webhook;
+        }
+        if self.mattermost_channel.is_none() && self.mattermost_webhook.is_none() {
+            self.mattermost_channel = mattermost_channel;
+            self.mattermost_webhook = mattermost_webhook;
+        }
+        self.matrix_homeserver = self.matrix_homeserver.take().or(matrix_homeserver);
+        self.matrix_room = self.matrix_room.take().or(matrix_room);
+        self.matrix_token = self.matrix_token.take().or(matrix_token);
+        self.mastodon_instance = self.mastodon_instance.take().or(mastodon_instance);
+        self.mastodon_token = self.mastodon_token.take().or(mastodon_token);
+        self.bluesky_handle = self.bluesky_handle.take().or(bluesky_handle);
+        self.bluesky_app_password = self.bluesky_app_password.take().or(bluesky_app_password);
+        self.bluesky_service = self.bluesky_service.take().or(bluesky_service);
+        self.social_template = self.social_template.take().or(social_template);
+    }
+
+    /// The config key holding the secret for "slack", "mattermost",
+    /// "matrix", "mastodon" or "bluesky"
+    pub fn secret_key(service: &str) -> &'static str {
+        match service {
+            "slack" => "slack_webhook",
+            "mattermost" => "mattermost_webhook",
+            "matrix" => "matrix_token",
+            "mastodon" => "mastodon_token",
+            _ => "bluesky_app_password",
+        }
+    }
+
+    /// The secret keys this config sets
+    fn secrets(&self) -> Vec<&'static str> {
+        ["slack", "mattermost", "matrix", "mastodon", "bluesky"]
+            .into_iter()
+            .filter(|service| self.plaintext(service).is_some())
+            .map(Self::secret_key)
+            .collect()
+    }
+
+    /// Whether the project config itself sets `key`, which is then used
+    /// before the keyring's secret
+    pub fn is_project_secret(&self, key: &str) -> bool {
+        self.project_secrets.contains(&key)
+    }
+
+    /// The secret for `service` as written in the config
+    pub fn plaintext(&self, service: &str) -> Option<String> {
+        match service {
+            "slack" => self.slack_webhook.clone(),
+            "mattermost" => self.mattermost_webhook.clone(),
+            "matrix" => self.matrix_token.clone(),
+            "mastodon" => self.mastodon_token.clone(),
+            _ => self.bluesky_app_password.clone(),
+        }
+    }
+
+    /// Whether announcements are made; true unless set to false
+    pub fn enabled(&self) -> bool {
+        self.enabled != Some(false)
+    }
+
+    /// Whether "slack" or "mattermost" is announced in: the config names the
+    /// channel or holds its webhook. The others are on once their server or
+    /// account is set.
+    pub fn posts_to(&self, service: &str) -> bool {
+        match service {
+            "slack" => self.slack_channel.is_some() || self.slack_webhook.is_some(),
+            "mattermost" => self.mattermost_channel.is_some() || self.mattermost_webhook.is_some(),
+            _ => true,
+        }
+    }
+
+    /// The OS keyring account holding the secret for `service`. Webhooks
+    /// are kept per channel ("slack:lab-releases"), so none without a
+    /// channel name.
+    pub fn keyring_account(&self, service: &str) -> Option<String> {
+        let channel = match service {
+            "slack" => &self.slack_channel,
+            "mattermost" => &self.mattermost_channel,
+            _ => return Some(service.to_string()),
+        };
+        channel
+            .as_ref()
+            .map(|channel| format!("{}:{}", service, channel))
+    }
+
+    /// The webhook, token or app password for "slack", "mattermost",
+    /// "matrix", "mastodon" or "bluesky": from `RELEASE_SCHOLAR_ANNOUNCE_<KEY>`,
+    /// the project config, the OS keyring, else the global config, with a
+    /// warning to move a plaintext one into the keyring. A webhook with no
+    /// channel name is only ever this config's own.
+    pub fn token(&self, service: &str) -> Option<String> {
+        let key = Self::secret_key(service);
+        let variable = format!("{}ANNOUNCE_{}", env::PREFIX, key.to_uppercase());
+        if let Some(token) = std::env::var(variable)
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+        {
+            return Some(token);
+        }
+        let plaintext = self.plaintext(service);
+        if plaintext.is_some() {
+            eprintln!(
+                "  {} [announce] {} is a credential kept in plaintext; store it with \
+                 `release-scholar auth login --service {}` and remove it from the config",
+                "!!".yellow().bold(),
+                key,
+                service
+            );
+        }
+        if self.is_project_secret(key) {
+            return plaintext;
+        }
+        match self.keyring_account(service) {
+            Some(account) => {
+                credentials::resolve(&account, plaintext, &format!("[announce] {}", key))
+            }
+            None => plaintext,
+        }
+    }
+
+    /// The template for Mastodon and Bluesky posts
+    pub fn social_template(&self) -> &str {
+        self.social_template
+            .as_deref()
+            .unwrap_or("{title} {version} is released: {doi_url}")
+    }
+
+    /// The Bluesky server posts go to, without a trailing slash
+    pub fn bluesky_service(&self) -> &str {
+        self.bluesky_service
+            .as_deref()
+            .map_or("https://bsky.social", |url| url.trim_end_matches('/'))
+    }
+}
+
+/// The DOI badges `publish` puts at the top of README.md
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BadgesConfig {
+    #[serde(default)]
+    pub doi: BadgeDoi,
+    #[serde(default)]
+    pub style: BadgeStyle,
+}
+
+/// Which DOI the README badge shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeDoi {
+    /// The DOI of the release just published, updated on every release
+    #[default]
+    Version,
+    /// The concept DOI, which always resolves to the latest version
+    Concept,
+    Both,
+    /// Leave README.md alone
+    None,
+}
+
+/// How the README badge looks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BadgeStyle {
+    /// zenodo.org/badge
+    #[default]
+    Zenodo,
+    /// shields.io styles
+    Flat,
+    FlatSquare,
+    Plastic,
+    ForTheBadge,
+    Social,
+}
+
+impl BadgeStyle {
+    /// Badge image URL for `label` and `doi`
+    pub fn image_url(self, label: &str, doi: &str) -> String {
+        let shields = match self {
+            BadgeStyle::Zenodo => return format!("https://zenodo.org/badge/DOI/{}.svg", doi),
+            BadgeStyle::Flat => "flat",
+            BadgeStyle::FlatSquare => "flat-square",
+            BadgeStyle::Plastic => "plastic",
+            BadgeStyle::ForTheBadge => "for-the-badge",
+            BadgeStyle::Social => "social",
+        };
+        // shields.io static badges: `-` and `_` are doubled; spaces, `/` and
+        // parentheses (which would end the Markdown link) are percent-encoded
+        let escape = |s: &str| {
+            s.replace('-', "--")
+                .replace('_', "__")
+                .replace(' ', "%20")
+                .replace('/', "%2F")
+                .replace('(', "%28")
+                .replace(')', "%29")
+        };
+        format!(
+            "https://img.shields.io/badge/{}-{}-blue?style={}",
+            escape(label),
+            escape(doi),
+            shields
+        )
+    }
+}
+
+impl Default for DoiLinksConfig {
+    fn default() -> Self {
+        DoiLinksConfig {
+            release_notes: true,
+            website: false,
+            metadata: true,
+            ledger: true,
+        }
+    }
+}
+
+/// Zenodo deposit fields that CITATION.cff has no place for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZenodoConfig {
+    /// Community identifiers, e.g. "astronomy-software"
+    #[serde(default)]
+    pub communities: Vec<String>,
+    /// Funding: `<funder DOI>::<award number>`, or the number alone for EU grants
+    #[serde(default)]
+    pub grants: Vec<String>,
+    /// Additional notes shown on the record
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub related_identifiers: Vec<RelatedIdentifierConfig>,
+    /// "open" (default), "embargoed", "restricted" or "closed"
+    pub access_right: Option<String>,
+    /// When an embargo ends (YYYY-MM-DD)
+    pub embargo_date: Option<String>,
+    /// Who may be granted access to a restricted record, and how
+    pub access_conditions: Option<String>,
+    /// Zenodo upload type, if not "software"
+    pub upload_type: Option<String>,
+}
+
+/// A related identifier added to every deposit (`[zenodo] related_identifiers`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RelatedIdentifierConfig {
+    pub identifier: String,
+    /// Zenodo relation type, e.g. "isDocumentedBy"
+    pub relation: String,
+    pub resource_type: Option<String>,
+    /// Identifier scheme ("doi", "url", "arxiv", ...); guessed if not given
+    pub scheme: Option<String>,
+}
+
+/// What `build` puts in the release directory, and where
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleConfig {
+    #[serde(default)]
+    pub layout: BundleLayout,
+    /// Copy CITATION.cff into the bundle
+    #[serde(default = "default_true")]
+    pub citation: bool,
+    /// Copy codemeta.json into the bundle
+    #[serde(default = "default_true")]
+    pub codemeta: bool,
+    /// Generate datacite.json and datacite.xml
+    #[serde(default = "default_true")]
+    pub datacite: bool,
+    /// Generate scholar.jsonld
+    #[serde(default = "default_true")]
+    pub jsonld: bool,
+    /// Generate RELEASE_NOTES.md, which also becomes the forge release body
+    #[serde(default = "default_true")]
+    pub release_notes: bool,
+    /// Generate provenance.intoto.json
+    #[serde(default = "default_true")]
+    pub provenance: bool,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        BundleConfig {
+            layout: BundleLayout::default(),
+            citation: true,
+            codemeta: true,
+            datacite: true,
+            jsonld: true,
+            release_notes: true,
+            provenance: true,
+        }
+    }
+}
+
+/// How `build` signs the provenance document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProvenanceConfig {
+    #[serde(default)]
+    pub sign: SignWith,
+    /// GPG key ID (gpg's default key if unset), or the SSH private key file
+    pub key: Option<String>,
+}
+
+/// How bundle files are signed, if at all
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignWith {
+    #[default]
+    None,
+    /// A detached, ASCII-armored signature (`.asc`)
+    Gpg,
+    /// An SSH signature (`.sig`), as git uses with `gpg.format = ssh`
+    Ssh,
+}
+
+/// How the files of a bundle are laid out in the release directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleLayout {
+    /// Every file at the top of the release directory
+    #[default]
+    Flat,
+    /// `archive/` (archive and checksums), `metadata/` (generated metadata)
+    /// and `citation/` (CITATION.cff and codemeta.json)
+    Grouped,
+}
+
+/// The kinds of file in a bundle, each a subdirectory in the grouped layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    Archive,
+    Metadata,
+    Citation,
+}
+
+impl Artifact {
+    /// Every kind, in the order the grouped layout lists them
+    pub const ALL: [Artifact; 3] = [Artifact::Archive, Artifact::Metadata, Artifact::Citation];
+
+    /// The kind's subdirectory in the grouped layout
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Artifact::Archive => "archive",
+            Artifact::Metadata => "metadata",
+            Artifact::Citation => "citation",
+        }
+    }
+}
+
+impl BundleLayout {
+    /// Directory that files of kind `artifact` go in
+    pub fn dir(self, release_dir: &Path, artifact: Artifact) -> PathBuf {
+        match self {
+            BundleLayout::Flat => release_dir.to_path_buf(),
+            BundleLayout::Grouped => release_dir.join(artifact.dir_name()),
+        }
+    }
+
+    /// The bundle's files: those at the top of `release_dir` and, in the
+    /// grouped layout, those in each artifact directory. Other directories
+    /// (docs a hook copied in, say) are left alone.
+    pub fn files(self, release_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut dirs = vec![release_dir.to_path_buf()];
+        if self == BundleLayout::Grouped {
+            dirs.extend(Artifact::ALL.map(|a| self.dir(release_dir, a)));
+        }
+        let mut files = Vec::new();
+        for dir in dirs.iter().filter(|d| d.is_dir()) {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Shell commands run at each release stage, from the project directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    pub pre_build: Option<String>,
+    pub post_build: Option<String>,
+    pub pre_publish: Option<String>,
+    /// Runs only once the record is published and has a DOI
+    pub post_publish: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_subject_scheme() -> String {
+    "url".to_string()
 }
 
 fn default_language() -> String {
     "eng".to_string()
 }
 
-fn default_required_files() -> Vec<String> {
+fn default_required_files() -> Vec<RequiredFile> {
+    ["LICENSE", "README.md", "CHANGELOG.md", "CITATION.cff"]
+        .into_iter()
+        .map(|f| RequiredFile::Path(f.to_string()))
+        .collect()
+}
+
+fn default_readme_sections() -> Vec<String> {
     vec![
-        "LICENSE".to_string(),
-        "README.md".to_string(),
-        "CHANGELOG.md".to_string(),
-        "CITATION.cff".to_string(),
+        "Installation|Install".to_string(),
+        "Usage|Workflow|Getting started".to_string(),
+        "How to cite|Citation|Citing".to_string(),
+        "License".to_string(),
     ]
 }
 
@@ -85,75 +1163,422 @@ fn default_archive_dir() -> String {
     "release".to_string()
 }
 
+fn default_tag_prefix() -> String {
+    "v".to_string()
+}
+
+fn default_archive_name() -> String {
+    "{name}-{tag}.tar.gz".to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
             forge: Forge::default(),
             forge_url: None,
             required_files: default_required_files(),
+            content: BTreeMap::new(),
+            readme_sections: default_readme_sections(),
             archive_dir: default_archive_dir(),
+            archive_name: default_archive_name(),
+            tag_prefix: default_tag_prefix(),
+            versioning: VersionScheme::default(),
+            tag_pattern: None,
             language: default_language(),
+            publisher: None,
             author: None,
             mirrors: None,
+            data: None,
+            subjects: None,
+            container: None,
+            size: None,
+            network: None,
+            doi_links: None,
+            badges: None,
+            announce: None,
+            hooks: None,
+            zenodo: None,
+            bundle: None,
+            provenance: None,
+            default_profile: None,
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            overridden_rules: Vec::new(),
+            ignored_env: Vec::new(),
+            rules: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
-    /// Load config: global defaults ← project overrides.
-    /// Author info merges (project fields override global fields).
-    pub fn load(project_dir: &Path) -> Self {
-        let global = load_global_config();
-        let project_path = project_dir.join(".release-scholar.toml");
+    /// Parse a single config file, without merging anything into it
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+        Config::parse(&content, path)
+    }
+
+    /// Parse config text; errors point at the line and column in `path`,
+    /// and unknown keys or values come with the closest known one
+    pub fn parse(content: &str, path: &Path) -> Result<Config, ConfigError> {
+        toml::from_str(content).map_err(|source| {
+            let Some(span) = source.span() else {
+                return ConfigError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                };
+            };
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            ConfigError::Invalid {
+                path: path.to_path_buf(),
+                line,
+                column,
+                message: diagnose(source.message()),
+            }
+        })
+    }
 
-        let mut config = if project_path.exists() {
-            let content = std::fs::read_to_string(&project_path).unwrap_or_default();
-            toml::from_str::<Config>(&content).unwrap_or_default()
+    /// Load config: global defaults ← profile ← project ← `RELEASE_SCHOLAR_*`
+    /// environment variables.
+    /// Author info merges (project fields override global fields).
+    /// `profile` (or else `RELEASE_SCHOLAR_PROFILE`, or `default_profile`)
+    /// names a `[profile.*]` table.
+    pub fn load(project_dir: &Path, profile: Option<&str>) -> Result<Self, ConfigError> {
+        let (config, mut problems) = Config::load_lenient(project_dir, profile);
+        if problems.is_empty() {
+            for variable in &config.ignored_env {
+                eprintln!(
+                    "  {} {} is not a config key; ignoring it",
+                    "!!".yellow().bold(),
+                    variable
+                );
+            }
+            Ok(config)
         } else {
-            Config::default()
+            Err(problems.remove(0))
+        }
+    }
+
+    /// Like `load`, but a file that cannot be read or parsed, an unknown
+    /// profile, or a bad environment override is left out (as if it did not exist) and returned alongside,
+    /// for `check` to report while still running everything else
+    pub fn load_lenient(project_dir: &Path, profile: Option<&str>) -> (Self, Vec<ConfigError>) {
+        let mut problems = Vec::new();
+        let mut read = |path: Option<PathBuf>| match path.filter(|p| p.exists()) {
+            Some(path) => Config::from_file(&path).unwrap_or_else(|e| {
+                problems.push(e);
+                Config::default()
+            }),
+            None => Config::default(),
         };
+        let global = read(Config::global_config_path());
+        let mut config = read(Some(project_dir.join(".release-scholar.toml")));
+        // Secrets the project sets itself are meant for it, so they come
+        // before the user's keyring
+        let project_secrets = (
+            config.mirrors.as_ref().map(MirrorsConfig::secrets),
+            config.announce.as_ref().map(AnnounceConfig::secrets),
+        );
 
-        // Merge author: project author takes priority, global fills gaps
-        if let Some(global_author) = &global.author {
-            match &mut config.author {
-                Some(project_author) => {
-                    project_author.merge_with_fallback(global_author);
-                }
-                None => {
-                    config.author = Some(global_author.clone());
+        let mut profiles = global.profiles.clone();
+        profiles.extend(std::mem::take(&mut config.profiles));
+        let name = profile
+            .map(str::to_string)
+            .or_else(env::profile)
+            .or_else(|| config.default_profile.clone())
+            .or_else(|| global.default_profile.clone());
+        if let Some(name) = name {
+            match profiles.get(&name) {
+                Some(selected) => {
+                    config.merge_identity(selected.author.as_ref(), selected.mirrors.as_ref());
+                    config.active_profile = Some(name);
                 }
+                None => problems.push(ConfigError::UnknownProfile {
+                    available: if profiles.is_empty() {
+                        "none".to_string()
+                    } else {
+                        profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                    },
+                    name,
+                }),
             }
         }
+        config.profiles = profiles;
+        let mut config = config.merged_with_global(global);
+        env::apply(&mut config, &mut problems);
+        if let (Some(mirrors), Some(secrets)) = (&mut config.mirrors, project_secrets.0) {
+            mirrors.project_secrets = secrets;
+        }
+        if let (Some(announce), Some(secrets)) = (&mut config.announce, project_secrets.1) {
+            announce.project_secrets = secrets;
+        }
+        for (file, rules) in &mut config.content {
+            rules.drop_invalid(file, &mut problems);
+        }
+        (config, problems)
+    }
 
-        // Merge mirrors: global provides defaults
-        if config.mirrors.is_none() {
-            config.mirrors = global.mirrors;
+    /// Fill in author and mirror fields the config does not set itself
+    fn merge_identity(&mut self, author: Option<&AuthorConfig>, mirrors: Option<&MirrorsConfig>) {
+        if let Some(fallback) = author {
+            match &mut self.author {
+                Some(author) => author.merge_with_fallback(fallback),
+                None => self.author = Some(fallback.clone()),
+            }
         }
+        if let Some(fallback) = mirrors {
+            match &mut self.mirrors {
+                Some(mirrors) => mirrors.merge_with_fallback(fallback),
+                None => self.mirrors = Some(fallback.clone()),
+            }
+        }
+    }
+
+    fn merged_with_global(mut self, global: Config) -> Config {
+        // Project author and mirror fields (repo names, interval) take
+        // priority; global fills gaps (name, ORCID, users, tokens)
+        self.merge_identity(global.author.as_ref(), global.mirrors.as_ref());
+
+        // Network settings are usually machine-specific: global provides defaults
+        if self.network.is_none() {
+            self.network = global.network;
+        }
+
+        // Signing keys belong to the machine (or the person), like the network
+        if self.provenance.is_none() {
+            self.provenance = global.provenance;
+        }
+
+        // A group's channels can be set once globally; a project adds or
+        // overrides its own
+        if let Some(fallback) = &global.announce {
+            match &mut self.announce {
+                Some(announce) => announce.merge_with_fallback(fallback),
+                None => self.announce = Some(fallback.clone()),
+            }
+        }
+
+        // An institution's rule sets live in the global config; a project
+        // may replace one by defining it under the same name, which `check`
+        // then points out
+        self.overridden_rules = self
+            .rules
+            .keys()
+            .filter(|name| global.rules.contains_key(*name))
+            .cloned()
+            .collect();
+        let mut rules = global.rules;
+        rules.extend(std::mem::take(&mut self.rules));
+        self.rules = rules;
 
-        config
+        self
     }
 
+    /// The profile `load` applied, if any
+    pub fn profile(&self) -> Option<&ProfileConfig> {
+        self.profiles.get(self.active_profile.as_deref()?)
+    }
+
+    /// Whether Zenodo commands use the sandbox without being told to
+    pub fn sandbox(&self) -> bool {
+        self.profile().is_some_and(|p| p.sandbox)
+    }
+
+    /// Zenodo deposit settings, or the defaults if none are configured
+    pub fn zenodo(&self) -> ZenodoConfig {
+        self.zenodo.clone().unwrap_or_default()
+    }
+
+    /// Zenodo community identifiers to submit depositions to: the project's
+    /// `[zenodo]` ones, then the profile's
+    pub fn communities(&self) -> Vec<String> {
+        let mut communities = self.zenodo().communities;
+        for community in self.profile().map_or(&[][..], |p| &p.communities) {
+            if !communities.contains(community) {
+                communities.push(community.clone());
+            }
+        }
+        communities
+    }
+
+    /// Web URL of the primary forge
+    pub fn forge_base_url(&self) -> String {
+        match (&self.forge, &self.forge_url) {
+            (Forge::Codeberg, Some(url)) => url.trim_end_matches('/').to_string(),
+            (Forge::Codeberg, None) => "https://codeberg.org".to_string(),
+            (Forge::Github, _) => "https://github.com".to_string(),
+            (Forge::Gitlab, _) => "https://gitlab.com".to_string(),
+        }
+    }
+
+    /// API root of the Codeberg (or self-hosted Forgejo/Gitea) instance that
+    /// the `codeberg_*` settings refer to
+    pub fn gitea_api_url(&self) -> String {
+        let base = match (&self.forge, &self.forge_url) {
+            (Forge::Codeberg, Some(url)) => url.trim_end_matches('/'),
+            _ => "https://codeberg.org",
+        };
+        format!("{}/api/v1", base)
+    }
+
+    /// Which tags are releases, from `tag_pattern` or else `tag_prefix`
+    pub fn tag_pattern(&self) -> Result<TagPattern, ConfigError> {
+        match &self.tag_pattern {
+            Some(pattern) => {
+                TagPattern::regex(pattern).map_err(|problem| ConfigError::TagPattern {
+                    pattern: pattern.clone(),
+                    problem,
+                })
+            }
+            None => Ok(TagPattern::prefix(&self.tag_prefix, self.versioning)),
+        }
+    }
+
+    /// The archive file name for a release, from the `archive_name` template
+    pub fn archive_file_name(&self, name: &str, release: &GitInfo) -> Result<String, ConfigError> {
+        let invalid = |problem: String| ConfigError::ArchiveName {
+            template: self.archive_name.clone(),
+            problem,
+        };
+        if !self.archive_name.ends_with(".tar.gz") {
+            return Err(invalid("it must end in .tar.gz".to_string()));
+        }
+
+        let placeholder = Regex::new(r"\{([^{}]*)\}").unwrap();
+        let mut unknown = None;
+        let file_name = placeholder.replace_all(&self.archive_name, |caps: &regex::Captures| {
+            match &caps[1] {
+                "name" => name.to_string(),
+                "version" => release.version.clone(),
+                "tag" => release.tag.clone(),
+                "date" => release.date.clone(),
+                other => {
+                    unknown.get_or_insert_with(|| other.to_string());
+                    String::new()
+                }
+            }
+        });
+        if let Some(other) = unknown {
+            return Err(invalid(format!(
+                "unknown placeholder {{{}}} (use {{name}}, {{version}}, {{tag}} or {{date}})",
+                other
+            )));
+        }
+        if file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+            return Err(invalid(format!("'{}' is not a plain file name", file_name)));
+        }
+        if file_name.ends_with(tarball::DATA_ARCHIVE_SUFFIX) {
+            return Err(invalid(format!(
+                "it must not end in {}, which names the data archive",
+                tarball::DATA_ARCHIVE_SUFFIX
+            )));
+        }
+        Ok(file_name.into_owned())
+    }
+
+    /// Data file checks, or the defaults if none are configured
+    pub fn data(&self) -> DataConfig {
+        self.data.clone().unwrap_or_default()
+    }
+
+    /// Size check limits, or the defaults if none are configured
+    pub fn size(&self) -> SizeConfig {
+        self.size.clone().unwrap_or_default()
+    }
+
+    /// Bundle layout and signing, or the defaults if none are configured
+    pub fn bundle(&self) -> BundleConfig {
+        self.bundle.clone().unwrap_or_default()
+    }
+
+    /// Where the bundle for `tag` is built: `archive_dir` (relative to the
+    /// project, unless absolute) and the tag
+    pub fn release_dir(&self, project_dir: &Path, tag: &str) -> PathBuf {
+        project_dir.join(&self.archive_dir).join(tag)
+    }
+
+    /// Build bundles under `dir` (relative to the working directory) instead
+    /// of `archive_dir`
+    pub fn set_output_dir(&mut self, dir: &Path) -> Result<(), ConfigError> {
+        let dir = std::path::absolute(dir).map_err(IoError::on("resolve", dir))?;
+        self.archive_dir = dir.to_string_lossy().into_owned();
+        Ok(())
+    }
+
+    /// Where minted DOIs are written back, or the defaults if none are configured
+    pub fn doi_links(&self) -> DoiLinksConfig {
+        self.doi_links.clone().unwrap_or_default()
+    }
+
+    /// README badge settings, or the defaults if none are configured
+    pub fn badges(&self) -> BadgesConfig {
+        self.badges.clone().unwrap_or_default()
+    }
+
+    /// Release announcement settings, or the defaults if none are configured
+    pub fn announce(&self) -> AnnounceConfig {
+        self.announce.clone().unwrap_or_default()
+    }
+
+    /// Build provenance settings, or the defaults if none are configured
+    pub fn provenance(&self) -> ProvenanceConfig {
+        self.provenance.clone().unwrap_or_default()
+    }
+
+    /// Network settings, or the defaults if none are configured
+    pub fn network(&self) -> NetworkConfig {
+        self.network.clone().unwrap_or_default()
+    }
+
+    /// The configuration as TOML, as `config list` shows it
     pub fn to_toml_string(&self) -> String {
         toml::to_string_pretty(self).unwrap_or_default()
     }
 
+    /// Whether the setting named `key` (its last dotted part) holds a
+    /// credential: a token, a password or a webhook URL
+    pub fn is_secret_key(key: &str) -> bool {
+        let name = key.rsplit('.').next().unwrap_or(key);
+        ["_token", "_password", "_webhook"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+    }
+
     /// Path to the global config file
     pub fn global_config_path() -> Option<std::path::PathBuf> {
         dirs::config_dir().map(|d| d.join("release-scholar").join("config.toml"))
     }
 }
 
-/// Load global config from ~/.config/release-scholar/config.toml
-/// (or ~/Library/Application Support/release-scholar/config.toml on macOS)
-fn load_global_config() -> Config {
-    let path = match Config::global_config_path() {
-        Some(p) => p,
-        None => return Config::default(),
-    };
-    if path.exists() {
-        let content = std::fs::read_to_string(&path).unwrap_or_default();
-        toml::from_str(&content).unwrap_or_default()
+/// Reword serde's "unknown field `x`, expected one of `a`, `b`" (and the
+/// same for enum variants), suggesting the closest expected name
+fn diagnose(message: &str) -> String {
+    let message = message.trim_end();
+    let kind = if message.starts_with("unknown field") {
+        "key"
+    } else if message.starts_with("unknown variant") {
+        "value"
     } else {
-        Config::default()
+        return message.lines().collect::<Vec<_>>().join("; ");
+    };
+    let names: Vec<&str> = message.split('`').skip(1).step_by(2).collect();
+    let Some((unknown, expected)) = names.split_first() else {
+        return message.to_string();
+    };
+    let closest = expected
+        .iter()
+        .map(|name| (strsim::jaro_winkler(unknown, name), name))
+        .filter(|(score, _)| *score > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    match closest {
+        Some((_, name)) => format!("unknown {} `{}`, did you mean `{}`?", kind, unknown, name),
+        None if expected.is_empty() => format!("unknown {} `{}`", kind, unknown),
+        None => format!(
+            "unknown {} `{}`, expected one of: {}",
+            kind,
+            unknown,
+            expected.join(", ")
+        ),
     }
 }
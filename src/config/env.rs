@@ -0,0 +1,196 @@
+//! `RELEASE_SCHOLAR_*` environment variables, which override any config key:
+//! `RELEASE_SCHOLAR_ARCHIVE_DIR`, `RELEASE_SCHOLAR_NETWORK_RETRIES`,
+//! `RELEASE_SCHOLAR_MIRRORS_GITHUB_TOKEN`, ...
+
+use super::{diagnose, Config};
+use crate::error::ConfigError;
+use serde::Deserialize;
+
+pub(super) const PREFIX: &str = "RELEASE_SCHOLAR_";
+
+/// Variables with the prefix that are not config keys: the profile, and
+/// what hooks are told about the release (so a hook can run release-scholar)
+const RESERVED: &[&str] = &[
+    "PROFILE",
+    "STAGE",
+    "PROJECT_DIR",
+    "TAG",
+    "VERSION",
+    "BUNDLE",
+    "SANDBOX",
+    "DOI",
+    "DOI_URL",
+];
+
+/// The profile named by `RELEASE_SCHOLAR_PROFILE`
+pub(super) fn profile() -> Option<String> {
+    std::env::var(format!("{}PROFILE", PREFIX))
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+}
+
+/// Apply every override to `config`. A variable that holds a value of the
+/// wrong type is left out and added to `problems`: an error for
+/// `Config::load`, reported by `check`. One that names no config key is
+/// left out too, but only noted in `config.ignored_env`, since other tools
+/// may share the prefix.
+pub(super) fn apply(config: &mut Config, problems: &mut Vec<ConfigError>) {
+    let mut overrides: Vec<(String, String)> = std::env::vars_os()
+        .filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            let key = name.strip_prefix(PREFIX)?;
+            if RESERVED.contains(&key) {
+                return None;
+            }
+            Some((name.clone(), value.into_string().ok()?))
+        })
+        .collect();
+    if overrides.is_empty() {
+        return;
+    }
+    overrides.sort();
+
+    let Ok(mut settings) = toml::Table::try_from(&*config) else {
+        return;
+    };
+    let mut ignored = Vec::new();
+    for (variable, value) in overrides {
+        let key = variable[PREFIX.len()..].to_lowercase();
+        match set(&settings, &key, &value) {
+            Ok(Some(updated)) => settings = updated,
+            Ok(None) => ignored.push(variable),
+            Err(message) => problems.push(ConfigError::Env { variable, message }),
+        }
+    }
+    if let Ok(mut updated) = toml::Value::Table(settings).try_into::<Config>() {
+        updated.active_profile = config.active_profile.take();
+        updated.overridden_rules = std::mem::take(&mut config.overridden_rules);
+        *config = updated;
+    }
+    config.ignored_env = ignored;
+}
+
+/// `settings` with `key` (e.g. `mirrors_github_token`) set to `raw`, or
+/// `None` if `key` is no config key. Underscores are ambiguous, so the key
+/// is matched against the config's own field names table by table:
+/// `data_size_file_warn_mb` ends up at `data.size.file_warn_mb`.
+fn set(settings: &toml::Table, key: &str, raw: &str) -> Result<Option<toml::Table>, String> {
+    let mut paths = Vec::new();
+    walk(settings, &mut Vec::new(), key, &mut paths);
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    paths.sort_by_key(Vec::len);
+
+    // `3`, `true` or `["a"]` as typed, otherwise (or if that has the wrong
+    // type) as a string
+    let mut values = Vec::new();
+    if let Ok(value) = toml::Value::deserialize(toml::de::ValueDeserializer::new(raw)) {
+        values.push(value);
+    }
+    if !values.iter().any(toml::Value::is_str) {
+        values.push(toml::Value::String(raw.to_string()));
+    }
+
+    let mut error = None;
+    for path in paths {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        for value in &values {
+            let mut updated = settings.clone();
+            if !insert(&mut updated, &path, value.clone()) {
+                break;
+            }
+            match toml::Value::Table(updated.clone()).try_into::<Config>() {
+                Ok(_) => return Ok(Some(updated)),
+                Err(e) => {
+                    error.get_or_insert_with(|| diagnose(e.message()));
+                }
+            }
+        }
+    }
+    Err(error.unwrap_or_else(|| "not a config key".to_string()))
+}
+
+/// Every path below `prefix` that `key` can name: a field of the table at
+/// `prefix`, or a field followed by a key in the table it holds. The name
+/// of an entry in a map (a rule set, a profile) can be anything, so there
+/// each underscore is tried as the end of the name.
+fn walk(settings: &toml::Table, prefix: &mut Vec<String>, key: &str, paths: &mut Vec<Vec<String>>) {
+    let names: Vec<String> = match fields(settings, prefix) {
+        Some(fields) => fields
+            .into_iter()
+            .filter(|field| key == field || key.starts_with(&format!("{}_", field)))
+            .collect(),
+        None => std::iter::once(key)
+            .chain(key.match_indices('_').map(|(i, _)| &key[..i]))
+            .map(str::to_string)
+            .collect(),
+    };
+    for name in names {
+        descend(settings, prefix, &name, key, paths);
+    }
+}
+
+/// `prefix.name` if it is all of `key`, else the paths below it for the rest
+fn descend(
+    settings: &toml::Table,
+    prefix: &mut Vec<String>,
+    name: &str,
+    key: &str,
+    paths: &mut Vec<Vec<String>>,
+) {
+    prefix.push(name.to_string());
+    match key
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('_'))
+    {
+        Some(rest) => walk(settings, prefix, rest, paths),
+        None => paths.push(prefix.clone()),
+    }
+    prefix.pop();
+}
+
+/// The field names of the table at `path`, as the config's own "unknown
+/// field" error lists them for a key it cannot have; `None` when the table
+/// is a map that takes any name
+fn fields(settings: &toml::Table, path: &[String]) -> Option<Vec<String>> {
+    const PROBE: &str = "_";
+    let mut probe: Vec<&str> = path.iter().map(String::as_str).collect();
+    probe.push(PROBE);
+    let mut probed = settings.clone();
+    if !insert(&mut probed, &probe, toml::Value::Boolean(true)) {
+        return Some(Vec::new());
+    }
+    let error = toml::Value::Table(probed).try_into::<Config>().err()?;
+    let message = error.message();
+    if !message.starts_with(&format!("unknown field `{}`", PROBE)) {
+        return None;
+    }
+    Some(
+        message
+            .split('`')
+            .skip(3)
+            .step_by(2)
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Set `path` in `table` to `value`, creating the tables on the way; false
+/// if a setting that is not a table is in the way
+fn insert(table: &mut toml::Table, path: &[&str], value: toml::Value) -> bool {
+    match path {
+        [] => false,
+        [name] => {
+            table.insert(name.to_string(), value);
+            true
+        }
+        [first, rest @ ..] => match table
+            .entry(first.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            toml::Value::Table(inner) => insert(inner, rest, value),
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::ConfigError;
+use colored::Colorize;
+use keyring::Entry;
+
+/// Keyring service name; accounts are "zenodo", "zenodo-sandbox", "codeberg", ...
+const SERVICE: &str = "release-scholar";
+
+/// Read a token from the OS keyring (macOS Keychain, Secret Service, Windows
+/// Credential Manager). A missing entry or unavailable keyring is `None`.
+pub fn lookup(account: &str) -> Option<String> {
+    Entry::new(SERVICE, account)
+        .ok()?
+        .get_password()
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+}
+
+/// Store `secret` in the OS keyring as `account`, replacing any there
+pub fn store(account: &str, secret: &str) -> Result<(), ConfigError> {
+    Entry::new(SERVICE, account)
+        .and_then(|e| e.set_password(secret))
+        .map_err(keyring_error("store", account))
+}
+
+/// Remove a token from the keyring; `Ok(false)` if there was none
+pub fn remove(account: &str) -> Result<bool, ConfigError> {
+    let entry = Entry::new(SERVICE, account).map_err(keyring_error("access", account))?;
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(keyring_error("remove", account)(e)),
+    }
+}
+
+fn keyring_error(
+    action: &'static str,
+    account: &str,
+) -> impl FnOnce(keyring::Error) -> ConfigError {
+    let account = account.to_string();
+    move |source| ConfigError::Keyring {
+        action,
+        account,
+        source,
+    }
+}
+
+/// The keyring token for `account`, falling back to a plaintext token from
+/// `source` (a file or config key). Reading never writes to the keyring:
+/// `auth login` offers to move a plaintext token there. When both are there
+/// and differ, the keyring's is used, with a warning: either may be the
+/// stale one.
+pub fn resolve(account: &str, plaintext: Option<String>, source: &str) -> Option<String> {
+    let plaintext = plaintext
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    if let Some(token) = lookup(account) {
+        if plaintext.as_ref().is_some_and(|p| *p != token) {
+            eprintln!(
+                "  {} The {} token in {} differs from the one in the OS keyring, which is used. \
+                 Delete the plaintext copy, or replace the keyring's with `release-scholar auth login`.",
+                "!!".yellow().bold(),
+                account,
+                source
+            );
+        }
+        return Some(token);
+    }
+    plaintext
+}
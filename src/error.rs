@@ -0,0 +1,402 @@
+use reqwest::StatusCode;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Any error from the library. Each module returns its own, more specific
+/// type; this wraps them for callers that do not care which one it was.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Git(#[from] GitError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Zenodo(#[from] ZenodoError),
+    #[error(transparent)]
+    Forge(#[from] ForgeError),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error(transparent)]
+    Hook(#[from] HookError),
+    #[error(transparent)]
+    Sign(#[from] SignError),
+    #[error(transparent)]
+    Announce(#[from] AnnounceError),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// A file operation that failed, and the file it failed on
+#[derive(Debug, Error)]
+#[error("Cannot {action} {}: {source}", path.display())]
+pub struct IoError {
+    pub action: &'static str,
+    pub path: PathBuf,
+    #[source]
+    pub source: std::io::Error,
+}
+
+impl IoError {
+    /// For `map_err`: `fs::read(&path).map_err(IoError::on("read", &path))`
+    pub fn on(
+        action: &'static str,
+        path: impl Into<PathBuf>,
+    ) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| IoError {
+            action,
+            path,
+            source,
+        }
+    }
+}
+
+/// Git repository access
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("Cannot open repo: {0}")]
+    Open(#[source] git2::Error),
+    /// Any other libgit2 failure; `action` says what was being done
+    #[error("Cannot {action}: {source}")]
+    Git {
+        action: String,
+        #[source]
+        source: git2::Error,
+    },
+    #[error("HEAD has no release tag ({expected})")]
+    NoReleaseTag { expected: String },
+}
+
+impl GitError {
+    /// For `map_err`: `repo.head().map_err(GitError::on("read HEAD"))`
+    pub fn on(action: impl Into<String>) -> impl FnOnce(git2::Error) -> Self {
+        let action = action.into();
+        move |source| GitError::Git { action, source }
+    }
+}
+
+/// Configuration, state and credential files
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error("Cannot parse {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    /// A config file that is not valid TOML or does not match the schema
+    #[error("{}:{line}:{column}: {message}", path.display())]
+    Invalid {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("{variable}: {message}")]
+    Env { variable: String, message: String },
+    #[error("Invalid archive_name '{template}': {problem}")]
+    ArchiveName { template: String, problem: String },
+    #[error("Invalid tag_pattern '{pattern}': {problem}")]
+    TagPattern { pattern: String, problem: String },
+    #[error("Invalid [content.\"{file}\"] rule '{rule}': {problem}")]
+    ContentRule {
+        file: String,
+        rule: String,
+        problem: String,
+    },
+    #[error("Unknown profile '{name}' (configured: {available})")]
+    UnknownProfile { name: String, available: String },
+    #[error("Unknown rule set '{name}' (available: {available})")]
+    UnknownRuleSet { name: String, available: String },
+    #[error("Rule sets extend each other in a cycle: {chain}")]
+    RuleSetCycle { chain: String },
+    #[error("Cannot serialize {what}: {source}")]
+    Serialize {
+        what: &'static str,
+        #[source]
+        source: toml::ser::Error,
+    },
+    #[error("Cannot determine config directory")]
+    NoConfigDir,
+    #[error("Invalid proxy URL {url}: {source}")]
+    Proxy {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Cannot create HTTP client: {0}")]
+    HttpClient(#[source] reqwest::Error),
+    #[error("Cannot {action} {account} token in the OS keyring: {source}")]
+    Keyring {
+        action: &'static str,
+        account: String,
+        #[source]
+        source: keyring::Error,
+    },
+}
+
+/// Zenodo API calls and tokens
+#[derive(Debug, Error)]
+pub enum ZenodoError {
+    /// The request did not get an answer
+    #[error("HTTP error {context}: {source}")]
+    Http {
+        context: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// Zenodo answered with an error status
+    #[error("Zenodo API error {status} {context}: {body}")]
+    Api {
+        status: StatusCode,
+        context: String,
+        body: String,
+    },
+    #[error("Cannot parse {what}: {source}")]
+    Parse {
+        what: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("token rejected (invalid or revoked)")]
+    TokenRejected,
+    #[error("Zenodo token lacks the {scope} scope")]
+    MissingScope { scope: &'static str, sandbox: bool },
+    #[error("Zenodo token check failed: {0}")]
+    TokenCheck(#[source] Box<ZenodoError>),
+    #[error("No Zenodo token found. Run `release-scholar auth login --service {account}`, set {env_var}, or save it to {}", path.display())]
+    NoToken {
+        account: &'static str,
+        env_var: &'static str,
+        path: PathBuf,
+    },
+    #[error("Zenodo did not reserve a DOI")]
+    Reservation,
+    #[error("Zenodo did not start a new version of record {record}")]
+    NewVersion { record: u64 },
+    #[error("{source} (draft {draft} was left behind)")]
+    DraftLeftBehind {
+        draft: u64,
+        #[source]
+        source: Box<ZenodoError>,
+    },
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+impl ZenodoError {
+    /// The HTTP status of an API error
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ZenodoError::Api { status, .. } => Some(*status),
+            ZenodoError::DraftLeftBehind { source, .. } | ZenodoError::TokenCheck(source) => {
+                source.status()
+            }
+            _ => None,
+        }
+    }
+
+    /// The request may have been carried out though no answer came back: it
+    /// timed out after being sent, or a gateway gave up waiting (5xx)
+    pub fn outcome_unknown(&self) -> bool {
+        match self {
+            ZenodoError::Http { source, .. } => {
+                !source.is_connect() && (source.is_timeout() || source.is_request())
+            }
+            ZenodoError::Api { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
+/// Codeberg/Forgejo, GitHub and GitLab APIs
+#[derive(Debug, Error)]
+pub enum ForgeError {
+    #[error("HTTP error {context}: {source}")]
+    Http {
+        context: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{forge} API error {status} {context}: {body}")]
+    Api {
+        forge: &'static str,
+        status: StatusCode,
+        context: String,
+        body: String,
+    },
+    #[error("Cannot parse {what}: {source}")]
+    Parse {
+        what: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{tag} is at {remote} on {forge}, not at {local} as here; push the tag again")]
+    TagMoved {
+        forge: &'static str,
+        tag: String,
+        remote: String,
+        local: String,
+    },
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+impl ForgeError {
+    /// The HTTP status of an API error
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ForgeError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// A release announcement that could not be posted to a chat channel
+#[derive(Debug, Error)]
+pub enum AnnounceError {
+    #[error("HTTP error reaching {service}: {source}")]
+    Http {
+        service: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{service} API error {status}: {body}")]
+    Api {
+        service: &'static str,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("Cannot parse {what}: {source}")]
+    Parse {
+        what: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Invalid {what} '{url}'")]
+    Url { what: &'static str, url: String },
+    #[error("No {service} credentials. Run `release-scholar auth login --service {account}` or set {env_var}")]
+    NoToken {
+        service: &'static str,
+        account: &'static str,
+        env_var: String,
+    },
+    #[error("The {service} message is {length} characters, over the limit of {limit}; shorten social_template under [announce]")]
+    TooLong {
+        service: &'static str,
+        length: usize,
+        limit: usize,
+    },
+    #[error("Unknown placeholder {{{name}}} in the template (use {known})")]
+    Placeholder { name: String, known: String },
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// Project metadata files that cannot be read or do not make sense
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error("Failed to parse CITATION.cff: {0}")]
+    Citation(#[source] serde_yaml::Error),
+    #[error("Failed to parse .zenodo.json: {0}")]
+    ZenodoJson(#[source] serde_json::Error),
+    #[error(".zenodo.json must contain a JSON object")]
+    ZenodoJsonNotObject,
+    #[error("Cannot write CITATION.cff: {0}")]
+    CitationWrite(#[source] serde_yaml::Error),
+    #[error("Failed to parse codemeta.json: {0}")]
+    CodeMeta(#[source] serde_json::Error),
+    #[error("codemeta.json must contain a JSON object")]
+    CodeMetaNotObject,
+    #[error("Failed to parse {file}: {source}")]
+    Manifest {
+        file: &'static str,
+        #[source]
+        source: toml_edit::TomlError,
+    },
+    #[error("Failed to parse package.json: {0}")]
+    PackageJson(#[source] serde_json::Error),
+    #[error("Invalid .zenodo.json: {0}")]
+    InvalidZenodoJson(#[source] serde_json::Error),
+    #[error("Cannot serialize metadata: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error("Data path '{0}' not found")]
+    DataPathNotFound(String),
+    #[error("Cannot read {path}: {source}")]
+    DataPath {
+        path: String,
+        #[source]
+        source: walkdir::Error,
+    },
+    #[error("Failed to parse notebook {path}: {source}")]
+    Notebook {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A `[hooks]` command that could not be started or did not succeed
+#[derive(Debug, Error)]
+pub enum HookError {
+    #[error("Cannot run {stage} hook `{command}`: {source}")]
+    Spawn {
+        stage: &'static str,
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{stage} hook `{command}` failed ({status})")]
+    Failed {
+        stage: &'static str,
+        command: String,
+        status: std::process::ExitStatus,
+    },
+}
+
+/// A build provenance document that could not be signed
+#[derive(Debug, Error)]
+pub enum SignError {
+    #[error("Cannot run {tool} to sign the provenance: {source}")]
+    Spawn {
+        tool: &'static str,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{tool} could not sign the provenance: {message}")]
+    Failed { tool: &'static str, message: String },
+    #[error("Signing with ssh needs the private key file as `key` under [provenance]")]
+    NoSshKey,
+}
+
+// The command-line front end reports errors as plain messages
+macro_rules! into_message {
+    ($($error:ty),*) => {
+        $(impl From<$error> for String {
+            fn from(e: $error) -> String {
+                e.to_string()
+            }
+        })*
+    };
+}
+
+into_message!(
+    Error,
+    IoError,
+    GitError,
+    ConfigError,
+    ZenodoError,
+    ForgeError,
+    AnnounceError,
+    ValidationError,
+    HookError,
+    SignError
+);
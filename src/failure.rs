@@ -0,0 +1,189 @@
+//! Why a command failed, as the exit status tells scripts and CI:
+//!
+//! | Status | Meaning |
+//! |--------|---------|
+//! | 0 | Success |
+//! | 1 | Any other error (git, files, hooks, signing) |
+//! | 2 | Validation failed: `check` found failures, or the project metadata is invalid |
+//! | 3 | Configuration or usage error: bad config or flags, missing token |
+//! | 4 | Network or API error: Zenodo or a forge could not be reached, or refused |
+//! | 5 | Aborted at a confirmation prompt |
+
+use release_scholar::error::{
+    AnnounceError, ConfigError, Error, ForgeError, GitError, HookError, IoError, SignError,
+    ValidationError, ZenodoError,
+};
+use release_scholar::zenodo;
+use std::fmt;
+use std::process::ExitCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Other = 1,
+    Validation = 2,
+    Config = 3,
+    Network = 4,
+    Aborted = 5,
+}
+
+impl From<Kind> for ExitCode {
+    fn from(kind: Kind) -> ExitCode {
+        ExitCode::from(kind as u8)
+    }
+}
+
+/// A command's error message, and the kind of failure it reports
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: Kind,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(kind: Kind, message: impl Into<String>) -> Self {
+        Failure {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Failure::new(Kind::Validation, message)
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        Failure::new(Kind::Config, message)
+    }
+
+    pub fn aborted() -> Self {
+        Failure::new(Kind::Aborted, "Aborted.")
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// Messages built by the commands themselves say nothing of their cause
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure::new(Kind::Other, message)
+    }
+}
+
+impl From<&str> for Failure {
+    fn from(message: &str) -> Self {
+        Failure::new(Kind::Other, message)
+    }
+}
+
+impl From<ConfigError> for Failure {
+    fn from(e: ConfigError) -> Self {
+        Failure::new(config_kind(&e), e.to_string())
+    }
+}
+
+fn config_kind(e: &ConfigError) -> Kind {
+    match e {
+        // The config is fine; reading it, or the keyring, is not
+        ConfigError::Io(_) | ConfigError::Keyring { .. } => Kind::Other,
+        _ => Kind::Config,
+    }
+}
+
+impl From<ZenodoError> for Failure {
+    fn from(e: ZenodoError) -> Self {
+        let kind = match &e {
+            ZenodoError::NoToken { .. } => Kind::Config,
+            ZenodoError::MissingScope { sandbox, .. } => {
+                return Failure::config(format!(
+                    "{}.\n  Create a token with deposit:write and deposit:actions at \
+                     {}/account/settings/applications/tokens/new/\n  and store it with \
+                     `release-scholar auth login --service {}`",
+                    e,
+                    zenodo::web_url(*sandbox),
+                    zenodo::keyring_account(*sandbox)
+                ))
+            }
+            ZenodoError::Config(e) => config_kind(e),
+            ZenodoError::Io(_) => Kind::Other,
+            _ => Kind::Network,
+        };
+        Failure::new(kind, e.to_string())
+    }
+}
+
+impl From<ForgeError> for Failure {
+    fn from(e: ForgeError) -> Self {
+        let kind = match &e {
+            ForgeError::Config(e) => config_kind(e),
+            ForgeError::Io(_) => Kind::Other,
+            _ => Kind::Network,
+        };
+        Failure::new(kind, e.to_string())
+    }
+}
+
+impl From<AnnounceError> for Failure {
+    fn from(e: AnnounceError) -> Self {
+        let kind = match &e {
+            AnnounceError::Url { .. }
+            | AnnounceError::NoToken { .. }
+            | AnnounceError::TooLong { .. }
+            | AnnounceError::Placeholder { .. } => Kind::Config,
+            AnnounceError::Config(e) => config_kind(e),
+            _ => Kind::Network,
+        };
+        Failure::new(kind, e.to_string())
+    }
+}
+
+impl From<ValidationError> for Failure {
+    fn from(e: ValidationError) -> Self {
+        let kind = match e {
+            ValidationError::Io(_) => Kind::Other,
+            _ => Kind::Validation,
+        };
+        Failure::new(kind, e.to_string())
+    }
+}
+
+impl From<GitError> for Failure {
+    fn from(e: GitError) -> Self {
+        let kind = match e {
+            GitError::NoReleaseTag { .. } => Kind::Validation,
+            _ => Kind::Other,
+        };
+        Failure::new(kind, e.to_string())
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Git(e) => e.into(),
+            Error::Config(e) => e.into(),
+            Error::Zenodo(e) => e.into(),
+            Error::Forge(e) => e.into(),
+            Error::Announce(e) => e.into(),
+            Error::Validation(e) => e.into(),
+            Error::Hook(e) => e.into(),
+            Error::Sign(e) => e.into(),
+            Error::Io(e) => e.into(),
+        }
+    }
+}
+
+macro_rules! other {
+    ($($error:ty),*) => {
+        $(impl From<$error> for Failure {
+            fn from(e: $error) -> Self {
+                Failure::new(Kind::Other, e.to_string())
+            }
+        })*
+    };
+}
+
+other!(IoError, HookError, SignError);
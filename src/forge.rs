@@ -0,0 +1,246 @@
+/// Codeberg (and other Forgejo/Gitea) API client
+pub mod gitea;
+/// GitHub API client
+pub mod github;
+/// GitLab API client
+pub mod gitlab;
+
+use crate::config::{Config, Forge};
+use crate::error::ForgeError;
+use clap::ValueEnum;
+use reqwest::blocking::Response;
+use std::path::Path;
+
+/// Forges a release can be created on, next to the Zenodo deposit
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReleaseTarget {
+    Codeberg,
+    Gitlab,
+}
+
+impl ReleaseTarget {
+    /// The forge's name, for messages
+    pub fn label(self) -> &'static str {
+        match self {
+            ReleaseTarget::Codeberg => "Codeberg",
+            ReleaseTarget::Gitlab => "GitLab",
+        }
+    }
+}
+
+/// What a forge release is made of
+pub struct Release<'a> {
+    pub tag: &'a str,
+    /// The commit the tag points at, where a forge that lacks the tag
+    /// creates it
+    pub commit: String,
+    pub version: &'a str,
+    pub name: String,
+    pub description: String,
+}
+
+/// A repository as the hosting checks see it, whichever forge it is on
+pub struct RepositoryInfo {
+    pub public: bool,
+    /// Empty for a repository nothing was pushed to yet
+    pub default_branch: String,
+    pub description: String,
+    pub topics: Vec<String>,
+}
+
+/// Visibility, default branch, description and topics of `owner/repo` on the
+/// primary forge. `token` may be empty for a public repository.
+pub fn repository_info(
+    config: &Config,
+    owner: &str,
+    repo: &str,
+    token: String,
+) -> Result<RepositoryInfo, ForgeError> {
+    let network = config.network();
+    match config.forge {
+        Forge::Github => {
+            let r = github::GitHubClient::new(owner, repo, token, &network)?.repository()?;
+            Ok(RepositoryInfo {
+                public: !r.private,
+                default_branch: r.default_branch,
+                description: r.description.unwrap_or_default(),
+                topics: r.topics,
+            })
+        }
+        Forge::Gitlab => {
+            let p = gitlab::GitLabClient::new(owner, repo, token, &network)?.project()?;
+            Ok(RepositoryInfo {
+                public: p.visibility == "public",
+                default_branch: p.default_branch.unwrap_or_default(),
+                description: p.description.unwrap_or_default(),
+                topics: p.topics,
+            })
+        }
+        Forge::Codeberg => {
+            let client =
+                gitea::GiteaClient::new(&config.gitea_api_url(), owner, repo, token, &network)?;
+            let r = client.repository()?;
+            Ok(RepositoryInfo {
+                public: !r.private,
+                default_branch: r.default_branch,
+                description: r.description,
+                topics: client.topics()?,
+            })
+        }
+    }
+}
+
+/// Where the `origin` remote points
+pub struct Remote {
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+/// Parse the `origin` remote: `https://host/owner/repo.git`, `ssh://git@host/owner/repo`
+/// or `git@host:owner/repo.git`
+pub fn origin(project_dir: &Path) -> Option<Remote> {
+    let repo = git2::Repository::open(project_dir).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    let parsed = parse_remote_url(remote.url()?);
+    // Not the URL itself: it may hold credentials
+    if let Some(r) = &parsed {
+        tracing::debug!("origin is {}/{}/{}", r.host, r.owner, r.repo);
+    }
+    parsed
+}
+
+fn parse_remote_url(url: &str) -> Option<Remote> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-like syntax: git@host:owner/repo
+        None => url.replacen(':', "/", 1),
+    };
+    let rest = rest.rsplit_once('@').map_or(rest.as_str(), |(_, r)| r);
+    let mut parts = rest.trim_end_matches('/').split('/');
+    let host = parts.next()?.split(':').next()?.to_string();
+    let path: Vec<&str> = parts.collect();
+    let (repo, owner) = path.split_last()?;
+    if owner.is_empty() {
+        return None;
+    }
+    Some(Remote {
+        host,
+        owner: owner.join("/"),
+        repo: repo.trim_end_matches(".git").to_string(),
+    })
+}
+
+/// Repository name: from the `origin` remote, else the project directory name
+pub fn repo_name(project_dir: &Path) -> String {
+    origin(project_dir).map(|r| r.repo).unwrap_or_else(|| {
+        project_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+/// Host name of "codeberg" (or the self-hosted instance), "github", or "gitlab"
+pub fn host(config: &Config, forge: &str) -> String {
+    match forge {
+        "github" => "github.com".to_string(),
+        "gitlab" => "gitlab.com".to_string(),
+        _ => config
+            .gitea_api_url()
+            .trim_end_matches("/api/v1")
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// Owner and name of the project's repository on "codeberg", "github", or
+/// "gitlab". The name is `[mirrors] <forge>_repo` if set, else [`repo_name`];
+/// the owner comes from `origin` when it points at that forge, else `user`.
+pub fn repository(
+    project_dir: &Path,
+    config: &Config,
+    forge: &str,
+    user: &str,
+) -> (String, String) {
+    let host = host(config, forge);
+    let origin = origin(project_dir);
+    let owner = origin
+        .as_ref()
+        .filter(|r| r.host.eq_ignore_ascii_case(&host))
+        .map_or(user.to_string(), |r| r.owner.clone());
+    let default = repo_name(project_dir);
+    let repo = config
+        .mirrors
+        .as_ref()
+        .map_or(default.clone(), |m| m.repo_name(forge, &default));
+    (owner, repo)
+}
+
+/// The CHANGELOG.md section for `version` (`## [1.2.0] - ...` or `## v1.2.0`),
+/// without its heading
+pub fn changelog_section(project_dir: &Path, version: &str) -> Option<String> {
+    let content = std::fs::read_to_string(project_dir.join("CHANGELOG.md")).ok()?;
+    let mut section = Vec::new();
+    let mut inside = false;
+    for line in content.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if inside {
+                break;
+            }
+            inside = heading
+                .split(|c: char| c.is_whitespace() || c == '[' || c == ']')
+                .any(|word| word.trim_start_matches('v') == version);
+            continue;
+        }
+        if inside {
+            section.push(line);
+        }
+    }
+    let section = section.join("\n").trim().to_string();
+    (!section.is_empty()).then_some(section)
+}
+
+/// `body` with a DOI badge appended, or `None` if it already mentions the DOI
+pub fn with_doi_badge(body: &str, doi: &str, doi_url: &str) -> Option<String> {
+    if body.contains(doi) {
+        return None;
+    }
+    let badge = format!(
+        "[![DOI](https://zenodo.org/badge/DOI/{}.svg)]({}) Archived on Zenodo: {}",
+        doi, doi_url, doi_url
+    );
+    let body = body.trim_end();
+    Some(if body.is_empty() {
+        badge
+    } else {
+        format!("{}\n\n---\n\n{}", body, badge)
+    })
+}
+
+fn http_error(context: &str) -> impl FnOnce(reqwest::Error) -> ForgeError + '_ {
+    move |source| ForgeError::Http {
+        context: context.to_string(),
+        source,
+    }
+}
+
+fn parse_error(what: &str) -> impl FnOnce(reqwest::Error) -> ForgeError + '_ {
+    move |source| ForgeError::Parse {
+        what: what.to_string(),
+        source,
+    }
+}
+
+/// The error for a non-success response, with the response body
+fn api_error(forge: &'static str, resp: Response, context: &str) -> ForgeError {
+    ForgeError::Api {
+        forge,
+        status: resp.status(),
+        context: context.to_string(),
+        body: resp.text().unwrap_or_default(),
+    }
+}
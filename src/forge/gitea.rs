@@ -0,0 +1,462 @@
+use super::Release;
+use super::{http_error, parse_error};
+use crate::config::NetworkConfig;
+use crate::error::{ForgeError, IoError};
+use crate::http;
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Releases and mirrors API of Forgejo and Gitea (Codeberg or a self-hosted instance)
+pub struct GiteaClient {
+    client: Client,
+    api_url: String,
+    owner: String,
+    repo: String,
+    /// `{api}/repos/{owner}/{repo}`
+    repo_url: String,
+    token: String,
+    network: NetworkConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct ReleaseRequest<'a> {
+    tag_name: &'a str,
+    target_commitish: &'a str,
+    name: &'a str,
+    body: &'a str,
+    draft: bool,
+    prerelease: bool,
+}
+
+/// A push mirror to add
+#[derive(Debug, Serialize)]
+pub struct PushMirrorRequest {
+    pub remote_address: String,
+    pub remote_username: String,
+    pub remote_password: String,
+    pub interval: String,
+    pub sync_on_commit: bool,
+}
+
+/// A push mirror as the API lists it
+#[derive(Debug, Deserialize)]
+pub struct PushMirror {
+    /// Name Gitea gave the mirror remote; used to delete it
+    pub remote_name: String,
+    pub remote_address: String,
+    #[serde(default)]
+    pub interval: String,
+    #[serde(default)]
+    pub sync_on_commit: bool,
+    /// When the mirror last pushed
+    pub last_update: Option<String>,
+    /// Error from the last push, empty if it succeeded
+    #[serde(default)]
+    pub last_error: String,
+}
+
+/// The fields of a repository that release-scholar reads
+#[derive(Debug, Deserialize)]
+pub struct Repository {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub default_branch: String,
+    #[serde(default)]
+    pub mirror: bool,
+    pub mirror_updated: Option<String>,
+    #[serde(default)]
+    pub mirror_interval: String,
+}
+
+/// Repository migration set up as a pull mirror of `clone_addr`
+#[derive(Debug, Serialize)]
+struct MigrateRequest<'a> {
+    clone_addr: &'a str,
+    repo_owner: &'a str,
+    repo_name: &'a str,
+    service: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth_token: Option<&'a str>,
+    mirror: bool,
+    mirror_interval: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+}
+
+/// A release as the API returns it
+#[derive(Debug, Deserialize)]
+pub struct ReleaseResponse {
+    pub id: u64,
+    pub html_url: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// A file attached to a release
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagResponse {
+    commit: TagCommit,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagCommit {
+    sha: String,
+}
+
+impl GiteaClient {
+    /// Client for `owner/repo` on the Gitea-compatible API at `api_url`
+    pub fn new(
+        api_url: &str,
+        owner: &str,
+        repo: &str,
+        token: String,
+        network: &NetworkConfig,
+    ) -> Result<Self, ForgeError> {
+        Ok(GiteaClient {
+            client: http::client(network, None)?,
+            api_url: api_url.to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            repo_url: format!("{}/repos/{}/{}", api_url, owner, repo),
+            token,
+            network: network.clone(),
+        })
+    }
+
+    fn send(&self, build: impl Fn() -> RequestBuilder) -> reqwest::Result<Response> {
+        http::send_with_retry(&self.network, build)
+    }
+
+    /// A GET request, with the token if there is one: public repositories
+    /// can be read without
+    fn read(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+        if self.token.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("token {}", self.token))
+        }
+    }
+
+    /// The repository's push mirrors
+    pub fn push_mirrors(&self) -> Result<Vec<PushMirror>, ForgeError> {
+        let url = format!("{}/push_mirrors", self.repo_url);
+        let resp = self
+            .send(|| {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("token {}", self.token))
+            })
+            .map_err(http_error("listing mirrors"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "listing mirrors"));
+        }
+        resp.json().map_err(parse_error("mirror list"))
+    }
+
+    /// Add a push mirror
+    pub fn add_push_mirror(&self, mirror: &PushMirrorRequest) -> Result<(), ForgeError> {
+        let url = format!("{}/push_mirrors", self.repo_url);
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .json(mirror)
+            })
+            .map_err(http_error("adding mirror"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "adding mirror"));
+        }
+        Ok(())
+    }
+
+    /// Remove the push mirror `remote_name`
+    pub fn delete_push_mirror(&self, remote_name: &str) -> Result<(), ForgeError> {
+        let url = format!("{}/push_mirrors/{}", self.repo_url, remote_name);
+        let resp = self
+            .send(|| {
+                self.client
+                    .delete(&url)
+                    .header("Authorization", format!("token {}", self.token))
+            })
+            .map_err(http_error("removing mirror"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "removing mirror"));
+        }
+        Ok(())
+    }
+
+    /// The repository's settings
+    pub fn repository(&self) -> Result<Repository, ForgeError> {
+        let resp = self
+            .send(|| self.read(&self.repo_url))
+            .map_err(http_error("fetching repository"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching repository"));
+        }
+        resp.json().map_err(parse_error("repository"))
+    }
+
+    /// The repository's topics
+    pub fn topics(&self) -> Result<Vec<String>, ForgeError> {
+        let url = format!("{}/topics", self.repo_url);
+        let resp = self
+            .send(|| self.read(&url))
+            .map_err(http_error("fetching topics"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching topics"));
+        }
+        let topics: serde_json::Value = resp.json().map_err(parse_error("topics"))?;
+        Ok(topics
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| {
+                t.iter()
+                    .filter_map(|t| t.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Start an immediate sync: all push mirrors, or the pull mirror itself
+    pub fn sync_mirrors(&self, pull: bool) -> Result<(), ForgeError> {
+        let endpoint = if pull {
+            "mirror-sync"
+        } else {
+            "push_mirrors-sync"
+        };
+        let url = format!("{}/{}", self.repo_url, endpoint);
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("token {}", self.token))
+            })
+            .map_err(http_error("starting mirror sync"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "starting mirror sync"));
+        }
+        Ok(())
+    }
+
+    /// Create this repository as a pull mirror of `clone_addr` on GitHub or
+    /// GitLab (`service`); `Ok(false)` if the repository already exists
+    pub fn create_pull_mirror(
+        &self,
+        clone_addr: &str,
+        service: &str,
+        auth_token: Option<&str>,
+        interval: &str,
+        description: Option<&str>,
+    ) -> Result<bool, ForgeError> {
+        let url = format!("{}/repos/migrate", self.api_url);
+        let body = MigrateRequest {
+            clone_addr,
+            repo_owner: &self.owner,
+            repo_name: &self.repo,
+            service,
+            auth_token,
+            mirror: true,
+            mirror_interval: interval,
+            description,
+        };
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .json(&body)
+            })
+            .map_err(http_error("creating pull mirror"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::CONFLICT {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "creating pull mirror"));
+        }
+        Ok(true)
+    }
+
+    /// Create a published release; `None` if the tag already has one. A tag
+    /// the forge does not have yet is created at `release.commit`; one it
+    /// has at another commit is an error, rather than a release of that.
+    pub fn create_release(&self, release: &Release) -> Result<Option<ReleaseResponse>, ForgeError> {
+        if let Some(remote) = self.tag_commit(release.tag)? {
+            if remote != release.commit {
+                return Err(ForgeError::TagMoved {
+                    forge: "Forge",
+                    tag: release.tag.to_string(),
+                    remote,
+                    local: release.commit.clone(),
+                });
+            }
+        }
+        let url = format!("{}/releases", self.repo_url);
+        let body = ReleaseRequest {
+            tag_name: release.tag,
+            target_commitish: &release.commit,
+            name: &release.name,
+            body: &release.description,
+            draft: false,
+            prerelease: false,
+        };
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .json(&body)
+            })
+            .map_err(http_error("creating forge release"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::CONFLICT {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "creating release"));
+        }
+        resp.json()
+            .map(Some)
+            .map_err(parse_error("release response"))
+    }
+
+    /// The commit `tag` points at on the forge, if it has the tag
+    pub fn tag_commit(&self, tag: &str) -> Result<Option<String>, ForgeError> {
+        let url = format!("{}/tags/{}", self.repo_url, tag);
+        let resp = self
+            .send(|| {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("token {}", self.token))
+            })
+            .map_err(http_error("fetching forge tag"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching tag"));
+        }
+        resp.json::<TagResponse>()
+            .map(|t| Some(t.commit.sha))
+            .map_err(parse_error("tag response"))
+    }
+
+    /// The release for `tag`, if there is one
+    pub fn get_release(&self, tag: &str) -> Result<Option<ReleaseResponse>, ForgeError> {
+        let url = format!("{}/releases/tags/{}", self.repo_url, tag);
+        let resp = self
+            .send(|| {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("token {}", self.token))
+            })
+            .map_err(http_error("fetching forge release"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching release"));
+        }
+        resp.json()
+            .map(Some)
+            .map_err(parse_error("release response"))
+    }
+
+    /// Replace a release's description
+    pub fn update_release_body(&self, release_id: u64, body: &str) -> Result<(), ForgeError> {
+        let url = format!("{}/releases/{}", self.repo_url, release_id);
+        let payload = serde_json::json!({ "body": body });
+        self.patch(&url, &payload, "updating release")
+    }
+
+    /// Set the repository's website field
+    pub fn set_website(&self, website: &str) -> Result<(), ForgeError> {
+        let payload = serde_json::json!({ "website": website });
+        self.patch(&self.repo_url, &payload, "setting website")
+    }
+
+    fn patch(&self, url: &str, payload: &serde_json::Value, what: &str) -> Result<(), ForgeError> {
+        let resp = self
+            .send(|| {
+                self.client
+                    .patch(url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .json(payload)
+            })
+            .map_err(http_error(what))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, what));
+        }
+        Ok(())
+    }
+
+    /// Attach a file to a release
+    pub fn upload_asset(&self, release_id: u64, file_path: &Path) -> Result<(), ForgeError> {
+        let len = std::fs::metadata(file_path)
+            .map_err(IoError::on("read", file_path))?
+            .len();
+        let filename = file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let url = format!("{}/releases/{}/assets", self.repo_url, release_id);
+        let resp = self
+            .send(|| {
+                let part = Part::reader_with_length(http::FileReader::new(file_path), len)
+                    .file_name(filename.clone());
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("token {}", self.token))
+                    .query(&[("name", &filename)])
+                    .multipart(Form::new().part("attachment", part))
+            })
+            .map_err(http_error("uploading to forge"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, &format!("uploading {}", filename)));
+        }
+        Ok(())
+    }
+}
+
+fn api_error(resp: Response, context: &str) -> ForgeError {
+    super::api_error("Forge", resp, context)
+}
@@ -0,0 +1,221 @@
+use super::{http_error, parse_error};
+use crate::config::NetworkConfig;
+use crate::error::ForgeError;
+use crate::http;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// GitHub REST API client for one repository
+pub struct GitHubClient {
+    client: Client,
+    owner: String,
+    repo: String,
+    /// `{api}/repos/{owner}/{repo}`
+    repo_url: String,
+    token: String,
+    network: NetworkConfig,
+}
+
+/// The fields of a repository that release-scholar reads
+#[derive(Debug, Deserialize)]
+pub struct Repository {
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub default_branch: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+/// A user or organization
+#[derive(Debug, Deserialize)]
+struct Account {
+    /// "User" or "Organization"
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// A release as the API returns it
+#[derive(Debug, Deserialize)]
+pub struct ReleaseResponse {
+    pub id: u64,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl GitHubClient {
+    /// Client for `owner/repo`
+    pub fn new(
+        owner: &str,
+        repo: &str,
+        token: String,
+        network: &NetworkConfig,
+    ) -> Result<Self, ForgeError> {
+        Ok(GitHubClient {
+            client: http::client(network, None)?,
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            repo_url: format!("{}/repos/{}/{}", GITHUB_API, owner, repo),
+            token,
+            network: network.clone(),
+        })
+    }
+
+    fn send(&self, build: impl Fn() -> RequestBuilder) -> reqwest::Result<Response> {
+        http::send_with_retry(&self.network, build)
+    }
+
+    /// A request, with the token if there is one: public repositories can be
+    /// read without
+    fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, url)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "release-scholar");
+        if self.token.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.token)
+        }
+    }
+
+    /// Whether the repository exists and the token can see it
+    pub fn repo_exists(&self) -> Result<bool, ForgeError> {
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, &self.repo_url))
+            .map_err(http_error("fetching GitHub repository"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching repository"));
+        }
+        Ok(true)
+    }
+
+    /// The repository's settings
+    pub fn repository(&self) -> Result<Repository, ForgeError> {
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, &self.repo_url))
+            .map_err(http_error("fetching GitHub repository"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching repository"));
+        }
+        resp.json().map_err(parse_error("repository"))
+    }
+
+    /// Create the repository under its owner: the organization, when the
+    /// owner is one, else the token's own account
+    pub fn create_repo(
+        &self,
+        description: &str,
+        private: bool,
+        homepage: &str,
+    ) -> Result<(), ForgeError> {
+        let owner_url = format!("{}/users/{}", GITHUB_API, self.owner);
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, &owner_url))
+            .map_err(http_error("fetching GitHub account"))?;
+        if !resp.status().is_success() {
+            return Err(api_error(resp, "fetching account"));
+        }
+        let owner: Account = resp.json().map_err(parse_error("account"))?;
+        let url = if owner.kind == "Organization" {
+            format!("{}/orgs/{}/repos", GITHUB_API, self.owner)
+        } else {
+            format!("{}/user/repos", GITHUB_API)
+        };
+        let payload = serde_json::json!({
+            "name": self.repo,
+            "description": description,
+            "homepage": homepage,
+            "private": private,
+        });
+        let resp = self
+            .send(|| self.request(reqwest::Method::POST, &url).json(&payload))
+            .map_err(http_error("creating GitHub repository"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "creating repository"));
+        }
+        Ok(())
+    }
+
+    /// The release for `tag`, if there is one
+    pub fn get_release(&self, tag: &str) -> Result<Option<ReleaseResponse>, ForgeError> {
+        let url = format!("{}/releases/tags/{}", self.repo_url, tag);
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, &url))
+            .map_err(http_error("fetching GitHub release"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching release"));
+        }
+        resp.json()
+            .map(Some)
+            .map_err(parse_error("release response"))
+    }
+
+    /// Replace a release's description
+    pub fn update_release_body(&self, release_id: u64, body: &str) -> Result<(), ForgeError> {
+        let url = format!("{}/releases/{}", self.repo_url, release_id);
+        let payload = serde_json::json!({ "body": body });
+        self.patch(&url, &payload, "updating release")
+    }
+
+    /// Set the repository's homepage field
+    pub fn set_homepage(&self, homepage: &str) -> Result<(), ForgeError> {
+        let payload = serde_json::json!({ "homepage": homepage });
+        self.patch(&self.repo_url, &payload, "setting homepage")
+    }
+
+    /// Set the repository's description and homepage
+    pub fn set_about(&self, description: &str, homepage: &str) -> Result<(), ForgeError> {
+        let payload = serde_json::json!({ "description": description, "homepage": homepage });
+        self.patch(&self.repo_url, &payload, "updating repository")
+    }
+
+    /// Replace the repository topics
+    pub fn set_topics(&self, topics: &[String]) -> Result<(), ForgeError> {
+        let url = format!("{}/topics", self.repo_url);
+        let payload = serde_json::json!({ "names": topics });
+        let resp = self
+            .send(|| self.request(reqwest::Method::PUT, &url).json(&payload))
+            .map_err(http_error("setting topics"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "setting topics"));
+        }
+        Ok(())
+    }
+
+    fn patch(&self, url: &str, payload: &serde_json::Value, what: &str) -> Result<(), ForgeError> {
+        let resp = self
+            .send(|| self.request(reqwest::Method::PATCH, url).json(payload))
+            .map_err(http_error(what))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, what));
+        }
+        Ok(())
+    }
+}
+
+fn api_error(resp: Response, context: &str) -> ForgeError {
+    super::api_error("GitHub", resp, context)
+}
@@ -0,0 +1,340 @@
+use super::Release;
+use super::{http_error, parse_error};
+use crate::config::NetworkConfig;
+use crate::error::{ForgeError, IoError};
+use crate::http;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+/// GitLab REST API client for one project
+pub struct GitLabClient {
+    client: Client,
+    /// The user or group, with any subgroups ("lab/tools")
+    owner: String,
+    /// URL-encoded `owner/repo`
+    project: String,
+    /// Project name, also used as the generic package release files go to
+    repo: String,
+    token: String,
+    network: NetworkConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct ReleaseRequest<'a> {
+    tag_name: &'a str,
+    name: &'a str,
+    description: &'a str,
+    assets: Assets,
+}
+
+#[derive(Debug, Serialize)]
+struct Assets {
+    links: Vec<AssetLink>,
+}
+
+#[derive(Debug, Serialize)]
+struct AssetLink {
+    name: String,
+    url: String,
+    link_type: &'static str,
+}
+
+/// The fields of a project that release-scholar reads
+#[derive(Debug, Deserialize)]
+pub struct Project {
+    /// "public", "internal" or "private"
+    pub visibility: String,
+    /// `None` until the first push
+    pub default_branch: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+/// The user or group a project is created in
+#[derive(Debug, Deserialize)]
+struct Namespace {
+    id: u64,
+}
+
+/// Result of creating a release
+pub enum Created {
+    /// URL of the new release page
+    New(String),
+    /// A release for the tag was already there; it is left alone
+    Exists(String),
+}
+
+impl GitLabClient {
+    /// Client for the project `owner/repo` on gitlab.com
+    pub fn new(
+        owner: &str,
+        repo: &str,
+        token: String,
+        network: &NetworkConfig,
+    ) -> Result<Self, ForgeError> {
+        Ok(GitLabClient {
+            client: http::client(network, None)?,
+            owner: owner.to_string(),
+            project: format!("{}%2F{}", owner.replace('/', "%2F"), repo),
+            repo: repo.to_string(),
+            token,
+            network: network.clone(),
+        })
+    }
+
+    fn send(&self, build: impl Fn() -> RequestBuilder) -> reqwest::Result<Response> {
+        http::send_with_retry(&self.network, build)
+    }
+
+    /// Whether the project exists and the token can see it
+    pub fn project_exists(&self) -> Result<bool, ForgeError> {
+        let url = format!("{}/projects/{}", GITLAB_API, self.project);
+        let resp = self
+            .send(|| self.client.get(&url).header("PRIVATE-TOKEN", &self.token))
+            .map_err(http_error("fetching GitLab project"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching project"));
+        }
+        Ok(true)
+    }
+
+    /// The project's settings
+    pub fn project(&self) -> Result<Project, ForgeError> {
+        let url = format!("{}/projects/{}", GITLAB_API, self.project);
+        let resp = self
+            .send(|| {
+                // Public projects can be read without a token
+                let request = self.client.get(&url);
+                if self.token.is_empty() {
+                    request
+                } else {
+                    request.header("PRIVATE-TOKEN", &self.token)
+                }
+            })
+            .map_err(http_error("fetching GitLab project"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching project"));
+        }
+        resp.json().map_err(parse_error("project"))
+    }
+
+    /// Create the project in its owner's namespace, a group's or a user's
+    pub fn create_project(&self, description: &str, private: bool) -> Result<(), ForgeError> {
+        let namespace_url = format!(
+            "{}/namespaces/{}",
+            GITLAB_API,
+            self.owner.replace('/', "%2F")
+        );
+        let resp = self
+            .send(|| {
+                self.client
+                    .get(&namespace_url)
+                    .header("PRIVATE-TOKEN", &self.token)
+            })
+            .map_err(http_error("fetching GitLab namespace"))?;
+        if !resp.status().is_success() {
+            return Err(api_error(resp, "fetching namespace"));
+        }
+        let namespace: Namespace = resp.json().map_err(parse_error("namespace"))?;
+
+        let url = format!("{}/projects", GITLAB_API);
+        let payload = serde_json::json!({
+            "name": self.repo,
+            "path": self.repo,
+            "namespace_id": namespace.id,
+            "description": description,
+            "visibility": if private { "private" } else { "public" },
+        });
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("PRIVATE-TOKEN", &self.token)
+                    .json(&payload)
+            })
+            .map_err(http_error("creating GitLab project"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "creating project"));
+        }
+        Ok(())
+    }
+
+    /// Set the project description and topics (GitLab has no website field)
+    pub fn set_about(&self, description: &str, topics: &[String]) -> Result<(), ForgeError> {
+        let url = format!("{}/projects/{}", GITLAB_API, self.project);
+        let payload = serde_json::json!({ "description": description, "topics": topics });
+        let resp = self
+            .send(|| {
+                self.client
+                    .put(&url)
+                    .header("PRIVATE-TOKEN", &self.token)
+                    .json(&payload)
+            })
+            .map_err(http_error("updating GitLab project"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "updating project"));
+        }
+        Ok(())
+    }
+
+    /// Upload a file to the project's generic package registry; returns its download URL
+    pub fn upload_package_file(
+        &self,
+        version: &str,
+        file_path: &Path,
+    ) -> Result<String, ForgeError> {
+        let len = std::fs::metadata(file_path)
+            .map_err(IoError::on("read", file_path))?
+            .len();
+        let filename = file_path.file_name().unwrap_or_default().to_string_lossy();
+        let url = format!(
+            "{}/projects/{}/packages/generic/{}/{}/{}",
+            GITLAB_API, self.project, self.repo, version, filename
+        );
+        let resp = self
+            .send(|| {
+                self.client
+                    .put(&url)
+                    .header("PRIVATE-TOKEN", &self.token)
+                    .body(http::file_body(file_path, len))
+            })
+            .map_err(http_error("uploading to GitLab"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, &format!("uploading {}", filename)));
+        }
+        Ok(url)
+    }
+
+    /// Create a release whose assets link to `files` (name → download URL)
+    pub fn create_release(
+        &self,
+        release: &Release,
+        files: &[(String, String)],
+    ) -> Result<Created, ForgeError> {
+        let url = format!("{}/projects/{}/releases", GITLAB_API, self.project);
+        let body = ReleaseRequest {
+            tag_name: release.tag,
+            name: &release.name,
+            description: &release.description,
+            assets: Assets {
+                links: files
+                    .iter()
+                    .map(|(name, url)| AssetLink {
+                        name: name.clone(),
+                        url: url.clone(),
+                        link_type: "package",
+                    })
+                    .collect(),
+            },
+        };
+        let resp = self
+            .send(|| {
+                self.client
+                    .post(&url)
+                    .header("PRIVATE-TOKEN", &self.token)
+                    .json(&body)
+            })
+            .map_err(http_error("creating GitLab release"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::CONFLICT {
+            return Ok(Created::Exists(self.release_url(release.tag)));
+        }
+        if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+            return Err(api_error(
+                resp,
+                &format!(
+                    "creating release for {} (is the tag pushed to GitLab yet?)",
+                    release.tag
+                ),
+            ));
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "creating release"));
+        }
+        let created: serde_json::Value = resp.json().unwrap_or_default();
+        let web_url = created
+            .pointer("/_links/self")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| self.release_url(release.tag));
+        Ok(Created::New(web_url))
+    }
+
+    /// Description of the release for `tag`, if there is one
+    pub fn release_description(&self, tag: &str) -> Result<Option<String>, ForgeError> {
+        let url = format!("{}/projects/{}/releases/{}", GITLAB_API, self.project, tag);
+        let resp = self
+            .send(|| self.client.get(&url).header("PRIVATE-TOKEN", &self.token))
+            .map_err(http_error("fetching GitLab release"))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(api_error(resp, "fetching release"));
+        }
+        let release: serde_json::Value = resp.json().map_err(parse_error("release response"))?;
+        Ok(Some(
+            release
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        ))
+    }
+
+    /// Replace the description of the release for `tag`
+    pub fn update_release_description(
+        &self,
+        tag: &str,
+        description: &str,
+    ) -> Result<(), ForgeError> {
+        let url = format!("{}/projects/{}/releases/{}", GITLAB_API, self.project, tag);
+        let payload = serde_json::json!({ "description": description });
+        let resp = self
+            .send(|| {
+                self.client
+                    .put(&url)
+                    .header("PRIVATE-TOKEN", &self.token)
+                    .json(&payload)
+            })
+            .map_err(http_error("updating GitLab release"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(api_error(resp, "updating release"));
+        }
+        Ok(())
+    }
+
+    fn release_url(&self, tag: &str) -> String {
+        format!(
+            "https://gitlab.com/{}/-/releases/{}",
+            self.project.replace("%2F", "/"),
+            tag
+        )
+    }
+}
+
+fn api_error(resp: Response, context: &str) -> ForgeError {
+    super::api_error("GitLab", resp, context)
+}
@@ -0,0 +1,107 @@
+use crate::config::HooksConfig;
+use crate::error::HookError;
+use colored::Colorize;
+use std::path::Path;
+use std::process::Command;
+
+/// A point in the release where a `[hooks]` command can run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    PreBuild,
+    PostBuild,
+    PrePublish,
+    PostPublish,
+}
+
+impl Stage {
+    /// The stage's name in `[hooks]` and in messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::PreBuild => "pre-build",
+            Stage::PostBuild => "post-build",
+            Stage::PrePublish => "pre-publish",
+            Stage::PostPublish => "post-publish",
+        }
+    }
+
+    fn command(self, hooks: &HooksConfig) -> Option<&str> {
+        match self {
+            Stage::PreBuild => hooks.pre_build.as_deref(),
+            Stage::PostBuild => hooks.post_build.as_deref(),
+            Stage::PrePublish => hooks.pre_publish.as_deref(),
+            Stage::PostPublish => hooks.post_publish.as_deref(),
+        }
+        .filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// What a hook is told about the release, as `RELEASE_SCHOLAR_*` variables
+#[derive(Debug, Default)]
+pub struct Context<'a> {
+    pub tag: &'a str,
+    pub version: &'a str,
+    /// The release bundle directory
+    pub bundle: Option<&'a Path>,
+    /// Only known after publishing
+    pub doi: Option<&'a str>,
+    pub sandbox: bool,
+}
+
+/// Run the hook configured for `stage`, if any, through the shell with the
+/// project directory as working directory. Its output goes straight to the
+/// terminal; a non-zero exit is an error.
+pub fn run(
+    project_dir: &Path,
+    hooks: Option<&HooksConfig>,
+    stage: Stage,
+    context: &Context,
+) -> Result<(), HookError> {
+    let Some(command) = hooks.and_then(|h| stage.command(h)) else {
+        return Ok(());
+    };
+    println!("  Running {} hook: {}", stage.name(), command.dimmed());
+
+    let mut shell = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    shell
+        .arg(command)
+        .current_dir(project_dir)
+        .env("RELEASE_SCHOLAR_STAGE", stage.name())
+        .env("RELEASE_SCHOLAR_PROJECT_DIR", project_dir)
+        .env("RELEASE_SCHOLAR_TAG", context.tag)
+        .env("RELEASE_SCHOLAR_VERSION", context.version)
+        .env(
+            "RELEASE_SCHOLAR_SANDBOX",
+            if context.sandbox { "1" } else { "0" },
+        );
+    if let Some(bundle) = context.bundle {
+        shell.env("RELEASE_SCHOLAR_BUNDLE", bundle);
+    }
+    if let Some(doi) = context.doi {
+        shell.env("RELEASE_SCHOLAR_DOI", doi).env(
+            "RELEASE_SCHOLAR_DOI_URL",
+            format!("https://doi.org/{}", doi),
+        );
+    }
+
+    let status = shell.status().map_err(|source| HookError::Spawn {
+        stage: stage.name(),
+        command: command.to_string(),
+        source,
+    })?;
+    if !status.success() {
+        return Err(HookError::Failed {
+            stage: stage.name(),
+            command: command.to_string(),
+            status,
+        });
+    }
+    Ok(())
+}
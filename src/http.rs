@@ -0,0 +1,484 @@
+use crate::config::NetworkConfig;
+use crate::error::ConfigError;
+use colored::Colorize;
+use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+use reqwest::header::HeaderMap;
+use reqwest::{Method, NoProxy, Proxy, StatusCode, Url};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Request bodies longer than this are logged by size only
+const MAX_LOGGED_BODY: usize = 64 * 1024;
+
+/// Why a link could not be confirmed
+pub enum LinkError {
+    /// The server answered, and the answer was "no" (404, unregistered DOI, ...)
+    Broken(String),
+    /// The server could not be reached; says nothing about the link itself
+    Unreachable(String),
+}
+
+/// Blocking HTTP client with the release-scholar user agent and the configured
+/// timeouts and proxy. `timeout` overrides the configured request timeout.
+pub fn client(network: &NetworkConfig, timeout: Option<Duration>) -> Result<Client, ConfigError> {
+    let mut builder = Client::builder()
+        .user_agent(user_agent())
+        .timeout(request_timeout(network, timeout))
+        .connect_timeout(connect_timeout(network));
+    if let Some(proxy) = proxy(network)? {
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(ConfigError::HttpClient)
+}
+
+/// Async counterpart of [`client`], for use inside a tokio runtime
+#[cfg(feature = "async")]
+pub fn async_client(
+    network: &NetworkConfig,
+    timeout: Option<Duration>,
+) -> Result<reqwest::Client, ConfigError> {
+    let mut builder = reqwest::Client::builder().user_agent(user_agent());
+    if let Some(timeout) = connect_timeout(network) {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = request_timeout(network, timeout) {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = proxy(network)? {
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(ConfigError::HttpClient)
+}
+
+fn user_agent() -> String {
+    format!("release-scholar/{}", env!("CARGO_PKG_VERSION"))
+}
+
+fn request_timeout(network: &NetworkConfig, timeout: Option<Duration>) -> Option<Duration> {
+    timeout.or(match network.timeout_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    })
+}
+
+/// Like the request timeout, 0 means none; a zero timeout would fail
+/// every connection before it was made
+fn connect_timeout(network: &NetworkConfig) -> Option<Duration> {
+    match network.connect_timeout_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+fn proxy(network: &NetworkConfig) -> Result<Option<Proxy>, ConfigError> {
+    let Some(url) = &network.proxy else {
+        return Ok(None);
+    };
+    let no_proxy = match &network.no_proxy {
+        Some(list) => NoProxy::from_string(list),
+        None => NoProxy::from_env(),
+    };
+    let proxy = Proxy::all(url).map_err(|source| ConfigError::Proxy {
+        url: url.clone(),
+        source,
+    })?;
+    Ok(Some(proxy.no_proxy(no_proxy)))
+}
+
+/// Check that a URL answers with a success status. Falls back to GET for
+/// servers that reject HEAD requests.
+pub fn url_resolves(client: &Client, url: &str) -> Result<(), LinkError> {
+    let resp = client
+        .head(url)
+        .send()
+        .map_err(|e| LinkError::Unreachable(e.to_string()))?;
+    let mut status = resp.status();
+    if matches!(
+        status,
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::FORBIDDEN | StatusCode::NOT_IMPLEMENTED
+    ) {
+        status = client
+            .get(url)
+            .send()
+            .map_err(|e| LinkError::Unreachable(e.to_string()))?
+            .status();
+    }
+
+    if status.is_success() {
+        Ok(())
+    } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        Err(LinkError::Unreachable(format!("HTTP {}", status)))
+    } else {
+        Err(LinkError::Broken(format!("HTTP {}", status)))
+    }
+}
+
+/// Check that a DOI is registered, using the doi.org handle API
+pub fn doi_resolves(client: &Client, doi: &str) -> Result<(), LinkError> {
+    let url = format!("https://doi.org/api/handles/{}", normalize_doi(doi));
+    let resp = client
+        .get(&url)
+        .send()
+        .map_err(|e| LinkError::Unreachable(e.to_string()))?;
+    match resp.status() {
+        StatusCode::OK => Ok(()),
+        StatusCode::NOT_FOUND => Err(LinkError::Broken("DOI not registered".to_string())),
+        status => Err(LinkError::Unreachable(format!("HTTP {}", status))),
+    }
+}
+
+/// Strip resolver prefixes so `https://doi.org/10.x/y` and `doi:10.x/y` become `10.x/y`
+pub fn normalize_doi(doi: &str) -> &str {
+    let doi = doi.trim();
+    for prefix in [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "doi:",
+    ] {
+        if let Some(rest) = doi.strip_prefix(prefix) {
+            return rest;
+        }
+    }
+    doi
+}
+
+/// A request body of `len` bytes read from `path` while it is sent, rather
+/// than held in memory. Call it in the `build` closure of
+/// [`send_with_retry`], so each attempt reads the file from the start.
+pub fn file_body(path: &Path, len: u64) -> Body {
+    Body::sized(FileReader::new(path), len)
+}
+
+/// Opens the file on the first read, so that failing to is an error of
+/// the request it is the body of
+pub(crate) struct FileReader {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl FileReader {
+    pub(crate) fn new(path: &Path) -> FileReader {
+        FileReader {
+            path: path.to_path_buf(),
+            file: None,
+        }
+    }
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(File::open(&self.path)?),
+        };
+        file.read(buf)
+    }
+}
+
+/// Send a request, retrying with exponential backoff what is safe to retry.
+/// Failed connections and 429 responses always are: the server did not act
+/// on the request. Timeouts and 5xx responses only are for idempotent
+/// methods (GET, PUT, DELETE, ...); a POST that timed out may have created
+/// something already, so that error is returned for the caller to check.
+/// `build` is called again for every attempt.
+pub fn send_with_retry(
+    network: &NetworkConfig,
+    build: impl Fn() -> RequestBuilder,
+) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let (client, request) = build().build_split();
+        let idempotent = request.as_ref().is_ok_and(|r| is_idempotent(r.method()));
+        let result = request.and_then(|request| {
+            let body = request.body().and_then(|b| b.as_bytes());
+            log_request(request.method(), request.url(), request.headers(), body);
+            let url = request.url().clone();
+            let started = Instant::now();
+            let result = client.execute(request);
+            log_response(
+                &url,
+                result.as_ref().map(|r| (r.status(), r.headers())),
+                started.elapsed(),
+            );
+            result
+        });
+        let retry = match &result {
+            Ok(resp) => retry_response(resp.status(), resp.headers(), idempotent),
+            Err(e) => retry_error(e, idempotent),
+        };
+        let Some((reason, retry_after)) = retry else {
+            return result;
+        };
+        if attempt >= network.retries {
+            return result;
+        }
+        attempt += 1;
+        std::thread::sleep(retry_delay(network, attempt, retry_after, &reason));
+    }
+}
+
+/// Async counterpart of [`send_with_retry`]
+#[cfg(feature = "async")]
+pub async fn send_with_retry_async(
+    network: &NetworkConfig,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    let mut attempt = 0;
+    loop {
+        let (client, request) = build().build_split();
+        let idempotent = request.as_ref().is_ok_and(|r| is_idempotent(r.method()));
+        let result = match request {
+            Ok(request) => {
+                let body = request.body().and_then(|b| b.as_bytes());
+                log_request(request.method(), request.url(), request.headers(), body);
+                let url = request.url().clone();
+                let started = Instant::now();
+                let result = client.execute(request).await;
+                log_response(
+                    &url,
+                    result.as_ref().map(|r| (r.status(), r.headers())),
+                    started.elapsed(),
+                );
+                result
+            }
+            Err(e) => Err(e),
+        };
+        let retry = match &result {
+            Ok(resp) => retry_response(resp.status(), resp.headers(), idempotent),
+            Err(e) => retry_error(e, idempotent),
+        };
+        let Some((reason, retry_after)) = retry else {
+            return result;
+        };
+        if attempt >= network.retries {
+            return result;
+        }
+        attempt += 1;
+        tokio::time::sleep(retry_delay(network, attempt, retry_after, &reason)).await;
+    }
+}
+
+/// Log a request at debug level, and its headers and body at trace level,
+/// without the credentials they carry
+fn log_request(method: &Method, url: &Url, headers: &HeaderMap, body: Option<&[u8]>) {
+    tracing::debug!("{} {}", method, redact_url(url));
+    if !tracing::enabled!(tracing::Level::TRACE) {
+        return;
+    }
+    for (name, value) in headers {
+        let value = if is_secret(name.as_str()) {
+            "[redacted]"
+        } else {
+            value.to_str().unwrap_or("[binary]")
+        };
+        tracing::trace!("> {}: {}", name, value);
+    }
+    let textual = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|t| t.contains("json") || t.starts_with("text/"));
+    match body {
+        Some(bytes) if textual && bytes.len() <= MAX_LOGGED_BODY => {
+            let body = match serde_json::from_slice::<Value>(bytes) {
+                Ok(mut json) => {
+                    redact_json(&mut json);
+                    json.to_string()
+                }
+                Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+            };
+            tracing::trace!("> {}", body);
+        }
+        Some(bytes) => tracing::trace!("> [{} bytes]", bytes.len()),
+        None => {}
+    }
+}
+
+fn log_response(
+    url: &Url,
+    result: Result<(StatusCode, &HeaderMap), &reqwest::Error>,
+    elapsed: Duration,
+) {
+    match result {
+        Ok((status, headers)) => {
+            tracing::debug!("{} in {} ms", status, elapsed.as_millis());
+            for (name, value) in headers {
+                if !is_secret(name.as_str()) {
+                    tracing::trace!("< {}: {}", name, value.to_str().unwrap_or("[binary]"));
+                }
+            }
+        }
+        // The error's own message repeats the URL, credentials and all
+        Err(e) => tracing::debug!(
+            "{} failed after {} ms: {}",
+            redact_url(url),
+            elapsed.as_millis(),
+            without_url(e)
+        ),
+    }
+}
+
+/// `e`'s message and cause, without the " for url (...)" its message ends in
+fn without_url(e: &reqwest::Error) -> String {
+    let mut message = e.to_string();
+    if let Some(url) = e.url() {
+        message = message.replace(&format!(" for url ({})", url), "");
+    }
+    match std::error::Error::source(e) {
+        Some(cause) => format!("{}: {}", message, cause),
+        None => message,
+    }
+}
+
+/// URLs that are credentials in themselves (webhooks), marked with
+/// [`secret_url`]
+static SECRET_URLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Keep the path of `url` (and of URLs below it) out of the logs: for
+/// webhooks, whose path is the secret
+pub fn secret_url(url: &str) {
+    // Compared against request URLs, which reqwest has normalised
+    let url = Url::parse(url.trim()).map_or(url.to_string(), String::from);
+    if let Ok(mut urls) = SECRET_URLS.lock() {
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+}
+
+/// Header, query parameter and JSON field names whose values are credentials
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["authorization", "cookie", "token", "password", "secret"]
+        .iter()
+        .any(|s| name.contains(s))
+}
+
+fn redact_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    let secret_path = SECRET_URLS
+        .lock()
+        .is_ok_and(|urls| urls.iter().any(|u| url.as_str().starts_with(u.as_str())));
+    if secret_path {
+        url.set_path("/[redacted]");
+        url.set_query(None);
+    }
+    if url.password().is_some() {
+        url.set_password(Some("redacted")).ok();
+    }
+    if url.query_pairs().any(|(k, _)| is_secret(&k)) {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if is_secret(&k) { "redacted".into() } else { v };
+                (k.into_owned(), v.into_owned())
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url
+}
+
+/// Gitea's push mirror requests carry the other forge's token in the body
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret(key) && value.is_string() {
+                    *value = "[redacted]".into();
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Methods that can be repeated without doing twice what they do
+fn is_idempotent(method: &Method) -> bool {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::PUT,
+        Method::DELETE,
+        Method::OPTIONS,
+    ]
+    .contains(method)
+}
+
+/// Why a response is worth retrying, and how long the server asked us to wait
+fn retry_response(
+    status: StatusCode,
+    headers: &HeaderMap,
+    idempotent: bool,
+) -> Option<(String, Option<Duration>)> {
+    let retry = status == StatusCode::TOO_MANY_REQUESTS || (idempotent && status.is_server_error());
+    retry.then(|| (format!("HTTP {}", status), retry_after(headers)))
+}
+
+/// A failed connection never reached the server; a timeout or other
+/// request error may have, after the server acted on it
+fn retry_error(e: &reqwest::Error, idempotent: bool) -> Option<(String, Option<Duration>)> {
+    (e.is_connect() || (idempotent && (e.is_timeout() || e.is_request())))
+        .then(|| ("connection error".to_string(), None))
+}
+
+/// How long to wait before looking whether a request whose answer was lost
+/// took effect, on the same backoff as [`send_with_retry`]
+pub(crate) fn lost_answer_delay(network: &NetworkConfig, attempt: u32) -> Duration {
+    retry_delay(network, attempt, None, "answer lost")
+}
+
+/// How long to wait before `attempt`, announced on stderr
+fn retry_delay(
+    network: &NetworkConfig,
+    attempt: u32,
+    retry_after: Option<Duration>,
+    reason: &str,
+) -> Duration {
+    let delay = retry_after.unwrap_or_else(|| backoff(network.retry_backoff_ms, attempt));
+    eprint!(
+        "{} ",
+        format!(
+            "(retry {}/{} in {:.1}s: {})",
+            attempt,
+            network.retries,
+            delay.as_secs_f64(),
+            reason
+        )
+        .dimmed()
+    );
+    delay
+}
+
+/// Honour a `Retry-After: <seconds>` header, within reason
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs: u64 = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_BACKOFF))
+}
+
+/// `base * 2^(attempt-1)`, capped, then randomised down by up to half
+fn backoff(base_ms: u64, attempt: u32) -> Duration {
+    let exp = base_ms.saturating_mul(1u64 << (attempt - 1).min(16));
+    let capped = exp.min(MAX_BACKOFF.as_millis() as u64);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = nanos % (capped / 2 + 1);
+    Duration::from_millis(capped / 2 + jitter)
+}
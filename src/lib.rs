@@ -0,0 +1,55 @@
+//! Validate, audit, and package scholarly software releases for Zenodo.
+//!
+//! This is the library behind the `release-scholar` command. It can be used to
+//! run the release checks, build archives, convert citation metadata, and talk
+//! to Zenodo from other tools without shelling out:
+//!
+//! ```no_run
+//! use release_scholar::config::Config;
+//! use release_scholar::report::Status;
+//! use std::path::Path;
+//!
+//! # fn main() -> Result<(), release_scholar::error::ConfigError> {
+//! let project = Path::new(".");
+//! let config = Config::load(project, None)?;
+//! let (report, git) = release_scholar::validation::run_all(project, &config, false);
+//! for result in report.results.iter().filter(|r| matches!(r.status, Status::Fail)) {
+//!     eprintln!("{}: {}", result.category, result.message);
+//! }
+//! if let Some(git) = git {
+//!     println!("release {}", git.tag);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Release announcements posted to chat (Slack, Mattermost, Matrix) and social media
+pub mod announce;
+/// Deterministic source archives, checksums and build provenance
+pub mod archive;
+/// Warnings acknowledged in `.release-scholar/baseline.toml`
+pub mod baseline;
+/// Project (`.release-scholar.toml`) and global configuration
+pub mod config;
+/// API tokens in the OS keyring
+pub mod credentials;
+/// Error types returned by the library
+pub mod error;
+/// Releases, mirrors and repository settings on Codeberg, GitHub and GitLab
+pub mod forge;
+/// Shell commands run before and after `build` and `publish`
+pub mod hooks;
+/// HTTP clients with retries, timeouts and proxy settings
+pub mod http;
+/// CITATION.cff and the formats generated from it (Zenodo, DataCite, JSON-LD, ...)
+pub mod metadata;
+/// Check results
+pub mod report;
+/// Rule sets that decide which check categories count, and how much
+pub mod rules;
+/// The `.release-scholar/state.toml` record of publish runs
+pub mod state;
+/// Release checks, each adding results to a [`report::Report`]
+pub mod validation;
+/// Zenodo deposition API client (blocking; async with the `async` feature)
+pub mod zenodo;
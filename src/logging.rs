@@ -0,0 +1,51 @@
+//! Diagnostic logging: off by default, to stderr with `-v` (HTTP requests, git
+//! operations, check timings) or `-vv` (request headers and bodies), and at
+//! full detail to `--log-file`
+
+use crate::failure::Failure;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, Layer};
+
+pub fn init(verbose: u8, log_file: Option<&Path>) -> Result<(), Failure> {
+    let level = match verbose {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    // What reqwest, hyper and friends log is noise next to ours
+    let ours = |level| {
+        Targets::new()
+            .with_target(env!("CARGO_CRATE_NAME"), level)
+            .with_default(LevelFilter::WARN)
+    };
+
+    let stderr = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_timer(fmt::time::uptime())
+        .with_target(false)
+        .with_filter(ours(level));
+    let file = match log_file {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| {
+                Failure::config(format!("Cannot create log file {}: {}", path.display(), e))
+            })?;
+            let layer = fmt::layer()
+                .with_writer(Mutex::new(file))
+                .with_ansi(false)
+                .with_filter(ours(LevelFilter::TRACE));
+            Some(layer)
+        }
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .init();
+    Ok(())
+}
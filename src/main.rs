@@ -1,23 +1,30 @@
-mod archive;
 mod commands;
-mod config;
-mod metadata;
-mod report;
-mod validation;
-mod zenodo;
+mod failure;
+mod logging;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand};
+use failure::Kind;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(
     name = "release-scholar",
     version,
-    about = "Validate, audit, and package scholarly software releases"
+    about = "Validate, audit, and package scholarly software releases",
+    after_help = "Exit status: 0 success, 1 other error, 2 validation failed, \
+                  3 configuration or usage error, 4 network or API error, 5 aborted"
 )]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Log HTTP requests, git operations and check timings to stderr; -vv adds
+    /// request headers and bodies (tokens are redacted)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Also write the most detailed log to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -27,54 +34,390 @@ enum Commands {
         /// Path to the project directory
         #[arg(long, default_value = ".")]
         project_dir: PathBuf,
+        /// Also generate .zenodo.json from CITATION.cff
+        #[arg(long)]
+        zenodo_json: bool,
+        /// License to write to LICENSE and CITATION.cff (asked for when not given)
+        #[arg(long, value_enum, ignore_case = true)]
+        license: Option<commands::init::License>,
+        /// Seed CHANGELOG.md with an entry per existing release tag, listing its commits
+        #[arg(long)]
+        changelog_from_tags: bool,
+        /// Also refresh existing files: add missing required CITATION.cff fields,
+        /// regenerate codemeta.json, append missing .gitignore patterns
+        #[arg(long)]
+        update: bool,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
     },
     /// Validate project readiness for release
     Check {
         /// Path to the project directory
         #[arg(long, default_value = ".")]
         project_dir: PathBuf,
+        #[command(flatten)]
+        options: commands::check::CheckOptions,
     },
     /// Build release archive and metadata bundle
     Build {
         /// Path to the project directory
         #[arg(long, default_value = ".")]
         project_dir: PathBuf,
+        /// Write the bundle under this directory instead of `archive_dir`
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Check the release bundle against its checksums.txt
+    Verify {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Also rebuild the archive from the tag and check it is byte-identical
+        #[arg(long)]
+        reproduce: bool,
+        /// Directory the bundle was built in, if `build --output-dir` was used
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
     },
     /// Publish release bundle to Zenodo
     Publish {
         /// Path to the project directory
         #[arg(long, default_value = ".")]
         project_dir: PathBuf,
-        /// Use Zenodo sandbox instead of production
+        #[command(flatten)]
+        sandbox: commands::Sandbox,
+        #[command(flatten)]
+        options: commands::publish::PublishOptions,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Delete unpublished Zenodo draft depositions
+    Discard {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        #[command(flatten)]
+        sandbox: commands::Sandbox,
+        /// Deposition ID to delete (otherwise choose interactively)
+        #[arg(long)]
+        id: Option<u64>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// List your Zenodo depositions (this project's by default)
+    ListDepositions {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        #[command(flatten)]
+        sandbox: commands::Sandbox,
+        /// List every deposition on the account, not just this project's
+        #[arg(long)]
+        all: bool,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Manage API tokens for Zenodo and the forges
+    Auth {
+        #[command(subcommand)]
+        action: commands::auth::AuthAction,
+    },
+    /// Show or change the global and per-project configuration
+    Config {
+        #[command(subcommand)]
+        action: commands::config::ConfigAction,
+    },
+    /// Keep CITATION.cff, codemeta.json and the package manifest consistent
+    Metadata {
+        #[command(subcommand)]
+        action: commands::metadata::MetadataAction,
+    },
+    /// Write a CI workflow that checks pull requests and builds release tags
+    CiInit {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// CI system to write the workflow for
+        #[arg(long, value_enum)]
+        provider: commands::ci_init::Provider,
+        /// Replace an existing workflow file
+        #[arg(long)]
+        force: bool,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Install git hooks that keep secrets and failing release tags from being pushed
+    Hooks {
+        #[command(subcommand)]
+        action: commands::hooks::HooksAction,
+    },
+    /// Show where the current tag is in the release process
+    Status {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        #[command(flatten)]
+        sandbox: commands::Sandbox,
+        /// Directory the bundle was built in, if `build --output-dir` was used
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// List the files added, removed and modified between two releases
+    Diff {
+        /// Older release: a tag (its built archive, or else the tagged tree)
+        /// or a path to a .tar.gz
+        old: String,
+        /// Newer release, likewise
+        new: String,
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Directory the bundles were built in, if `build --output-dir` was used
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Show the Zenodo record metadata `publish` would send, as it will read
+    Preview {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Print the deposit metadata as JSON, exactly as sent
+        #[arg(long)]
+        json: bool,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Print a citation for the current release
+    Cite {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Citation format or style
+        #[arg(long, value_enum, default_value = "bibtex")]
+        format: commands::cite::CiteFormat,
+        /// Cite every version through the concept DOI, rather than this release
+        #[arg(long)]
+        all_versions: bool,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Open the published DOI, the Zenodo draft or the repository in a browser
+    Open {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        #[command(flatten)]
+        sandbox: commands::Sandbox,
+        /// Release to open (default: the one whose deposition was created last,
+        /// published or not)
+        #[arg(long)]
+        tag: Option<String>,
+        #[command(flatten)]
+        target: commands::open::OpenTarget,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Write a release announcement (email, mailing list, newsletter) from a template
+    Announce {
+        /// Path to the project directory
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Template file, with {title}, {version}, {doi_url}, {changes}, ... to fill in
+        #[arg(long)]
+        template: PathBuf,
+        /// Write the announcement into the release bundle instead of printing it
         #[arg(long)]
-        sandbox: bool,
-        /// Actually publish (without this, creates a draft only)
+        bundle: bool,
+        /// Directory the bundle was built in, if `build --output-dir` was used
+        #[arg(long, requires = "bundle")]
+        output_dir: Option<PathBuf>,
+        /// Config profile to use (a [profile.NAME] table in the global config)
         #[arg(long)]
-        confirm: bool,
+        profile: Option<String>,
     },
-    /// Set up push mirrors from Codeberg to GitHub/GitLab
+    /// Set up, list, or remove mirrors between Codeberg and GitHub/GitLab
     Mirror {
         /// Path to the project directory
         #[arg(long, default_value = ".")]
         project_dir: PathBuf,
+        #[command(flatten)]
+        options: commands::mirror::MirrorOptions,
+        /// Config profile to use (a [profile.NAME] table in the global config)
+        #[arg(long)]
+        profile: Option<String>,
     },
 }
 
-fn main() {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // --help and --version are not errors
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            e.print().ok();
+            return Kind::Config.into();
+        }
+    };
+    if let Err(e) = logging::init(cli.verbose, cli.log_file.as_deref()) {
+        eprintln!("{}", e);
+        return e.kind.into();
+    }
     let result = match cli.command {
-        Commands::Init { project_dir } => commands::init::run(&project_dir),
-        Commands::Check { project_dir } => commands::check::run(&project_dir),
-        Commands::Build { project_dir } => commands::build::run(&project_dir),
+        Commands::Init {
+            project_dir,
+            zenodo_json,
+            license,
+            changelog_from_tags,
+            update,
+            profile,
+        } => commands::init::run(
+            &project_dir,
+            zenodo_json,
+            license,
+            changelog_from_tags,
+            update,
+            profile.as_deref(),
+        ),
+        Commands::Check {
+            project_dir,
+            options,
+        } => commands::check::run(&project_dir, &options),
+        Commands::Build {
+            project_dir,
+            output_dir,
+            profile,
+        } => commands::build::run(&project_dir, output_dir.as_deref(), profile.as_deref()),
+        Commands::Verify {
+            project_dir,
+            reproduce,
+            output_dir,
+            profile,
+        } => commands::verify::run(
+            &project_dir,
+            reproduce,
+            output_dir.as_deref(),
+            profile.as_deref(),
+        ),
         Commands::Publish {
             project_dir,
             sandbox,
-            confirm,
-        } => commands::publish::run(&project_dir, sandbox, confirm),
-        Commands::Mirror { project_dir } => commands::mirror::run(&project_dir),
+            options,
+            profile,
+        } => commands::publish::run(&project_dir, sandbox, &options, profile.as_deref()),
+        Commands::Discard {
+            project_dir,
+            sandbox,
+            id,
+            profile,
+        } => commands::discard::run(&project_dir, sandbox, id, profile.as_deref()),
+        Commands::ListDepositions {
+            project_dir,
+            sandbox,
+            all,
+            profile,
+        } => commands::list_depositions::run(&project_dir, sandbox, all, profile.as_deref()),
+        Commands::Auth { action } => commands::auth::run(action),
+        Commands::Config { action } => commands::config::run(action),
+        Commands::Metadata { action } => commands::metadata::run(action),
+        Commands::CiInit {
+            project_dir,
+            provider,
+            force,
+            profile,
+        } => commands::ci_init::run(&project_dir, provider, force, profile.as_deref()),
+        Commands::Hooks { action } => commands::hooks::run(action),
+        Commands::Status {
+            project_dir,
+            sandbox,
+            output_dir,
+            profile,
+        } => commands::status::run(
+            &project_dir,
+            sandbox,
+            output_dir.as_deref(),
+            profile.as_deref(),
+        ),
+        Commands::Diff {
+            old,
+            new,
+            project_dir,
+            output_dir,
+            profile,
+        } => commands::diff::run(
+            &project_dir,
+            &old,
+            &new,
+            output_dir.as_deref(),
+            profile.as_deref(),
+        ),
+        Commands::Preview {
+            project_dir,
+            json,
+            profile,
+        } => commands::preview::run(&project_dir, json, profile.as_deref()),
+        Commands::Cite {
+            project_dir,
+            format,
+            all_versions,
+            profile,
+        } => commands::cite::run(&project_dir, format, all_versions, profile.as_deref()),
+        Commands::Open {
+            project_dir,
+            sandbox,
+            tag,
+            target,
+            profile,
+        } => commands::open::run(
+            &project_dir,
+            sandbox,
+            tag.as_deref(),
+            &target,
+            profile.as_deref(),
+        ),
+        Commands::Announce {
+            project_dir,
+            template,
+            bundle,
+            output_dir,
+            profile,
+        } => commands::announce::run(
+            &project_dir,
+            &template,
+            bundle,
+            output_dir.as_deref(),
+            profile.as_deref(),
+        ),
+        Commands::Mirror {
+            project_dir,
+            options,
+            profile,
+        } => commands::mirror::run(&project_dir, &options, profile.as_deref()),
     };
-    if let Err(e) = result {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            e.kind.into()
+        }
     }
 }
@@ -1,2 +1,25 @@
+/// BibTeX entries
+pub mod bibtex;
+/// CITATION.cff, the source of the metadata
 pub mod citation;
+/// codemeta.json
+pub mod codemeta;
+/// DataCite metadata (JSON and XML)
+pub mod datacite;
+/// Frictionless `datapackage.json` for the data files
+pub mod datapackage;
+pub mod doi;
+/// schema.org JSON-LD for a project website
+pub mod jsonld;
+pub mod ledger;
+pub mod manifest;
+pub mod release_notes;
+/// RIS and EndNote records
+pub mod ris;
+/// Formatted references (APA, Chicago) and CFF snippets
+pub mod styles;
+/// Keywords mapped to controlled-vocabulary subjects
+pub mod subjects;
+pub mod sync;
+/// Zenodo deposit metadata, from CITATION.cff and `.zenodo.json`
 pub mod zenodo;
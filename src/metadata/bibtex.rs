@@ -0,0 +1,185 @@
+use crate::metadata::citation::{CffAuthor, CffReference, CitationCff};
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Render CITATION.cff as BibTeX: the preferred-citation entry (if any)
+/// followed by a @software entry for the release itself
+pub fn from_citation(cff: &CitationCff) -> String {
+    let mut out = String::new();
+    if let Some(paper) = &cff.preferred_citation {
+        out.push_str(&from_reference(paper));
+        out.push('\n');
+    }
+    out.push_str(&software_entry(cff));
+    out
+}
+
+fn software_entry(cff: &CitationCff) -> String {
+    let year = cff
+        .date_released
+        .as_deref()
+        .and_then(|d| d.get(..4))
+        .map(String::from);
+    let month = cff
+        .date_released
+        .as_deref()
+        .and_then(|d| d.get(5..7))
+        .and_then(|m| m.parse::<usize>().ok())
+        .and_then(|m| MONTHS.get(m.wrapping_sub(1)));
+
+    let mut fields: Vec<(&str, String)> = vec![
+        ("author", braced(&authors(&cff.authors))),
+        ("title", format!("{{{{{}}}}}", escape(&cff.title))),
+    ];
+    if let Some(year) = &year {
+        fields.push(("year", braced(year)));
+    }
+    if let Some(month) = month {
+        fields.push(("month", month.to_string()));
+    }
+    if let Some(version) = &cff.version {
+        fields.push(("version", braced(&escape(version))));
+    }
+    if let Some(doi) = &cff.doi {
+        fields.push(("doi", braced(doi)));
+    }
+    if let Some(url) = &cff.repository_code {
+        fields.push(("url", braced(url)));
+    }
+    if let Some(license) = &cff.license {
+        fields.push(("license", braced(&escape(license))));
+    }
+
+    entry(
+        "software",
+        &cite_key(&cff.authors, &cff.title, year.as_deref()),
+        &fields,
+    )
+}
+
+/// Render a CFF reference (e.g. preferred-citation) as a BibTeX entry
+pub fn from_reference(reference: &CffReference) -> String {
+    let kind = match reference.ref_type.as_str() {
+        "article" | "magazine-article" | "newspaper-article" => "article",
+        "book" => "book",
+        "conference-paper" | "proceedings" => "inproceedings",
+        "thesis" => "phdthesis",
+        "report" => "techreport",
+        "software" | "software-code" => "software",
+        _ => "misc",
+    };
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if !reference.authors.is_empty() {
+        fields.push(("author", braced(&authors(&reference.authors))));
+    }
+    fields.push(("title", format!("{{{{{}}}}}", escape(&reference.title))));
+    if let Some(journal) = &reference.journal {
+        let field = if kind == "inproceedings" {
+            "booktitle"
+        } else {
+            "journal"
+        };
+        fields.push((field, braced(&escape(journal))));
+    }
+    if let Some(year) = &reference.year {
+        fields.push(("year", braced(year)));
+    }
+    if let Some(volume) = &reference.volume {
+        fields.push(("volume", braced(&escape(volume))));
+    }
+    if let Some(issue) = &reference.issue {
+        fields.push(("number", braced(&escape(issue))));
+    }
+    match (&reference.start, &reference.end) {
+        (Some(start), Some(end)) => {
+            fields.push(("pages", format!("{{{}--{}}}", escape(start), escape(end))))
+        }
+        (Some(start), None) => fields.push(("pages", braced(&escape(start)))),
+        _ => {}
+    }
+    if let Some(doi) = &reference.doi {
+        fields.push(("doi", braced(doi)));
+    }
+    if let Some(url) = &reference.url {
+        fields.push(("url", braced(url)));
+    }
+
+    entry(
+        kind,
+        &cite_key(
+            &reference.authors,
+            &reference.title,
+            reference.year.as_deref(),
+        ),
+        &fields,
+    )
+}
+
+fn entry(kind: &str, key: &str, fields: &[(&str, String)]) -> String {
+    let mut out = format!("@{}{{{},\n", kind, key);
+    for (name, value) in fields {
+        out.push_str(&format!("  {} = {},\n", name, value));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn authors(authors: &[CffAuthor]) -> String {
+    authors
+        .iter()
+        .map(|a| {
+            if a.is_entity() {
+                // Double braces keep BibTeX from splitting the name into parts
+                format!("{{{}}}", escape(&a.display_name()))
+            } else {
+                escape(&a.display_name())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+fn braced(value: &str) -> String {
+    format!("{{{}}}", value)
+}
+
+/// cffconvert-style key: `Family_Title_Year`
+fn cite_key(authors: &[CffAuthor], title: &str, year: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(author) = authors.first() {
+        parts.push(author.sort_name().to_string());
+    }
+    parts.push(title.to_string());
+    if let Some(year) = year {
+        parts.push(year.to_string());
+    }
+    parts
+        .join("_")
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '_' || c == '-' => Some(c),
+            ' ' => Some('_'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Escape LaTeX's special characters; an unescaped brace would end the
+/// field early or leave it unclosed
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
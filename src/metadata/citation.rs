@@ -1,5 +1,7 @@
-use serde::{Deserialize, Serialize};
+use crate::error::{IoError, ValidationError};
+use serde::{Deserialize, Deserializer, Serialize};
 
+/// A CITATION.cff file: the fields release-scholar reads
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CitationCff {
     #[serde(rename = "cff-version")]
@@ -14,24 +16,46 @@ pub struct CitationCff {
     pub license: Option<String>,
     #[serde(rename = "date-released", skip_serializing_if = "Option::is_none")]
     pub date_released: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
     #[serde(rename = "repository-code", skip_serializing_if = "Option::is_none")]
     pub repository_code: Option<String>,
     #[serde(rename = "abstract", skip_serializing_if = "Option::is_none")]
     pub abstract_text: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub keywords: Vec<String>,
+    /// Left out if it cannot be read, which `check` reports, rather than
+    /// failing the whole file
+    #[serde(
+        rename = "preferred-citation",
+        default,
+        deserialize_with = "readable_reference",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub preferred_citation: Option<CffReference>,
+    /// Those that can be read, likewise
+    #[serde(
+        default,
+        deserialize_with = "readable_references",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub references: Vec<CffReference>,
 }
 
 fn default_type() -> String {
     "software".to_string()
 }
 
+/// A CFF author: either a person (family/given names) or an entity (`name`),
+/// such as a lab, consortium, or institution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CffAuthor {
-    #[serde(rename = "family-names")]
-    pub family_names: String,
-    #[serde(rename = "given-names")]
-    pub given_names: String,
+    #[serde(rename = "family-names", skip_serializing_if = "Option::is_none")]
+    pub family_names: Option<String>,
+    #[serde(rename = "given-names", skip_serializing_if = "Option::is_none")]
+    pub given_names: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orcid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,9 +64,123 @@ pub struct CffAuthor {
     pub affiliation: Option<String>,
 }
 
+/// A CFF reference object, as used by `preferred-citation` and `references`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CffReference {
+    #[serde(rename = "type")]
+    pub ref_type: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<CffAuthor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub volume: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issue: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end: Option<String>,
+}
+
+/// CFF allows integers for fields like `year` and `volume`; keep them as strings
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_yaml::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_yaml::Value::String(s)) => Some(s),
+        Some(serde_yaml::Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn readable_reference<'de, D>(deserializer: D) -> Result<Option<CffReference>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_yaml::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| CffReference::read(v).ok()))
+}
+
+fn readable_references<'de, D>(deserializer: D) -> Result<Vec<CffReference>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Option::<Vec<serde_yaml::Value>>::deserialize(deserializer)?;
+    Ok(values
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| CffReference::read(v).ok())
+        .collect())
+}
+
+impl CffReference {
+    /// A reference from its YAML, or why it cannot be read
+    pub fn read(value: serde_yaml::Value) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_value(value)
+    }
+}
+
+impl CffAuthor {
+    /// Whether the author is an entity (lab, institution) rather than a person
+    pub fn is_entity(&self) -> bool {
+        self.family_names.is_none() && self.name.is_some()
+    }
+
+    /// "Family, Given" for persons, the entity name for entities
+    pub fn display_name(&self) -> String {
+        match (&self.family_names, &self.given_names, &self.name) {
+            (Some(family), Some(given), _) => format!("{}, {}", family, given),
+            (Some(family), None, _) => family.clone(),
+            (None, _, Some(name)) => name.clone(),
+            (None, Some(given), None) => given.clone(),
+            (None, None, None) => String::new(),
+        }
+    }
+
+    /// Family name for persons, entity name for entities
+    pub fn sort_name(&self) -> &str {
+        self.family_names
+            .as_deref()
+            .or(self.name.as_deref())
+            .or(self.given_names.as_deref())
+            .unwrap_or("")
+    }
+}
+
 impl CitationCff {
-    pub fn from_file(path: &std::path::Path) -> Result<Self, String> {
-        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-        serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse CITATION.cff: {}", e))
+    /// Read and parse a CITATION.cff file
+    pub fn from_file(path: &std::path::Path) -> Result<Self, ValidationError> {
+        let content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+        serde_yaml::from_str(&content).map_err(ValidationError::Citation)
     }
 }
@@ -0,0 +1,75 @@
+use crate::error::{IoError, ValidationError};
+use crate::metadata::citation::CitationCff;
+use crate::metadata::jsonld::{self, JsonLdAuthor};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+const CODEMETA_CONTEXT: &str = "https://w3id.org/codemeta/3.0";
+
+/// CodeMeta 3.0 `codemeta.json`, with the fields CITATION.cff can provide
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeMeta {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub author: Vec<JsonLdAuthor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+}
+
+impl CodeMeta {
+    /// codemeta.json for the project described by `cff`
+    pub fn from_citation(cff: &CitationCff) -> Self {
+        CodeMeta {
+            context: CODEMETA_CONTEXT.to_string(),
+            schema_type: "SoftwareSourceCode".to_string(),
+            name: cff.title.clone(),
+            description: cff.abstract_text.clone(),
+            version: cff.version.clone(),
+            author: jsonld::authors(cff),
+            license: cff
+                .license
+                .as_ref()
+                .map(|l| format!("https://spdx.org/licenses/{}", l)),
+            code_repository: cff.repository_code.clone(),
+            date_published: cff.date_released.clone(),
+            keywords: cff.keywords.clone(),
+            identifier: cff.doi.as_ref().map(|d| format!("https://doi.org/{}", d)),
+        }
+    }
+
+    /// An existing codemeta.json with these fields replaced; fields CFF has
+    /// no equivalent for (dependencies, languages, funding...) are kept
+    pub fn merged_into(&self, mut existing: Map<String, Value>) -> Map<String, Value> {
+        if let Ok(Value::Object(fields)) = serde_json::to_value(self) {
+            existing.extend(fields);
+        }
+        existing
+    }
+}
+
+/// The top-level fields of a codemeta.json
+pub fn read_codemeta(path: &Path) -> Result<Map<String, Value>, ValidationError> {
+    let content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+    match serde_json::from_str(&content) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(ValidationError::CodeMetaNotObject),
+        Err(e) => Err(ValidationError::CodeMeta(e)),
+    }
+}
@@ -0,0 +1,372 @@
+use crate::config::Config;
+use crate::metadata::citation::CitationCff;
+use serde::Serialize;
+
+const SCHEMA_VERSION: &str = "http://datacite.org/schema/kernel-4";
+
+/// DataCite Metadata Schema 4 record, in the JSON shape used by the DataCite REST API
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteResource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    pub types: DataCiteTypes,
+    pub creators: Vec<DataCiteCreator>,
+    pub titles: Vec<DataCiteTitle>,
+    pub publisher: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publication_year: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subjects: Vec<DataCiteSubject>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dates: Vec<DataCiteDate>,
+    pub language: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_identifiers: Vec<DataCiteRelatedIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rights_list: Vec<DataCiteRights>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub descriptions: Vec<DataCiteDescription>,
+    pub schema_version: String,
+}
+
+/// The resource type
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteTypes {
+    pub resource_type_general: String,
+    pub resource_type: String,
+}
+
+/// An author
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteCreator {
+    pub name: String,
+    pub name_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub name_identifiers: Vec<DataCiteNameIdentifier>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub affiliation: Vec<DataCiteAffiliation>,
+}
+
+/// An author identifier, such as an ORCID
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteNameIdentifier {
+    pub name_identifier: String,
+    pub name_identifier_scheme: String,
+    pub scheme_uri: String,
+}
+
+/// An author affiliation
+#[derive(Debug, Serialize)]
+pub struct DataCiteAffiliation {
+    pub name: String,
+}
+
+/// A title
+#[derive(Debug, Serialize)]
+pub struct DataCiteTitle {
+    pub title: String,
+}
+
+/// A keyword or controlled-vocabulary subject
+#[derive(Debug, Serialize)]
+pub struct DataCiteSubject {
+    pub subject: String,
+}
+
+/// A date, such as the release date
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteDate {
+    pub date: String,
+    pub date_type: String,
+}
+
+/// A related identifier, such as the repository or the paper
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteRelatedIdentifier {
+    pub related_identifier: String,
+    pub related_identifier_type: String,
+    pub relation_type: String,
+}
+
+/// The license
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteRights {
+    pub rights: String,
+    pub rights_identifier: String,
+    pub rights_identifier_scheme: String,
+    pub scheme_uri: String,
+}
+
+/// A description, such as the abstract
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteDescription {
+    pub description: String,
+    pub description_type: String,
+}
+
+impl DataCiteResource {
+    /// The DataCite record for `cff`, with the publisher from `config`
+    pub fn from_citation(cff: &CitationCff, config: &Config) -> Self {
+        let creators = cff
+            .authors
+            .iter()
+            .map(|a| DataCiteCreator {
+                name: a.display_name(),
+                name_type: if a.is_entity() {
+                    "Organizational"
+                } else {
+                    "Personal"
+                }
+                .to_string(),
+                given_name: a.given_names.clone(),
+                family_name: a.family_names.clone(),
+                name_identifiers: a
+                    .orcid
+                    .iter()
+                    .map(|o| DataCiteNameIdentifier {
+                        name_identifier: o.clone(),
+                        name_identifier_scheme: "ORCID".to_string(),
+                        scheme_uri: "https://orcid.org".to_string(),
+                    })
+                    .collect(),
+                affiliation: a
+                    .affiliation
+                    .iter()
+                    .map(|name| DataCiteAffiliation { name: name.clone() })
+                    .collect(),
+            })
+            .collect();
+
+        let related_identifiers = cff
+            .repository_code
+            .iter()
+            .map(|url| DataCiteRelatedIdentifier {
+                related_identifier: url.clone(),
+                related_identifier_type: "URL".to_string(),
+                relation_type: "IsSupplementTo".to_string(),
+            })
+            .collect();
+
+        DataCiteResource {
+            doi: cff.doi.clone(),
+            types: DataCiteTypes {
+                resource_type_general: "Software".to_string(),
+                resource_type: cff.cff_type.clone(),
+            },
+            creators,
+            titles: vec![DataCiteTitle {
+                title: cff.title.clone(),
+            }],
+            // The DOI is minted on Zenodo unless configured otherwise
+            publisher: config
+                .publisher
+                .clone()
+                .unwrap_or_else(|| "Zenodo".to_string()),
+            publication_year: cff
+                .date_released
+                .as_deref()
+                .and_then(|d| d.get(..4))
+                .map(String::from),
+            subjects: cff
+                .keywords
+                .iter()
+                .map(|k| DataCiteSubject { subject: k.clone() })
+                .collect(),
+            dates: cff
+                .date_released
+                .iter()
+                .map(|d| DataCiteDate {
+                    date: d.clone(),
+                    date_type: "Issued".to_string(),
+                })
+                .collect(),
+            language: config.language.clone(),
+            related_identifiers,
+            version: cff.version.clone(),
+            rights_list: cff
+                .license
+                .iter()
+                .map(|l| DataCiteRights {
+                    rights: l.clone(),
+                    rights_identifier: l.clone(),
+                    rights_identifier_scheme: "SPDX".to_string(),
+                    scheme_uri: "https://spdx.org/licenses/".to_string(),
+                })
+                .collect(),
+            descriptions: cff
+                .abstract_text
+                .iter()
+                .map(|d| DataCiteDescription {
+                    description: d.clone(),
+                    description_type: "Abstract".to_string(),
+                })
+                .collect(),
+            schema_version: SCHEMA_VERSION.to_string(),
+        }
+    }
+
+    /// The record as DataCite JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Render as DataCite XML (kernel-4). `None` until there is a DOI, which
+    /// the schema requires as the identifier.
+    pub fn to_xml(&self) -> Option<String> {
+        let doi = self.doi.as_ref()?;
+        let mut x = String::new();
+        x.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        x.push_str(&format!(
+            "<resource xmlns=\"{0}\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+             xsi:schemaLocation=\"{0} {0}/metadata.xsd\">\n",
+            SCHEMA_VERSION
+        ));
+
+        x.push_str(&format!(
+            "  <identifier identifierType=\"DOI\">{}</identifier>\n",
+            escape(doi)
+        ));
+
+        x.push_str("  <creators>\n");
+        for c in &self.creators {
+            x.push_str("    <creator>\n");
+            x.push_str(&format!(
+                "      <creatorName nameType=\"{}\">{}</creatorName>\n",
+                c.name_type,
+                escape(&c.name)
+            ));
+            if let Some(given) = &c.given_name {
+                x.push_str(&format!("      <givenName>{}</givenName>\n", escape(given)));
+            }
+            if let Some(family) = &c.family_name {
+                x.push_str(&format!(
+                    "      <familyName>{}</familyName>\n",
+                    escape(family)
+                ));
+            }
+            for id in &c.name_identifiers {
+                x.push_str(&format!(
+                    "      <nameIdentifier nameIdentifierScheme=\"{}\" schemeURI=\"{}\">{}</nameIdentifier>\n",
+                    id.name_identifier_scheme,
+                    id.scheme_uri,
+                    escape(&id.name_identifier)
+                ));
+            }
+            for a in &c.affiliation {
+                x.push_str(&format!(
+                    "      <affiliation>{}</affiliation>\n",
+                    escape(&a.name)
+                ));
+            }
+            x.push_str("    </creator>\n");
+        }
+        x.push_str("  </creators>\n");
+
+        x.push_str("  <titles>\n");
+        for t in &self.titles {
+            x.push_str(&format!("    <title>{}</title>\n", escape(&t.title)));
+        }
+        x.push_str("  </titles>\n");
+        x.push_str(&format!(
+            "  <publisher>{}</publisher>\n",
+            escape(&self.publisher)
+        ));
+        if let Some(year) = &self.publication_year {
+            x.push_str(&format!(
+                "  <publicationYear>{}</publicationYear>\n",
+                escape(year)
+            ));
+        }
+        x.push_str(&format!(
+            "  <resourceType resourceTypeGeneral=\"{}\">{}</resourceType>\n",
+            self.types.resource_type_general,
+            escape(&self.types.resource_type)
+        ));
+
+        if !self.subjects.is_empty() {
+            x.push_str("  <subjects>\n");
+            for s in &self.subjects {
+                x.push_str(&format!("    <subject>{}</subject>\n", escape(&s.subject)));
+            }
+            x.push_str("  </subjects>\n");
+        }
+        if !self.dates.is_empty() {
+            x.push_str("  <dates>\n");
+            for d in &self.dates {
+                x.push_str(&format!(
+                    "    <date dateType=\"{}\">{}</date>\n",
+                    d.date_type,
+                    escape(&d.date)
+                ));
+            }
+            x.push_str("  </dates>\n");
+        }
+        x.push_str(&format!(
+            "  <language>{}</language>\n",
+            escape(&self.language)
+        ));
+        if !self.related_identifiers.is_empty() {
+            x.push_str("  <relatedIdentifiers>\n");
+            for r in &self.related_identifiers {
+                x.push_str(&format!(
+                    "    <relatedIdentifier relatedIdentifierType=\"{}\" relationType=\"{}\">{}</relatedIdentifier>\n",
+                    r.related_identifier_type,
+                    r.relation_type,
+                    escape(&r.related_identifier)
+                ));
+            }
+            x.push_str("  </relatedIdentifiers>\n");
+        }
+        if let Some(version) = &self.version {
+            x.push_str(&format!("  <version>{}</version>\n", escape(version)));
+        }
+        if !self.rights_list.is_empty() {
+            x.push_str("  <rightsList>\n");
+            for r in &self.rights_list {
+                x.push_str(&format!(
+                    "    <rights rightsIdentifier=\"{}\" rightsIdentifierScheme=\"{}\" schemeURI=\"{}\">{}</rights>\n",
+                    escape(&r.rights_identifier),
+                    r.rights_identifier_scheme,
+                    r.scheme_uri,
+                    escape(&r.rights)
+                ));
+            }
+            x.push_str("  </rightsList>\n");
+        }
+        if !self.descriptions.is_empty() {
+            x.push_str("  <descriptions>\n");
+            for d in &self.descriptions {
+                x.push_str(&format!(
+                    "    <description descriptionType=\"{}\">{}</description>\n",
+                    d.description_type,
+                    escape(&d.description)
+                ));
+            }
+            x.push_str("  </descriptions>\n");
+        }
+        x.push_str("</resource>\n");
+        Some(x)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,145 @@
+use crate::archive::checksum;
+use crate::config::DataConfig;
+use crate::error::{IoError, ValidationError};
+use serde::Serialize;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Frictionless Data Package descriptor (<https://specs.frictionlessdata.io/data-package/>)
+#[derive(Debug, Serialize)]
+pub struct DataPackage {
+    pub profile: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<DataLicense>,
+    pub resources: Vec<DataResource>,
+}
+
+/// A license of the data
+#[derive(Debug, Serialize)]
+pub struct DataLicense {
+    pub name: String,
+}
+
+/// A data file
+#[derive(Debug, Serialize)]
+pub struct DataResource {
+    pub name: String,
+    pub path: String,
+    pub profile: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mediatype: Option<String>,
+    pub bytes: u64,
+    pub hash: String,
+}
+
+impl DataPackage {
+    /// The package for the data files the config describes
+    pub fn from_config(
+        project_dir: &Path,
+        data: &DataConfig,
+        project_name: &str,
+        version: &str,
+    ) -> Result<Self, ValidationError> {
+        let mut files = Vec::new();
+        for data_path in &data.paths {
+            let full_path = project_dir.join(data_path);
+            if !full_path.exists() {
+                return Err(ValidationError::DataPathNotFound(data_path.clone()));
+            }
+            for entry in WalkDir::new(&full_path).sort_by_file_name() {
+                let entry = entry.map_err(|source| ValidationError::DataPath {
+                    path: data_path.clone(),
+                    source,
+                })?;
+                if entry.file_type().is_file() {
+                    files.push(entry.into_path());
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+
+        let mut resources = Vec::new();
+        for file in &files {
+            let rel_path = file
+                .strip_prefix(project_dir)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let bytes = std::fs::metadata(file)
+                .map_err(IoError::on("read", &rel_path))?
+                .len();
+            let hash = checksum::sha256_file(file)?;
+            let format = file.extension().map(|e| e.to_string_lossy().to_lowercase());
+            let mediatype = format.as_deref().and_then(media_type).map(String::from);
+
+            resources.push(DataResource {
+                name: resource_name(&rel_path),
+                path: rel_path,
+                profile: "data-resource".to_string(),
+                format,
+                mediatype,
+                bytes,
+                hash: format!("sha256:{}", hash),
+            });
+        }
+
+        Ok(DataPackage {
+            profile: "data-package".to_string(),
+            name: data
+                .name
+                .clone()
+                .unwrap_or_else(|| resource_name(project_name)),
+            title: data.title.clone(),
+            version: Some(version.to_string()),
+            licenses: data
+                .license
+                .iter()
+                .map(|l| DataLicense { name: l.clone() })
+                .collect(),
+            resources,
+        })
+    }
+
+    /// The package as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Frictionless names must be lowercase alphanumerics plus `.`, `_` and `-`
+fn resource_name(path: &str) -> String {
+    path.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn media_type(format: &str) -> Option<&'static str> {
+    match format {
+        "csv" => Some("text/csv"),
+        "tsv" => Some("text/tab-separated-values"),
+        "txt" => Some("text/plain"),
+        "json" => Some("application/json"),
+        "geojson" => Some("application/geo+json"),
+        "xml" => Some("application/xml"),
+        "parquet" => Some("application/vnd.apache.parquet"),
+        "xlsx" => Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        "zip" => Some("application/zip"),
+        "gz" => Some("application/gzip"),
+        _ => None,
+    }
+}
@@ -0,0 +1,144 @@
+//! Recording the DOIs Zenodo minted in CITATION.cff and codemeta.json, so
+//! the canonical metadata points at the archived record
+
+use crate::error::{IoError, ValidationError};
+use crate::metadata::codemeta::read_codemeta;
+use crate::metadata::sync::replace_top_level;
+use serde_json::json;
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// Descriptions marking the `identifiers` entries written here, so the next
+/// release replaces them instead of piling up
+const CONCEPT_DESCRIPTION: &str = "Concept DOI: always resolves to the latest version";
+const VERSION_DESCRIPTION: &str = "Version DOI for ";
+
+/// The DOIs a publish minted, to write back into the project files
+pub struct MintedDoi<'a> {
+    pub version: &'a str,
+    /// Shared by every version; known once the first version is published
+    pub concept: Option<&'a str>,
+    pub tag: &'a str,
+}
+
+impl MintedDoi<'_> {
+    /// The DOI for the `doi` fields: the concept DOI where there is one, as
+    /// it stays right for the releases built from these files later
+    pub fn canonical(&self) -> &str {
+        self.concept.unwrap_or(self.version)
+    }
+}
+
+/// Write the DOIs into whichever of CITATION.cff and codemeta.json exist;
+/// returns the files that changed
+pub fn write_back(
+    project_dir: &Path,
+    doi: &MintedDoi,
+) -> Result<Vec<&'static str>, ValidationError> {
+    let mut changed = Vec::new();
+    let citation = project_dir.join("CITATION.cff");
+    if citation.exists() && write_citation(&citation, doi)? {
+        changed.push("CITATION.cff");
+    }
+    let codemeta = project_dir.join("codemeta.json");
+    if codemeta.exists() && write_codemeta(&codemeta, doi)? {
+        changed.push("codemeta.json");
+    }
+    Ok(changed)
+}
+
+/// The version DOI recorded in CITATION.cff for `tag`, if `write_back` put
+/// one there
+pub fn recorded_version(project_dir: &Path, tag: &str) -> Option<String> {
+    let content = std::fs::read_to_string(project_dir.join("CITATION.cff")).ok()?;
+    let cff: Value = serde_yaml::from_str(&content).ok()?;
+    let description = format!("{}{}", VERSION_DESCRIPTION, tag);
+    cff.get("identifiers")?
+        .as_sequence()?
+        .iter()
+        .find(|entry| entry.get("description").and_then(|d| d.as_str()) == Some(&description))?
+        .get("value")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// `doi`, plus `identifiers` entries for the version and concept DOIs
+fn write_citation(path: &Path, doi: &MintedDoi) -> Result<bool, ValidationError> {
+    let original = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+    let cff: Value = serde_yaml::from_str(&original).map_err(ValidationError::Citation)?;
+
+    let identifier = |value: &str, description: String| {
+        let mut entry = Mapping::new();
+        entry.insert("type".into(), "doi".into());
+        entry.insert("value".into(), value.into());
+        entry.insert("description".into(), description.into());
+        Value::Mapping(entry)
+    };
+    let mut identifiers: Vec<Value> = cff
+        .get("identifiers")
+        .and_then(|v| v.as_sequence())
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            let description = entry.get("description").and_then(|d| d.as_str());
+            !description
+                .is_some_and(|d| d == CONCEPT_DESCRIPTION || d.starts_with(VERSION_DESCRIPTION))
+        })
+        .cloned()
+        .collect();
+    identifiers.push(identifier(
+        doi.version,
+        format!("{}{}", VERSION_DESCRIPTION, doi.tag),
+    ));
+    if let Some(concept) = doi.concept {
+        identifiers.push(identifier(concept, CONCEPT_DESCRIPTION.to_string()));
+    }
+
+    let mut content = original.clone();
+    for (key, value) in [
+        ("doi", Value::from(doi.canonical())),
+        ("identifiers", Value::Sequence(identifiers)),
+    ] {
+        let mut entry = Mapping::new();
+        entry.insert(key.into(), value);
+        let snippet = serde_yaml::to_string(&entry).map_err(ValidationError::CitationWrite)?;
+        content = replace_top_level(&content, key, &snippet);
+    }
+    if content == original {
+        return Ok(false);
+    }
+    std::fs::write(path, content).map_err(IoError::on("write", path))?;
+    Ok(true)
+}
+
+/// `identifier` (as generated from CITATION.cff's `doi`), and the version
+/// DOI among the `relatedLink`s
+fn write_codemeta(path: &Path, doi: &MintedDoi) -> Result<bool, ValidationError> {
+    let mut meta = read_codemeta(path)?;
+    let original = meta.clone();
+    meta.insert(
+        "identifier".to_string(),
+        json!(format!("https://doi.org/{}", doi.canonical())),
+    );
+    if doi.concept.is_some() {
+        let version_url = format!("https://doi.org/{}", doi.version);
+        let mut links: Vec<serde_json::Value> = match meta.get("relatedLink") {
+            Some(serde_json::Value::Array(items)) => items.clone(),
+            Some(link) => vec![link.clone()],
+            None => Vec::new(),
+        };
+        if !links
+            .iter()
+            .any(|l| l.as_str() == Some(version_url.as_str()))
+        {
+            links.push(json!(version_url));
+        }
+        meta.insert("relatedLink".to_string(), json!(links));
+    }
+    if meta == original {
+        return Ok(false);
+    }
+    let json = serde_json::to_string_pretty(&meta).map_err(ValidationError::Serialize)?;
+    std::fs::write(path, json + "\n").map_err(IoError::on("write", path))?;
+    Ok(true)
+}
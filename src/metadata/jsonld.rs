@@ -0,0 +1,107 @@
+use crate::metadata::citation::CitationCff;
+use serde::Serialize;
+
+/// schema.org SoftwareSourceCode, for embedding in project web pages as JSON-LD
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftwareSourceCode {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub author: Vec<JsonLdAuthor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+}
+
+/// An author
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLdAuthor {
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affiliation: Option<JsonLdOrganization>,
+}
+
+/// An entity author's organization
+#[derive(Debug, Serialize)]
+pub struct JsonLdOrganization {
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    pub name: String,
+}
+
+impl SoftwareSourceCode {
+    /// The JSON-LD for the project described by `cff`
+    pub fn from_citation(cff: &CitationCff) -> Self {
+        SoftwareSourceCode {
+            context: "https://schema.org".to_string(),
+            schema_type: "SoftwareSourceCode".to_string(),
+            name: cff.title.clone(),
+            description: cff.abstract_text.clone(),
+            version: cff.version.clone(),
+            author: authors(cff),
+            license: cff
+                .license
+                .as_ref()
+                .map(|l| format!("https://spdx.org/licenses/{}", l)),
+            code_repository: cff.repository_code.clone(),
+            date_published: cff.date_released.clone(),
+            keywords: cff.keywords.clone(),
+            identifier: cff.doi.as_ref().map(|d| format!("https://doi.org/{}", d)),
+        }
+    }
+
+    /// The JSON-LD as a string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// CFF authors as schema.org `Person`s and `Organization`s
+pub(crate) fn authors(cff: &CitationCff) -> Vec<JsonLdAuthor> {
+    cff.authors
+        .iter()
+        .map(|a| JsonLdAuthor {
+            schema_type: if a.is_entity() {
+                "Organization"
+            } else {
+                "Person"
+            }
+            .to_string(),
+            id: a.orcid.clone(),
+            name: a.name.clone().filter(|_| a.is_entity()),
+            given_name: a.given_names.clone(),
+            family_name: a.family_names.clone(),
+            email: a.email.clone(),
+            affiliation: a.affiliation.as_ref().map(|name| JsonLdOrganization {
+                schema_type: "Organization".to_string(),
+                name: name.clone(),
+            }),
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+//! RELEASES.md: a table of every release deposited on Zenodo, kept in the
+//! repository so the record of them does not depend on the state file
+
+use crate::error::IoError;
+use std::path::Path;
+
+/// The ledger's name in the project directory
+pub const FILE_NAME: &str = "RELEASES.md";
+
+const HEADER: &str = "# Releases
+
+Every release of this project deposited on Zenodo, oldest first. `publish`
+adds a row for each; the checksum is the SHA-256 of the source archive.
+
+| Version | Date | DOI | Archive SHA-256 | Zenodo record |
+|---------|------|-----|-----------------|---------------|
+";
+
+/// One published release
+pub struct Entry<'a> {
+    pub tag: &'a str,
+    /// `YYYY-MM-DD`
+    pub date: &'a str,
+    pub doi: &'a str,
+    pub sha256: &'a str,
+    pub record_url: &'a str,
+}
+
+impl Entry<'_> {
+    fn row(&self) -> String {
+        format!(
+            "| {} | {} | [{}](https://doi.org/{}) | `{}` | {} |\n",
+            self.tag, self.date, self.doi, self.doi, self.sha256, self.record_url
+        )
+    }
+}
+
+/// Append `entry` to RELEASES.md, creating the file if needed. A release
+/// already listed (by its DOI) is left as it is; returns whether the file
+/// changed.
+pub fn record(project_dir: &Path, entry: &Entry) -> Result<bool, IoError> {
+    let path = project_dir.join(FILE_NAME);
+    let mut content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => HEADER.to_string(),
+        Err(e) => return Err(IoError::on("read", &path)(e)),
+    };
+    if content.contains(&format!("[{}]", entry.doi)) {
+        return Ok(false);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&entry.row());
+    std::fs::write(&path, content).map_err(IoError::on("write", &path))?;
+    Ok(true)
+}
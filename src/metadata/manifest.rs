@@ -0,0 +1,416 @@
+//! Language package manifests (Cargo.toml, pyproject.toml, package.json):
+//! reading and updating the fields they share with CITATION.cff.
+
+use crate::error::{IoError, ValidationError};
+use crate::metadata::sync::{Field, FieldValue, Person, SharedFields};
+use serde_json::json;
+use std::path::Path;
+use toml_edit::{value, Array, DocumentMut, InlineTable, Item, Table};
+
+/// A package manifest that records the project's metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manifest {
+    Cargo,
+    PyProject,
+    PackageJson,
+}
+
+impl Manifest {
+    /// The project's manifest; the first found if there are several
+    pub fn detect(project_dir: &Path) -> Option<Manifest> {
+        [Manifest::Cargo, Manifest::PyProject, Manifest::PackageJson]
+            .into_iter()
+            .find(|m| project_dir.join(m.file_name()).exists())
+    }
+
+    /// The manifest's file name
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Manifest::Cargo => "Cargo.toml",
+            Manifest::PyProject => "pyproject.toml",
+            Manifest::PackageJson => "package.json",
+        }
+    }
+
+    /// The shared fields the manifest records
+    pub fn read(self, project_dir: &Path) -> Result<SharedFields, ValidationError> {
+        let path = project_dir.join(self.file_name());
+        let content = std::fs::read_to_string(&path).map_err(IoError::on("read", &path))?;
+        match self {
+            Manifest::Cargo | Manifest::PyProject => {
+                let doc = self.parse_toml(&content)?;
+                Ok(match self.toml_table(&doc) {
+                    Some(table) => self.toml_fields(table),
+                    None => SharedFields::new(),
+                })
+            }
+            Manifest::PackageJson => {
+                let doc: serde_json::Value =
+                    serde_json::from_str(&content).map_err(ValidationError::PackageJson)?;
+                Ok(package_json_fields(&doc))
+            }
+        }
+    }
+
+    /// Set `changes`, leaving the rest of the file as it is. Fields the
+    /// manifest inherits (`version.workspace = true`) or computes at build
+    /// time (pyproject `dynamic`) are not touched.
+    pub fn write(
+        self,
+        project_dir: &Path,
+        changes: &[(Field, &FieldValue)],
+    ) -> Result<(), ValidationError> {
+        let path = project_dir.join(self.file_name());
+        let content = std::fs::read_to_string(&path).map_err(IoError::on("read", &path))?;
+        let updated = match self {
+            Manifest::Cargo | Manifest::PyProject => {
+                let mut doc = self.parse_toml(&content)?;
+                let section = self.section();
+                if !doc.contains_key(section) {
+                    doc[section] = Item::Table(Table::new());
+                }
+                if let Some(table) = doc[section].as_table_mut() {
+                    for (field, value) in changes {
+                        self.set_toml(table, *field, value);
+                    }
+                }
+                doc.to_string()
+            }
+            Manifest::PackageJson => {
+                let mut doc: serde_json::Value =
+                    serde_json::from_str(&content).map_err(ValidationError::PackageJson)?;
+                for (field, value) in changes {
+                    set_package_json(&mut doc, *field, value);
+                }
+                serde_json::to_string_pretty(&doc).map_err(ValidationError::Serialize)? + "\n"
+            }
+        };
+        std::fs::write(&path, updated).map_err(IoError::on("write", &path))?;
+        Ok(())
+    }
+
+    /// Whether `write` would set `field`: not the title, and not fields
+    /// the manifest inherits or computes
+    pub fn can_set(self, project_dir: &Path, field: Field) -> bool {
+        let key = match field {
+            Field::Title => return false,
+            Field::Version => "version",
+            Field::License => "license",
+            _ => return true,
+        };
+        if self == Manifest::PackageJson {
+            return true;
+        }
+        let content =
+            std::fs::read_to_string(project_dir.join(self.file_name())).unwrap_or_default();
+        let Ok(doc) = self.parse_toml(&content) else {
+            return true;
+        };
+        self.toml_table(&doc)
+            .is_none_or(|table| !self.is_dynamic(table, key))
+    }
+
+    /// `value` cut down to what the registry accepts for `field`, and what
+    /// was dropped. crates.io takes at most five keywords, each ASCII, at
+    /// most 20 characters, starting with a letter or digit and otherwise
+    /// made of letters, digits, `_`, `-` and `+`.
+    pub fn fit(self, field: Field, value: &FieldValue) -> (FieldValue, Vec<String>) {
+        if self != Manifest::Cargo || field != Field::Keywords {
+            return (value.clone(), Vec::new());
+        }
+        let (mut kept, mut dropped): (Vec<String>, Vec<String>) = value
+            .as_list()
+            .iter()
+            .cloned()
+            .partition(|k| is_crates_io_keyword(k));
+        if kept.len() > CRATES_IO_KEYWORDS {
+            dropped.extend(kept.split_off(CRATES_IO_KEYWORDS));
+        }
+        (FieldValue::List(kept), dropped)
+    }
+
+    fn section(self) -> &'static str {
+        match self {
+            Manifest::Cargo => "package",
+            _ => "project",
+        }
+    }
+
+    fn parse_toml(self, content: &str) -> Result<DocumentMut, ValidationError> {
+        content
+            .parse::<DocumentMut>()
+            .map_err(|source| ValidationError::Manifest {
+                file: self.file_name(),
+                source,
+            })
+    }
+
+    fn toml_table(self, doc: &DocumentMut) -> Option<&Table> {
+        doc.get(self.section()).and_then(Item::as_table)
+    }
+
+    fn toml_fields(self, table: &Table) -> SharedFields {
+        let mut fields = SharedFields::new();
+        let text = |key: &str| {
+            table
+                .get(key)
+                .and_then(Item::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        if !self.is_dynamic(table, "version") {
+            if let Some(version) = text("version") {
+                fields.insert(Field::Version, FieldValue::Text(version));
+            }
+        }
+
+        let license = match self {
+            // PEP 621 `license = { text = "MIT" }`, or a PEP 639 SPDX string
+            Manifest::PyProject => text("license").or_else(|| {
+                table
+                    .get("license")
+                    .and_then(Item::as_table_like)
+                    .and_then(|t| t.get("text"))
+                    .and_then(Item::as_str)
+                    .map(str::to_string)
+            }),
+            _ => text("license"),
+        };
+        if let Some(license) = license {
+            fields.insert(Field::License, FieldValue::Text(license));
+        }
+
+        let repository = match self {
+            Manifest::PyProject => pyproject_repository_key(table).and_then(|key| {
+                table
+                    .get("urls")
+                    .and_then(Item::as_table_like)
+                    .and_then(|urls| urls.get(key))
+                    .and_then(Item::as_str)
+                    .map(str::to_string)
+            }),
+            _ => text("repository"),
+        };
+        if let Some(repository) = repository {
+            fields.insert(Field::Repository, FieldValue::Text(repository));
+        }
+
+        let strings = |item: Option<&Item>| -> Vec<String> {
+            item.and_then(Item::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let keywords = strings(table.get("keywords"));
+        if !keywords.is_empty() {
+            fields.insert(Field::Keywords, FieldValue::List(keywords));
+        }
+
+        let authors: Vec<Person> = match self {
+            Manifest::PyProject => table
+                .get("authors")
+                .and_then(Item::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_inline_table())
+                        .filter_map(|t| {
+                            Some(Person {
+                                name: t.get("name")?.as_str()?.to_string(),
+                                email: t.get("email").and_then(|e| e.as_str()).map(str::to_string),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => strings(table.get("authors"))
+                .iter()
+                .map(|a| Person::parse(a))
+                .collect(),
+        };
+        if !authors.is_empty() {
+            fields.insert(Field::Authors, FieldValue::People(authors));
+        }
+        fields
+    }
+
+    fn is_dynamic(self, table: &Table, key: &str) -> bool {
+        let inherited = table.get(key).is_some_and(Item::is_table_like);
+        let dynamic = table
+            .get("dynamic")
+            .and_then(Item::as_array)
+            .is_some_and(|a| a.iter().any(|v| v.as_str() == Some(key)));
+        inherited || dynamic
+    }
+
+    fn set_toml(self, table: &mut Table, field: Field, new: &FieldValue) {
+        let text = new.as_text().unwrap_or_default();
+        match field {
+            Field::Title => {}
+            Field::Version => {
+                if !self.is_dynamic(table, "version") {
+                    table["version"] = value(text);
+                }
+            }
+            Field::License => {
+                if self.is_dynamic(table, "license") {
+                    return;
+                }
+                let legacy = table
+                    .get_mut("license")
+                    .and_then(Item::as_table_like_mut)
+                    .filter(|t| t.contains_key("text"));
+                match legacy {
+                    Some(license) => license.insert("text", value(text)),
+                    None => table.insert("license", value(text)),
+                };
+            }
+            Field::Repository => match self {
+                Manifest::PyProject => {
+                    let key = pyproject_repository_key(table).unwrap_or("Repository");
+                    if !table.contains_key("urls") {
+                        table["urls"] = Item::Table(Table::new());
+                    }
+                    if let Some(urls) = table["urls"].as_table_like_mut() {
+                        urls.insert(key, value(text));
+                    }
+                }
+                _ => table["repository"] = value(text),
+            },
+            Field::Keywords => {
+                table["keywords"] = value(Array::from_iter(new.as_list()));
+            }
+            Field::Authors => {
+                let authors: Array = match self {
+                    Manifest::PyProject => new
+                        .as_people()
+                        .iter()
+                        .map(|p| {
+                            let mut author = InlineTable::new();
+                            author.insert("name", p.name.as_str().into());
+                            if let Some(email) = &p.email {
+                                author.insert("email", email.as_str().into());
+                            }
+                            author
+                        })
+                        .collect(),
+                    _ => new.as_people().iter().map(|p| p.to_string()).collect(),
+                };
+                table["authors"] = value(authors);
+            }
+        }
+    }
+}
+
+/// The `[project.urls]` key that points at the source code
+/// How many keywords crates.io accepts
+const CRATES_IO_KEYWORDS: usize = 5;
+
+fn is_crates_io_keyword(keyword: &str) -> bool {
+    keyword.len() <= 20
+        && keyword
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric())
+        && keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+}
+
+fn pyproject_repository_key(table: &Table) -> Option<&'static str> {
+    let urls = table.get("urls").and_then(Item::as_table_like)?;
+    [
+        "Repository",
+        "repository",
+        "Source",
+        "source",
+        "Source Code",
+    ]
+    .into_iter()
+    .find(|key| urls.contains_key(key))
+}
+
+fn package_json_fields(doc: &serde_json::Value) -> SharedFields {
+    let mut fields = SharedFields::new();
+    let text = |key: &str| {
+        doc.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(version) = text("version") {
+        fields.insert(Field::Version, FieldValue::Text(version));
+    }
+    if let Some(license) = text("license") {
+        fields.insert(Field::License, FieldValue::Text(license));
+    }
+    let repository = text("repository").or_else(|| {
+        doc.get("repository")
+            .and_then(|r| r.get("url"))
+            .and_then(|u| u.as_str())
+            .map(str::to_string)
+    });
+    if let Some(repository) = repository {
+        let url = repository
+            .trim_start_matches("git+")
+            .trim_end_matches(".git");
+        fields.insert(Field::Repository, FieldValue::Text(url.to_string()));
+    }
+    if let Some(keywords) = doc.get("keywords").and_then(|k| k.as_array()) {
+        let keywords: Vec<String> = keywords
+            .iter()
+            .filter_map(|k| k.as_str().map(str::to_string))
+            .collect();
+        if !keywords.is_empty() {
+            fields.insert(Field::Keywords, FieldValue::List(keywords));
+        }
+    }
+
+    // `author` plus `contributors`, each a "Name <email>" string or an object
+    let person = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => Some(Person::parse(s)),
+        _ => Some(Person {
+            name: v.get("name")?.as_str()?.to_string(),
+            email: v.get("email").and_then(|e| e.as_str()).map(str::to_string),
+        }),
+    };
+    let mut authors: Vec<Person> = doc.get("author").and_then(person).into_iter().collect();
+    if let Some(contributors) = doc.get("contributors").and_then(|c| c.as_array()) {
+        authors.extend(contributors.iter().filter_map(person));
+    }
+    if !authors.is_empty() {
+        fields.insert(Field::Authors, FieldValue::People(authors));
+    }
+    fields
+}
+
+fn set_package_json(doc: &mut serde_json::Value, field: Field, new: &FieldValue) {
+    let text = new.as_text().unwrap_or_default();
+    match field {
+        Field::Title => {}
+        Field::Version => doc["version"] = json!(text),
+        Field::License => doc["license"] = json!(text),
+        Field::Repository => match doc.get_mut("repository") {
+            Some(serde_json::Value::Object(repo)) => {
+                repo.insert("url".to_string(), json!(format!("git+{}.git", text)));
+            }
+            _ => doc["repository"] = json!(text),
+        },
+        Field::Keywords => doc["keywords"] = json!(new.as_list()),
+        Field::Authors => {
+            let people: Vec<String> = new.as_people().iter().map(|p| p.to_string()).collect();
+            if let Some((author, contributors)) = people.split_first() {
+                doc["author"] = json!(author);
+                if contributors.is_empty() {
+                    if let Some(doc) = doc.as_object_mut() {
+                        doc.remove("contributors");
+                    }
+                } else {
+                    doc["contributors"] = json!(contributors);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! RELEASE_NOTES.md: what a release contains and who made it, for the
+//! bundle and the forge release page
+
+use crate::error::GitError;
+use crate::forge;
+use crate::metadata::citation::CitationCff;
+use crate::metadata::styles;
+use crate::validation::git::{self, GitInfo, TagPattern};
+use std::path::Path;
+
+/// A larger diffstat says little file by file; only its summary is kept
+const MAX_LISTED_FILES: usize = 50;
+
+/// Release notes for `release`: its commits since the previous release tag,
+/// and the files they changed
+pub fn render(
+    project_dir: &Path,
+    release: &GitInfo,
+    tags: &TagPattern,
+    cff: Option<&CitationCff>,
+) -> Result<String, GitError> {
+    let title = cff.map_or_else(
+        || {
+            project_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        },
+        |c| c.title.clone(),
+    );
+    let mut notes = format!("**{} {}**, released {}\n", title, release.tag, release.date);
+
+    notes.push_str("\n## Changes\n\n");
+    match forge::changelog_section(project_dir, &release.version) {
+        Some(section) => notes.push_str(&section),
+        None => notes.push_str(&format!(
+            "CHANGELOG.md has no entry for {}.",
+            release.version
+        )),
+    }
+    notes.push('\n');
+
+    if let Some(cff) = cff.filter(|c| !c.authors.is_empty()) {
+        notes.push_str("\n## Authors\n\n");
+        for author in &cff.authors {
+            let name = match (&author.given_names, &author.family_names) {
+                (Some(given), Some(family)) => format!("{} {}", given, family),
+                _ => author.display_name(),
+            };
+            let mut line = format!("- {}", name);
+            if let Some(affiliation) = &author.affiliation {
+                line.push_str(&format!(" ({})", affiliation));
+            }
+            if let Some(orcid) = &author.orcid {
+                line.push_str(&format!(", ORCID {}", orcid));
+            }
+            notes.push_str(&line);
+            notes.push('\n');
+        }
+    }
+
+    let previous = git::previous_release(project_dir, tags, &release.version)?;
+    let changes = git::changes(
+        project_dir,
+        previous.as_ref().map(|p| p.tag.as_str()),
+        &release.tag,
+        MAX_LISTED_FILES,
+    )?;
+    match &previous {
+        Some(previous) => notes.push_str(&format!("\n## Since {}\n\n", previous.tag)),
+        None => notes.push_str("\n## Files\n\nThe first release.\n\n"),
+    }
+    notes.push_str(&format!(
+        "{} commit(s) by {} contributor(s): {}.\n\n",
+        changes.commits,
+        changes.contributors.len(),
+        changes.contributors.join(", ")
+    ));
+    notes.push_str(&format!("```\n{}\n```\n", changes.diffstat));
+
+    if let Some(cff) = cff {
+        notes.push_str("\n## How to cite\n\n");
+        notes.push_str("If you use this software in your research, please cite it:\n\n");
+        notes.push_str(&format!("> {}\n", styles::apa(cff).trim_end()));
+        notes.push_str(
+            "\nCITATION.cff in the repository has the full citation metadata; \
+             GitHub's \"Cite this repository\" and reference managers such as Zotero read it.\n",
+        );
+    }
+    Ok(notes)
+}
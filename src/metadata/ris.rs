@@ -0,0 +1,79 @@
+use crate::metadata::citation::CitationCff;
+
+/// Render CITATION.cff as an RIS record (`TY  - COMP`), importable by Zotero and EndNote
+pub fn from_citation(cff: &CitationCff) -> String {
+    let mut lines = vec![tag("TY", "COMP")];
+    for author in &cff.authors {
+        lines.push(tag("AU", &author.display_name()));
+    }
+    lines.push(tag("TI", &cff.title));
+    if let Some(date) = &cff.date_released {
+        if let Some(year) = date.get(..4) {
+            lines.push(tag("PY", year));
+        }
+        lines.push(tag("DA", &date.replace('-', "/")));
+    }
+    if let Some(version) = &cff.version {
+        // RIS has no version tag; ET (edition) is what reference managers map it to
+        lines.push(tag("ET", version));
+    }
+    if let Some(doi) = &cff.doi {
+        lines.push(tag("DO", doi));
+    }
+    if let Some(url) = &cff.repository_code {
+        lines.push(tag("UR", url));
+    }
+    if let Some(abstract_text) = &cff.abstract_text {
+        lines.push(tag("AB", &one_line(abstract_text)));
+    }
+    for keyword in &cff.keywords {
+        lines.push(tag("KW", keyword));
+    }
+    lines.push("ER  - ".to_string());
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Render CITATION.cff in EndNote's tagged import format (.enw)
+pub fn endnote_from_citation(cff: &CitationCff) -> String {
+    let mut lines = vec!["%0 Computer Program".to_string()];
+    for author in &cff.authors {
+        lines.push(format!("%A {}", author.display_name()));
+    }
+    lines.push(format!("%T {}", cff.title));
+    if let Some(year) = cff.date_released.as_deref().and_then(|d| d.get(..4)) {
+        lines.push(format!("%D {}", year));
+    }
+    if let Some(version) = &cff.version {
+        lines.push(format!("%7 {}", version));
+    }
+    if let Some(doi) = &cff.doi {
+        lines.push(format!("%R {}", doi));
+    }
+    if let Some(url) = &cff.repository_code {
+        lines.push(format!("%U {}", url));
+    }
+    if let Some(abstract_text) = &cff.abstract_text {
+        lines.push(format!("%X {}", one_line(abstract_text)));
+    }
+    for keyword in &cff.keywords {
+        lines.push(format!("%K {}", keyword));
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+fn tag(name: &str, value: &str) -> String {
+    format!("{}  - {}", name, value)
+}
+
+/// `text` with its line breaks and runs of whitespace collapsed to single
+/// spaces: both formats end a field at the end of its line, so a wrapped
+/// abstract would lose all but its first line
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,187 @@
+use crate::metadata::citation::{CffAuthor, CitationCff};
+
+const MONTHS: &[&str] = &[
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// APA 7 reference for software:
+/// `Doe, J., & Smith, A. B. (2024). Title (Version 1.0.0) [Computer software]. https://doi.org/...`
+pub fn apa(cff: &CitationCff) -> String {
+    let names: Vec<String> = cff.authors.iter().map(apa_name).collect();
+    let authors = match names.as_slice() {
+        [] => String::new(),
+        [one] => one.clone(),
+        // APA lists up to 20 authors, then an ellipsis and the last one
+        [first @ .., last] if first.len() < 20 => format!("{}, & {}", first.join(", "), last),
+        [first @ .., last] => format!("{}, . . . {}", first[..19].join(", "), last),
+    };
+    let year = year(cff).unwrap_or("n.d.");
+
+    let mut out = if authors.is_empty() {
+        cff.title.clone()
+    } else {
+        format!("{} ({}). {}", sentence(&authors), year, cff.title)
+    };
+    if let Some(version) = &cff.version {
+        out.push_str(&format!(" (Version {})", version));
+    }
+    out.push_str(" [Computer software].");
+    if authors.is_empty() {
+        out.push_str(&format!(" ({}).", year));
+    }
+    if let Some(link) = link(cff) {
+        out.push_str(&format!(" {}", link));
+    }
+    out.push('\n');
+    out
+}
+
+/// Chicago (17th ed.) bibliography entry:
+/// `Doe, Jane, and Alan Smith. Title. Version 1.0.0. March 14, 2024. https://doi.org/...`
+pub fn chicago(cff: &CitationCff) -> String {
+    let names: Vec<String> = cff
+        .authors
+        .iter()
+        .enumerate()
+        .map(|(i, a)| match (i, &a.family_names, &a.given_names) {
+            // Only the first author is inverted
+            (0, _, _) => a.display_name(),
+            (_, Some(family), Some(given)) => format!("{} {}", given, family),
+            _ => a.display_name(),
+        })
+        .collect();
+    let authors = match names.as_slice() {
+        [] => String::new(),
+        [one] => one.clone(),
+        [a, b] => format!("{}, and {}", a, b),
+        // More than ten: the first seven, then et al.
+        all if all.len() > 10 => format!("{}, et al", all[..7].join(", ")),
+        [first @ .., last] => format!("{}, and {}", first.join(", "), last),
+    };
+
+    let mut parts = Vec::new();
+    if !authors.is_empty() {
+        parts.push(authors);
+    }
+    parts.push(cff.title.clone());
+    if let Some(version) = &cff.version {
+        parts.push(format!("Version {}", version));
+    }
+    if let Some(date) = long_date(cff) {
+        parts.push(date);
+    }
+    let mut out = parts
+        .iter()
+        .map(|p| sentence(p))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(link) = link(cff) {
+        out.push_str(&format!(" {}", link));
+    }
+    out.push('\n');
+    out
+}
+
+/// A CFF reference for the release, to paste under `references:` in
+/// another project's CITATION.cff
+pub fn cff_reference(cff: &CitationCff) -> String {
+    use serde_yaml::{Mapping, Value};
+    let mut entry = Mapping::new();
+    entry.insert("type".into(), "software".into());
+    entry.insert("title".into(), cff.title.as_str().into());
+    let authors: Vec<Value> = cff
+        .authors
+        .iter()
+        .map(|a| {
+            // Contact details belong in the cited project's file, not here
+            let author = CffAuthor {
+                email: None,
+                ..a.clone()
+            };
+            serde_yaml::to_value(author).unwrap_or(Value::Null)
+        })
+        .collect();
+    entry.insert("authors".into(), Value::Sequence(authors));
+    for (key, value) in [
+        ("version", &cff.version),
+        ("doi", &cff.doi),
+        ("date-released", &cff.date_released),
+        ("repository-code", &cff.repository_code),
+        ("license", &cff.license),
+    ] {
+        if let Some(value) = value {
+            entry.insert(key.into(), value.as_str().into());
+        }
+    }
+    serde_yaml::to_string(&Value::Sequence(vec![Value::Mapping(entry)])).unwrap_or_default()
+}
+
+/// `Doe, J. A.` for persons, the name for entities
+fn apa_name(author: &CffAuthor) -> String {
+    match (&author.family_names, &author.given_names) {
+        (Some(family), Some(given)) => {
+            let initials: Vec<String> = given
+                .split_whitespace()
+                .filter_map(|g| {
+                    // Hyphenated given names keep the hyphen: Jean-Paul → J.-P.
+                    let parts: Vec<String> = g
+                        .split('-')
+                        .filter_map(|p| p.chars().next())
+                        .map(|c| format!("{}.", c))
+                        .collect();
+                    (!parts.is_empty()).then(|| parts.join("-"))
+                })
+                .collect();
+            format!("{}, {}", family, initials.join(" "))
+        }
+        _ => author.display_name(),
+    }
+}
+
+fn year(cff: &CitationCff) -> Option<&str> {
+    cff.date_released.as_deref().and_then(|d| d.get(..4))
+}
+
+/// `March 14, 2024` (or just the year, if that is all there is)
+fn long_date(cff: &CitationCff) -> Option<String> {
+    let date = cff.date_released.as_deref()?;
+    let month = date
+        .get(5..7)
+        .and_then(|m| m.parse::<usize>().ok())
+        .and_then(|m| MONTHS.get(m.wrapping_sub(1)));
+    let day = date.get(8..10).and_then(|d| d.parse::<u32>().ok());
+    match (month, day) {
+        (Some(month), Some(day)) => Some(format!("{} {}, {}", month, day, year(cff)?)),
+        _ => year(cff).map(str::to_string),
+    }
+}
+
+/// The DOI as a URL, or else the repository
+fn link(cff: &CitationCff) -> Option<String> {
+    match &cff.doi {
+        Some(doi) if doi.starts_with("http") => Some(doi.clone()),
+        Some(doi) => Some(format!("https://doi.org/{}", doi)),
+        None => cff.repository_code.clone(),
+    }
+}
+
+/// End with a full stop, unless there already is one
+fn sentence(text: &str) -> String {
+    let text = text.trim();
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::config::SubjectsConfig;
+use crate::metadata::zenodo::ZenodoSubject;
+
+const FOS_SCHEME_URI: &str = "http://www.oecd.org/science/inno/38235147.pdf";
+
+/// OECD Fields of Science and Technology (FOS 2007): code, field name, extra keywords
+const FIELDS_OF_SCIENCE: &[(&str, &str, &[&str])] = &[
+    ("1.1", "Mathematics", &["statistics", "applied mathematics"]),
+    (
+        "1.2",
+        "Computer and information sciences",
+        &["computer science", "informatics", "machine learning"],
+    ),
+    (
+        "1.3",
+        "Physical sciences",
+        &["physics", "astronomy", "astrophysics"],
+    ),
+    ("1.4", "Chemical sciences", &["chemistry"]),
+    (
+        "1.5",
+        "Earth and related environmental sciences",
+        &["geoscience", "geology", "climate science", "meteorology"],
+    ),
+    (
+        "1.6",
+        "Biological sciences",
+        &["biology", "bioinformatics", "genomics", "ecology"],
+    ),
+    ("2.1", "Civil engineering", &[]),
+    (
+        "2.2",
+        "Electrical engineering, electronic engineering, information engineering",
+        &["electrical engineering"],
+    ),
+    ("2.3", "Mechanical engineering", &[]),
+    ("3.1", "Basic medicine", &[]),
+    ("3.2", "Clinical medicine", &["medicine"]),
+    ("3.3", "Health sciences", &["public health", "epidemiology"]),
+    (
+        "4.1",
+        "Agriculture, forestry, and fisheries",
+        &["agriculture"],
+    ),
+    ("5.1", "Psychology", &[]),
+    ("5.2", "Economics and business", &["economics"]),
+    ("5.3", "Educational sciences", &["education"]),
+    ("5.4", "Sociology", &[]),
+    ("5.5", "Law", &[]),
+    ("5.6", "Political science", &["political sciences"]),
+    (
+        "5.7",
+        "Social and economic geography",
+        &["geography", "human geography"],
+    ),
+    (
+        "5.8",
+        "Media and communications",
+        &["communication studies", "media studies"],
+    ),
+    (
+        "6.1",
+        "History and archaeology",
+        &["history", "archaeology"],
+    ),
+    (
+        "6.2",
+        "Languages and literature",
+        &["linguistics", "literature", "philology"],
+    ),
+    (
+        "6.3",
+        "Philosophy, ethics and religion",
+        &["philosophy", "ethics", "religious studies"],
+    ),
+    ("6.4", "Arts", &["art history", "musicology"]),
+];
+
+/// Split keywords into controlled-vocabulary subjects and the remaining free-text
+/// keywords. Config aliases win over the built-in Fields of Science table.
+pub fn map_keywords(
+    keywords: &[String],
+    config: Option<&SubjectsConfig>,
+) -> (Vec<String>, Vec<ZenodoSubject>) {
+    let config = match config {
+        Some(c) => c,
+        None => return (keywords.to_vec(), Vec::new()),
+    };
+
+    let mut remaining = Vec::new();
+    let mut subjects: Vec<ZenodoSubject> = Vec::new();
+    for keyword in keywords {
+        let subject = alias_subject(keyword, config).or_else(|| {
+            if config.fields_of_science {
+                fos_subject(keyword)
+            } else {
+                None
+            }
+        });
+        match subject {
+            Some(s) => {
+                if !subjects
+                    .iter()
+                    .any(|existing| existing.identifier == s.identifier)
+                {
+                    subjects.push(s);
+                }
+            }
+            None => remaining.push(keyword.clone()),
+        }
+    }
+    (remaining, subjects)
+}
+
+fn alias_subject(keyword: &str, config: &SubjectsConfig) -> Option<ZenodoSubject> {
+    config
+        .aliases
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(keyword.trim()))
+        .map(|(_, alias)| ZenodoSubject {
+            term: alias.term.clone(),
+            identifier: alias.identifier.clone(),
+            scheme: alias.scheme.clone(),
+        })
+}
+
+fn fos_subject(keyword: &str) -> Option<ZenodoSubject> {
+    let keyword = keyword.trim();
+    FIELDS_OF_SCIENCE
+        .iter()
+        .find(|(_, name, synonyms)| {
+            name.eq_ignore_ascii_case(keyword)
+                || synonyms.iter().any(|s| s.eq_ignore_ascii_case(keyword))
+        })
+        .map(|(code, name, _)| ZenodoSubject {
+            term: format!("FOS: {}", name),
+            identifier: format!("{}#{}", FOS_SCHEME_URI, code),
+            scheme: "url".to_string(),
+        })
+}
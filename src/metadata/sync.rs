@@ -0,0 +1,571 @@
+//! The fields CITATION.cff, codemeta.json and the package manifest have in
+//! common, and bringing the three into agreement.
+
+use crate::error::{IoError, ValidationError};
+use crate::metadata::codemeta::read_codemeta;
+use crate::metadata::manifest::Manifest;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A field kept the same across the metadata files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Title,
+    Version,
+    Authors,
+    License,
+    Repository,
+    Keywords,
+}
+
+impl Field {
+    /// Every field, in the order they are reported
+    pub const ALL: [Field; 6] = [
+        Field::Title,
+        Field::Version,
+        Field::Authors,
+        Field::License,
+        Field::Repository,
+        Field::Keywords,
+    ];
+
+    /// The field's name in messages and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Title => "title",
+            Field::Version => "version",
+            Field::Authors => "authors",
+            Field::License => "license",
+            Field::Repository => "repository",
+            Field::Keywords => "keywords",
+        }
+    }
+}
+
+/// An author as the three formats can all express it
+#[derive(Debug, Clone)]
+pub struct Person {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+impl Person {
+    /// From `Name <email>`, as in Cargo.toml and package.json
+    pub fn parse(s: &str) -> Person {
+        match s.split_once('<') {
+            Some((name, rest)) => Person {
+                name: name.trim().to_string(),
+                email: Some(rest.trim_end_matches('>').trim().to_string()),
+            },
+            None => Person {
+                name: s.trim().to_string(),
+                email: None,
+            },
+        }
+    }
+
+    /// Whether `name` is this person's, ignoring case
+    pub fn same_name(&self, name: &str) -> bool {
+        self.name.trim().eq_ignore_ascii_case(name.trim())
+    }
+}
+
+impl fmt::Display for Person {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.email {
+            Some(email) => write!(f, "{} <{}>", self.name, email),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A field's value in one file
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+    Text(String),
+    List(Vec<String>),
+    People(Vec<Person>),
+}
+
+/// Authors are compared by name: not every format records email addresses
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FieldValue::Text(a), FieldValue::Text(b)) => a == b,
+            (FieldValue::List(a), FieldValue::List(b)) => a == b,
+            (FieldValue::People(a), FieldValue::People(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.same_name(&b.name))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldValue::Text(s) => write!(f, "{}", s),
+            FieldValue::List(items) => write!(f, "{}", items.join(", ")),
+            FieldValue::People(people) => {
+                let names: Vec<&str> = people.iter().map(|p| p.name.as_str()).collect();
+                write!(f, "{}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl FieldValue {
+    /// The value, if it is a single string
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            FieldValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The values, if it is a list; empty otherwise
+    pub fn as_list(&self) -> &[String] {
+        match self {
+            FieldValue::List(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// The people, if it is a list of people; empty otherwise
+    pub fn as_people(&self) -> &[Person] {
+        match self {
+            FieldValue::People(people) => people,
+            _ => &[],
+        }
+    }
+}
+
+/// The shared fields one file has
+pub type SharedFields = BTreeMap<Field, FieldValue>;
+
+/// Where a shared field can live
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Citation,
+    CodeMeta,
+    Manifest(Manifest),
+}
+
+impl Source {
+    /// The file's name in the project directory
+    pub fn file_name(&self) -> &str {
+        match self {
+            Source::Citation => "CITATION.cff",
+            Source::CodeMeta => "codemeta.json",
+            Source::Manifest(manifest) => manifest.file_name(),
+        }
+    }
+
+    fn path(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(self.file_name())
+    }
+
+    /// Package names are identifiers, not titles, so manifests are left out
+    /// of title syncing, as are fields they inherit or compute
+    fn supports(&self, project_dir: &Path, field: Field) -> bool {
+        match self {
+            Source::Manifest(manifest) => manifest.can_set(project_dir, field),
+            _ => true,
+        }
+    }
+
+    /// The shared fields the file records
+    pub fn read(&self, project_dir: &Path) -> Result<SharedFields, ValidationError> {
+        match self {
+            Source::Citation => read_citation(&self.path(project_dir)),
+            Source::CodeMeta => Ok(codemeta_fields(&read_codemeta(&self.path(project_dir))?)),
+            Source::Manifest(manifest) => manifest.read(project_dir),
+        }
+    }
+
+    fn write(
+        &self,
+        project_dir: &Path,
+        changes: &[(Field, &FieldValue)],
+    ) -> Result<(), ValidationError> {
+        match self {
+            Source::Citation => write_citation(&self.path(project_dir), changes),
+            Source::CodeMeta => write_codemeta(&self.path(project_dir), changes),
+            Source::Manifest(manifest) => manifest.write(project_dir, changes),
+        }
+    }
+}
+
+/// The metadata files present in a project, CITATION.cff first
+pub fn sources(project_dir: &Path) -> Vec<Source> {
+    let mut sources = Vec::new();
+    if project_dir.join("CITATION.cff").exists() {
+        sources.push(Source::Citation);
+    }
+    if project_dir.join("codemeta.json").exists() {
+        sources.push(Source::CodeMeta);
+    }
+    if let Some(manifest) = Manifest::detect(project_dir) {
+        sources.push(Source::Manifest(manifest));
+    }
+    sources
+}
+
+/// A field that has to change in one file to agree with the others
+#[derive(Debug)]
+pub struct Change {
+    pub source: Source,
+    pub field: Field,
+    pub old: Option<FieldValue>,
+    pub new: FieldValue,
+    /// Items of the value the file cannot hold, left out of `new`
+    pub dropped: Vec<String>,
+}
+
+/// The changes that make every file agree. Each field is taken from
+/// `preferred` when it has it, otherwise from the first file that does.
+pub fn plan(
+    project_dir: &Path,
+    preferred: impl Fn(&Source) -> bool,
+) -> Result<Vec<Change>, ValidationError> {
+    let mut docs = Vec::new();
+    for source in sources(project_dir) {
+        let fields = source.read(project_dir)?;
+        docs.push((source, fields));
+    }
+    docs.sort_by_key(|(source, _)| !preferred(source));
+
+    let mut changes = Vec::new();
+    for field in Field::ALL {
+        let Some(value) = docs
+            .iter()
+            .filter(|(source, _)| source.supports(project_dir, field))
+            .find_map(|(_, fields)| fields.get(&field))
+        else {
+            continue;
+        };
+        let value = &with_known_emails(value, &docs);
+        for (source, fields) in &docs {
+            let old = fields.get(&field);
+            let (new, dropped) = match source {
+                Source::Manifest(manifest) => manifest.fit(field, value),
+                _ => (value.clone(), Vec::new()),
+            };
+            if source.supports(project_dir, field) && old != Some(&new) {
+                changes.push(Change {
+                    source: source.clone(),
+                    field,
+                    old: old.cloned(),
+                    new,
+                    dropped,
+                });
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Authors with the email addresses other files know for them filled in,
+/// so syncing from a file without emails does not drop them elsewhere
+fn with_known_emails(value: &FieldValue, docs: &[(Source, SharedFields)]) -> FieldValue {
+    let FieldValue::People(people) = value else {
+        return value.clone();
+    };
+    let known = docs
+        .iter()
+        .filter_map(|(_, fields)| fields.get(&Field::Authors))
+        .flat_map(FieldValue::as_people);
+    let people = people
+        .iter()
+        .map(|person| Person {
+            name: person.name.clone(),
+            email: person.email.clone().or_else(|| {
+                known
+                    .clone()
+                    .find(|p| p.email.is_some() && p.same_name(&person.name))
+                    .and_then(|p| p.email.clone())
+            }),
+        })
+        .collect();
+    FieldValue::People(people)
+}
+
+/// Write planned changes, one file at a time
+pub fn apply(project_dir: &Path, changes: &[Change]) -> Result<(), ValidationError> {
+    let mut by_source: Vec<(&Source, Vec<(Field, &FieldValue)>)> = Vec::new();
+    for change in changes {
+        match by_source.iter_mut().find(|(s, _)| *s == &change.source) {
+            Some((_, fields)) => fields.push((change.field, &change.new)),
+            None => by_source.push((&change.source, vec![(change.field, &change.new)])),
+        }
+    }
+    for (source, fields) in by_source {
+        source.write(project_dir, &fields)?;
+    }
+    Ok(())
+}
+
+// CITATION.cff
+
+fn read_citation(path: &Path) -> Result<SharedFields, ValidationError> {
+    let content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+    let doc: serde_yaml::Value =
+        serde_yaml::from_str(&content).map_err(ValidationError::Citation)?;
+    let text = |key: &str| match doc.get(key)? {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+
+    let mut fields = SharedFields::new();
+    let mut set_text = |field, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            fields.insert(field, FieldValue::Text(value));
+        }
+    };
+    set_text(Field::Title, text("title"));
+    set_text(Field::Version, text("version"));
+    set_text(Field::License, text("license"));
+    set_text(Field::Repository, text("repository-code"));
+
+    if let Some(authors) = doc.get("authors").and_then(|v| v.as_sequence()) {
+        let people: Vec<Person> = authors.iter().filter_map(cff_person).collect();
+        if !people.is_empty() {
+            fields.insert(Field::Authors, FieldValue::People(people));
+        }
+    }
+    if let Some(keywords) = doc.get("keywords").and_then(|v| v.as_sequence()) {
+        let keywords: Vec<String> = keywords
+            .iter()
+            .filter_map(|k| k.as_str().map(str::to_string))
+            .collect();
+        if !keywords.is_empty() {
+            fields.insert(Field::Keywords, FieldValue::List(keywords));
+        }
+    }
+    Ok(fields)
+}
+
+fn cff_person(author: &serde_yaml::Value) -> Option<Person> {
+    let get = |key| author.get(key).and_then(|v| v.as_str());
+    let name = match (get("given-names"), get("family-names"), get("name")) {
+        (Some(given), Some(family), _) => format!("{} {}", given, family),
+        (None, Some(family), _) => family.to_string(),
+        (_, None, Some(name)) => name.to_string(),
+        _ => return None,
+    };
+    Some(Person {
+        name,
+        email: get("email").map(str::to_string),
+    })
+}
+
+/// Rewrites just the changed top-level keys, so the rest of the file keeps
+/// its comments and layout. Existing author entries are kept as they are
+/// (ORCIDs, affiliations); new authors get their names split.
+fn write_citation(path: &Path, changes: &[(Field, &FieldValue)]) -> Result<(), ValidationError> {
+    let mut content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+    let doc: serde_yaml::Value =
+        serde_yaml::from_str(&content).map_err(ValidationError::Citation)?;
+
+    for (field, value) in changes {
+        let (key, yaml) = match field {
+            Field::Title => ("title", text_yaml(value)),
+            Field::Version => ("version", text_yaml(value)),
+            Field::License => ("license", text_yaml(value)),
+            Field::Repository => ("repository-code", text_yaml(value)),
+            Field::Keywords => (
+                "keywords",
+                serde_yaml::Value::Sequence(
+                    value.as_list().iter().map(|k| k.as_str().into()).collect(),
+                ),
+            ),
+            Field::Authors => {
+                let existing = doc
+                    .get("authors")
+                    .and_then(|v| v.as_sequence())
+                    .cloned()
+                    .unwrap_or_default();
+                let authors = value
+                    .as_people()
+                    .iter()
+                    .map(|person| {
+                        existing
+                            .iter()
+                            .find(|a| cff_person(a).is_some_and(|p| p.same_name(&person.name)))
+                            .cloned()
+                            .unwrap_or_else(|| new_cff_author(person))
+                    })
+                    .collect();
+                ("authors", serde_yaml::Value::Sequence(authors))
+            }
+        };
+        let mut entry = serde_yaml::Mapping::new();
+        entry.insert(key.into(), yaml);
+        let snippet = serde_yaml::to_string(&entry).map_err(ValidationError::CitationWrite)?;
+        content = replace_top_level(&content, key, &snippet);
+    }
+    std::fs::write(path, content).map_err(IoError::on("write", path))?;
+    Ok(())
+}
+
+fn text_yaml(value: &FieldValue) -> serde_yaml::Value {
+    value.as_text().unwrap_or_default().into()
+}
+
+fn new_cff_author(person: &Person) -> serde_yaml::Value {
+    let mut author = serde_yaml::Mapping::new();
+    match person.name.rsplit_once(' ') {
+        Some((given, family)) => {
+            author.insert("family-names".into(), family.into());
+            author.insert("given-names".into(), given.into());
+        }
+        None => {
+            author.insert("name".into(), person.name.as_str().into());
+        }
+    }
+    if let Some(email) = &person.email {
+        author.insert("email".into(), email.as_str().into());
+    }
+    serde_yaml::Value::Mapping(author)
+}
+
+/// Swap the block for a top-level YAML key (its line and the indented or
+/// `- ` lines after it) for `snippet`, or append `snippet` if the key is new
+pub(crate) fn replace_top_level(content: &str, key: &str, snippet: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let prefix = format!("{}:", key);
+    let Some(start) = lines.iter().position(|l| l.starts_with(&prefix)) else {
+        let mut content = content.to_string();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        return content + snippet;
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| !(l.starts_with(' ') || l.starts_with('\t') || l.starts_with('-')))
+        .map_or(lines.len(), |i| start + 1 + i);
+
+    let mut out: Vec<&str> = lines[..start].to_vec();
+    out.extend(snippet.lines());
+    out.extend(&lines[end..]);
+    out.join("\n") + "\n"
+}
+
+// codemeta.json
+
+const SPDX_URL: &str = "https://spdx.org/licenses/";
+
+fn codemeta_fields(meta: &serde_json::Map<String, serde_json::Value>) -> SharedFields {
+    let mut fields = SharedFields::new();
+    let text = |key: &str| {
+        meta.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(name) = text("name") {
+        fields.insert(Field::Title, FieldValue::Text(name));
+    }
+    if let Some(version) = text("version").or_else(|| text("softwareVersion")) {
+        fields.insert(Field::Version, FieldValue::Text(version));
+    }
+    if let Some(license) = text("license") {
+        let id = license.trim_start_matches(SPDX_URL).to_string();
+        fields.insert(Field::License, FieldValue::Text(id));
+    }
+    if let Some(repo) = text("codeRepository") {
+        fields.insert(Field::Repository, FieldValue::Text(repo));
+    }
+
+    let keywords: Vec<String> = match meta.get("keywords") {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|k| k.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_json::Value::String(s)) => s.split(',').map(|k| k.trim().to_string()).collect(),
+        _ => Vec::new(),
+    };
+    if !keywords.is_empty() {
+        fields.insert(Field::Keywords, FieldValue::List(keywords));
+    }
+
+    let authors: Vec<Person> = match meta.get("author") {
+        Some(serde_json::Value::Array(items)) => items.iter().filter_map(codemeta_person).collect(),
+        Some(author) => codemeta_person(author).into_iter().collect(),
+        None => Vec::new(),
+    };
+    if !authors.is_empty() {
+        fields.insert(Field::Authors, FieldValue::People(authors));
+    }
+    fields
+}
+
+fn codemeta_person(author: &serde_json::Value) -> Option<Person> {
+    let get = |key| author.get(key).and_then(|v| v.as_str());
+    let name = match (get("givenName"), get("familyName"), get("name")) {
+        (Some(given), Some(family), _) => format!("{} {}", given, family),
+        (None, Some(family), _) => family.to_string(),
+        (_, None, Some(name)) => name.to_string(),
+        _ => return None,
+    };
+    Some(Person {
+        name,
+        email: get("email").map(str::to_string),
+    })
+}
+
+fn write_codemeta(path: &Path, changes: &[(Field, &FieldValue)]) -> Result<(), ValidationError> {
+    let mut meta = read_codemeta(path)?;
+    for (field, value) in changes {
+        let text = value.as_text().unwrap_or_default();
+        let (key, json) = match field {
+            Field::Title => ("name", json!(text)),
+            Field::Version => ("version", json!(text)),
+            Field::License => ("license", json!(format!("{}{}", SPDX_URL, text))),
+            Field::Repository => ("codeRepository", json!(text)),
+            Field::Keywords => ("keywords", json!(value.as_list())),
+            Field::Authors => {
+                let existing: Vec<serde_json::Value> = match meta.get("author") {
+                    Some(serde_json::Value::Array(items)) => items.clone(),
+                    Some(author) => vec![author.clone()],
+                    None => Vec::new(),
+                };
+                let authors: Vec<serde_json::Value> = value
+                    .as_people()
+                    .iter()
+                    .map(|person| {
+                        existing
+                            .iter()
+                            .find(|a| codemeta_person(a).is_some_and(|p| p.same_name(&person.name)))
+                            .cloned()
+                            .unwrap_or_else(|| new_codemeta_author(person))
+                    })
+                    .collect();
+                ("author", json!(authors))
+            }
+        };
+        meta.insert(key.to_string(), json);
+    }
+    let json = serde_json::to_string_pretty(&meta).map_err(ValidationError::Serialize)?;
+    std::fs::write(path, json + "\n").map_err(IoError::on("write", path))?;
+    Ok(())
+}
+
+fn new_codemeta_author(person: &Person) -> serde_json::Value {
+    let mut author = match person.name.rsplit_once(' ') {
+        Some((given, family)) => json!({
+            "@type": "Person",
+            "givenName": given,
+            "familyName": family,
+        }),
+        None => json!({ "@type": "Person", "name": person.name }),
+    };
+    if let Some(email) = &person.email {
+        author["email"] = json!(email);
+    }
+    author
+}
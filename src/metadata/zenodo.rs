@@ -1,20 +1,28 @@
 use crate::config::Config;
-use crate::metadata::citation::CitationCff;
-use serde::Serialize;
+use crate::error::{IoError, ValidationError};
+use crate::metadata::citation::{CffReference, CitationCff};
+use crate::metadata::subjects;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
 
+/// The body of a deposit metadata request
 #[derive(Debug, Serialize)]
 pub struct ZenodoDeposit {
     pub metadata: ZenodoMetadata,
 }
 
-#[derive(Debug, Serialize)]
+/// Zenodo deposit metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZenodoMetadata {
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub creators: Vec<ZenodoCreator>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subjects: Vec<ZenodoSubject>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -24,11 +32,15 @@ pub struct ZenodoMetadata {
     pub upload_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub related_identifiers: Vec<ZenodoRelatedIdentifier>,
+    /// Fields from .zenodo.json that CITATION.cff has no equivalent for
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+/// A creator (author)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZenodoCreator {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,15 +49,26 @@ pub struct ZenodoCreator {
     pub affiliation: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// Controlled-vocabulary subject (e.g. a Fields of Science or MeSH term)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZenodoSubject {
+    pub term: String,
+    pub identifier: String,
+    pub scheme: String,
+}
+
+/// A related identifier, such as the repository or the paper
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZenodoRelatedIdentifier {
     pub identifier: String,
     pub relation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resource_type: Option<String>,
     pub scheme: String,
 }
 
 impl ZenodoDeposit {
+    /// The deposit for `cff`, with the `[zenodo]` settings from `config`
     pub fn from_citation(cff: &CitationCff, config: &Config) -> Self {
         let creators = cff
             .authors
@@ -57,7 +80,7 @@ impl ZenodoDeposit {
                         .to_string()
                 });
                 ZenodoCreator {
-                    name: format!("{}, {}", a.family_names, a.given_names),
+                    name: a.display_name(),
                     orcid,
                     affiliation: a.affiliation.clone(),
                 }
@@ -75,23 +98,338 @@ impl ZenodoDeposit {
             });
         }
 
+        // The paper describing the software
+        if let Some(paper) = &cff.preferred_citation {
+            if let Some(related) = reference_identifier(paper, "isDescribedBy") {
+                related_identifiers.push(related);
+            }
+        }
+
+        // Works this release builds on: datasets it derives from, papers it cites
+        for reference in &cff.references {
+            let relation = match reference.ref_type.as_str() {
+                "data" | "database" => "isDerivedFrom",
+                _ => "cites",
+            };
+            if let Some(related) = reference_identifier(reference, relation) {
+                related_identifiers.push(related);
+            }
+        }
+
+        let (keywords, subjects) = subjects::map_keywords(&cff.keywords, config.subjects.as_ref());
+
+        // Fields CFF has no place for, from the `[zenodo]` config
+        let zenodo = config.zenodo();
+        for related in &zenodo.related_identifiers {
+            related_identifiers.push(ZenodoRelatedIdentifier {
+                identifier: related.identifier.clone(),
+                relation: related.relation.clone(),
+                resource_type: related.resource_type.clone(),
+                scheme: related
+                    .scheme
+                    .clone()
+                    .unwrap_or_else(|| guess_scheme(&related.identifier).to_string()),
+            });
+        }
+        let mut extra = serde_json::Map::new();
+        let communities = config.communities();
+        if !communities.is_empty() {
+            let communities = communities
+                .iter()
+                .map(|c| json!({ "identifier": c }))
+                .collect();
+            extra.insert("communities".to_string(), Value::Array(communities));
+        }
+        if !zenodo.grants.is_empty() {
+            let grants = zenodo.grants.iter().map(|g| json!({ "id": g })).collect();
+            extra.insert("grants".to_string(), Value::Array(grants));
+        }
+        for (field, value) in [
+            ("notes", zenodo.notes),
+            ("access_right", zenodo.access_right),
+            ("embargo_date", zenodo.embargo_date),
+            ("access_conditions", zenodo.access_conditions),
+        ] {
+            if let Some(value) = value {
+                extra.insert(field.to_string(), Value::String(value));
+            }
+        }
+
         ZenodoDeposit {
             metadata: ZenodoMetadata {
                 title: cff.title.clone(),
                 description: cff.abstract_text.clone(),
                 creators,
-                keywords: cff.keywords.clone(),
+                keywords,
+                subjects,
                 license: cff.license.clone(),
                 version: cff.version.clone(),
                 publication_date: cff.date_released.clone(),
-                upload_type: "software".to_string(),
+                upload_type: zenodo.upload_type.unwrap_or_else(|| "software".to_string()),
                 language: Some(config.language.clone()),
                 related_identifiers,
+                extra,
             },
         }
     }
 
+    /// Overlay an existing `.zenodo.json` (GitHub–Zenodo integration format).
+    /// Top-level keys present in .zenodo.json take priority over CITATION.cff.
+    pub fn merge_zenodo_json(&mut self, project_dir: &Path) -> Result<(), ValidationError> {
+        let path = project_dir.join(".zenodo.json");
+        if !path.exists() {
+            return Ok(());
+        }
+        let overrides = read_zenodo_json(&path)?;
+
+        let mut merged =
+            serde_json::to_value(&self.metadata).map_err(ValidationError::Serialize)?;
+        if let Some(fields) = merged.as_object_mut() {
+            fields.extend(overrides);
+        }
+        self.metadata =
+            serde_json::from_value(merged).map_err(ValidationError::InvalidZenodoJson)?;
+        Ok(())
+    }
+
+    /// The deposit as JSON, as sent
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or_default()
     }
+
+    /// Render the metadata in `.zenodo.json` form. Version and publication
+    /// date are left out so the file does not go stale between releases.
+    pub fn to_zenodo_json(&self) -> String {
+        let mut metadata = self.metadata.clone();
+        metadata.version = None;
+        metadata.publication_date = None;
+        serde_json::to_string_pretty(&metadata).unwrap_or_default()
+    }
+}
+
+const UPLOAD_TYPES: &[&str] = &[
+    "publication",
+    "poster",
+    "presentation",
+    "dataset",
+    "image",
+    "video",
+    "software",
+    "lesson",
+    "physicalobject",
+    "other",
+];
+
+const ACCESS_RIGHTS: &[&str] = &["open", "embargoed", "restricted", "closed"];
+
+const RELATIONS: &[&str] = &[
+    "isCitedBy",
+    "cites",
+    "isSupplementTo",
+    "isSupplementedBy",
+    "isContinuedBy",
+    "continues",
+    "isDescribedBy",
+    "describes",
+    "hasMetadata",
+    "isMetadataFor",
+    "isNewVersionOf",
+    "isPreviousVersionOf",
+    "isPartOf",
+    "hasPart",
+    "isReferencedBy",
+    "references",
+    "isDocumentedBy",
+    "documents",
+    "isCompiledBy",
+    "compiles",
+    "isVariantFormOf",
+    "isOriginalFormOf",
+    "isIdenticalTo",
+    "isAlternateIdentifier",
+    "isReviewedBy",
+    "reviews",
+    "isDerivedFrom",
+    "isSourceOf",
+    "requires",
+    "isRequiredBy",
+    "isObsoletedBy",
+    "obsoletes",
+];
+
+impl ZenodoMetadata {
+    /// Check the fields against the Zenodo deposit API's constraints.
+    /// Returns `(field, problem)` pairs, using Zenodo's field paths.
+    pub fn validate(&self) -> Vec<(String, String)> {
+        let mut errors = Vec::new();
+        let mut error = |field: String, message: &str| errors.push((field, message.to_string()));
+
+        if self.title.trim().is_empty() {
+            error("metadata.title".into(), "required");
+        }
+        if self
+            .description
+            .as_deref()
+            .is_none_or(|d| d.trim().is_empty())
+        {
+            error(
+                "metadata.description".into(),
+                "required (set `abstract` in CITATION.cff)",
+            );
+        }
+        if !UPLOAD_TYPES.contains(&self.upload_type.as_str()) {
+            error(
+                "metadata.upload_type".into(),
+                &format!("'{}' is not a Zenodo upload type", self.upload_type),
+            );
+        }
+
+        if self.creators.is_empty() {
+            error(
+                "metadata.creators".into(),
+                "at least one creator is required",
+            );
+        }
+        let orcid_re = regex::Regex::new(r"^\d{4}-\d{4}-\d{4}-\d{3}[\dX]$").unwrap();
+        for (i, creator) in self.creators.iter().enumerate() {
+            if creator.name.trim().is_empty() {
+                error(format!("metadata.creators.{}.name", i), "required");
+            }
+            if let Some(orcid) = &creator.orcid {
+                if !orcid_re.is_match(orcid) {
+                    error(
+                        format!("metadata.creators.{}.orcid", i),
+                        &format!("'{}' is not a valid ORCID", orcid),
+                    );
+                }
+            }
+        }
+
+        if let Some(date) = &self.publication_date {
+            let date_re = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+            if !date_re.is_match(date) {
+                error(
+                    "metadata.publication_date".into(),
+                    &format!("'{}' is not an ISO 8601 date (YYYY-MM-DD)", date),
+                );
+            }
+        }
+
+        if let Some(language) = &self.language {
+            if language.len() != 3 || !language.chars().all(|c| c.is_ascii_lowercase()) {
+                error(
+                    "metadata.language".into(),
+                    &format!("'{}' is not an ISO 639-2/3 code", language),
+                );
+            }
+        }
+
+        let access_right = self
+            .extra
+            .get("access_right")
+            .and_then(|v| v.as_str())
+            .unwrap_or("open");
+        if !ACCESS_RIGHTS.contains(&access_right) {
+            error(
+                "metadata.access_right".into(),
+                &format!(
+                    "'{}' is not one of {}",
+                    access_right,
+                    ACCESS_RIGHTS.join(", ")
+                ),
+            );
+        }
+        if matches!(access_right, "open" | "embargoed") && self.license.is_none() {
+            error(
+                "metadata.license".into(),
+                "required for open and embargoed access",
+            );
+        }
+        if access_right == "embargoed" && !self.extra.contains_key("embargo_date") {
+            error(
+                "metadata.embargo_date".into(),
+                "required for embargoed access",
+            );
+        }
+
+        for (i, keyword) in self.keywords.iter().enumerate() {
+            if keyword.trim().is_empty() {
+                error(format!("metadata.keywords.{}", i), "must not be empty");
+            }
+        }
+        for (i, related) in self.related_identifiers.iter().enumerate() {
+            if related.identifier.trim().is_empty() {
+                error(
+                    format!("metadata.related_identifiers.{}.identifier", i),
+                    "required",
+                );
+            }
+            if !RELATIONS.contains(&related.relation.as_str()) {
+                error(
+                    format!("metadata.related_identifiers.{}.relation", i),
+                    &format!("'{}' is not a Zenodo relation type", related.relation),
+                );
+            }
+        }
+
+        errors
+    }
+}
+
+/// Build a related identifier for a CFF reference, preferring its DOI over its URL
+fn reference_identifier(
+    reference: &CffReference,
+    relation: &str,
+) -> Option<ZenodoRelatedIdentifier> {
+    let (identifier, scheme) = match (&reference.doi, &reference.url) {
+        (Some(doi), _) => (doi.clone(), "doi"),
+        (None, Some(url)) => (url.clone(), "url"),
+        (None, None) => return None,
+    };
+    Some(ZenodoRelatedIdentifier {
+        identifier,
+        relation: relation.to_string(),
+        resource_type: Some(zenodo_resource_type(&reference.ref_type).to_string()),
+        scheme: scheme.to_string(),
+    })
+}
+
+/// The identifier scheme Zenodo expects for a configured related identifier
+fn guess_scheme(identifier: &str) -> &'static str {
+    let lower = identifier.to_lowercase();
+    if lower.starts_with("10.") || lower.contains("doi.org/") {
+        "doi"
+    } else if lower.starts_with("arxiv:") {
+        "arxiv"
+    } else if lower.starts_with("swh:") {
+        "swh"
+    } else {
+        "url"
+    }
+}
+
+/// Map a CFF reference type onto Zenodo's resource_type vocabulary
+fn zenodo_resource_type(cff_type: &str) -> &'static str {
+    match cff_type {
+        "article" | "magazine-article" | "newspaper-article" => "publication-article",
+        "conference-paper" | "proceedings" => "publication-conferencepaper",
+        "book" => "publication-book",
+        "thesis" => "publication-thesis",
+        "report" => "publication-report",
+        "software" | "software-code" | "software-container" | "software-executable" => "software",
+        "data" | "database" => "dataset",
+        _ => "publication",
+    }
+}
+
+/// Read `.zenodo.json` as a JSON object of metadata fields
+pub fn read_zenodo_json(
+    path: &Path,
+) -> Result<serde_json::Map<String, serde_json::Value>, ValidationError> {
+    let content = std::fs::read_to_string(path).map_err(IoError::on("read", path))?;
+    match serde_json::from_str(&content) {
+        Ok(serde_json::Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(ValidationError::ZenodoJsonNotObject),
+        Err(e) => Err(ValidationError::ZenodoJson(e)),
+    }
 }
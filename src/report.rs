@@ -1,5 +1,8 @@
 use colored::Colorize;
+use std::cmp::Reverse;
+use std::time::Duration;
 
+/// Whether a check passed, failed or warned
 #[derive(Debug, Clone)]
 pub enum Status {
     Pass,
@@ -7,50 +10,99 @@ pub enum Status {
     Warn,
 }
 
+/// One check's result
 #[derive(Debug, Clone)]
 pub struct CheckResult {
     pub category: String,
     pub message: String,
     pub status: Status,
+    /// Which check this is, e.g. `large-file`, for results whose message
+    /// holds details that change between runs
+    pub rule: Option<&'static str>,
+    /// What the check is about, e.g. the large file's path
+    pub subject: Option<String>,
 }
 
+/// The results of a check run, in the order they were added
+#[derive(Default)]
 pub struct Report {
     pub results: Vec<CheckResult>,
+    /// How long each group of checks took, in the order they ran
+    pub timings: Vec<(&'static str, Duration)>,
 }
 
 impl Report {
+    /// An empty report
     pub fn new() -> Self {
         Report {
             results: Vec::new(),
+            timings: Vec::new(),
         }
     }
 
+    /// Add a result
     pub fn add(&mut self, category: &str, message: &str, status: Status) {
         self.results.push(CheckResult {
             category: category.to_string(),
             message: message.to_string(),
             status,
+            rule: None,
+            subject: None,
         });
     }
 
+    /// Add a result of `rule` about `subject` (empty if the rule is about the
+    /// project as a whole); a baseline knows it by these, not by its message
+    pub fn add_on(
+        &mut self,
+        category: &str,
+        rule: &'static str,
+        subject: &str,
+        message: &str,
+        status: Status,
+    ) {
+        self.results.push(CheckResult {
+            category: category.to_string(),
+            message: message.to_string(),
+            status,
+            rule: Some(rule),
+            subject: Some(subject.to_string()).filter(|s| !s.is_empty()),
+        });
+    }
+
+    /// Add a passed check
     pub fn pass(&mut self, category: &str, message: &str) {
         self.add(category, message, Status::Pass);
     }
 
+    /// Add a failed check
     pub fn fail(&mut self, category: &str, message: &str) {
         self.add(category, message, Status::Fail);
     }
 
+    /// Add a warning
     pub fn warn(&mut self, category: &str, message: &str) {
         self.add(category, message, Status::Warn);
     }
 
+    /// Add a failure of `rule` about `subject`
+    pub fn fail_on(&mut self, category: &str, rule: &'static str, subject: &str, message: &str) {
+        self.add_on(category, rule, subject, message, Status::Fail);
+    }
+
+    /// Add a warning of `rule` about `subject`
+    pub fn warn_on(&mut self, category: &str, rule: &'static str, subject: &str, message: &str) {
+        self.add_on(category, rule, subject, message, Status::Warn);
+    }
+
+    /// Whether any check failed
     pub fn has_failures(&self) -> bool {
         self.results
             .iter()
             .any(|r| matches!(r.status, Status::Fail))
     }
 
+    /// Print the results to stdout, grouped by category
     pub fn print(&self) {
         println!("\n{}", "═══ Release Scholar Report ═══".bold());
         println!();
@@ -104,4 +156,34 @@ impl Report {
         }
         println!();
     }
+
+    /// How long each group of checks took, slowest first, with its share of
+    /// the total
+    pub fn print_timings(&self) {
+        let total: Duration = self.timings.iter().map(|(_, took)| *took).sum();
+        let mut timings = self.timings.clone();
+        timings.sort_by_key(|(_, took)| Reverse(*took));
+
+        println!("{}", "═══ Timings ═══".bold());
+        println!();
+        for (name, took) in &timings {
+            let share = if total.is_zero() {
+                0.0
+            } else {
+                took.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+            println!(
+                "  {:<16} {:>8.1} ms  {}",
+                name,
+                took.as_secs_f64() * 1000.0,
+                format!("{:>3.0}%", share).dimmed()
+            );
+        }
+        println!(
+            "  {:<16} {:>8.1} ms",
+            "total".bold(),
+            total.as_secs_f64() * 1000.0
+        );
+        println!();
+    }
 }
@@ -0,0 +1,132 @@
+use crate::config::{Config, RuleSetConfig, Severity};
+use crate::error::ConfigError;
+use crate::report::{Report, Status};
+use std::collections::BTreeMap;
+
+/// Rule sets every installation has; a `[rules.NAME]` table of the same
+/// name replaces one
+pub const BUILT_IN: &[&str] = &["default", "strict", "institutional"];
+
+/// What an institution answers for once the release is public: how it is
+/// cited and licensed, what it leaks, whether it can be rebuilt and tested
+const INSTITUTIONAL: &[&str] = &[
+    "Files",
+    "Citation",
+    "Data",
+    "Version",
+    "README",
+    "Security",
+    "Reproducibility",
+    "Tests",
+    "CI",
+    "Identifiers",
+    "Hosting",
+];
+
+/// A rule set with its `extends` chain resolved
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub name: String,
+    severity: Option<Severity>,
+    categories: BTreeMap<String, Severity>,
+}
+
+impl RuleSet {
+    /// The rule set `name`, from the config or else built in
+    pub fn resolve(config: &Config, name: &str) -> Result<RuleSet, ConfigError> {
+        let mut chain = Vec::new();
+        let (severity, categories) = resolve(config, name, &mut chain)?;
+        Ok(RuleSet {
+            name: name.to_string(),
+            severity,
+            categories,
+        })
+    }
+
+    /// Every rule set `name` could be: the built-in ones and the configured ones
+    pub fn available(config: &Config) -> Vec<String> {
+        let mut names: Vec<String> = BUILT_IN.iter().map(|n| n.to_string()).collect();
+        for name in config.rules.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// How much `category` counts: its own setting, else the rule set's
+    pub fn severity(&self, category: &str) -> Option<Severity> {
+        self.categories
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(category))
+            .map(|(_, s)| *s)
+            .or(self.severity)
+    }
+
+    /// Drop the results of categories that are off, and move the others to
+    /// the severity the rule set gives them
+    pub fn apply(&self, report: &mut Report) {
+        report.results.retain_mut(|result| {
+            match (self.severity(&result.category), &result.status) {
+                (Some(Severity::Off), _) => return false,
+                (Some(Severity::Warn), Status::Fail) => result.status = Status::Warn,
+                (Some(Severity::Fail), Status::Warn) => result.status = Status::Fail,
+                _ => {}
+            }
+            true
+        });
+    }
+}
+
+fn built_in(name: &str) -> Option<RuleSetConfig> {
+    match name {
+        "default" => Some(RuleSetConfig::default()),
+        "strict" => Some(RuleSetConfig {
+            severity: Some(Severity::Fail),
+            ..RuleSetConfig::default()
+        }),
+        "institutional" => Some(RuleSetConfig {
+            categories: INSTITUTIONAL
+                .iter()
+                .map(|c| (c.to_string(), Severity::Fail))
+                .collect(),
+            ..RuleSetConfig::default()
+        }),
+        _ => None,
+    }
+}
+
+type Resolved = (Option<Severity>, BTreeMap<String, Severity>);
+
+/// A rule set's own settings over those of what it extends. `chain` is the
+/// names on the way here, to catch cycles.
+fn resolve(config: &Config, name: &str, chain: &mut Vec<String>) -> Result<Resolved, ConfigError> {
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_string());
+        return Err(ConfigError::RuleSetCycle {
+            chain: chain.join(" -> "),
+        });
+    }
+    chain.push(name.to_string());
+    let rules = config
+        .rules
+        .get(name)
+        .cloned()
+        .or_else(|| built_in(name))
+        .ok_or_else(|| ConfigError::UnknownRuleSet {
+            name: name.to_string(),
+            available: RuleSet::available(config).join(", "),
+        })?;
+
+    let (mut severity, mut categories) = match (&rules.extends, built_in(name)) {
+        // A configured rule set adjusting the built-in one it replaces
+        (Some(base), Some(built_in)) if base == name => (built_in.severity, built_in.categories),
+        (Some(base), _) => resolve(config, base, chain)?,
+        (None, _) => Default::default(),
+    };
+    if rules.severity.is_some() {
+        severity = rules.severity;
+    }
+    categories.extend(rules.categories);
+    Ok((severity, categories))
+}
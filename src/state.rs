@@ -0,0 +1,147 @@
+use crate::error::{ConfigError, IoError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The state file, relative to the project directory
+pub const STATE_FILE: &str = ".release-scholar/state.toml";
+
+/// What release-scholar knows about past publish runs, kept in
+/// `.release-scholar/state.toml` so later commands don't have to ask Zenodo
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReleaseState {
+    #[serde(default)]
+    pub production: Environment,
+    #[serde(default)]
+    pub sandbox: Environment,
+}
+
+/// Depositions on one Zenodo instance (production or sandbox)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Environment {
+    /// DOI that always resolves to the latest version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concept_doi: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concept_record_id: Option<String>,
+    /// Keyed by git tag
+    #[serde(default)]
+    pub releases: BTreeMap<String, TagState>,
+}
+
+/// A release: its deposition and what the publish run did
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TagState {
+    pub deposition_id: u64,
+    #[serde(default)]
+    pub published: bool,
+    /// Minted on publishing, or reserved before the build with
+    /// `publish --reserve-doi`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    /// Last time `publish --update-metadata` changed the published record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_updated_at: Option<String>,
+    /// Uploaded file name → checksum reported by Zenodo
+    #[serde(default)]
+    pub uploads: BTreeMap<String, String>,
+}
+
+impl Environment {
+    /// Every DOI of the record: the concept DOI and those of its versions
+    pub fn dois(&self) -> Vec<&str> {
+        self.concept_doi
+            .iter()
+            .chain(self.releases.values().filter_map(|r| r.doi.as_ref()))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl ReleaseState {
+    /// Where the state file is kept in `project_dir`
+    pub fn path(project_dir: &Path) -> PathBuf {
+        project_dir.join(STATE_FILE)
+    }
+
+    /// Load the state file; a missing file is an empty state
+    pub fn load(project_dir: &Path) -> Result<Self, ConfigError> {
+        let path = Self::path(project_dir);
+        if !path.exists() {
+            return Ok(ReleaseState::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(IoError::on("read", &path))?;
+        toml::from_str(&content).map_err(|source| ConfigError::Parse { path, source })
+    }
+
+    /// Write the state file, creating `.release-scholar/` if needed
+    pub fn save(&self, project_dir: &Path) -> Result<(), ConfigError> {
+        let path = Self::path(project_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(IoError::on("create", parent))?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|source| ConfigError::Serialize {
+            what: "release state",
+            source,
+        })?;
+        std::fs::write(&path, content).map_err(IoError::on("write", &path))?;
+        Ok(())
+    }
+
+    /// The depositions on production Zenodo, or the sandbox
+    pub fn env(&self, sandbox: bool) -> &Environment {
+        if sandbox {
+            &self.sandbox
+        } else {
+            &self.production
+        }
+    }
+
+    /// Likewise, to change them
+    pub fn env_mut(&mut self, sandbox: bool) -> &mut Environment {
+        if sandbox {
+            &mut self.sandbox
+        } else {
+            &mut self.production
+        }
+    }
+}
+
+/// Current UTC time as RFC 3339 (`2026-02-16T09:30:00Z`)
+pub fn now_utc() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    format_utc(secs)
+}
+
+/// A Unix timestamp (e.g. a commit time) as RFC 3339 UTC
+pub fn format_utc(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
@@ -1,5 +1,143 @@
+/// CI configuration
+pub mod ci;
+/// CITATION.cff fields
 pub mod citation;
+/// The configuration itself
+pub mod config;
+pub mod data;
+/// FAIR4RS scoring
+pub mod fair4rs;
+/// Required files and their content rules
 pub mod files;
+/// The release tag and the working tree
 pub mod git;
+/// The repository's settings on the forge
+pub mod hosting;
+/// DOIs and URLs in CITATION.cff
+pub mod identifiers;
+/// JOSS submission checklist
+pub mod joss;
+pub mod notebooks;
+pub mod paper;
+/// README sections and content
+pub mod readme;
+/// Lockfiles, pinned toolchains and containers
+pub mod reproducibility;
+/// Secret scanning
 pub mod security;
+/// Repository and file sizes
 pub mod size;
+/// Version agreement across the metadata files
+pub mod versions;
+
+use crate::config::Config;
+use crate::report::Report;
+use git::GitInfo;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Run every check without printing; the git info is `None` when HEAD has no
+/// release tag
+pub fn run_all(project_dir: &Path, config: &Config, online: bool) -> (Report, Option<GitInfo>) {
+    let mut report = Report::new();
+    let mut timings = Vec::new();
+
+    // Files that could not be loaded into `config`
+    stage(&mut timings, "config", || {
+        config::validate(project_dir, &mut report)
+    });
+
+    // Git validation (an invalid tag_pattern is reported with the config)
+    let tags = config.tag_pattern().unwrap_or_default();
+    let git_info = stage(&mut timings, "git", || {
+        git::validate(project_dir, &tags, &mut report)
+    });
+
+    // File existence
+    stage(&mut timings, "files", || {
+        files::validate(project_dir, config, &mut report)
+    });
+
+    // README sections and links (external links only when online)
+    stage(&mut timings, "readme", || {
+        readme::validate(project_dir, config, &mut report);
+        readme::validate_links(project_dir, online, &config.network(), &mut report);
+    });
+
+    // Tests and CI
+    stage(&mut timings, "ci", || {
+        ci::validate(project_dir, &mut report)
+    });
+
+    // Dependency lockfiles
+    stage(&mut timings, "reproducibility", || {
+        reproducibility::validate(project_dir, config, &mut report)
+    });
+
+    // Citation validation
+    let version = git_info.as_ref().map(|g| g.version.as_str());
+    stage(&mut timings, "citation", || {
+        citation::validate(project_dir, version, &mut report);
+        versions::validate(project_dir, version, &mut report);
+    });
+
+    // paper.md for journal submissions, if there is one
+    stage(&mut timings, "paper", || {
+        paper::validate(project_dir, &mut report)
+    });
+
+    // Security audit
+    stage(&mut timings, "security", || {
+        security::validate(project_dir, config, &mut report)
+    });
+
+    // Provenance and license of declared data
+    stage(&mut timings, "data", || {
+        data::validate(project_dir, config, &mut report)
+    });
+
+    // Notebooks committed with their outputs
+    stage(&mut timings, "notebooks", || {
+        notebooks::validate(project_dir, &mut report)
+    });
+
+    // Size audit
+    stage(&mut timings, "size", || {
+        size::validate(project_dir, config, &mut report)
+    });
+
+    // Online checks
+    if online {
+        stage(&mut timings, "identifiers", || {
+            identifiers::validate(project_dir, &config.network(), &mut report)
+        });
+        stage(&mut timings, "hosting", || {
+            hosting::validate(project_dir, config, &mut report)
+        });
+    }
+
+    report.timings = timings;
+    (report, git_info)
+}
+
+/// Run one group of checks, logging and recording how long it took
+fn stage<T>(
+    timings: &mut Vec<(&'static str, Duration)>,
+    name: &'static str,
+    checks: impl FnOnce() -> T,
+) -> T {
+    let _span = tracing::debug_span!("check", stage = name).entered();
+    let started = Instant::now();
+    let result = checks();
+    let took = started.elapsed();
+    tracing::debug!("{} checks took {} ms", name, took.as_millis());
+    timings.push((name, took));
+    result
+}
+
+/// Whether the file at `path` exists and contains `needle`
+pub(crate) fn file_contains(path: &Path, needle: &str) -> bool {
+    std::fs::read_to_string(path)
+        .map(|c| c.contains(needle))
+        .unwrap_or(false)
+}
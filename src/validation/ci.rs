@@ -0,0 +1,125 @@
+use crate::report::Report;
+use crate::validation::file_contains;
+use std::path::Path;
+use walkdir::WalkDir;
+
+const CI_CONFIGS: &[(&str, &str)] = &[
+    (".github/workflows", "GitHub Actions"),
+    (".forgejo/workflows", "Forgejo Actions"),
+    (".gitea/workflows", "Gitea Actions"),
+    (".woodpecker.yml", "Woodpecker CI"),
+    (".woodpecker.yaml", "Woodpecker CI"),
+    (".woodpecker", "Woodpecker CI"),
+    (".gitlab-ci.yml", "GitLab CI"),
+    (".circleci/config.yml", "CircleCI"),
+    (".travis.yml", "Travis CI"),
+    ("Jenkinsfile", "Jenkins"),
+    ("azure-pipelines.yml", "Azure Pipelines"),
+];
+
+const TEST_DIRS: &[&str] = &["tests", "test", "spec", "src/test"];
+
+const PYTEST_CONFIGS: &[&str] = &["pytest.ini", "conftest.py", "tox.ini", "noxfile.py"];
+
+/// Journal review checklists expect a test suite and continuous integration
+pub fn validate(project_dir: &Path, report: &mut Report) {
+    let tests = detect_tests(project_dir);
+    if tests.is_empty() {
+        report.warn_on(
+            "Tests",
+            "test-suite",
+            "",
+            "No test suite detected (tests/, pytest config, cargo tests, npm test script)",
+        );
+    } else {
+        report.pass("Tests", &format!("Test suite found: {}", tests.join(", ")));
+    }
+
+    let ci = detect_ci(project_dir);
+    if ci.is_empty() {
+        report.warn_on(
+            "CI",
+            "ci-config",
+            "",
+            "No CI configuration found (.github/workflows, .woodpecker.yml, .gitlab-ci.yml, ...)",
+        );
+    } else {
+        report.pass("CI", &format!("CI configured: {}", ci.join(", ")));
+    }
+}
+
+fn detect_ci(project_dir: &Path) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    for (path, name) in CI_CONFIGS {
+        let full = project_dir.join(path);
+        let present = if full.is_dir() {
+            std::fs::read_dir(&full)
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or(false)
+        } else {
+            full.exists()
+        };
+        if present && !found.contains(name) {
+            found.push(*name);
+        }
+    }
+    found
+}
+
+fn detect_tests(project_dir: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+
+    for dir in TEST_DIRS {
+        if project_dir.join(dir).is_dir() {
+            found.push(format!("{}/", dir));
+        }
+    }
+
+    for config in PYTEST_CONFIGS {
+        if project_dir.join(config).exists() {
+            found.push(config.to_string());
+        }
+    }
+    if file_contains(&project_dir.join("pyproject.toml"), "[tool.pytest") {
+        found.push("pytest (pyproject.toml)".to_string());
+    }
+
+    if project_dir.join("Cargo.toml").exists() && has_rust_unit_tests(&project_dir.join("src")) {
+        found.push("cargo unit tests".to_string());
+    }
+
+    if let Some(script) = npm_test_script(project_dir) {
+        found.push(format!("npm test ({})", script));
+    }
+
+    found
+}
+
+fn has_rust_unit_tests(src_dir: &Path) -> bool {
+    WalkDir::new(src_dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        .any(|e| {
+            std::fs::read_to_string(e.path())
+                .map(|c| {
+                    c.lines().any(|l| {
+                        let l = l.trim_start();
+                        l.starts_with("#[test]") || l.starts_with("#[cfg(test)]")
+                    })
+                })
+                .unwrap_or(false)
+        })
+}
+
+/// The `test` script from package.json, unless it is npm's placeholder
+fn npm_test_script(project_dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(project_dir.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let script = package.get("scripts")?.get("test")?.as_str()?;
+    if script.contains("no test specified") {
+        None
+    } else {
+        Some(script.to_string())
+    }
+}
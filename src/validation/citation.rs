@@ -1,7 +1,10 @@
+use crate::metadata::citation::CffReference;
+use crate::metadata::zenodo::read_zenodo_json;
 use crate::report::Report;
 use regex::Regex;
 use std::path::Path;
 
+/// Check CITATION.cff, and that `.zenodo.json` agrees with it
 pub fn validate(project_dir: &Path, expected_version: Option<&str>, report: &mut Report) {
     let cff_path = project_dir.join("CITATION.cff");
     if !cff_path.exists() {
@@ -47,14 +50,19 @@ pub fn validate(project_dir: &Path, expected_version: Option<&str>, report: &mut
             let orcid_re =
                 Regex::new(r"^https://orcid\.org/\d{4}-\d{4}-\d{4}-\d{3}[\dX]$").unwrap();
             for (i, author) in list.iter().enumerate() {
-                if author
+                // Persons need family-names; entities (labs, institutions) need name
+                let is_person = author
                     .get("family-names")
                     .and_then(|v| v.as_str())
-                    .is_none()
-                {
+                    .is_some();
+                let is_entity = author.get("name").and_then(|v| v.as_str()).is_some();
+                if !is_person && !is_entity {
                     report.fail(
                         "Citation",
-                        &format!("Author {} missing family-names", i + 1),
+                        &format!(
+                            "Author {} missing family-names (or name for entities)",
+                            i + 1
+                        ),
                     );
                 }
                 if let Some(orcid) = author.get("orcid").and_then(|v| v.as_str()) {
@@ -86,6 +94,16 @@ pub fn validate(project_dir: &Path, expected_version: Option<&str>, report: &mut
                     &format!("version '{}' does not match git tag '{}'", v, expected),
                 );
             }
+            // `version: 2024.10` is read as the number 2024.1
+            None if doc.get("version").is_some_and(|v| v.is_number()) => {
+                report.fail(
+                    "Citation",
+                    &format!(
+                        "version is a number in YAML; quote it to match the git tag (version: \"{}\")",
+                        expected
+                    ),
+                );
+            }
             None => {
                 report.fail("Citation", "version missing");
             }
@@ -105,4 +123,76 @@ pub fn validate(project_dir: &Path, expected_version: Option<&str>, report: &mut
     } else {
         report.fail("Citation", "date-released missing");
     }
+
+    validate_references(&doc, report);
+    validate_zenodo_json(project_dir, &doc, report);
+}
+
+/// `preferred-citation` and `references` entries that cannot be read are
+/// left out of the record and the citations; say which
+fn validate_references(doc: &serde_yaml::Value, report: &mut Report) {
+    let preferred = doc
+        .get("preferred-citation")
+        .map(|v| ("preferred-citation".to_string(), v));
+    let references = doc
+        .get("references")
+        .and_then(|v| v.as_sequence())
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, v)| (format!("Reference {}", i + 1), v));
+    for (what, value) in preferred.into_iter().chain(references) {
+        if let Err(e) = CffReference::read(value.clone()) {
+            report.warn_on(
+                "Citation",
+                "unreadable-reference",
+                &what,
+                &format!("{} cannot be read and is left out: {}", what, e),
+            );
+        }
+    }
+}
+
+/// If a .zenodo.json exists, make sure it agrees with CITATION.cff
+fn validate_zenodo_json(project_dir: &Path, doc: &serde_yaml::Value, report: &mut Report) {
+    let path = project_dir.join(".zenodo.json");
+    if !path.exists() {
+        return;
+    }
+    let fields = match read_zenodo_json(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            report.fail("Citation", &e.to_string());
+            return;
+        }
+    };
+
+    let mut mismatches = Vec::new();
+    for (cff_key, zenodo_key) in [
+        ("title", "title"),
+        ("version", "version"),
+        ("license", "license"),
+    ] {
+        let cff_value = doc.get(cff_key).and_then(|v| v.as_str());
+        let zenodo_value = fields.get(zenodo_key).and_then(|v| v.as_str());
+        if let (Some(a), Some(b)) = (cff_value, zenodo_value) {
+            if !a.eq_ignore_ascii_case(b) {
+                mismatches.push(format!("{} ('{}' vs '{}')", cff_key, a, b));
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        report.pass("Citation", ".zenodo.json consistent with CITATION.cff");
+    } else {
+        report.warn_on(
+            "Citation",
+            "zenodo-json-mismatch",
+            "",
+            &format!(
+                ".zenodo.json differs from CITATION.cff: {}",
+                mismatches.join(", ")
+            ),
+        );
+    }
 }
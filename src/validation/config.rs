@@ -0,0 +1,52 @@
+use crate::config::Config;
+use crate::report::Report;
+use crate::rules::RuleSet;
+use crate::validation::git::GitInfo;
+use std::path::Path;
+
+/// A config file that does not parse is left out of the run, so every
+/// other check still happens, but the release is blocked until it is fixed
+pub fn validate(project_dir: &Path, report: &mut Report) {
+    let (config, mut problems) = Config::load_lenient(project_dir, None);
+
+    // Settings that parse but only fail once `build` uses them
+    let sample = GitInfo {
+        version: "1.0.0".to_string(),
+        tag: "v1.0.0".to_string(),
+        date: "2024-01-01".to_string(),
+    };
+    if let Err(e) = config.archive_file_name("project", &sample) {
+        problems.push(e);
+    }
+    if let Err(e) = config.tag_pattern() {
+        problems.push(e);
+    }
+    for name in config.rules.keys() {
+        if let Err(e) = RuleSet::resolve(&config, name) {
+            problems.push(e);
+        }
+    }
+
+    for problem in &problems {
+        report.fail("Config", &problem.to_string());
+    }
+    for name in &config.overridden_rules {
+        report.warn_on("Config", "global-rules-replaced", name,
+            &format!(
+                "[rules.{}] in .release-scholar.toml replaces the global config's rule set of that name",
+                name
+            ),
+        );
+    }
+    for variable in &config.ignored_env {
+        report.warn_on(
+            "Config",
+            "unknown-variable",
+            variable,
+            &format!("{} is not a config key, so it is ignored", variable),
+        );
+    }
+    if problems.is_empty() && project_dir.join(".release-scholar.toml").exists() {
+        report.pass("Config", ".release-scholar.toml is valid");
+    }
+}
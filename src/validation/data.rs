@@ -0,0 +1,102 @@
+//! Declared data directories must say where their data comes from and under
+//! what license, in a README or a datapackage.json of their own
+
+use crate::config::Config;
+use crate::report::Report;
+use regex::Regex;
+use std::path::Path;
+
+const DESCRIPTORS: &[&str] = &[
+    "datapackage.json",
+    "README.md",
+    "README.txt",
+    "README.rst",
+    "README",
+];
+
+/// Check the data files against the `[data]` settings
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
+    let data = config.data();
+    for data_path in &data.paths {
+        let full_path = project_dir.join(data_path);
+        if !full_path.exists() {
+            report.fail(
+                "Data",
+                &format!("Data path '{}' ([data] paths) not found", data_path),
+            );
+            continue;
+        }
+        // A data file is documented by the directory it sits in
+        let dir = if full_path.is_dir() {
+            full_path.as_path()
+        } else {
+            full_path.parent().unwrap_or(project_dir)
+        };
+        let Some(descriptor) = DESCRIPTORS
+            .iter()
+            .map(|d| dir.join(d))
+            .find(|d| d.is_file())
+        else {
+            report.fail(
+                "Data",
+                &format!(
+                    "{} has no README or datapackage.json saying where the data comes from \
+                     and how it is licensed",
+                    data_path
+                ),
+            );
+            continue;
+        };
+
+        let shown = descriptor
+            .strip_prefix(project_dir)
+            .unwrap_or(&descriptor)
+            .display()
+            .to_string();
+        let content = std::fs::read_to_string(&descriptor).unwrap_or_default();
+        let missing = missing_topics(&descriptor, &content);
+        if missing.is_empty() {
+            report.pass("Data", &format!("{} documented in {}", data_path, shown));
+        } else {
+            report.warn_on(
+                "Data",
+                "data-description",
+                &shown,
+                &format!(
+                    "{} does not mention the data's {}",
+                    shown,
+                    missing.join(" or ")
+                ),
+            );
+        }
+    }
+}
+
+/// Which of provenance and license a descriptor leaves out
+fn missing_topics(descriptor: &Path, content: &str) -> Vec<&'static str> {
+    let (provenance, license) = if descriptor.ends_with("datapackage.json") {
+        let package: serde_json::Value = serde_json::from_str(content).unwrap_or_default();
+        let has = |key: &str| {
+            package
+                .get(key)
+                .and_then(|v| v.as_array())
+                .is_some_and(|v| !v.is_empty())
+        };
+        (has("sources"), has("licenses"))
+    } else {
+        let provenance = Regex::new(
+            r"(?i)\b(provenance|sources?|origin|collected|obtained|derived|downloaded)\b",
+        )
+        .unwrap();
+        let license = Regex::new(r"(?i)\blicen[cs]").unwrap();
+        (provenance.is_match(content), license.is_match(content))
+    };
+    let mut missing = Vec::new();
+    if !provenance {
+        missing.push("provenance");
+    }
+    if !license {
+        missing.push("license");
+    }
+    missing
+}
@@ -0,0 +1,195 @@
+use crate::metadata::citation::CitationCff;
+use crate::report::{Report, Status};
+use colored::Colorize;
+use std::path::Path;
+
+/// FAIR Principles for Research Software (Barker et al., 2022)
+const PRINCIPLES: &[(&str, &str)] = &[
+    ("F", "Findable"),
+    ("A", "Accessible"),
+    ("I", "Interoperable"),
+    ("R", "Reusable"),
+];
+
+/// One measurable signal for a FAIR4RS sub-principle
+pub struct Indicator {
+    /// Sub-principle identifier, e.g. "F1" or "R1.1"
+    pub principle: &'static str,
+    pub description: &'static str,
+    /// What to do when the indicator is not met
+    pub hint: &'static str,
+    pub met: bool,
+}
+
+/// Map project metadata and the results of the other checks onto FAIR4RS indicators
+pub fn assess(project_dir: &Path, report: &Report) -> Vec<Indicator> {
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff")).ok();
+    let has_file = |name: &str| project_dir.join(name).exists();
+    let cff_has = |f: &dyn Fn(&CitationCff) -> bool| cff.as_ref().is_some_and(f);
+
+    let has_doi = cff_has(&|c| c.doi.is_some());
+    let persons_have_orcid = cff_has(&|c| {
+        c.authors
+            .iter()
+            .filter(|a| !a.is_entity())
+            .all(|a| a.orcid.is_some())
+    });
+
+    vec![
+        Indicator {
+            principle: "F1",
+            description: "Software has a persistent identifier (DOI)",
+            hint: "publish to Zenodo and record the DOI in CITATION.cff",
+            met: has_doi,
+        },
+        Indicator {
+            principle: "F1.2",
+            description: "Release version is identified by a semver tag",
+            hint: "tag HEAD as a release (vX.Y.Z, or see tag_prefix)",
+            met: category_passes(report, "Git", "HEAD is tagged"),
+        },
+        Indicator {
+            principle: "F2",
+            description: "Metadata has an abstract and keywords",
+            hint: "add `abstract` and `keywords` to CITATION.cff",
+            met: cff_has(&|c| c.abstract_text.is_some() && !c.keywords.is_empty()),
+        },
+        Indicator {
+            principle: "F3",
+            description: "Metadata records the software's identifier",
+            hint: "add `doi` or `repository-code` to CITATION.cff",
+            met: cff_has(&|c| c.doi.is_some() || c.repository_code.is_some()),
+        },
+        Indicator {
+            principle: "F4",
+            description: "Metadata is published in an indexable format (codemeta.json)",
+            hint: "add a codemeta.json (e.g. with cffconvert or the CodeMeta generator)",
+            met: has_file("codemeta.json"),
+        },
+        Indicator {
+            principle: "A1",
+            description: "Source is retrievable over HTTPS",
+            hint: "set `repository-code` in CITATION.cff to the public https:// URL",
+            met: cff_has(&|c| {
+                c.repository_code
+                    .as_deref()
+                    .is_some_and(|u| u.starts_with("https://"))
+            }),
+        },
+        Indicator {
+            principle: "A2",
+            description: "Metadata is archived independently of the repository",
+            hint: "deposit the release on Zenodo, which keeps metadata even if the software is withdrawn",
+            met: has_doi,
+        },
+        Indicator {
+            principle: "I1",
+            description: "Metadata uses community standards (CITATION.cff and CodeMeta)",
+            hint: "provide both CITATION.cff and codemeta.json",
+            met: cff.is_some() && has_file("codemeta.json"),
+        },
+        Indicator {
+            principle: "I2",
+            description: "Qualified references to related objects",
+            hint: "add `preferred-citation` or `references` to CITATION.cff",
+            met: cff_has(&|c| c.preferred_citation.is_some() || !c.references.is_empty()),
+        },
+        Indicator {
+            principle: "R1",
+            description: "Every person author has an ORCID",
+            hint: "add `orcid` to each author in CITATION.cff",
+            met: persons_have_orcid,
+        },
+        Indicator {
+            principle: "R1.1",
+            description: "Clear license (LICENSE file and SPDX id in metadata)",
+            hint: "add a LICENSE file and `license` to CITATION.cff",
+            met: has_file("LICENSE") && cff_has(&|c| c.license.is_some()),
+        },
+        Indicator {
+            principle: "R1.2",
+            description: "Provenance is documented (CHANGELOG.md)",
+            hint: "keep a CHANGELOG.md",
+            met: has_file("CHANGELOG.md"),
+        },
+        Indicator {
+            principle: "R2",
+            description: "Dependencies are pinned",
+            hint: "commit lockfiles and pin container base images",
+            met: !category_warns(report, "Reproducibility") && !category_warns(report, "Container"),
+        },
+        Indicator {
+            principle: "R3",
+            description: "Tests and CI meet community practice",
+            hint: "add a test suite and CI configuration",
+            met: !category_warns(report, "Tests") && !category_warns(report, "CI"),
+        },
+    ]
+}
+
+/// Print a per-principle score with the indicators that were not met
+pub fn print(indicators: &[Indicator]) {
+    println!("{}", "═══ FAIR4RS Readiness ═══".bold());
+    println!();
+
+    for (prefix, name) in PRINCIPLES {
+        let group: Vec<&Indicator> = indicators
+            .iter()
+            .filter(|i| i.principle.starts_with(prefix))
+            .collect();
+        let met = group.iter().filter(|i| i.met).count();
+        println!(
+            "  {:<14} {} {}/{}",
+            name.bold(),
+            bar(met, group.len()),
+            met,
+            group.len()
+        );
+        for indicator in group.iter().filter(|i| !i.met) {
+            println!(
+                "      {} {}: {} — {}",
+                "✗".red(),
+                indicator.principle,
+                indicator.description,
+                indicator.hint.dimmed()
+            );
+        }
+    }
+
+    let met = indicators.iter().filter(|i| i.met).count();
+    let percent = met * 100 / indicators.len().max(1);
+    println!();
+    println!(
+        "  Overall: {}/{} indicators ({}%)",
+        met,
+        indicators.len(),
+        percent
+    );
+    println!();
+}
+
+fn bar(met: usize, total: usize) -> String {
+    const WIDTH: usize = 10;
+    let filled = (met * WIDTH).checked_div(total).unwrap_or(0);
+    let bar = format!("{}{}", "█".repeat(filled), "░".repeat(WIDTH - filled));
+    if met == total {
+        bar.green().to_string()
+    } else if met * 2 >= total {
+        bar.yellow().to_string()
+    } else {
+        bar.red().to_string()
+    }
+}
+
+fn category_passes(report: &Report, category: &str, prefix: &str) -> bool {
+    report.results.iter().any(|r| {
+        r.category == category && matches!(r.status, Status::Pass) && r.message.starts_with(prefix)
+    })
+}
+
+fn category_warns(report: &Report, category: &str) -> bool {
+    report
+        .results
+        .iter()
+        .any(|r| r.category == category && !matches!(r.status, Status::Pass))
+}
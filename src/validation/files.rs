@@ -1,14 +1,245 @@
-use crate::config::Config;
+use crate::config::{Config, ContentRules};
+use crate::metadata::citation::CitationCff;
 use crate::report::Report;
+use git2::Repository;
+use globset::GlobBuilder;
+use regex::Regex;
+use std::collections::HashSet;
 use std::path::Path;
 
+/// `when` conditions for required files: name, what it means, and for
+/// ecosystems the manifests that mark one
+const CONDITIONS: &[(&str, &str, &[&str])] = &[
+    (
+        "multiple-authors",
+        "the project has more than one git author",
+        &[],
+    ),
+    (
+        "python",
+        "this is a Python project",
+        &[
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "requirements.txt",
+        ],
+    ),
+    ("rust", "this is a Rust project", &["Cargo.toml"]),
+    ("node", "this is a Node.js project", &["package.json"]),
+    (
+        "java",
+        "this is a Java project",
+        &["pom.xml", "build.gradle", "build.gradle.kts"],
+    ),
+    ("r", "this is an R package", &["DESCRIPTION"]),
+    ("julia", "this is a Julia project", &["Project.toml"]),
+];
+
+/// Check that the required files exist and follow their content rules
 pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
-    for file in &config.required_files {
-        let path = project_dir.join(file);
-        if path.exists() {
-            report.pass("Files", &format!("{} exists", file));
+    let mut files: Option<Vec<String>> = None;
+    for required in &config.required_files {
+        let reason = match required.when() {
+            None => None,
+            Some(when) => match condition(project_dir, when) {
+                Ok(Some(reason)) => Some(reason),
+                Ok(None) => continue,
+                Err(problem) => {
+                    report.fail("Files", &problem);
+                    continue;
+                }
+            },
+        };
+        let because = reason.map_or(String::new(), |r| format!(" (required because {})", r));
+
+        let pattern = required.path();
+        if !is_glob(pattern) {
+            if project_dir.join(pattern).exists() {
+                report.pass("Files", &format!("{} exists{}", pattern, because));
+            } else {
+                report.fail("Files", &format!("{} is missing{}", pattern, because));
+            }
+            continue;
+        }
+
+        // `*` stays within a directory; `**` crosses them
+        let glob = GlobBuilder::new(pattern).literal_separator(true).build();
+        let matcher = match glob {
+            Ok(glob) => glob.compile_matcher(),
+            Err(e) => {
+                report.fail(
+                    "Files",
+                    &format!("required_files: invalid pattern {}: {}", pattern, e.kind()),
+                );
+                continue;
+            }
+        };
+        let files = files.get_or_insert_with(|| project_files(project_dir, config));
+        let matches = files.iter().filter(|f| matcher.is_match(f)).count();
+        if matches > 0 {
+            report.pass(
+                "Files",
+                &format!("{} matches {} file(s){}", pattern, matches, because),
+            );
         } else {
-            report.fail("Files", &format!("{} is missing", file));
+            report.fail("Files", &format!("No file matches {}{}", pattern, because));
+        }
+    }
+
+    for (file, rules) in &config.content {
+        content(project_dir, config, file, rules, report);
+    }
+}
+
+/// Check a file against its `[content]` rules
+fn content(
+    project_dir: &Path,
+    config: &Config,
+    file: &str,
+    rules: &ContentRules,
+    report: &mut Report,
+) {
+    let text = match std::fs::read_to_string(project_dir.join(file)) {
+        Ok(text) => text,
+        // A missing required file has been reported already
+        Err(_) if config.required_files.iter().any(|r| r.path() == file) => return,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            report.fail(
+                "Files",
+                &format!("{} is missing (it has [content] rules)", file),
+            );
+            return;
+        }
+        Err(e) => {
+            report.fail(
+                "Files",
+                &format!("Cannot read {} to check its content: {}", file, e),
+            );
+            return;
+        }
+    };
+    let lower = text.to_lowercase();
+    // Unknown placeholders are left out when the config is loaded; a title
+    // that cannot be read leaves the rule unchecked rather than matching ''
+    let expand = |needle: &str| -> Result<String, String> {
+        let mut needle = needle.to_string();
+        if needle.contains("{title}") {
+            let title = CitationCff::from_file(&project_dir.join("CITATION.cff"))
+                .map(|c| c.title)
+                .ok()
+                .filter(|t| !t.trim().is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "cannot be checked for '{}': CITATION.cff has no title for {{title}}",
+                        needle
+                    )
+                })?;
+            needle = needle.replace("{title}", &title);
+        }
+        let name = project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(needle.replace("{name}", &name))
+    };
+
+    let mut problems = Vec::new();
+    if rules.not_empty && text.trim().is_empty() {
+        problems.push("is empty".to_string());
+    }
+    for needle in &rules.contains {
+        match expand(needle) {
+            Ok(needle) if !lower.contains(&needle.to_lowercase()) => {
+                problems.push(format!("does not mention '{}'", needle))
+            }
+            Ok(_) => {}
+            Err(problem) => problems.push(problem),
+        }
+    }
+    for needle in &rules.excludes {
+        match expand(needle) {
+            Ok(needle) if lower.contains(&needle.to_lowercase()) => {
+                problems.push(format!("contains '{}'", needle))
+            }
+            Ok(_) => {}
+            Err(problem) => problems.push(problem),
         }
     }
+    // Patterns that do not compile are left out when the config is loaded
+    for pattern in &rules.matches {
+        if let Ok(re) = Regex::new(pattern) {
+            if !re.is_match(&text) {
+                problems.push(format!("has nothing matching /{}/", pattern));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        report.pass("Files", &format!("{} content is as required", file));
+    }
+    for problem in problems {
+        report.fail("Files", &format!("{} {}", file, problem));
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+/// Whether the condition holds, as the reason the file is required;
+/// `None` when it does not apply
+fn condition(project_dir: &Path, when: &str) -> Result<Option<&'static str>, String> {
+    let Some((name, reason, manifests)) = CONDITIONS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(when.trim()))
+    else {
+        let names: Vec<&str> = CONDITIONS.iter().map(|(name, _, _)| *name).collect();
+        return Err(format!(
+            "required_files: unknown condition when = \"{}\" (expected one of: {})",
+            when,
+            names.join(", ")
+        ));
+    };
+    let holds = match *name {
+        "multiple-authors" => git_authors(project_dir) > 1,
+        _ => manifests.iter().any(|m| project_dir.join(m).exists()),
+    };
+    Ok(holds.then_some(*reason))
+}
+
+/// Distinct commit author emails reachable from HEAD
+fn git_authors(project_dir: &Path) -> usize {
+    let Ok(repo) = Repository::open(project_dir) else {
+        return 0;
+    };
+    let Ok(mut walk) = repo.revwalk() else {
+        return 0;
+    };
+    if walk.push_head().is_err() {
+        return 0;
+    }
+    let mut authors = HashSet::new();
+    for oid in walk.flatten() {
+        if let Ok(commit) = repo.find_commit(oid) {
+            if let Some(email) = commit.author().email() {
+                authors.insert(email.to_lowercase());
+            }
+        }
+    }
+    authors.len()
+}
+
+/// Files in the git index, which are what the release archive holds,
+/// leaving out any release bundles committed under `archive_dir`
+fn project_files(project_dir: &Path, config: &Config) -> Vec<String> {
+    let Ok(index) = Repository::open(project_dir).and_then(|r| r.index()) else {
+        return Vec::new();
+    };
+    let archive_dir = project_dir.join(&config.archive_dir);
+    index
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .filter(|path| !project_dir.join(path).starts_with(&archive_dir))
+        .collect()
 }
@@ -1,15 +1,73 @@
+use crate::config::VersionScheme;
+use crate::error::GitError;
 use crate::report::Report;
+use crate::state;
 use git2::Repository;
 use regex::Regex;
 use std::path::Path;
 
+/// The release HEAD is tagged as
 pub struct GitInfo {
     pub version: String,
-    #[allow(dead_code)]
     pub tag: String,
+    /// Date of the tagged commit (YYYY-MM-DD, UTC)
+    pub date: String,
 }
 
-pub fn validate(project_dir: &Path, report: &mut Report) -> Option<GitInfo> {
+/// Which tags are releases: the prefix and a version in the project's
+/// scheme (`v1.2.3`, `mypkg-v1.2.3`, `2024.06.1`), or any regex with a
+/// `version` group
+#[derive(Debug, Clone)]
+pub struct TagPattern {
+    regex: Regex,
+    /// What a release tag looks like, for messages ("vX.Y.Z")
+    pub expected: String,
+}
+
+impl TagPattern {
+    /// Tags of `prefix` followed by a version in `scheme`
+    pub fn prefix(prefix: &str, scheme: VersionScheme) -> TagPattern {
+        let regex = format!(
+            r"^{}(?P<version>{})$",
+            regex::escape(prefix),
+            scheme.regex()
+        );
+        TagPattern {
+            regex: Regex::new(&regex).unwrap(),
+            expected: format!("{}{}", prefix, scheme.example()),
+        }
+    }
+
+    /// A pattern matched against the whole tag name; `(?P<version>...)`
+    /// captures the version
+    pub fn regex(pattern: &str) -> Result<TagPattern, String> {
+        let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+        if !regex.capture_names().any(|name| name == Some("version")) {
+            return Err("it needs a (?P<version>...) group".to_string());
+        }
+        Ok(TagPattern {
+            regex,
+            expected: format!("a tag matching {}", pattern),
+        })
+    }
+
+    /// The version in a release tag (`v1.2.0` -> `1.2.0`), `None` for other tags
+    pub fn version<'a>(&self, tag: &'a str) -> Option<&'a str> {
+        self.regex
+            .captures(tag)
+            .and_then(|caps| caps.name("version"))
+            .map(|m| m.as_str())
+    }
+}
+
+impl Default for TagPattern {
+    fn default() -> Self {
+        TagPattern::prefix("v", VersionScheme::default())
+    }
+}
+
+/// Check the repository and its release tag; the release, if HEAD is tagged as one
+pub fn validate(project_dir: &Path, tags: &TagPattern, report: &mut Report) -> Option<GitInfo> {
     let repo = match Repository::open(project_dir) {
         Ok(r) => r,
         Err(e) => {
@@ -20,6 +78,10 @@ pub fn validate(project_dir: &Path, report: &mut Report) -> Option<GitInfo> {
 
     // Check working directory is clean
     let statuses = repo.statuses(None);
+    tracing::debug!(
+        "git status: {} modified or untracked",
+        statuses.as_ref().map(|s| s.len()).unwrap_or(0)
+    );
     match statuses {
         Ok(s) => {
             let dirty: Vec<String> = s
@@ -30,8 +92,10 @@ pub fn validate(project_dir: &Path, report: &mut Report) -> Option<GitInfo> {
             if dirty.is_empty() {
                 report.pass("Git", "Working directory is clean");
             } else {
-                report.warn(
+                report.warn_on(
                     "Git",
+                    "uncommitted-changes",
+                    "",
                     &format!(
                         "Working directory has {} uncommitted change(s): {}",
                         dirty.len(),
@@ -45,55 +109,251 @@ pub fn validate(project_dir: &Path, report: &mut Report) -> Option<GitInfo> {
         }
     }
 
-    // Find semver tag on HEAD
-    let head = match repo.head() {
-        Ok(h) => h,
+    // Find release tag on HEAD
+    let found_tag = match head_tag(&repo, tags) {
+        Ok(t) => t,
         Err(e) => {
-            report.fail("Git", &format!("Cannot read HEAD: {}", e));
+            report.fail("Git", &e.to_string());
             return None;
         }
     };
-    let head_oid = head.target().unwrap();
 
-    let semver_re = Regex::new(r"^v(\d+\.\d+\.\d+)$").unwrap();
-    let tag_names = match repo.tag_names(None) {
-        Ok(t) => t,
+    match found_tag {
+        Some(info) => {
+            report.pass(
+                "Git",
+                &format!("HEAD is tagged: {} (version {})", info.tag, info.version),
+            );
+            scope(project_dir, tags, &info, report);
+            Some(info)
+        }
+        None => {
+            report.fail(
+                "Git",
+                &format!("HEAD has no release tag (expected {})", tags.expected),
+            );
+            None
+        }
+    }
+}
+
+/// How much the release changes, for reviewers sizing it up
+fn scope(project_dir: &Path, tags: &TagPattern, release: &GitInfo, report: &mut Report) {
+    let previous = match previous_release(project_dir, tags, &release.version) {
+        Ok(previous) => previous,
         Err(e) => {
-            report.fail("Git", &format!("Cannot list tags: {}", e));
-            return None;
+            report.warn_on(
+                "Git",
+                "previous-release",
+                "",
+                &format!("Cannot find the previous release: {}", e),
+            );
+            return;
         }
     };
+    let from = previous.as_ref().map(|p| p.tag.as_str());
+    match changes(project_dir, from, &release.tag, 0) {
+        Ok(changes) => {
+            let since = from.map_or("First release".to_string(), |t| format!("Since {}", t));
+            report.pass("Git", &format!("{}: {}", since, changes.summary()));
+        }
+        Err(e) => report.warn_on(
+            "Git",
+            "change-summary",
+            "",
+            &format!("Cannot summarize the changes in {}: {}", release.tag, e),
+        ),
+    }
+}
+
+/// The release tag on HEAD, for commands that need one to proceed
+pub fn release_tag(project_dir: &Path, tags: &TagPattern) -> Result<GitInfo, GitError> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    head_tag(&repo, tags)?.ok_or_else(|| GitError::NoReleaseTag {
+        expected: tags.expected.clone(),
+    })
+}
+
+/// The release tag pointing at HEAD, if any
+fn head_tag(repo: &Repository, tags: &TagPattern) -> Result<Option<GitInfo>, GitError> {
+    let head = repo.head().map_err(GitError::on("read HEAD"))?;
+    let Some(head_oid) = head.target() else {
+        return Ok(None);
+    };
 
-    let mut found_tag: Option<(String, String)> = None;
-    for i in 0..tag_names.len() {
-        let name = match tag_names.get(i) {
-            Some(n) => n,
-            None => continue,
+    let tag_names = repo.tag_names(None).map_err(GitError::on("list tags"))?;
+    tracing::debug!("HEAD is {}; {} tag(s) to match", head_oid, tag_names.len());
+    for name in tag_names.iter().flatten() {
+        let Some(version) = tags.version(name) else {
+            continue;
         };
-        if let Some(caps) = semver_re.captures(name) {
-            // Resolve tag to commit
-            let tag_oid = match repo.revparse_single(&format!("refs/tags/{}", name)) {
-                Ok(obj) => obj.peel_to_commit().map(|c| c.id()).unwrap_or(obj.id()),
-                Err(_) => continue,
-            };
-            if tag_oid == head_oid {
-                found_tag = Some((name.to_string(), caps[1].to_string()));
-                break;
-            }
+        // Resolve tag to commit
+        let Ok(commit) = repo
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|obj| obj.peel_to_commit())
+        else {
+            continue;
+        };
+        if commit.id() == head_oid {
+            return Ok(Some(GitInfo {
+                version: version.to_string(),
+                tag: name.to_string(),
+                date: state::format_utc(commit.time().seconds())[..10].to_string(),
+            }));
         }
     }
+    Ok(None)
+}
 
-    match found_tag {
-        Some((tag, version)) => {
-            report.pass(
-                "Git",
-                &format!("HEAD is tagged: {} (version {})", tag, version),
-            );
-            Some(GitInfo { version, tag })
+/// The release tag with the highest version below `version`: the release
+/// this one follows
+pub fn previous_release(
+    project_dir: &Path,
+    tags: &TagPattern,
+    version: &str,
+) -> Result<Option<GitInfo>, GitError> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    let numbers = |v: &str| -> Vec<u64> { v.split('.').filter_map(|n| n.parse().ok()).collect() };
+    let current = numbers(version);
+
+    let tag_names = repo.tag_names(None).map_err(GitError::on("list tags"))?;
+    let mut previous: Option<(Vec<u64>, GitInfo)> = None;
+    for name in tag_names.iter().flatten() {
+        let Some(version) = tags.version(name) else {
+            continue;
+        };
+        let candidate = numbers(version);
+        if candidate >= current || previous.as_ref().is_some_and(|(p, _)| candidate <= *p) {
+            continue;
         }
-        None => {
-            report.fail("Git", "HEAD has no semver tag (expected vX.Y.Z)");
-            None
+        let Ok(commit) = repo
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|obj| obj.peel_to_commit())
+        else {
+            continue;
+        };
+        let info = GitInfo {
+            version: version.to_string(),
+            tag: name.to_string(),
+            date: state::format_utc(commit.time().seconds())[..10].to_string(),
+        };
+        previous = Some((candidate, info));
+    }
+    tracing::debug!(
+        "release before {}: {}",
+        version,
+        previous.as_ref().map_or("none", |(_, p)| p.tag.as_str())
+    );
+    Ok(previous.map(|(_, info)| info))
+}
+
+/// What changed between two release tags
+pub struct Changes {
+    pub commits: usize,
+    /// Commit authors, most commits first
+    pub contributors: Vec<String>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// `git diff --stat` output; only the summary line past `max_files` files
+    pub diffstat: String,
+}
+
+impl Changes {
+    /// `12 commits by 3 contributors (Jane Doe, ...), 8 files changed, +120 -14`
+    pub fn summary(&self) -> String {
+        let mut names = self
+            .contributors
+            .iter()
+            .take(5)
+            .cloned()
+            .collect::<Vec<_>>();
+        if self.contributors.len() > names.len() {
+            names.push(format!("{} more", self.contributors.len() - names.len()));
+        }
+        format!(
+            "{} commit(s) by {} contributor(s) ({}), {} file(s) changed, +{} -{}",
+            self.commits,
+            self.contributors.len(),
+            names.join(", "),
+            self.files_changed,
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+/// The commits, authors and files in `from..to` (two tags); everything up
+/// to `to` when `from` is `None`
+pub fn changes(
+    project_dir: &Path,
+    from: Option<&str>,
+    to: &str,
+    max_files: usize,
+) -> Result<Changes, GitError> {
+    let repo = Repository::open(project_dir).map_err(GitError::Open)?;
+    let resolve = |tag: &str| {
+        repo.revparse_single(&format!("refs/tags/{}", tag))
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(GitError::on(format!("resolve {}", tag)))
+    };
+    let old = from.map(resolve).transpose()?;
+    let new = resolve(to)?;
+
+    let mut walk = repo.revwalk().map_err(GitError::on("walk history"))?;
+    walk.push(new.id()).map_err(GitError::on("walk history"))?;
+    if let Some(old) = &old {
+        walk.hide(old.id()).map_err(GitError::on("walk history"))?;
+    }
+    let mut commits = 0;
+    // Keyed by email, so a changed display name does not count twice
+    let mut authors: Vec<(String, String, usize)> = Vec::new();
+    for oid in walk {
+        let oid = oid.map_err(GitError::on("walk history"))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(GitError::on("read a commit"))?;
+        commits += 1;
+        let author = commit.author();
+        let email = author.email().unwrap_or_default().to_lowercase();
+        match authors.iter_mut().find(|(e, _, _)| *e == email) {
+            Some((_, _, count)) => *count += 1,
+            None => authors.push((email, author.name().unwrap_or("?").to_string(), 1)),
         }
     }
+    authors.sort_by_key(|(_, _, count)| std::cmp::Reverse(*count));
+
+    let old_tree = old
+        .map(|c| c.tree())
+        .transpose()
+        .map_err(GitError::on("read a release tree"))?;
+    let new_tree = new.tree().map_err(GitError::on("read a release tree"))?;
+    let diff = repo
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)
+        .map_err(GitError::on("diff the release trees"))?;
+    let stats = diff.stats().map_err(GitError::on("count changes"))?;
+    tracing::debug!(
+        "{}..{}: {} commit(s), {} file(s) changed",
+        from.unwrap_or("(root)"),
+        to,
+        commits,
+        stats.files_changed()
+    );
+    let format = if stats.files_changed() > max_files {
+        git2::DiffStatsFormat::SHORT
+    } else {
+        git2::DiffStatsFormat::FULL
+    };
+    let buf = stats
+        .to_buf(format, 80)
+        .map_err(GitError::on("format the diffstat"))?;
+
+    Ok(Changes {
+        commits,
+        contributors: authors.into_iter().map(|(_, name, _)| name).collect(),
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+        diffstat: buf.as_str().unwrap_or_default().trim_end().to_string(),
+    })
 }
@@ -0,0 +1,137 @@
+use crate::config::Config;
+use crate::error::ForgeError;
+use crate::forge;
+use crate::report::Report;
+use git2::Repository;
+use std::path::Path;
+
+/// Online check of the repository on the primary forge: many data stewards
+/// want it public, described and tagged with topics, and the release commit
+/// on its default branch, before a DOI is minted
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
+    let forge_name = config.forge.to_string();
+    let host = forge::host(config, &forge_name);
+    let origin = forge::origin(project_dir).filter(|r| r.host.eq_ignore_ascii_case(&host));
+    let mirrors = config.mirrors.clone().unwrap_or_default();
+    let user = match forge_name.as_str() {
+        "github" => mirrors.github_user.clone(),
+        "gitlab" => mirrors.gitlab_user.clone(),
+        _ => mirrors.codeberg_user.clone(),
+    };
+    if origin.is_none() && user.is_none() {
+        report.warn_on("Hosting", "hosting-repository", &host,
+            &format!(
+                "Cannot tell which {} repository to check: origin is not on it and [mirrors] {}_user is not set",
+                host, forge_name
+            ),
+        );
+        return;
+    }
+    let (owner, repo) =
+        forge::repository(project_dir, config, &forge_name, &user.unwrap_or_default());
+    let name = format!("{}/{}/{}", host, owner, repo);
+
+    let token = mirrors.token(&forge_name).unwrap_or_default();
+    let info = match forge::repository_info(config, &owner, &repo, token) {
+        Ok(info) => info,
+        Err(ForgeError::Api { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {
+            report.fail(
+                "Hosting",
+                &format!("{} not found (or private and no token is set)", name),
+            );
+            return;
+        }
+        Err(e) => {
+            report.warn_on(
+                "Hosting",
+                "hosting-query",
+                &name,
+                &format!("Cannot query {}: {}", name, e),
+            );
+            return;
+        }
+    };
+
+    if info.public {
+        report.pass("Hosting", &format!("{} is public", name));
+    } else {
+        report.fail(
+            "Hosting",
+            &format!(
+                "{} is not public; a DOI should point at code anyone can see",
+                name
+            ),
+        );
+    }
+    if info.description.trim().is_empty() {
+        report.warn_on(
+            "Hosting",
+            "repository-description",
+            &name,
+            &format!("{} has no description", name),
+        );
+    } else {
+        report.pass("Hosting", "Repository has a description");
+    }
+    if info.topics.is_empty() {
+        report.warn_on(
+            "Hosting",
+            "repository-topics",
+            &name,
+            &format!("{} has no topics", name),
+        );
+    } else {
+        report.pass(
+            "Hosting",
+            &format!("Repository has topics ({})", info.topics.join(", ")),
+        );
+    }
+
+    if info.default_branch.is_empty() {
+        report.fail("Hosting", &format!("Nothing has been pushed to {}", name));
+    } else if origin.is_some() {
+        default_branch(project_dir, &info.default_branch, report);
+    } else {
+        report.warn_on(
+            "Hosting",
+            "release-on-default-branch",
+            "",
+            &format!(
+                "Cannot check that the release is on {}: origin does not point at {}",
+                info.default_branch, host
+            ),
+        );
+    }
+}
+
+/// Whether HEAD is on the forge's default branch, as last fetched from origin
+fn default_branch(project_dir: &Path, branch: &str, report: &mut Report) {
+    let Ok(repo) = Repository::open(project_dir) else {
+        return;
+    };
+    let Some(head) = repo.head().ok().and_then(|h| h.target()) else {
+        return;
+    };
+    let tip = repo
+        .find_reference(&format!("refs/remotes/origin/{}", branch))
+        .and_then(|r| r.peel_to_commit())
+        .map(|c| c.id());
+    match tip {
+        Ok(tip) if tip == head || repo.graph_descendant_of(tip, head).unwrap_or(false) => {
+            report.pass(
+                "Hosting",
+                &format!("Release commit is on the default branch ({})", branch),
+            );
+        }
+        Ok(_) => report.fail(
+            "Hosting",
+            &format!(
+                "Release commit is not on origin/{} (the default branch); merge and push it, or run `git fetch`",
+                branch
+            ),
+        ),
+        Err(_) => report.warn_on("Hosting", "default-branch-fetched", branch,
+            &format!("origin/{} has not been fetched; run `git fetch origin`", branch),
+        ),
+    }
+}
@@ -0,0 +1,84 @@
+use crate::config::NetworkConfig;
+use crate::http::{self, LinkError};
+use crate::metadata::citation::CitationCff;
+use crate::report::Report;
+use std::path::Path;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+enum Target {
+    Doi(String),
+    Url(String),
+}
+
+/// Online check that every DOI/URL in CITATION.cff resolves
+pub fn validate(project_dir: &Path, network: &NetworkConfig, report: &mut Report) {
+    let cff_path = project_dir.join("CITATION.cff");
+    // Missing or unparsable CITATION.cff is already reported by the citation check
+    let cff = match CitationCff::from_file(&cff_path) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let mut targets: Vec<(String, Target)> = Vec::new();
+    if let Some(url) = &cff.repository_code {
+        targets.push(("repository-code".to_string(), Target::Url(url.clone())));
+    }
+    if let Some(doi) = &cff.doi {
+        targets.push(("doi".to_string(), Target::Doi(doi.clone())));
+    }
+    if let Some(paper) = &cff.preferred_citation {
+        push_reference_targets(&mut targets, "preferred-citation", &paper.doi, &paper.url);
+    }
+    for (i, reference) in cff.references.iter().enumerate() {
+        let label = format!("references[{}]", i + 1);
+        push_reference_targets(&mut targets, &label, &reference.doi, &reference.url);
+    }
+
+    if targets.is_empty() {
+        return;
+    }
+
+    let client = match http::client(network, Some(TIMEOUT)) {
+        Ok(c) => c,
+        Err(e) => {
+            report.warn_on("Identifiers", "identifiers-unreadable", "", &e.to_string());
+            return;
+        }
+    };
+
+    for (label, target) in &targets {
+        let (value, result) = match target {
+            Target::Doi(doi) => (doi, http::doi_resolves(&client, doi)),
+            Target::Url(url) => (url, http::url_resolves(&client, url)),
+        };
+        match result {
+            Ok(()) => report.pass("Identifiers", &format!("{} resolves: {}", label, value)),
+            Err(LinkError::Broken(reason)) => report.fail(
+                "Identifiers",
+                &format!("{} does not resolve: {} ({})", label, value, reason),
+            ),
+            Err(LinkError::Unreachable(reason)) => report.warn_on(
+                "Identifiers",
+                "identifier-unchecked",
+                label,
+                &format!("{} could not be checked: {} ({})", label, value, reason),
+            ),
+        }
+    }
+}
+
+fn push_reference_targets(
+    targets: &mut Vec<(String, Target)>,
+    label: &str,
+    doi: &Option<String>,
+    url: &Option<String>,
+) {
+    if let Some(doi) = doi {
+        targets.push((format!("{} doi", label), Target::Doi(doi.clone())));
+    }
+    if let Some(url) = url {
+        targets.push((format!("{} url", label), Target::Url(url.clone())));
+    }
+}
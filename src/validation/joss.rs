@@ -0,0 +1,169 @@
+use crate::metadata::citation::CitationCff;
+use crate::report::{Report, Status};
+use crate::validation::paper::Paper;
+use std::path::Path;
+
+/// OSI-approved licenses (SPDX ids), which JOSS requires
+const OSI_LICENSES: &[&str] = &[
+    "0BSD",
+    "AFL-3.0",
+    "AGPL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-2-Clause-Patent",
+    "BSD-3-Clause",
+    "BSL-1.0",
+    "CDDL-1.0",
+    "CECILL-2.1",
+    "ECL-2.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.1",
+    "EUPL-1.2",
+    "GPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "ISC",
+    "LGPL-2.1",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "LPPL-1.3c",
+    "MIT",
+    "MIT-0",
+    "MPL-2.0",
+    "MS-PL",
+    "MS-RL",
+    "NCSA",
+    "OSL-3.0",
+    "PostgreSQL",
+    "UPL-1.0",
+    "Unlicense",
+    "Zlib",
+];
+
+/// JOSS asks for 250-1000 words
+const PAPER_WORDS: (usize, usize) = (250, 1000);
+
+/// A README shorter than this rarely covers installation, usage and the
+/// problem the software solves
+const README_WORDS: usize = 300;
+
+/// The Journal of Open Source Software submission checklist, on top of the
+/// standard checks (whose test results it reuses)
+pub fn validate(project_dir: &Path, report: &mut Report) {
+    paper(project_dir, report);
+
+    let cff = CitationCff::from_file(&project_dir.join("CITATION.cff")).ok();
+    match cff.as_ref().and_then(|c| c.license.as_deref()) {
+        Some(license) if OSI_LICENSES.contains(&license) => {
+            report.pass("JOSS", &format!("License {} is OSI-approved", license));
+        }
+        Some(license) => report.fail(
+            "JOSS",
+            &format!(
+                "License {} is not OSI-approved, which JOSS requires",
+                license
+            ),
+        ),
+        None => report.fail(
+            "JOSS",
+            "No license in CITATION.cff to check against the OSI list",
+        ),
+    }
+
+    let readme = std::fs::read_to_string(project_dir.join("README.md")).unwrap_or_default();
+    let words = readme.split_whitespace().count();
+    if words >= README_WORDS {
+        report.pass("JOSS", &format!("README is substantial ({} words)", words));
+    } else {
+        report.warn_on("JOSS", "readme-words", "",
+            &format!(
+                "README has {} words; reviewers expect the statement of need, installation and example usage there",
+                words
+            ),
+        );
+    }
+
+    let tests_pass = report
+        .results
+        .iter()
+        .any(|r| r.category == "Tests" && matches!(r.status, Status::Pass));
+    if tests_pass {
+        report.pass("JOSS", "Automated tests found");
+    } else {
+        report.fail("JOSS", "No automated tests found; JOSS requires them");
+    }
+
+    let guidelines = [
+        "CONTRIBUTING.md",
+        ".github/CONTRIBUTING.md",
+        "docs/CONTRIBUTING.md",
+    ]
+    .into_iter()
+    .find(|f| project_dir.join(f).exists());
+    let readme_section = readme
+        .lines()
+        .any(|l| l.starts_with('#') && l.to_lowercase().contains("contribut"));
+    match guidelines {
+        Some(file) => report.pass("JOSS", &format!("Contribution guidelines in {}", file)),
+        None if readme_section => {
+            report.pass("JOSS", "Contribution guidelines in README.md");
+        }
+        None => report.fail(
+            "JOSS",
+            "No contribution guidelines (CONTRIBUTING.md or a Contributing section in the README)",
+        ),
+    }
+}
+
+/// What JOSS asks of the paper beyond what the Paper checks cover
+fn paper(project_dir: &Path, report: &mut Report) {
+    let Some(path) = Paper::find(project_dir) else {
+        report.fail("JOSS", "No paper.md (or paper/paper.md)");
+        return;
+    };
+    // A paper.md that does not parse is already reported under Paper
+    let Ok(paper) = Paper::read(&path) else {
+        return;
+    };
+    let name = path.strip_prefix(project_dir).unwrap_or(&path).display();
+
+    for author in &paper.front_matter.authors {
+        if author.affiliation_indices().is_empty() {
+            report.fail(
+                "JOSS",
+                &format!("{} has no affiliation in {}", author.display_name(), name),
+            );
+        }
+    }
+
+    let headings: Vec<String> = paper.headings().iter().map(|h| h.to_lowercase()).collect();
+    for section in ["Summary", "Statement of need"] {
+        if !headings.iter().any(|h| h == &section.to_lowercase()) {
+            report.fail("JOSS", &format!("{} has no '{}' section", name, section));
+        }
+    }
+
+    let words = paper.word_count();
+    if words < PAPER_WORDS.0 || words > PAPER_WORDS.1 {
+        report.warn_on(
+            "JOSS",
+            "paper-words",
+            &name.to_string(),
+            &format!(
+                "{} has {} words; JOSS papers are {}-{} words",
+                name, words, PAPER_WORDS.0, PAPER_WORDS.1
+            ),
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Jupyter notebooks committed with their outputs: base64 plots and HTML
+//! dataframes that bloat the repository, the archive and every diff
+
+use crate::error::{IoError, ValidationError};
+use crate::report::Report;
+use git2::Repository;
+use serde_json::Value;
+use std::path::Path;
+
+/// A notebook whose outputs take more than this is flagged
+const OUTPUT_WARN_BYTES: u64 = 100_000;
+
+/// What the outputs of one notebook hold
+#[derive(Debug, Clone)]
+pub struct NotebookOutputs {
+    /// Relative to the project root
+    pub path: String,
+    /// Size of the outputs as stored in the notebook
+    pub bytes: u64,
+    pub images: usize,
+    /// HTML outputs with a table, as pandas renders a dataframe
+    pub tables: usize,
+}
+
+impl NotebookOutputs {
+    /// Whether the outputs are large enough to warn about
+    pub fn is_large(&self) -> bool {
+        self.bytes >= OUTPUT_WARN_BYTES
+    }
+}
+
+/// Check notebooks for large outputs
+pub fn validate(project_dir: &Path, report: &mut Report) {
+    let notebooks = tracked_notebooks(project_dir);
+    if notebooks.is_empty() {
+        return;
+    }
+
+    let mut large = 0;
+    for path in &notebooks {
+        match measure(project_dir, path) {
+            Ok(outputs) if outputs.is_large() => {
+                large += 1;
+                report.warn_on(
+                    "Notebooks",
+                    "notebook-outputs",
+                    path,
+                    &format!(
+                        "{} holds {:.1} MB of outputs ({} image(s), {} HTML table(s)) — \
+                         clear them with `release-scholar check --fix` or nbstripout",
+                        path,
+                        outputs.bytes as f64 / 1_000_000.0,
+                        outputs.images,
+                        outputs.tables
+                    ),
+                );
+            }
+            Ok(_) => {}
+            Err(e) => report.warn_on("Notebooks", "notebook-unreadable", path, &e.to_string()),
+        }
+    }
+    if large == 0 {
+        report.pass(
+            "Notebooks",
+            &format!("{} notebook(s) without large outputs", notebooks.len()),
+        );
+    }
+}
+
+/// The tracked notebooks whose outputs are large enough to flag
+pub fn large_outputs(project_dir: &Path) -> Vec<NotebookOutputs> {
+    tracked_notebooks(project_dir)
+        .iter()
+        .filter_map(|path| measure(project_dir, path).ok())
+        .filter(NotebookOutputs::is_large)
+        .collect()
+}
+
+/// Empty every code cell's outputs and execution count, as Jupyter's
+/// "Clear All Outputs" does, keeping the rest of the notebook as it is
+pub fn clear_outputs(project_dir: &Path, path: &str) -> Result<(), ValidationError> {
+    let full_path = project_dir.join(path);
+    let mut notebook = read(project_dir, path)?;
+    for cell in cells_mut(&mut notebook) {
+        if cell.get("cell_type").and_then(Value::as_str) == Some("code") {
+            cell["outputs"] = Value::Array(Vec::new());
+            cell["execution_count"] = Value::Null;
+        }
+    }
+
+    // Jupyter writes one-space indents and a final newline
+    let mut json = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+    serde::Serialize::serialize(&notebook, &mut serializer).map_err(ValidationError::Serialize)?;
+    json.push(b'\n');
+    std::fs::write(&full_path, json).map_err(IoError::on("write", &full_path))?;
+    Ok(())
+}
+
+fn measure(project_dir: &Path, path: &str) -> Result<NotebookOutputs, ValidationError> {
+    let notebook = read(project_dir, path)?;
+    let mut outputs = NotebookOutputs {
+        path: path.to_string(),
+        bytes: 0,
+        images: 0,
+        tables: 0,
+    };
+    let cells = notebook["cells"].as_array().into_iter().flatten();
+    for cell in cells {
+        let Some(cell_outputs) = cell.get("outputs").and_then(Value::as_array) else {
+            continue;
+        };
+        for output in cell_outputs {
+            outputs.bytes += output.to_string().len() as u64;
+            let Some(data) = output.get("data").and_then(Value::as_object) else {
+                continue;
+            };
+            if data.keys().any(|mime| mime.starts_with("image/")) {
+                outputs.images += 1;
+            }
+            let html = data.get("text/html").map(html_text).unwrap_or_default();
+            if html.contains("<table") {
+                outputs.tables += 1;
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+fn read(project_dir: &Path, path: &str) -> Result<Value, ValidationError> {
+    let full_path = project_dir.join(path);
+    let content = std::fs::read_to_string(&full_path).map_err(IoError::on("read", &full_path))?;
+    serde_json::from_str(&content).map_err(|source| ValidationError::Notebook {
+        path: path.to_string(),
+        source,
+    })
+}
+
+fn cells_mut(notebook: &mut Value) -> impl Iterator<Item = &mut Value> {
+    notebook
+        .get_mut("cells")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// Notebook text fields are a string or a list of lines
+fn html_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn tracked_notebooks(project_dir: &Path) -> Vec<String> {
+    let Ok(index) = Repository::open(project_dir).and_then(|r| r.index()) else {
+        return Vec::new();
+    };
+    index
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .filter(|path| path.ends_with(".ipynb") && project_dir.join(path).is_file())
+        .collect()
+}
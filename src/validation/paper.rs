@@ -0,0 +1,419 @@
+//! paper.md, the short paper that journals such as JOSS publish alongside
+//! the software: YAML front matter followed by Markdown.
+
+use crate::metadata::citation::{CffAuthor, CitationCff};
+use crate::report::Report;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Where journals expect the paper, relative to the project root
+const LOCATIONS: &[&str] = &[
+    "paper.md",
+    "paper/paper.md",
+    "docs/paper.md",
+    "joss/paper.md",
+];
+
+/// A JOSS `paper.md`
+pub struct Paper {
+    pub path: PathBuf,
+    pub front_matter: FrontMatter,
+    /// The Markdown after the front matter
+    pub body: String,
+}
+
+/// The paper's YAML front matter
+#[derive(Debug, Default, Deserialize)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub authors: Vec<PaperAuthor>,
+    #[serde(default)]
+    pub affiliations: Vec<PaperAffiliation>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub date: Option<String>,
+    pub bibliography: Option<String>,
+}
+
+/// An author in the front matter
+#[derive(Debug, Deserialize)]
+pub struct PaperAuthor {
+    pub name: Option<String>,
+    #[serde(rename = "given-names")]
+    pub given_names: Option<String>,
+    pub surname: Option<String>,
+    pub orcid: Option<String>,
+    /// Affiliation indices: `1`, or `"1, 2"`
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub affiliation: Option<String>,
+}
+
+/// An affiliation in the front matter
+#[derive(Debug, Deserialize)]
+pub struct PaperAffiliation {
+    pub name: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub index: Option<String>,
+}
+
+impl PaperAuthor {
+    /// The author's name as written
+    pub fn display_name(&self) -> String {
+        match (&self.name, &self.given_names, &self.surname) {
+            (Some(name), _, _) => name.clone(),
+            (None, Some(given), Some(surname)) => format!("{} {}", given, surname),
+            (None, given, surname) => given.clone().or(surname.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// The indices of the author's affiliations
+    pub fn affiliation_indices(&self) -> Vec<String> {
+        self.affiliation
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty())
+            .collect()
+    }
+}
+
+impl Paper {
+    /// The project's paper.md, if it has one
+    pub fn find(project_dir: &Path) -> Option<PathBuf> {
+        LOCATIONS
+            .iter()
+            .map(|l| project_dir.join(l))
+            .find(|p| p.is_file())
+    }
+
+    /// Read the paper at `path` and its front matter
+    pub fn read(path: &Path) -> Result<Paper, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let rest = content
+            .strip_prefix("---")
+            .ok_or("paper.md does not start with YAML front matter (---)")?;
+        let (yaml, body) = rest
+            .split_once("\n---")
+            .or_else(|| rest.split_once("\n..."))
+            .ok_or("paper.md front matter is not closed with ---")?;
+        let front_matter = serde_yaml::from_str(yaml)
+            .map_err(|e| format!("Invalid paper.md front matter: {}", e))?;
+        Ok(Paper {
+            path: path.to_path_buf(),
+            front_matter,
+            body: body.split_once('\n').map_or("", |(_, b)| b).to_string(),
+        })
+    }
+
+    /// `## Heading` texts of the body
+    pub fn headings(&self) -> Vec<String> {
+        self.body
+            .lines()
+            .filter(|l| l.starts_with('#'))
+            .map(|l| l.trim_start_matches('#').trim().to_string())
+            .collect()
+    }
+
+    /// Words in the body, not counting headings
+    pub fn word_count(&self) -> usize {
+        self.body
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .flat_map(str::split_whitespace)
+            .count()
+    }
+
+    /// The bibliography file named in the front matter, next to paper.md
+    pub fn bibliography_path(&self) -> Option<PathBuf> {
+        let name = self.front_matter.bibliography.as_deref()?;
+        Some(self.path.parent()?.join(name))
+    }
+
+    /// Pandoc citation keys (`@key`, `[@key, p. 3]`, `@{key}`) outside code
+    pub fn citation_keys(&self) -> Vec<String> {
+        let code = Regex::new(r"(?s)```.*?```|`[^`\n]*`").unwrap();
+        let citation =
+            Regex::new(r"(?:^|[^\w@])@(?:\{([^}\s]+)\}|(\w(?:[\w:.#$%&+?<>~/-]*\w)?))").unwrap();
+        let body = code.replace_all(&self.body, "");
+        let mut keys: Vec<String> = Vec::new();
+        for captures in citation.captures_iter(&body) {
+            let key = captures.get(1).or(captures.get(2)).unwrap().as_str();
+            if !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+        keys
+    }
+
+    fn affiliation(&self, index: &str) -> Option<&PaperAffiliation> {
+        self.front_matter
+            .affiliations
+            .iter()
+            .find(|a| a.index.as_deref() == Some(index))
+    }
+}
+
+/// Entry keys of a BibTeX file
+pub fn bibtex_keys(content: &str) -> HashSet<String> {
+    let entry = Regex::new(r"@(\w+)\s*[{(]\s*([^,\s]+)\s*,").unwrap();
+    entry
+        .captures_iter(content)
+        .filter(|c| !["comment", "string", "preamble"].contains(&c[1].to_lowercase().as_str()))
+        .map(|c| c[2].to_string())
+        .collect()
+}
+
+/// A paper.md, if the project has one, must be complete, cite only what its
+/// bibliography holds, and list the authors as CITATION.cff does
+pub fn validate(project_dir: &Path, report: &mut Report) {
+    let Some(path) = Paper::find(project_dir) else {
+        return;
+    };
+    let paper = match Paper::read(&path) {
+        Ok(p) => p,
+        Err(e) => {
+            report.fail("Paper", &e);
+            return;
+        }
+    };
+    let name = path.strip_prefix(project_dir).unwrap_or(&path).display();
+    let front = &paper.front_matter;
+
+    let mut missing = Vec::new();
+    if front.title.as_deref().is_none_or(|t| t.trim().is_empty()) {
+        missing.push("title");
+    }
+    if front.tags.is_empty() {
+        missing.push("tags");
+    }
+    if front.authors.is_empty() {
+        missing.push("authors");
+    }
+    if front.date.is_none() {
+        missing.push("date");
+    }
+    if front.bibliography.is_none() {
+        missing.push("bibliography");
+    }
+    if missing.is_empty() {
+        report.pass("Paper", &format!("{} front matter is complete", name));
+    } else {
+        report.fail(
+            "Paper",
+            &format!("{} front matter lacks: {}", name, missing.join(", ")),
+        );
+    }
+
+    // The journal templates write `13 August 2017`
+    let date_re = Regex::new(
+        r"^(\d{1,2} (January|February|March|April|May|June|July|August|September|October|November|December) \d{4}|\d{4}-\d{2}-\d{2})$",
+    )
+    .unwrap();
+    if let Some(date) = &front.date {
+        if !date_re.is_match(date.trim()) {
+            report.warn_on(
+                "Paper",
+                "paper-date",
+                &name.to_string(),
+                &format!(
+                    "{} date '{}' is not a date such as '13 August 2017' or 2017-08-13",
+                    name, date
+                ),
+            );
+        }
+    }
+
+    for author in &front.authors {
+        for index in author.affiliation_indices() {
+            if paper.affiliation(&index).is_none() {
+                report.fail(
+                    "Paper",
+                    &format!(
+                        "{} refers to affiliation {}, which {} does not list",
+                        author.display_name(),
+                        index,
+                        name
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Ok(cff) = CitationCff::from_file(&project_dir.join("CITATION.cff")) {
+        authors(&paper, &name.to_string(), &cff, report);
+    }
+
+    let Some(bib) = paper.bibliography_path() else {
+        return;
+    };
+    let content = match std::fs::read_to_string(&bib) {
+        Ok(c) => c,
+        Err(_) => {
+            report.fail(
+                "Paper",
+                &format!("Bibliography {} not found", bib.display()),
+            );
+            return;
+        }
+    };
+    let available = bibtex_keys(&content);
+    let keys = paper.citation_keys();
+    let unresolved: Vec<&str> = keys
+        .iter()
+        .filter(|k| !available.contains(*k))
+        .map(String::as_str)
+        .collect();
+    let bib_name = front.bibliography.as_deref().unwrap_or_default();
+    if unresolved.is_empty() {
+        report.pass(
+            "Paper",
+            &format!(
+                "All {} citation(s) in {} resolve in {}",
+                keys.len(),
+                name,
+                bib_name
+            ),
+        );
+    } else {
+        report.fail(
+            "Paper",
+            &format!(
+                "{} cites keys missing from {}: {}",
+                name,
+                bib_name,
+                unresolved.join(", ")
+            ),
+        );
+    }
+}
+
+/// The paper's authors against CITATION.cff: the same people, with the same
+/// ORCIDs and affiliations
+fn authors(paper: &Paper, name: &str, cff: &CitationCff, report: &mut Report) {
+    let mut consistent = true;
+    for author in &paper.front_matter.authors {
+        let display = author.display_name();
+        let Some(cff_author) = cff
+            .authors
+            .iter()
+            .find(|a| cff_name(a).eq_ignore_ascii_case(display.trim()))
+        else {
+            report.warn_on(
+                "Paper",
+                "paper-author-not-in-citation",
+                &display,
+                &format!("{} author {} is not in CITATION.cff", name, display),
+            );
+            consistent = false;
+            continue;
+        };
+
+        match (
+            bare_orcid(author.orcid.as_deref()),
+            bare_orcid(cff_author.orcid.as_deref()),
+        ) {
+            (Some(a), Some(b)) if a != b => {
+                report.fail(
+                    "Paper",
+                    &format!(
+                        "{} has ORCID {} in {} but {} in CITATION.cff",
+                        display, a, name, b
+                    ),
+                );
+                consistent = false;
+            }
+            (None, Some(b)) => {
+                report.warn_on(
+                    "Paper",
+                    "paper-author-orcid",
+                    &display,
+                    &format!("{} has no ORCID in {} (CITATION.cff: {})", display, name, b),
+                );
+                consistent = false;
+            }
+            _ => {}
+        }
+
+        if let Some(affiliation) = &cff_author.affiliation {
+            let listed = author
+                .affiliation_indices()
+                .iter()
+                .filter_map(|i| paper.affiliation(i))
+                .any(|a| a.name.trim().eq_ignore_ascii_case(affiliation.trim()));
+            if !listed {
+                report.warn_on(
+                    "Paper",
+                    "paper-author-affiliation",
+                    &display,
+                    &format!(
+                        "{}'s affiliation in CITATION.cff ({}) is not among theirs in {}",
+                        display, affiliation, name
+                    ),
+                );
+                consistent = false;
+            }
+        }
+    }
+
+    for cff_author in &cff.authors {
+        let cff_display = cff_name(cff_author);
+        let in_paper = paper
+            .front_matter
+            .authors
+            .iter()
+            .any(|a| a.display_name().trim().eq_ignore_ascii_case(&cff_display));
+        if !in_paper {
+            report.warn_on(
+                "Paper",
+                "citation-author-not-in-paper",
+                &cff_display,
+                &format!("CITATION.cff author {} is not in {}", cff_display, name),
+            );
+            consistent = false;
+        }
+    }
+
+    if consistent {
+        report.pass("Paper", &format!("{} authors match CITATION.cff", name));
+    }
+}
+
+fn cff_name(author: &CffAuthor) -> String {
+    match (&author.given_names, &author.family_names, &author.name) {
+        (Some(given), Some(family), _) => format!("{} {}", given, family),
+        (None, Some(family), _) => family.clone(),
+        (_, None, Some(name)) => name.clone(),
+        _ => String::new(),
+    }
+}
+
+/// `0000-0002-1825-0097`, whether or not the ORCID is written as a URL
+fn bare_orcid(orcid: Option<&str>) -> Option<&str> {
+    let orcid = orcid?.trim();
+    Some(
+        orcid
+            .strip_prefix("https://orcid.org/")
+            .or_else(|| orcid.strip_prefix("http://orcid.org/"))
+            .unwrap_or(orcid),
+    )
+}
+
+/// YAML reads `affiliation: 1` (or `date: 2024`) as a number; keep it as a string
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+            Some(serde_yaml::Value::String(s)) => Some(s),
+            Some(serde_yaml::Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        },
+    )
+}
@@ -0,0 +1,224 @@
+use crate::config::{Config, NetworkConfig};
+use crate::http::{self, LinkError};
+use crate::report::Report;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const LINK_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// Check README.md for the configured sections and a pointer to CITATION.cff
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
+    let readme_path = project_dir.join("README.md");
+    // A missing README is already reported by the files check
+    let content = match std::fs::read_to_string(&readme_path) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    // A `# comment` in a shell example is not a section; nor is a line
+    // indented as code
+    let headings: Vec<String> = outside_fences(&content)
+        .filter(|l| l.len() - l.trim_start().len() < 4 && l.trim_start().starts_with('#'))
+        .map(|l| l.trim_start().trim_start_matches('#').trim().to_lowercase())
+        .collect();
+
+    for section in &config.readme_sections {
+        let alternatives: Vec<&str> = section.split('|').map(str::trim).collect();
+        let label = alternatives.first().copied().unwrap_or(section);
+        let found = alternatives.iter().any(|alt| {
+            let alt = alt.to_lowercase();
+            headings.iter().any(|h| h.contains(&alt))
+        });
+        if found {
+            report.pass("README", &format!("'{}' section present", label));
+        } else {
+            report.warn_on(
+                "README",
+                "readme-section",
+                label,
+                &format!("'{}' section missing", label),
+            );
+        }
+    }
+
+    if content.contains("CITATION.cff") {
+        report.pass("README", "Mentions CITATION.cff");
+    } else {
+        report.warn_on(
+            "README",
+            "readme-citation",
+            "",
+            "Does not mention CITATION.cff — tell readers how to cite the software",
+        );
+    }
+}
+
+/// Check links in README.md: relative links must point at existing files,
+/// external links must resolve (only when `online`)
+pub fn validate_links(
+    project_dir: &Path,
+    online: bool,
+    network: &NetworkConfig,
+    report: &mut Report,
+) {
+    let readme_path = project_dir.join("README.md");
+    let content = match std::fs::read_to_string(&readme_path) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let links = extract_links(&content);
+    let (external, other): (Vec<String>, Vec<String>) = links
+        .into_iter()
+        .partition(|l| l.starts_with("http://") || l.starts_with("https://"));
+    // ftp:, doi:, tel:, //host/... name no file in the project, and
+    // are not checked
+    let scheme_re = Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap();
+    let relative: Vec<String> = other
+        .into_iter()
+        .filter(|l| !scheme_re.is_match(l) && !l.starts_with("//"))
+        .collect();
+
+    // Relative links can be checked without network access
+    let mut broken_relative = 0;
+    for link in &relative {
+        let path = link.split(['?', '#']).next().unwrap_or("");
+        let path = percent_decode_str(path).decode_utf8_lossy();
+        // `/docs/x.md` is relative to the repository root, as on the forges
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            continue;
+        }
+        if !project_dir.join(path).exists() {
+            report.fail("README", &format!("Broken relative link: {}", link));
+            broken_relative += 1;
+        }
+    }
+    if broken_relative == 0 && !relative.is_empty() {
+        report.pass(
+            "README",
+            &format!(
+                "{} relative link(s) point to existing files",
+                relative.len()
+            ),
+        );
+    }
+
+    if !online || external.is_empty() {
+        return;
+    }
+
+    let client = match http::client(network, Some(LINK_TIMEOUT)) {
+        Ok(c) => c,
+        Err(e) => {
+            report.warn_on("README", "readme-unreadable", "", &e.to_string());
+            return;
+        }
+    };
+
+    // Bounded pool of workers pulling from a shared index
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<Result<(), LinkError>>>> =
+        external.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..MAX_CONCURRENT_REQUESTS.min(external.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let url = match external.get(i) {
+                    Some(u) => u,
+                    None => break,
+                };
+                let result = http::url_resolves(&client, url);
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    let mut resolved = 0;
+    let mut broken = 0;
+    for (url, result) in external.iter().zip(results) {
+        match result.into_inner().unwrap() {
+            Some(Ok(())) => resolved += 1,
+            Some(Err(LinkError::Broken(reason))) => {
+                report.fail("README", &format!("Dead link: {} ({})", url, reason));
+                broken += 1;
+            }
+            Some(Err(LinkError::Unreachable(reason))) => {
+                report.warn_on(
+                    "README",
+                    "link-unchecked",
+                    url,
+                    &format!("Link could not be checked: {} ({})", url, reason),
+                );
+            }
+            None => {}
+        }
+    }
+    if broken == 0 && resolved > 0 {
+        report.pass("README", &format!("{} external link(s) resolve", resolved));
+    }
+}
+
+/// The lines of `markdown` outside fenced code blocks (``` or ~~~). A
+/// fence is closed by one of the same character at least as long; an
+/// unclosed fence runs to the end.
+fn outside_fences(markdown: &str) -> impl Iterator<Item = &str> {
+    let mut fence: Option<(char, usize)> = None;
+    markdown.lines().filter(move |line| {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let run = marker.map_or(0, |c| trimmed.chars().take_while(|x| *x == c).count());
+        match (fence, marker) {
+            (None, Some(c)) if run >= 3 => {
+                fence = Some((c, run));
+                false
+            }
+            (Some((open, len)), Some(c))
+                if c == open && run >= len && trimmed[run..].trim().is_empty() =>
+            {
+                fence = None;
+                false
+            }
+            (Some(_), _) => false,
+            (None, _) => true,
+        }
+    })
+}
+
+/// Extract link targets from markdown, ignoring code blocks and spans
+fn extract_links(markdown: &str) -> Vec<String> {
+    let code_span_re = Regex::new(r"`[^`\n]*`").unwrap();
+    let text = outside_fences(markdown).collect::<Vec<_>>().join("\n");
+    let text = code_span_re.replace_all(&text, "");
+
+    let patterns = [
+        // [text](target "title") and ![alt](target)
+        r#"\]\(\s*<?([^)\s>]+)>?(?:\s+"[^"]*")?\s*\)"#,
+        // [ref]: target
+        r"(?m)^\s*\[[^\]]+\]:\s*<?(\S+?)>?\s*$",
+        // <https://...>
+        r"<(https?://[^>\s]+)>",
+        // HTML href/src attributes
+        r#"(?:href|src)\s*=\s*"([^"]+)""#,
+    ];
+
+    let mut links = Vec::new();
+    for pattern in patterns {
+        let re = Regex::new(pattern).unwrap();
+        for caps in re.captures_iter(&text) {
+            let link = caps[1].to_string();
+            if link.starts_with('#') || link.starts_with("mailto:") {
+                continue;
+            }
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+    }
+    links
+}
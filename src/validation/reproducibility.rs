@@ -0,0 +1,340 @@
+use crate::config::Config;
+use crate::report::Report;
+use crate::validation::file_contains;
+use git2::Repository;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Manifest → accepted lockfiles, per ecosystem
+const LOCKFILES: &[(&str, &[&str], &str)] = &[
+    (
+        "package.json",
+        &[
+            "package-lock.json",
+            "npm-shrinkwrap.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+            "bun.lockb",
+        ],
+        "Node.js",
+    ),
+    ("Gemfile", &["Gemfile.lock"], "Ruby"),
+    ("composer.json", &["composer.lock"], "PHP"),
+    ("Pipfile", &["Pipfile.lock"], "Pipenv"),
+    (
+        "environment.yml",
+        &["conda-lock.yml", "explicit.txt"],
+        "Conda",
+    ),
+];
+
+const PYTHON_LOCKFILES: &[&str] = &[
+    "poetry.lock",
+    "pdm.lock",
+    "uv.lock",
+    "Pipfile.lock",
+    "requirements.lock",
+    "conda-lock.yml",
+];
+
+/// Check for lockfiles, a pinned toolchain and reproducible containers
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
+    let tracked = tracked_files(project_dir);
+    validate_lockfiles(project_dir, &tracked, report);
+    validate_containers(project_dir, &tracked, config, report);
+}
+
+fn validate_lockfiles(project_dir: &Path, tracked: &Option<HashSet<String>>, report: &mut Report) {
+    let present = |path: &str| match tracked {
+        Some(files) => files.contains(path),
+        None => project_dir.join(path).exists(),
+    };
+
+    let mut checked = 0;
+
+    // Rust: lockfiles matter for binaries; libraries conventionally omit them
+    if present("Cargo.toml") && is_rust_binary(project_dir) {
+        checked += 1;
+        if present("Cargo.lock") {
+            report.pass("Reproducibility", "Cargo.lock committed");
+        } else {
+            report.warn_on(
+                "Reproducibility",
+                "cargo-lock",
+                "",
+                "Cargo.toml has binary targets but Cargo.lock is not committed",
+            );
+        }
+    }
+
+    // Python: a lockfile, or fully pinned requirements
+    if present("pyproject.toml") || present("setup.py") || present("requirements.txt") {
+        checked += 1;
+        if let Some(lock) = PYTHON_LOCKFILES.iter().find(|l| present(l)) {
+            report.pass("Reproducibility", &format!("{} committed", lock));
+        } else if present("requirements.txt") {
+            let unpinned = unpinned_requirements(&project_dir.join("requirements.txt"));
+            if unpinned.is_empty() {
+                report.pass("Reproducibility", "requirements.txt pins every dependency");
+            } else {
+                report.warn_on(
+                    "Reproducibility",
+                    "unpinned-requirements",
+                    "",
+                    &format!(
+                        "requirements.txt has {} unpinned dependency(ies): {}",
+                        unpinned.len(),
+                        unpinned
+                            .iter()
+                            .take(5)
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                );
+            }
+        } else {
+            report.warn_on("Reproducibility", "python-lockfile", "",
+                "Python project has no lockfile (poetry.lock, uv.lock, pdm.lock, ...) or pinned requirements.txt",
+            );
+        }
+    }
+
+    for (manifest, locks, ecosystem) in LOCKFILES {
+        if !present(manifest) {
+            continue;
+        }
+        checked += 1;
+        match locks.iter().find(|l| present(l)) {
+            Some(lock) => report.pass("Reproducibility", &format!("{} committed", lock)),
+            None => report.warn_on(
+                "Reproducibility",
+                "lockfile",
+                manifest,
+                &format!(
+                    "{} ({}) has no committed lockfile ({})",
+                    manifest,
+                    ecosystem,
+                    locks.join(", ")
+                ),
+            ),
+        }
+    }
+
+    // Go: go.sum is only needed once there are dependencies
+    if present("go.mod") && file_contains(&project_dir.join("go.mod"), "require") {
+        checked += 1;
+        if present("go.sum") {
+            report.pass("Reproducibility", "go.sum committed");
+        } else {
+            report.warn_on(
+                "Reproducibility",
+                "go-sum",
+                "",
+                "go.mod has dependencies but go.sum is not committed",
+            );
+        }
+    }
+
+    if checked == 0 {
+        report.pass("Reproducibility", "No dependency manifests detected");
+    }
+}
+
+/// Base images in container recipes must be pinned (digest or version tag)
+fn validate_containers(
+    project_dir: &Path,
+    tracked: &Option<HashSet<String>>,
+    config: &Config,
+    report: &mut Report,
+) {
+    let recipes: Vec<String> = match tracked {
+        Some(files) => {
+            let mut recipes: Vec<String> = files
+                .iter()
+                .filter(|p| is_container_recipe(p))
+                .cloned()
+                .collect();
+            recipes.sort();
+            recipes
+        }
+        None => ["Dockerfile", "Containerfile"]
+            .iter()
+            .filter(|f| project_dir.join(f).exists())
+            .map(|f| f.to_string())
+            .collect(),
+    };
+    if recipes.is_empty() {
+        return;
+    }
+
+    for recipe in &recipes {
+        let content = match std::fs::read_to_string(project_dir.join(recipe)) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        for image in base_images(&content) {
+            match image_pinning(&image) {
+                Pinning::Digest => report.pass(
+                    "Container",
+                    &format!("{}: {} pinned by digest", recipe, image),
+                ),
+                Pinning::VersionTag => report.pass(
+                    "Container",
+                    &format!("{}: {} pinned to a version tag", recipe, image),
+                ),
+                Pinning::Variable => report.warn_on(
+                    "Container",
+                    "base-image-variable",
+                    &format!("{}: {}", recipe, image),
+                    &format!(
+                        "{}: base image {} comes from a build argument — cannot verify pinning",
+                        recipe, image
+                    ),
+                ),
+                Pinning::Unpinned(reason) => report.warn_on(
+                    "Container",
+                    "base-image-unpinned",
+                    &format!("{}: {}", recipe, image),
+                    &format!(
+                        "{}: base image {} is {} — pin a version tag or @sha256 digest",
+                        recipe, image, reason
+                    ),
+                ),
+            }
+        }
+    }
+
+    let require_reference = config
+        .container
+        .as_ref()
+        .is_some_and(|c| c.require_image_reference);
+    if require_reference {
+        if has_image_reference(project_dir) {
+            report.pass(
+                "Container",
+                "Container image reference recorded in metadata",
+            );
+        } else {
+            report.warn_on("Container", "container-image", "",
+                "No container image reference in codemeta.json, CITATION.cff references, or .zenodo.json",
+            );
+        }
+    }
+}
+
+enum Pinning {
+    Digest,
+    VersionTag,
+    Variable,
+    Unpinned(&'static str),
+}
+
+fn is_container_recipe(path: &str) -> bool {
+    let name = Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    name == "Dockerfile"
+        || name == "Containerfile"
+        || name == "Singularity"
+        || name.starts_with("Dockerfile.")
+        || name.ends_with(".Dockerfile")
+        || name.ends_with(".dockerfile")
+}
+
+/// Base images from Dockerfile `FROM` lines and Apptainer/Singularity `From:` headers,
+/// skipping references to earlier build stages and `scratch`
+fn base_images(content: &str) -> Vec<String> {
+    let mut stages: Vec<String> = Vec::new();
+    let mut images = Vec::new();
+    for line in content.lines() {
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or("");
+        if keyword.eq_ignore_ascii_case("FROM") {
+            let args: Vec<&str> = words.filter(|w| !w.starts_with("--")).collect();
+            let image = match args.first() {
+                Some(i) => i.to_string(),
+                None => continue,
+            };
+            if image != "scratch" && !stages.contains(&image.to_lowercase()) {
+                images.push(image);
+            }
+            if let (Some(kw), Some(name)) = (args.get(1), args.get(2)) {
+                if kw.eq_ignore_ascii_case("AS") {
+                    stages.push(name.to_lowercase());
+                }
+            }
+        } else if keyword == "From:" {
+            if let Some(image) = words.next() {
+                images.push(image.to_string());
+            }
+        }
+    }
+    images
+}
+
+fn image_pinning(image: &str) -> Pinning {
+    if image.contains('$') {
+        return Pinning::Variable;
+    }
+    if image.contains("@sha256:") {
+        return Pinning::Digest;
+    }
+    // The tag follows the last ':' after the last '/' (registry ports contain ':')
+    let name = image.rsplit('/').next().unwrap_or(image);
+    match name.split_once(':') {
+        None => Pinning::Unpinned("untagged (implicitly latest)"),
+        Some((_, "latest")) => Pinning::Unpinned("tagged latest"),
+        Some((_, tag)) if tag.chars().any(|c| c.is_ascii_digit()) => Pinning::VersionTag,
+        Some(_) => Pinning::Unpinned("on a mutable tag"),
+    }
+}
+
+/// Look for a container image reference in the project's metadata files
+fn has_image_reference(project_dir: &Path) -> bool {
+    let registries = ["docker.io", "ghcr.io", "quay.io", "registry.", "docker://"];
+    let mentions_registry = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map(|c| registries.iter().any(|r| c.contains(r)))
+            .unwrap_or(false)
+    };
+    if mentions_registry(&project_dir.join("codemeta.json"))
+        || mentions_registry(&project_dir.join(".zenodo.json"))
+    {
+        return true;
+    }
+    std::fs::read_to_string(project_dir.join("CITATION.cff"))
+        .map(|c| c.contains("type: software-container"))
+        .unwrap_or(false)
+}
+
+/// Paths in the git index, or None if the project is not a repository
+fn tracked_files(project_dir: &Path) -> Option<HashSet<String>> {
+    let repo = Repository::open(project_dir).ok()?;
+    let index = repo.index().ok()?;
+    Some(
+        index
+            .iter()
+            .map(|e| String::from_utf8_lossy(&e.path).to_string())
+            .collect(),
+    )
+}
+
+fn is_rust_binary(project_dir: &Path) -> bool {
+    project_dir.join("src/main.rs").exists()
+        || project_dir.join("src/bin").is_dir()
+        || file_contains(&project_dir.join("Cargo.toml"), "[[bin]]")
+}
+
+/// Requirement lines not pinned with `==`, `===` or a direct `@` reference
+fn unpinned_requirements(path: &Path) -> Vec<String> {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('-'))
+        .filter(|l| !l.contains("==") && !l.contains(" @ "))
+        .map(String::from)
+        .collect()
+}
@@ -1,7 +1,13 @@
+mod scan_cache;
+
+use crate::config::{Config, DataConfig};
 use crate::report::Report;
-use git2::Repository;
+use git2::{DiffOptions, Repository};
 use regex::Regex;
+use scan_cache::ScanCache;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 
 // severity: true = FAIL (high confidence), false = WARN (often false positive)
 const SECRET_PATTERNS: &[(&str, &str, bool)] = &[
@@ -47,11 +53,39 @@ const SENSITIVE_FILE_PATTERNS: &[&str] = &[
     ".pfx",
 ];
 
+/// Sensitive file patterns that are ordinary in declared data directories
+const DATA_FILE_PATTERNS: &[&str] = &[".sqlite"];
+
 const RECOMMENDED_GITIGNORE_PATTERNS: &[&str] = &[".env", ".DS_Store", "*.pem", "*.key", "id_rsa"];
 
+/// A secret pattern matched on a line that a change adds
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub kind: &'static str,
+    /// A high-confidence pattern; the others are often false positives
+    pub certain: bool,
+    pub path: String,
+    pub line: u32,
+    /// The line, with the match masked
+    pub text: String,
+    /// The hunk header and up to three lines either side, as in a diff,
+    /// with any secrets on them masked
+    pub context: Vec<String>,
+}
+
+/// What a scan of the lines some changes add found
+#[derive(Debug, Default)]
+pub struct DiffScan {
+    pub findings: Vec<Finding>,
+    /// Files whose new content is too large to diff, and in a pack (which
+    /// cannot be streamed) too large to inflate, so went unscanned
+    pub unscanned: Vec<String>,
+}
+
 // Common build artifact patterns by ecosystem
 
-pub fn validate(project_dir: &Path, report: &mut Report) {
+/// Scan the tracked files and the git history for secrets
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
     let repo = match Repository::open(project_dir) {
         Ok(r) => r,
         Err(_) => {
@@ -60,55 +94,388 @@ pub fn validate(project_dir: &Path, report: &mut Report) {
         }
     };
 
-    scan_tracked_files_for_secrets(&repo, project_dir, report);
-    scan_sensitive_files(&repo, report);
+    let data = config.data();
+    scan_tracked_files_for_secrets(&repo, project_dir, &data, report);
+    scan_sensitive_files(&repo, &data, report);
     scan_git_history(&repo, report);
     audit_gitignore(project_dir, report);
 }
 
-fn scan_tracked_files_for_secrets(repo: &Repository, project_dir: &Path, report: &mut Report) {
-    let patterns: Vec<(Regex, &str, bool)> = SECRET_PATTERNS
+fn secret_patterns() -> Vec<(Regex, &'static str, bool)> {
+    SECRET_PATTERNS
         .iter()
         .filter_map(|(pat, name, is_fail)| Regex::new(pat).ok().map(|r| (r, *name, *is_fail)))
-        .collect();
+        .collect()
+}
+
+/// Diffing inflates both sides of a change in memory, so blobs larger than
+/// this are left out of diffs; they are streamed through the patterns
+/// instead, a line at a time (see [`stream_blob`])
+const DIFF_MAX_BLOB_SIZE: i64 = 16 * 1024 * 1024;
+
+/// How far into a streamed blob to look for a NUL byte, which makes it
+/// binary (as git decides)
+const BINARY_PROBE: usize = 8000;
+
+fn diff_options() -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options.max_size(DIFF_MAX_BLOB_SIZE);
+    options
+}
+
+/// Secrets on the lines `commit` adds to its first parent
+pub fn scan_commit(repo: &Repository, commit: &git2::Commit) -> Result<DiffScan, git2::Error> {
+    let tree = commit.tree()?;
+    let parent_tree = commit.parent(0).ok().map(|p| p.tree()).transpose()?;
+    let diff =
+        repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_options()))?;
+    scan_diff(repo, &diff)
+}
+
+/// Secrets on the lines the index adds to HEAD: what the next commit would
+/// contain, without reading the rest of the tree
+pub fn scan_staged(repo: &Repository) -> Result<DiffScan, git2::Error> {
+    // An unborn branch has no HEAD; everything staged is then new
+    let head = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_index(head.as_ref(), None, Some(&mut diff_options()))?;
+    tracing::debug!("{} file(s) staged", diff.deltas().len());
+    scan_diff(repo, &diff)
+}
+
+const CONTEXT: usize = 3;
+
+/// Secrets on the lines `diff` adds. A file too large to diff is scanned
+/// whole, so the lines it already had are reported again.
+fn scan_diff(repo: &Repository, diff: &git2::Diff) -> Result<DiffScan, git2::Error> {
+    let patterns = secret_patterns();
+    let mut findings = Vec::new();
+    for idx in 0..diff.deltas().len() {
+        // None for binary files
+        let Some(patch) = git2::Patch::from_diff(diff, idx)? else {
+            continue;
+        };
+        let path = patch
+            .delta()
+            .new_file()
+            .path()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for h in 0..patch.num_hunks() {
+            let (hunk, count) = patch.hunk(h)?;
+            let mut lines = Vec::with_capacity(count);
+            for l in 0..count {
+                let line = patch.line_in_hunk(h, l)?;
+                let text = String::from_utf8_lossy(line.content())
+                    .trim_end()
+                    .to_string();
+                lines.push((line.origin(), line.new_lineno(), text));
+            }
+            let header = String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string();
+            for (i, (origin, lineno, text)) in lines.iter().enumerate() {
+                if *origin != '+' {
+                    continue;
+                }
+                for (re, kind, certain) in &patterns {
+                    if !re.is_match(text) {
+                        continue;
+                    }
+                    let around =
+                        &lines[i.saturating_sub(CONTEXT)..(i + CONTEXT + 1).min(lines.len())];
+                    let context = std::iter::once(header.clone())
+                        .chain(
+                            around
+                                .iter()
+                                .map(|(o, _, t)| format!("{}{}", o, mask_all(&patterns, t))),
+                        )
+                        .collect();
+                    findings.push(Finding {
+                        kind,
+                        certain: *certain,
+                        path: path.clone(),
+                        line: lineno.unwrap_or(0),
+                        text: mask(re, text),
+                        context,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut unscanned = Vec::new();
+    for (path, oid) in large_blobs(repo, diff)? {
+        let streamed = stream_blob(repo, oid, |first_line, chunk| {
+            // Most chunks match nothing; only look line by line at the rest
+            if !patterns.iter().any(|(re, _, _)| re.is_match(chunk)) {
+                return;
+            }
+            let lines: Vec<&str> = chunk.lines().collect();
+            for (i, text) in lines.iter().enumerate() {
+                for (re, kind, certain) in &patterns {
+                    if !re.is_match(text) {
+                        continue;
+                    }
+                    let context = std::iter::once("@@ too large to diff; scanned whole @@".into())
+                        .chain(
+                            lines[i.saturating_sub(CONTEXT)..i]
+                                .iter()
+                                .map(|t| format!(" {}", mask_all(&patterns, t))),
+                        )
+                        .chain(std::iter::once(format!("+{}", mask_all(&patterns, text))))
+                        .collect();
+                    findings.push(Finding {
+                        kind,
+                        certain: *certain,
+                        path: path.clone(),
+                        line: first_line + i as u32,
+                        text: mask(re, text),
+                        context,
+                    });
+                }
+            }
+        });
+        if let Err(e) = streamed {
+            tracing::debug!("cannot stream {} ({}): {}", path, oid, e);
+            unscanned.push(path);
+        }
+    }
+    Ok(DiffScan {
+        findings,
+        unscanned,
+    })
+}
+
+/// The new side of each change in `diff` that is too large for the diff to
+/// have read, with its path
+fn large_blobs(
+    repo: &Repository,
+    diff: &git2::Diff,
+) -> Result<Vec<(String, git2::Oid)>, git2::Error> {
+    let odb = repo.odb()?;
+    let mut blobs = Vec::new();
+    for delta in diff.deltas() {
+        let file = delta.new_file();
+        if delta.status() == git2::Delta::Deleted || file.id().is_zero() {
+            continue;
+        }
+        let Ok((size, git2::ObjectType::Blob)) = odb.read_header(file.id()) else {
+            continue;
+        };
+        if size as i64 > DIFF_MAX_BLOB_SIZE {
+            let path = file
+                .path()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            blobs.push((path, file.id()));
+        }
+    }
+    Ok(blobs)
+}
+
+/// How much of a blob [`stream_blob`] hands over at a time
+const STREAM_CHUNK: u64 = 8 * 1024 * 1024;
+
+/// The largest packed blob [`stream_blob`] inflates in memory
+const INFLATE_MAX: usize = 256 * 1024 * 1024;
+
+/// Call `chunk` with the blob `oid` a few MB of whole lines at a time, and
+/// the number of the first line in each, reading it from the object
+/// database rather than inflating it whole. Binary blobs are skipped. Git
+/// can only stream loose objects: a packed blob is inflated, unless it is
+/// larger than [`INFLATE_MAX`], which fails.
+fn stream_blob(
+    repo: &Repository,
+    oid: git2::Oid,
+    mut chunk: impl FnMut(u32, &str),
+) -> Result<(), git2::Error> {
+    let odb = repo.odb()?;
+    let blob;
+    let reader: Box<dyn Read> = match odb.reader(oid) {
+        Ok((reader, _, _)) => Box::new(reader),
+        Err(e) => {
+            if odb.read_header(oid)?.0 > INFLATE_MAX {
+                return Err(e);
+            }
+            blob = repo.find_blob(oid)?;
+            Box::new(blob.content())
+        }
+    };
+    let mut reader = BufReader::new(reader);
+    let io_error = |e: std::io::Error| git2::Error::from_str(&e.to_string());
+    let head = reader.fill_buf().map_err(io_error)?;
+    if head[..head.len().min(BINARY_PROBE)].contains(&0) {
+        return Ok(());
+    }
+    let mut first_line = 1;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        (&mut reader)
+            .take(STREAM_CHUNK)
+            .read_to_end(&mut buf)
+            .map_err(io_error)?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+        // Up to the end of the line the chunk stopped in
+        reader.read_until(b'\n', &mut buf).map_err(io_error)?;
+        chunk(first_line, &String::from_utf8_lossy(&buf));
+        first_line += buf.iter().filter(|&&b| b == b'\n').count() as u32;
+    }
+}
+
+fn mask_all(patterns: &[(Regex, &str, bool)], line: &str) -> String {
+    patterns
+        .iter()
+        .fold(line.to_string(), |line, (re, _, _)| mask(re, &line))
+}
+
+/// `line` with what `re` matches cut down to its first four characters, so
+/// reporting a secret does not spread it further
+fn mask(re: &Regex, line: &str) -> String {
+    re.replace_all(line, |caps: &regex::Captures| {
+        let head: String = caps[0].chars().take(4).collect();
+        format!("{}****", head)
+    })
+    .into_owned()
+}
+
+/// Files at least this large are memory-mapped instead of read into memory,
+/// so scanning a multi-GB data file does not need that much RAM
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// A tracked file's bytes, read or mapped
+enum FileContent {
+    Read(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl FileContent {
+    fn open(path: &Path) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        let len = file.metadata().ok()?.len();
+        if len < MMAP_THRESHOLD {
+            return std::fs::read(path).ok().map(FileContent::Read);
+        }
+        // SAFETY: the map is read-only and dropped once the file is scanned.
+        // Were the file truncated meanwhile, reading past its new end would
+        // fault, as it would for any tool scanning a file being rewritten.
+        unsafe { memmap2::Mmap::map(&file) }
+            .ok()
+            .map(FileContent::Mapped)
+    }
+
+    /// The content, if it is text
+    fn text(&self) -> Option<&str> {
+        let bytes = match self {
+            FileContent::Read(bytes) => bytes.as_slice(),
+            FileContent::Mapped(map) => map,
+        };
+        std::str::from_utf8(bytes).ok()
+    }
+}
+
+/// Forget what earlier scans found, so the next one reads every tracked file
+pub fn clear_scan_cache(repo: &Repository) -> std::io::Result<()> {
+    match std::fs::remove_file(ScanCache::path(repo)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Scans the tracked files as they are on disk. Only files whose content
+/// (blob OID) the last scan has not seen are read; the rest reuse its result.
+fn scan_tracked_files_for_secrets(
+    repo: &Repository,
+    project_dir: &Path,
+    data: &DataConfig,
+    report: &mut Report,
+) {
+    let patterns = secret_patterns();
 
     let index = match repo.index() {
         Ok(i) => i,
         Err(_) => return,
     };
-
+    let index_written = std::fs::metadata(repo.path().join("index"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    let mut cache = ScanCache::load(repo, SECRET_PATTERNS);
+
+    let mut scanned = 0;
     let mut found_secrets = false;
     for entry in index.iter() {
         let path_str = String::from_utf8_lossy(&entry.path);
+        if !data.scan_secrets && data.contains(&path_str) {
+            continue;
+        }
         let full_path = project_dir.join(&*path_str);
 
-        // Only scan text-like files
-        if let Ok(content) = std::fs::read_to_string(&full_path) {
-            for (re, name, is_fail) in &patterns {
-                if re.is_match(&content) {
-                    if *is_fail {
-                        report.fail(
-                            "Security",
-                            &format!("Possible {} found in tracked file: {}", name, path_str),
-                        );
-                    } else {
-                        report.warn(
-                            "Security",
-                            &format!("Possible {} found in tracked file: {}", name, path_str),
-                        );
-                    }
-                    found_secrets = true;
+        let oid = scan_cache::worktree_oid(&entry, &full_path, index_written);
+        let found = match oid.and_then(|oid| cache.get(oid)) {
+            Some(found) => found,
+            None => {
+                scanned += 1;
+                // Only scan text-like files
+                let found: Vec<String> = match FileContent::open(&full_path) {
+                    Some(content) => match content.text() {
+                        Some(text) => patterns
+                            .iter()
+                            .filter(|(re, _, _)| re.is_match(text))
+                            .map(|(_, name, _)| name.to_string())
+                            .collect(),
+                        None => Vec::new(),
+                    },
+                    None => Vec::new(),
+                };
+                if let Some(oid) = oid {
+                    cache.insert(oid, found.clone());
                 }
+                found
+            }
+        };
+
+        for (_, name, is_fail) in patterns
+            .iter()
+            .filter(|(_, n, _)| found.iter().any(|f| f == n))
+        {
+            if *is_fail {
+                report.fail(
+                    "Security",
+                    &format!("Possible {} found in tracked file: {}", name, path_str),
+                );
+            } else {
+                report.warn_on(
+                    "Security",
+                    "possible-secret",
+                    &format!("{} ({})", path_str, name),
+                    &format!("Possible {} found in tracked file: {}", name, path_str),
+                );
             }
+            found_secrets = true;
         }
     }
+    tracing::debug!(
+        "scanned {} of {} tracked file(s) for secrets (the others are unchanged since the last scan)",
+        scanned,
+        index.len()
+    );
+    if let Err(e) = cache.save(SECRET_PATTERNS) {
+        tracing::debug!("cannot save the secret scan cache: {}", e);
+    }
 
     if !found_secrets {
         report.pass("Security", "No secrets detected in tracked files");
     }
 }
 
-fn scan_sensitive_files(repo: &Repository, report: &mut Report) {
+fn scan_sensitive_files(repo: &Repository, data: &DataConfig, report: &mut Report) {
     let index = match repo.index() {
         Ok(i) => i,
         Err(_) => return,
@@ -123,9 +490,18 @@ fn scan_sensitive_files(repo: &Repository, report: &mut Report) {
             .to_string_lossy()
             .to_string();
 
+        let in_data = data.contains(&path_str);
         for pattern in SENSITIVE_FILE_PATTERNS {
+            if in_data && DATA_FILE_PATTERNS.contains(pattern) {
+                continue;
+            }
             if filename == *pattern || filename.ends_with(pattern) {
-                report.warn("Security", &format!("Sensitive file tracked: {}", path_str));
+                report.warn_on(
+                    "Security",
+                    "sensitive-file",
+                    &path_str,
+                    &format!("Sensitive file tracked: {}", path_str),
+                );
                 found = true;
             }
         }
@@ -151,6 +527,7 @@ fn scan_git_history(repo: &Repository, report: &mut Report) {
     revwalk.push_head().ok();
 
     let mut found_in_history = false;
+    let mut unscanned = Vec::new();
     let mut commits_checked = 0;
     let max_commits = 100;
 
@@ -176,7 +553,11 @@ fn scan_git_history(repo: &Repository, report: &mut Report) {
         // Get parent tree for diff
         let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
 
-        let diff = match repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None) {
+        let diff = match repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&tree),
+            Some(&mut diff_options()),
+        ) {
             Ok(d) => d,
             Err(_) => continue,
         };
@@ -201,11 +582,44 @@ fn scan_git_history(repo: &Repository, report: &mut Report) {
             }),
         )
         .ok();
+
+        // Versions too large to diff, streamed through the patterns instead
+        for (path, blob) in large_blobs(repo, &diff).unwrap_or_default() {
+            let streamed = stream_blob(repo, blob, |_, chunk| {
+                if patterns.iter().any(|(re, _)| re.is_match(chunk)) {
+                    found_in_history = true;
+                }
+            });
+            if streamed.is_err() {
+                unscanned.push(format!("{} ({})", path, &oid.to_string()[..7]));
+            }
+        }
+    }
+
+    if !unscanned.is_empty() {
+        const SHOWN: usize = 5;
+        let mut list = unscanned[..unscanned.len().min(SHOWN)].join(", ");
+        if unscanned.len() > SHOWN {
+            list.push_str(&format!(" and {} more", unscanned.len() - SHOWN));
+        }
+        report.warn_on(
+            "Security",
+            "history-unscanned",
+            "",
+            &format!(
+                "{} version(s) of large files in git history could not be scanned \
+                 (too large to inflate from a pack): {}",
+                unscanned.len(),
+                list
+            ),
+        );
     }
 
     if found_in_history {
-        report.warn(
+        report.warn_on(
             "Security",
+            "history-secrets",
+            "",
             "Potential secrets found in git history (review recommended)",
         );
     } else {
@@ -222,7 +636,7 @@ fn scan_git_history(repo: &Repository, report: &mut Report) {
 fn audit_gitignore(project_dir: &Path, report: &mut Report) {
     let gitignore_path = project_dir.join(".gitignore");
     if !gitignore_path.exists() {
-        report.warn("Gitignore", ".gitignore not found");
+        report.warn_on("Gitignore", "gitignore-missing", "", ".gitignore not found");
         return;
     }
 
@@ -242,8 +656,10 @@ fn audit_gitignore(project_dir: &Path, report: &mut Report) {
     if missing_security.is_empty() {
         report.pass("Gitignore", "Covers common sensitive file patterns");
     } else {
-        report.warn(
+        report.warn_on(
             "Gitignore",
+            "gitignore-security-patterns",
+            "",
             &format!("Missing security patterns: {}", missing_security.join(", ")),
         );
     }
@@ -264,14 +680,34 @@ fn audit_gitignore(project_dir: &Path, report: &mut Report) {
         );
     } else {
         for missing in &missing_artifacts {
-            report.warn(
+            report.warn_on(
                 "Gitignore",
+                "gitignore-build-pattern",
+                missing,
                 &format!("Missing build artifact pattern: {}", missing),
             );
         }
     }
 }
 
+/// Recommended patterns (sensitive files, build output of the detected
+/// languages) that .gitignore does not cover yet
+pub fn missing_gitignore_patterns(project_dir: &Path) -> Vec<&'static str> {
+    let content = std::fs::read_to_string(project_dir.join(".gitignore")).unwrap_or_default();
+    let mut missing = Vec::new();
+    let artifacts = detect_relevant_artifacts(project_dir);
+    for pattern in RECOMMENDED_GITIGNORE_PATTERNS
+        .iter()
+        .copied()
+        .chain(artifacts.into_iter().map(|(p, _)| p))
+    {
+        if !gitignore_contains(&content, pattern) && !missing.contains(&pattern) {
+            missing.push(pattern);
+        }
+    }
+    missing
+}
+
 fn gitignore_contains(content: &str, pattern: &str) -> bool {
     content.lines().any(|line| {
         let trimmed = line.trim();
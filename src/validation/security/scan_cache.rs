@@ -0,0 +1,113 @@
+//! What the secret scan found in each file content it has read, keyed by blob
+//! OID, so a re-run only reads the files that changed
+
+use git2::{IndexEntry, Oid, Repository};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Under the git directory: private to the clone, never committed, and out
+/// of the way of `check --watch`
+const CACHE_FILE: &str = "release-scholar/secret-scan";
+
+pub struct ScanCache {
+    path: PathBuf,
+    /// Pattern names found in each blob; empty when none matched
+    blobs: HashMap<Oid, Vec<String>>,
+    /// The blobs seen this run; only these are saved
+    seen: HashMap<Oid, Vec<String>>,
+}
+
+impl ScanCache {
+    pub fn path(repo: &Repository) -> PathBuf {
+        repo.path().join(CACHE_FILE)
+    }
+
+    /// The cache from the last scan, or an empty one if there is none or it
+    /// was written with other patterns
+    pub fn load(repo: &Repository, patterns: &[(&str, &str, bool)]) -> Self {
+        let path = Self::path(repo);
+        let header = header(patterns);
+        let mut blobs = HashMap::new();
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            let mut lines = content.lines();
+            if lines.next() == Some(header.as_str()) {
+                for line in lines {
+                    let mut fields = line.split('\t');
+                    let Some(Ok(oid)) = fields.next().map(Oid::from_str) else {
+                        continue;
+                    };
+                    blobs.insert(oid, fields.map(str::to_string).collect());
+                }
+            }
+        }
+        ScanCache {
+            path,
+            blobs,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// What the last scan found in this blob, if it read it
+    pub fn get(&mut self, oid: Oid) -> Option<Vec<String>> {
+        let found = self.blobs.get(&oid)?.clone();
+        self.seen.insert(oid, found.clone());
+        Some(found)
+    }
+
+    pub fn insert(&mut self, oid: Oid, found: Vec<String>) {
+        self.seen.insert(oid, found);
+    }
+
+    pub fn save(&self, patterns: &[(&str, &str, bool)]) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = header(patterns);
+        content.push('\n');
+        for (oid, found) in &self.seen {
+            content.push_str(&oid.to_string());
+            for name in found {
+                content.push('\t');
+                content.push_str(name);
+            }
+            content.push('\n');
+        }
+        std::fs::write(&self.path, content)
+    }
+}
+
+/// Names the patterns, so changing them invalidates the cache
+fn header(patterns: &[(&str, &str, bool)]) -> String {
+    let mut hasher = Sha256::new();
+    for (pattern, name, _) in patterns {
+        hasher.update(pattern.as_bytes());
+        hasher.update([0]);
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+    }
+    format!("# release-scholar secret scan {:x}", hasher.finalize())
+}
+
+/// The blob OID of a tracked file as it is on disk. The index already holds
+/// it when the file's size and mtime match the index entry and the file was
+/// not modified after the index was written (so a same-second edit cannot
+/// hide); otherwise the file is hashed.
+pub fn worktree_oid(
+    entry: &IndexEntry,
+    full_path: &Path,
+    index_written: Option<u64>,
+) -> Option<Oid> {
+    let metadata = std::fs::metadata(full_path).ok()?;
+    let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let unchanged = entry.file_size == metadata.len() as u32
+        && entry.mtime.seconds() as u64 == mtime.as_secs()
+        && entry.mtime.nanoseconds() == mtime.subsec_nanos()
+        && index_written.is_some_and(|written| mtime.as_secs() < written);
+    if unchanged {
+        Some(entry.id)
+    } else {
+        Oid::hash_file(git2::ObjectType::Blob, full_path).ok()
+    }
+}
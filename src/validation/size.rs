@@ -1,11 +1,10 @@
+use crate::config::{Config, PathSizeLimits};
 use crate::report::Report;
-use git2::Repository;
+use git2::{AttrCheckFlags, Oid, Repository};
+use globset::{GlobBuilder, GlobMatcher};
 use std::path::Path;
 
-const LARGE_FILE_THRESHOLD: u64 = 1_000_000; // 1 MB
-const VERY_LARGE_FILE_THRESHOLD: u64 = 10_000_000; // 10 MB
-const REPO_SIZE_WARN_THRESHOLD: u64 = 50_000_000; // 50 MB
-const REPO_SIZE_FAIL_THRESHOLD: u64 = 200_000_000; // 200 MB
+const MB: f64 = 1_000_000.0;
 
 const BINARY_EXTENSIONS: &[&str] = &[
     ".zip", ".tar", ".gz", ".bz2", ".xz", ".7z", ".rar", ".jar", ".war", ".ear", ".exe", ".dll",
@@ -14,7 +13,19 @@ const BINARY_EXTENSIONS: &[&str] = &[
     ".pptx", ".woff", ".woff2", ".ttf", ".eot", ".sqlite", ".db", ".min.js", ".min.css", ".map",
 ];
 
-pub fn validate(project_dir: &Path, report: &mut Report) {
+/// The files under one `[size.paths]` glob, or the `[data]` paths, and how
+/// much they hold
+struct PathGroup<'a> {
+    label: &'a str,
+    /// `None` for the data paths
+    matcher: Option<GlobMatcher>,
+    limits: PathSizeLimits,
+    total: u64,
+    files: usize,
+}
+
+/// Check the repository and file sizes against the configured limits
+pub fn validate(project_dir: &Path, config: &Config, report: &mut Report) {
     let repo = match Repository::open(project_dir) {
         Ok(r) => r,
         Err(_) => return,
@@ -25,87 +36,201 @@ pub fn validate(project_dir: &Path, report: &mut Report) {
         Err(_) => return,
     };
 
+    let limits = config.size();
+    let mut groups: Vec<PathGroup> = Vec::new();
+    for (pattern, path_limits) in &limits.paths {
+        // `*` stays within a directory; `**` crosses them
+        match GlobBuilder::new(pattern).literal_separator(true).build() {
+            Ok(glob) => groups.push(PathGroup {
+                label: pattern,
+                matcher: Some(glob.compile_matcher()),
+                limits: path_limits.or_global(&limits),
+                total: 0,
+                files: 0,
+            }),
+            Err(e) => report.fail(
+                "Size",
+                &format!("size.paths: invalid pattern {}: {}", pattern, e.kind()),
+            ),
+        }
+    }
+
+    // Declared data is allowed wherever it sits, within its own limits
+    let data = config.data();
+    let mut data_group = PathGroup {
+        label: "Data paths",
+        matcher: None,
+        limits: data.size.or_global(&limits),
+        total: 0,
+        files: 0,
+    };
+
     let mut total_size: u64 = 0;
-    let mut large_files: Vec<(String, u64)> = Vec::new();
+    let mut large_files: Vec<(String, u64, Option<f64>)> = Vec::new();
     let mut binary_files: Vec<(String, u64)> = Vec::new();
     let mut file_count: usize = 0;
+    let mut lfs_files: Vec<LfsFile> = Vec::new();
 
     for entry in index.iter() {
         let path_str = String::from_utf8_lossy(&entry.path).to_string();
         let full_path = project_dir.join(&path_str);
 
-        let size = match std::fs::metadata(&full_path) {
+        let on_disk = match std::fs::metadata(&full_path) {
             Ok(m) => m.len(),
             Err(_) => continue,
         };
+        // Only the pointer to an LFS file is committed; that is what counts
+        let size = match lfs_pointer(&repo, &path_str, entry.id) {
+            Some(pointer) => {
+                let size = pointer.pointer_size;
+                lfs_files.push(LfsFile {
+                    fetched: on_disk == pointer.size,
+                    ..pointer
+                });
+                size
+            }
+            None => on_disk,
+        };
 
-        total_size += size;
-        file_count += 1;
+        // Otherwise the most specific (longest) matching glob sets the limits,
+        // and the file counts towards the total of all tracked files as well
+        let data_file = data.contains(&path_str);
+        let group = if data_file {
+            Some(&mut data_group)
+        } else {
+            groups
+                .iter_mut()
+                .filter(|g| g.matcher.as_ref().is_some_and(|m| m.is_match(&path_str)))
+                .max_by_key(|g| g.label.len())
+        };
+        if !data_file {
+            total_size += size;
+            file_count += 1;
+        }
+        let (warn_mb, fail_mb) = match group {
+            Some(group) => {
+                group.total += size;
+                group.files += 1;
+                (group.limits.file_warn_mb, group.limits.file_fail_mb)
+            }
+            None => (Some(limits.file_warn_mb), Some(limits.file_fail_mb)),
+        };
 
         // Check for large files
-        if size >= LARGE_FILE_THRESHOLD {
-            large_files.push((path_str.clone(), size));
+        let at_least = |mb: Option<f64>| mb.is_some_and(|mb| size as f64 >= mb * MB);
+        if at_least(warn_mb) || at_least(fail_mb) {
+            large_files.push((path_str.clone(), size, fail_mb));
         }
 
         // Check for binary/vendor files that probably shouldn't be tracked
         let lower = path_str.to_lowercase();
-        if BINARY_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) && size >= LARGE_FILE_THRESHOLD
-        {
+        if BINARY_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) && at_least(warn_mb) {
             binary_files.push((path_str, size));
         }
     }
 
     // Report total repo size
-    let total_mb = total_size as f64 / 1_000_000.0;
-    if total_size >= REPO_SIZE_FAIL_THRESHOLD {
-        report.fail(
+    let total_mb = total_size as f64 / MB;
+    let others = if data_group.files > 0 {
+        "Tracked files besides the data"
+    } else {
+        "Tracked files"
+    };
+    groups.insert(0, data_group);
+    if total_mb >= limits.total_fail_mb {
+        report.fail_on(
             "Size",
+            "total-size",
+            "",
             &format!(
-                "Tracked files total {:.1} MB — too large for a code repository",
-                total_mb
+                "{} total {:.1} MB — too large for a code repository (limit {} MB)",
+                others, total_mb, limits.total_fail_mb
             ),
         );
-    } else if total_size >= REPO_SIZE_WARN_THRESHOLD {
-        report.warn(
+    } else if total_mb >= limits.total_warn_mb {
+        report.warn_on(
             "Size",
-            &format!("Tracked files total {:.1} MB — consider reducing", total_mb),
+            "total-size",
+            "",
+            &format!(
+                "{} total {:.1} MB — consider reducing (over {} MB)",
+                others, total_mb, limits.total_warn_mb
+            ),
         );
     } else {
         report.pass(
             "Size",
-            &format!(
-                "Tracked files total {:.1} MB ({} files)",
-                total_mb, file_count
-            ),
+            &format!("{} total {:.1} MB ({} files)", others, total_mb, file_count),
         );
     }
 
+    // Report each path group's total against its own limits
+    for group in groups.iter().filter(|g| g.files > 0) {
+        let group_mb = group.total as f64 / MB;
+        let message = format!(
+            "{}: {:.1} MB in {} files",
+            group.label, group_mb, group.files
+        );
+        let over = |limit: Option<f64>| limit.filter(|mb| group_mb >= *mb);
+        if let Some(limit) = over(group.limits.total_fail_mb) {
+            report.fail_on(
+                "Size",
+                "group-size",
+                group.label,
+                &format!("{} — over the {} MB limit", message, limit),
+            );
+        } else if let Some(limit) = over(group.limits.total_warn_mb) {
+            report.warn_on(
+                "Size",
+                "group-size",
+                group.label,
+                &format!("{} — over {} MB", message, limit),
+            );
+        } else {
+            report.pass("Size", &message);
+        }
+    }
+
     // Report large files
     if large_files.is_empty() {
-        report.pass("Size", "No large files detected (>1 MB)");
+        report.pass(
+            "Size",
+            &format!("No large files detected (>{} MB)", limits.file_warn_mb),
+        );
     } else {
-        for (path, size) in &large_files {
-            let size_mb = *size as f64 / 1_000_000.0;
-            if *size >= VERY_LARGE_FILE_THRESHOLD {
-                report.fail(
+        for (path, size, fail_mb) in &large_files {
+            let size_mb = *size as f64 / MB;
+            if fail_mb.is_some_and(|mb| size_mb >= mb) {
+                report.fail_on(
                     "Size",
+                    "large-file",
+                    path,
                     &format!(
                         "{} is {:.1} MB — consider removing or using Git LFS",
                         path, size_mb
                     ),
                 );
             } else {
-                report.warn("Size", &format!("{} is {:.1} MB", path, size_mb));
+                report.warn_on(
+                    "Size",
+                    "large-file",
+                    path,
+                    &format!("{} is {:.1} MB", path, size_mb),
+                );
             }
         }
     }
 
+    report_lfs(&lfs_files, report);
+
     // Report binary/vendor files
     if !binary_files.is_empty() {
         for (path, size) in &binary_files {
-            let size_mb = *size as f64 / 1_000_000.0;
-            report.warn(
+            let size_mb = *size as f64 / MB;
+            report.warn_on(
                 "Size",
+                "binary-file",
+                path,
                 &format!(
                     "Binary/vendor file tracked: {} ({:.1} MB) — consider .gitignore or Git LFS",
                     path, size_mb
@@ -114,3 +239,82 @@ pub fn validate(project_dir: &Path, report: &mut Report) {
         }
     }
 }
+
+/// A file stored in Git LFS: the size of its committed pointer and of the
+/// content it stands for
+struct LfsFile {
+    pointer_size: u64,
+    size: u64,
+    /// The working tree holds the content, not just the pointer
+    fetched: bool,
+}
+
+/// Pointers are about 130 bytes; a bigger blob is a file committed before
+/// it was tracked with LFS
+const MAX_POINTER_SIZE: usize = 1024;
+
+/// The LFS pointer committed for `path`, if `.gitattributes` puts it in LFS
+/// (`filter=lfs`) and the blob is one
+fn lfs_pointer(repo: &Repository, path: &str, blob: Oid) -> Option<LfsFile> {
+    let filter = repo
+        .get_attr(Path::new(path), "filter", AttrCheckFlags::default())
+        .ok()??;
+    if filter != "lfs" {
+        return None;
+    }
+    let (pointer_size, _) = repo.odb().ok()?.read_header(blob).ok()?;
+    if pointer_size > MAX_POINTER_SIZE {
+        return None;
+    }
+    let blob = repo.find_blob(blob).ok()?;
+    let pointer = std::str::from_utf8(blob.content()).ok()?;
+    if !pointer.starts_with("version https://git-lfs.github.com/spec/") {
+        return None;
+    }
+    let size = pointer
+        .lines()
+        .find_map(|line| line.strip_prefix("size "))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(LfsFile {
+        pointer_size: pointer_size as u64,
+        size,
+        fetched: false,
+    })
+}
+
+/// LFS content is not in git's tree, so it stays out of the release archive
+fn report_lfs(files: &[LfsFile], report: &mut Report) {
+    if files.is_empty() {
+        return;
+    }
+    let content: u64 = files.iter().map(|f| f.size).sum();
+    let pointers: u64 = files.iter().map(|f| f.pointer_size).sum();
+    report.warn_on(
+        "Size",
+        "lfs-content",
+        "",
+        &format!(
+            "{} file(s) in Git LFS: {:.1} MB of content behind {:.1} KB of committed pointers. \
+             The release archive holds only the pointers; publish the content separately \
+             (e.g. as a data deposit) or move it out of LFS",
+            files.len(),
+            content as f64 / MB,
+            pointers as f64 / 1000.0
+        ),
+    );
+    let missing = files.iter().filter(|f| !f.fetched).count();
+    if missing > 0 {
+        report.warn_on(
+            "Size",
+            "lfs-pointers",
+            "",
+            &format!(
+                "{} Git LFS file(s) are only pointers in the working tree; run `git lfs pull` \
+                 to fetch their content",
+                missing
+            ),
+        );
+    }
+}
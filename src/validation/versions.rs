@@ -0,0 +1,70 @@
+use crate::metadata::sync::{self, Field, Source};
+use crate::report::Report;
+use std::path::Path;
+
+/// The version in codemeta.json and the package manifest must match the git
+/// tag (or, without a tag, CITATION.cff), so stale metadata does not end up
+/// in the archive. CITATION.cff itself is checked against the tag by the
+/// citation checks.
+pub fn validate(project_dir: &Path, tag_version: Option<&str>, report: &mut Report) {
+    let sources = sync::sources(project_dir);
+    let cff_version = || {
+        Source::Citation
+            .read(project_dir)
+            .ok()?
+            .remove(&Field::Version)?
+            .as_text()
+            .map(str::to_string)
+    };
+    let (expected, reference) = match tag_version {
+        Some(v) => (v.to_string(), "git tag"),
+        None => match cff_version() {
+            Some(v) => (v, "CITATION.cff"),
+            None => return,
+        },
+    };
+
+    let mut matching = Vec::new();
+    for source in sources.iter().filter(|s| **s != Source::Citation) {
+        let fields = match source.read(project_dir) {
+            Ok(fields) => fields,
+            Err(e) => {
+                report.fail("Version", &e.to_string());
+                continue;
+            }
+        };
+        match fields.get(&Field::Version).and_then(|v| v.as_text()) {
+            Some(v) if v == expected => matching.push(source.file_name()),
+            Some(v) => report.fail(
+                "Version",
+                &format!(
+                    "{} version '{}' does not match {} '{}'",
+                    source.file_name(),
+                    v,
+                    reference,
+                    expected
+                ),
+            ),
+            // Manifests may compute the version at build time
+            None if *source == Source::CodeMeta => report.warn_on(
+                "Version",
+                "codemeta-version",
+                "",
+                "codemeta.json has no version",
+            ),
+            None => {}
+        }
+    }
+
+    if !matching.is_empty() {
+        report.pass(
+            "Version",
+            &format!(
+                "{} version matches {} ({})",
+                matching.join(", "),
+                reference,
+                expected
+            ),
+        );
+    }
+}
@@ -1,4 +1,13 @@
+mod api;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+
+use crate::config::NetworkConfig;
+use crate::credentials;
+use crate::error::{IoError, ZenodoError};
+use crate::http;
 use crate::metadata::zenodo::ZenodoDeposit;
+use api::{Answer, Api, Call, DepositionFile};
 use reqwest::blocking::Client;
 use serde::Deserialize;
 use std::path::Path;
@@ -6,12 +15,15 @@ use std::path::Path;
 const ZENODO_API: &str = "https://zenodo.org/api";
 const ZENODO_SANDBOX_API: &str = "https://sandbox.zenodo.org/api";
 
+/// Blocking Zenodo API client
 pub struct ZenodoClient {
     client: Client,
-    base_url: String,
+    api: Api,
     token: String,
+    network: NetworkConfig,
 }
 
+/// A deposition as the API returns it
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct DepositionResponse {
@@ -20,19 +32,54 @@ pub struct DepositionResponse {
     pub metadata: Option<serde_json::Value>,
     pub doi: Option<String>,
     pub conceptrecid: Option<String>,
+    pub conceptdoi: Option<String>,
     pub doi_url: Option<String>,
+    /// Whether the deposition has been published
+    #[serde(default)]
+    pub submitted: bool,
+    pub title: Option<String>,
 }
 
+impl DepositionResponse {
+    /// The deposition's title
+    pub fn metadata_title(&self) -> Option<&str> {
+        self.metadata_str("title").or(self.title.as_deref())
+    }
+
+    /// The deposition's version
+    pub fn metadata_version(&self) -> Option<&str> {
+        self.metadata_str("version")
+    }
+
+    /// The DOI reserved with `prereserve_doi`, which the record gets when
+    /// it is published
+    pub fn reserved_doi(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()?
+            .get("prereserve_doi")?
+            .get("doi")?
+            .as_str()
+    }
+
+    fn metadata_str(&self, key: &str) -> Option<&str> {
+        self.metadata.as_ref()?.get(key)?.as_str()
+    }
+}
+
+/// A deposition's API and web links
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct DepositionLinks {
     pub html: Option<String>,
     pub bucket: Option<String>,
     pub publish: Option<String>,
+    /// The draft of the next version, once `newversion` made one
+    pub latest_draft: Option<String>,
     #[serde(rename = "self")]
     pub self_link: Option<String>,
 }
 
+/// A file uploaded to a deposition
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct FileResponse {
@@ -42,79 +89,210 @@ pub struct FileResponse {
 }
 
 impl ZenodoClient {
-    pub fn new(sandbox: bool) -> Result<Self, String> {
-        let token = load_token(sandbox)?;
-        let base_url = if sandbox {
-            ZENODO_SANDBOX_API
-        } else {
-            ZENODO_API
-        }
-        .to_string();
+    /// Client for the stored token: the environment's, the OS keyring's or
+    /// the token file's
+    pub fn new(sandbox: bool, network: &NetworkConfig) -> Result<Self, ZenodoError> {
+        Self::with_token(sandbox, load_token(sandbox)?, network)
+    }
 
-        let client = Client::builder()
-            .user_agent(format!("release-scholar/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .map_err(|e| format!("Cannot create HTTP client: {}", e))?;
+    /// Client for an explicit token, e.g. one being tested before it is stored
+    pub fn with_token(
+        sandbox: bool,
+        token: String,
+        network: &NetworkConfig,
+    ) -> Result<Self, ZenodoError> {
+        let client = http::client(network, None)?;
         Ok(ZenodoClient {
             client,
-            base_url,
+            api: Api::new(sandbox),
             token,
+            network: network.clone(),
         })
     }
 
-    /// Create a new empty deposition
-    pub fn create_deposition(&self) -> Result<DepositionResponse, String> {
-        let url = format!("{}/deposit/depositions", self.base_url);
-        let resp = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.token)
-            .header("Content-Type", "application/json")
-            .body("{}")
-            .send()
-            .map_err(|e| format!("HTTP error creating deposition: {}", e))?;
-
+    /// Send `call`, retrying as the network settings allow, and read the answer
+    fn execute(&self, call: &Call) -> Result<Answer, ZenodoError> {
+        let upload = match &call.upload {
+            Some(path) => {
+                let len = std::fs::metadata(path)
+                    .map_err(IoError::on("read", path))?
+                    .len();
+                Some((path, len))
+            }
+            None => None,
+        };
+        let resp = http::send_with_retry(&self.network, || {
+            let request = self
+                .client
+                .request(call.method.clone(), &call.url)
+                .bearer_auth(&self.token)
+                .query(&call.query);
+            match (&call.json, upload) {
+                (Some(json), _) => request.json(json),
+                (None, Some((path, len))) => request
+                    .header("Content-Type", "application/octet-stream")
+                    .body(http::file_body(path, len)),
+                (None, None) => request,
+            }
+        })
+        .map_err(http_error(&call.context))?;
         let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().unwrap_or_default();
-            return Err(format!(
-                "Zenodo API error {} creating deposition: {}",
-                status, body
-            ));
+        let body = resp.bytes().map_err(http_error(&call.context))?;
+        Ok(Answer { status, body })
+    }
+
+    /// Check that the token is accepted by making a cheap authenticated request
+    pub fn verify_token(&self) -> Result<(), ZenodoError> {
+        let call = self.api.verify_token();
+        api::token_accepted(&call, &self.execute(&call)?, self.api.sandbox())
+    }
+
+    /// Check the token's scopes before doing anything: `deposit:write`
+    /// always, `deposit:actions` when the run will publish or edit a record.
+    /// They are read from the token's info; where Zenodo does not give it,
+    /// only `deposit:write` can be checked, by using it.
+    pub fn check_scopes(&self, need_actions: bool) -> Result<(), ZenodoError> {
+        let scopes = self
+            .execute(&self.api.token_info())
+            .and_then(api::token_scopes);
+        match scopes {
+            Ok(Some(scopes)) => has_scopes(&scopes, need_actions, self.api.sandbox()),
+            Ok(None) => match self.verify_token() {
+                Ok(()) => Ok(()),
+                Err(e @ ZenodoError::MissingScope { .. }) => Err(e),
+                Err(e) => Err(ZenodoError::TokenCheck(Box::new(e))),
+            },
+            Err(e) => Err(ZenodoError::TokenCheck(Box::new(e))),
         }
+    }
 
-        resp.json::<DepositionResponse>()
-            .map_err(|e| format!("Cannot parse deposition response: {}", e))
+    /// Create a draft titled and versioned as given, so that a later run (or
+    /// a lost answer, below) can find it among the drafts
+    pub fn create_deposition(
+        &self,
+        title: &str,
+        version: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let metadata = api::draft_metadata(title, version, false);
+        self.create(metadata, "creating deposition")
     }
 
-    /// Upload a file to a deposition's bucket
+    /// Create a draft with a DOI reserved for it, titled and versioned so a
+    /// later run finds it among the drafts
+    pub fn reserve_doi(
+        &self,
+        title: &str,
+        version: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let metadata = api::draft_metadata(title, version, true);
+        self.create(metadata, "reserving a DOI")
+    }
+
+    /// Start the next version of the record whose `conceptrecid` is
+    /// `concept`, so the release shares the concept DOI of those before it.
+    /// Zenodo's draft is a copy of the latest version: its files are removed,
+    /// and it is titled and versioned as given (with a DOI reserved, if
+    /// `reserve_doi`) so a later run finds it among the drafts.
+    pub fn new_version(
+        &self,
+        concept: &str,
+        title: &str,
+        version: &str,
+        reserve_doi: bool,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        // The concept's record URL redirects to its latest version
+        let call = self.api.latest_record(concept);
+        let latest = api::record_id(&call, self.execute(&call)?)?;
+        // Asked again, Zenodo answers with the draft it made the first time
+        let parent = self.action(latest, "newversion")?;
+        let draft_id = api::latest_draft(&parent, latest)?;
+
+        let call = self.api.list_files(draft_id);
+        let files: Vec<DepositionFile> = api::read(&call, self.execute(&call)?, "files response")?;
+        for file in files {
+            let call = self.api.delete_file(draft_id, &file.id);
+            api::done(&call, &self.execute(&call)?)?;
+        }
+
+        let draft = self
+            .get_deposition(draft_id)?
+            .ok_or(ZenodoError::NewVersion { record: latest })?;
+        let body = api::new_version_metadata(draft, title, version, reserve_doi);
+        self.put_metadata(draft_id, body, "titling the new version")
+    }
+
+    /// POST a new deposition with `metadata`. A create whose answer was lost
+    /// is not simply sent again, since Zenodo may have made the draft already:
+    /// the drafts are searched for it by title and version first.
+    fn create(
+        &self,
+        metadata: serde_json::Value,
+        context: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let title = metadata["title"].as_str().unwrap_or_default();
+        let version = metadata["version"].as_str().unwrap_or_default();
+        let call = self.api.create(metadata.clone(), context);
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .execute(&call)
+                .and_then(|answer| api::read(&call, answer, "deposition response"));
+            match result {
+                Err(e) if e.outcome_unknown() && attempt < self.network.retries => {
+                    attempt += 1;
+                    std::thread::sleep(http::lost_answer_delay(&self.network, attempt));
+                    let depositions = self.list_depositions()?;
+                    if let Some(draft) = find_release(&depositions, title, version) {
+                        if let Some(draft) = self.get_deposition(draft.id)? {
+                            return Ok(draft);
+                        }
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetch a deposition by ID; `None` if it no longer exists
+    pub fn get_deposition(
+        &self,
+        deposition_id: u64,
+    ) -> Result<Option<DepositionResponse>, ZenodoError> {
+        let call = self.api.get_deposition(deposition_id);
+        api::found(&call, self.execute(&call)?)
+    }
+
+    /// Delete an unpublished deposition (Zenodo refuses for published ones)
+    pub fn delete_deposition(&self, deposition_id: u64) -> Result<(), ZenodoError> {
+        let call = self.api.delete_deposition(deposition_id);
+        api::done(&call, &self.execute(&call)?)
+    }
+
+    /// List all of the authenticated user's depositions, most recent first
+    pub fn list_depositions(&self) -> Result<Vec<DepositionResponse>, ZenodoError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let call = self.api.list_depositions(page);
+            let batch: Vec<DepositionResponse> =
+                api::read(&call, self.execute(&call)?, "depositions response")?;
+            let last = batch.len() < api::PAGE_SIZE;
+            all.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    /// Upload a file to a deposition's bucket, streamed from disk
     pub fn upload_file(
         &self,
         bucket_url: &str,
         file_path: &Path,
         filename: &str,
-    ) -> Result<FileResponse, String> {
-        let data = std::fs::read(file_path)
-            .map_err(|e| format!("Cannot read {}: {}", file_path.display(), e))?;
-
-        let url = format!("{}/{}", bucket_url, filename);
-        let resp = self
-            .client
-            .put(&url)
-            .bearer_auth(&self.token)
-            .header("Content-Type", "application/octet-stream")
-            .body(data)
-            .send()
-            .map_err(|e| format!("HTTP error uploading file: {}", e))?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().unwrap_or_default();
-            return Err(format!("Zenodo API error {} uploading: {}", status, body));
-        }
-
-        resp.json::<FileResponse>()
-            .map_err(|e| format!("Cannot parse upload response: {}", e))
+    ) -> Result<FileResponse, ZenodoError> {
+        let call = self.api.upload_file(bucket_url, file_path, filename);
+        api::read(&call, self.execute(&call)?, "upload response")
     }
 
     /// Update deposition metadata
@@ -122,96 +300,223 @@ impl ZenodoClient {
         &self,
         deposition_id: u64,
         deposit: &ZenodoDeposit,
-    ) -> Result<DepositionResponse, String> {
-        let url = format!("{}/deposit/depositions/{}", self.base_url, deposition_id);
-        let resp = self
-            .client
-            .put(&url)
-            .bearer_auth(&self.token)
-            .header("Content-Type", "application/json")
-            .json(deposit)
-            .send()
-            .map_err(|e| format!("HTTP error updating metadata: {}", e))?;
+    ) -> Result<DepositionResponse, ZenodoError> {
+        self.put_metadata(
+            deposition_id,
+            serde_json::json!(deposit),
+            "updating metadata",
+        )
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().unwrap_or_default();
-            return Err(format!(
-                "Zenodo API error {} updating metadata: {}",
-                status, body
-            ));
+    fn put_metadata(
+        &self,
+        deposition_id: u64,
+        body: serde_json::Value,
+        context: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let call = self.api.put_metadata(deposition_id, body, context);
+        api::read(&call, self.execute(&call)?, "metadata response")
+    }
+
+    /// Publish the deposition (makes it permanent!). If the answer is lost,
+    /// the deposition is looked at before publishing is asked for again.
+    pub fn publish(&self, deposition_id: u64) -> Result<DepositionResponse, ZenodoError> {
+        let mut attempt = 0;
+        loop {
+            match self.action(deposition_id, "publish") {
+                Err(e) if e.outcome_unknown() && attempt < self.network.retries => {
+                    attempt += 1;
+                    std::thread::sleep(http::lost_answer_delay(&self.network, attempt));
+                    if let Some(published) =
+                        self.get_deposition(deposition_id)?.filter(|d| d.submitted)
+                    {
+                        return Ok(published);
+                    }
+                }
+                result => return result,
+            }
         }
+    }
 
-        resp.json::<DepositionResponse>()
-            .map_err(|e| format!("Cannot parse metadata response: {}", e))
+    /// Let Zenodo validate metadata by applying it to a throwaway draft, which
+    /// is deleted again. Returns the `(field, message)` pairs Zenodo rejected.
+    pub fn validate_remote(
+        &self,
+        deposit: &ZenodoDeposit,
+    ) -> Result<Vec<(String, String)>, ZenodoError> {
+        // A title no release has, and a version only this check uses
+        let check = format!("check-{}", crate::state::now_utc());
+        let draft = self.create_deposition("release-scholar metadata check", &check)?;
+        let call =
+            self.api
+                .put_metadata(draft.id, serde_json::json!(deposit), "validating metadata");
+        let result = self.execute(&call);
+        let cleanup = self.delete_deposition(draft.id);
+
+        let errors = api::validation_errors(&call, result?)?;
+        cleanup.map_err(|e| ZenodoError::DraftLeftBehind {
+            draft: draft.id,
+            source: Box::new(e),
+        })?;
+        Ok(errors)
     }
 
-    /// Publish the deposition (makes it permanent!)
-    pub fn publish(&self, deposition_id: u64) -> Result<DepositionResponse, String> {
-        let url = format!(
-            "{}/deposit/depositions/{}/actions/publish",
-            self.base_url, deposition_id
-        );
-        let resp = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .map_err(|e| format!("HTTP error publishing: {}", e))?;
+    /// Unlock a published deposition so its metadata can be changed
+    pub fn edit(&self, deposition_id: u64) -> Result<DepositionResponse, ZenodoError> {
+        self.action(deposition_id, "edit")
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().unwrap_or_default();
-            return Err(format!("Zenodo API error {} publishing: {}", status, body));
-        }
+    /// Leave edit mode without saving changes
+    pub fn discard_edit(&self, deposition_id: u64) -> Result<DepositionResponse, ZenodoError> {
+        self.action(deposition_id, "discard")
+    }
 
-        resp.json::<DepositionResponse>()
-            .map_err(|e| format!("Cannot parse publish response: {}", e))
+    fn action(&self, deposition_id: u64, action: &str) -> Result<DepositionResponse, ZenodoError> {
+        let call = self.api.action(deposition_id, action);
+        api::read(&call, self.execute(&call)?, &format!("{} response", action))
     }
 
+    /// The Zenodo website this client's API belongs to
     pub fn base_web_url(&self) -> &str {
-        if self.base_url.contains("sandbox") {
-            "https://sandbox.zenodo.org"
-        } else {
-            "https://zenodo.org"
-        }
+        self.api.web_url()
     }
 }
 
-fn load_token(sandbox: bool) -> Result<String, String> {
-    // Try environment variable first
-    let env_var = if sandbox {
-        "ZENODO_SANDBOX_TOKEN"
+/// `deposit:write`, and `deposit:actions` if `need_actions`, among `scopes`
+fn has_scopes(scopes: &[String], need_actions: bool, sandbox: bool) -> Result<(), ZenodoError> {
+    let needed: &[&'static str] = if need_actions {
+        &["deposit:write", "deposit:actions"]
     } else {
-        "ZENODO_TOKEN"
+        &["deposit:write"]
     };
+    match needed
+        .iter()
+        .find(|scope| !scopes.iter().any(|s| s == *scope))
+    {
+        Some(scope) => Err(ZenodoError::MissingScope { scope, sandbox }),
+        None => Ok(()),
+    }
+}
+
+/// The Zenodo website (not the API), for links to records and drafts
+pub fn web_url(sandbox: bool) -> &'static str {
+    if sandbox {
+        "https://sandbox.zenodo.org"
+    } else {
+        "https://zenodo.org"
+    }
+}
+
+fn http_error(context: &str) -> impl FnOnce(reqwest::Error) -> ZenodoError + '_ {
+    move |source| ZenodoError::Http {
+        context: context.to_string(),
+        source,
+    }
+}
+
+/// `{"errors": [{"field": ..., "message"|"messages": ...}]}` from a 400 response
+fn parse_validation_errors(body: &serde_json::Value) -> Vec<(String, String)> {
+    let errors: Vec<(String, String)> = body
+        .get("errors")
+        .and_then(|e| e.as_array())
+        .map(|errors| {
+            errors
+                .iter()
+                .map(|e| {
+                    let field = e.get("field").and_then(|f| f.as_str()).unwrap_or("?");
+                    let message = match (e.get("message"), e.get("messages")) {
+                        (Some(m), _) => m.as_str().unwrap_or_default().to_string(),
+                        (None, Some(serde_json::Value::Array(ms))) => ms
+                            .iter()
+                            .filter_map(|m| m.as_str())
+                            .collect::<Vec<_>>()
+                            .join("; "),
+                        _ => String::new(),
+                    };
+                    (field.to_string(), message)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if errors.is_empty() {
+        let message = body
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("validation failed");
+        vec![("metadata".to_string(), message.to_string())]
+    } else {
+        errors
+    }
+}
+
+/// The record ID in a Zenodo DOI ("10.5281/zenodo.12345" is record 12345)
+pub fn record_id(doi: &str) -> Option<u64> {
+    doi.rsplit_once("zenodo.")?.1.parse().ok()
+}
 
+/// The deposition for a release: same title and version as CITATION.cff
+pub fn find_release<'a>(
+    depositions: &'a [DepositionResponse],
+    title: &str,
+    version: &str,
+) -> Option<&'a DepositionResponse> {
+    depositions
+        .iter()
+        .find(|d| d.metadata_title() == Some(title) && d.metadata_version() == Some(version))
+}
+
+fn load_token(sandbox: bool) -> Result<String, ZenodoError> {
+    // Try environment variable first
+    let env_var = token_env_var(sandbox);
     if let Ok(token) = std::env::var(env_var) {
         if !token.is_empty() {
             return Ok(token.trim().to_string());
         }
     }
 
-    // Try config file
-    let filename = if sandbox { "sandbox-token" } else { "token" };
+    // Then the OS keyring, then the legacy plaintext token file
+    let account = keyring_account(sandbox);
+    let token_path = token_file(sandbox).ok_or(crate::error::ConfigError::NoConfigDir)?;
+    let plaintext = if token_path.exists() {
+        Some(
+            std::fs::read_to_string(&token_path)
+                .map_err(IoError::on("read token from", &token_path))?,
+        )
+    } else {
+        None
+    };
+    if let Some(token) = credentials::resolve(account, plaintext, &token_path.display().to_string())
+    {
+        return Ok(token);
+    }
 
-    let config_dir = dirs::config_dir()
-        .ok_or("Cannot determine config directory")?
-        .join("release-scholar");
-    let token_path = config_dir.join(filename);
+    Err(ZenodoError::NoToken {
+        account,
+        env_var,
+        path: token_path,
+    })
+}
 
-    if token_path.exists() {
-        let token = std::fs::read_to_string(&token_path)
-            .map_err(|e| format!("Cannot read token from {}: {}", token_path.display(), e))?;
-        let token = token.trim().to_string();
-        if !token.is_empty() {
-            return Ok(token);
-        }
+/// Environment variable that overrides the stored Zenodo token
+pub fn token_env_var(sandbox: bool) -> &'static str {
+    if sandbox {
+        "ZENODO_SANDBOX_TOKEN"
+    } else {
+        "ZENODO_TOKEN"
     }
+}
 
-    Err(format!(
-        "No Zenodo token found. Set {} or save to {}",
-        env_var,
-        config_dir.join(filename).display()
-    ))
+/// Legacy plaintext token file next to the global config
+pub fn token_file(sandbox: bool) -> Option<std::path::PathBuf> {
+    let filename = if sandbox { "sandbox-token" } else { "token" };
+    dirs::config_dir().map(|d| d.join("release-scholar").join(filename))
+}
+
+/// Keyring account holding the Zenodo token
+pub fn keyring_account(sandbox: bool) -> &'static str {
+    if sandbox {
+        "zenodo-sandbox"
+    } else {
+        "zenodo"
+    }
 }
@@ -0,0 +1,300 @@
+//! Zenodo's REST API as both clients call it. Each request is described here
+//! as a [`Call`] and each answer read here, so the blocking and the async
+//! client only send calls and hand back what came of them.
+
+use super::{parse_validation_errors, DepositionResponse, ZENODO_API, ZENODO_SANDBOX_API};
+use crate::error::ZenodoError;
+use bytes::Bytes;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Depositions asked for per page when listing them
+pub(super) const PAGE_SIZE: usize = 100;
+
+/// One request to Zenodo, sent with the client's token
+pub(super) struct Call {
+    pub method: Method,
+    pub url: String,
+    pub query: Vec<(&'static str, String)>,
+    pub json: Option<serde_json::Value>,
+    /// A file sent as the body, read from disk by the client
+    pub upload: Option<PathBuf>,
+    /// What the call does, for errors: "fetching deposition"
+    pub context: String,
+}
+
+impl Call {
+    fn new(method: Method, url: String, context: impl Into<String>) -> Self {
+        Call {
+            method,
+            url,
+            query: Vec::new(),
+            json: None,
+            upload: None,
+            context: context.into(),
+        }
+    }
+
+    fn json(mut self, body: serde_json::Value) -> Self {
+        self.json = Some(body);
+        self
+    }
+}
+
+/// What came back for a call: the status and the body, read in full
+pub(super) struct Answer {
+    pub status: StatusCode,
+    pub body: Bytes,
+}
+
+/// What Zenodo's OAuth server says about a token
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    scopes: Option<Vec<String>>,
+}
+
+/// A file as the deposition files API lists it
+#[derive(Debug, Deserialize)]
+pub(super) struct DepositionFile {
+    pub id: String,
+}
+
+/// The calls, for the production or the sandbox API
+pub(super) struct Api {
+    base_url: &'static str,
+}
+
+impl Api {
+    pub fn new(sandbox: bool) -> Self {
+        Api {
+            base_url: if sandbox {
+                ZENODO_SANDBOX_API
+            } else {
+                ZENODO_API
+            },
+        }
+    }
+
+    pub fn sandbox(&self) -> bool {
+        self.base_url == ZENODO_SANDBOX_API
+    }
+
+    pub fn web_url(&self) -> &'static str {
+        super::web_url(self.sandbox())
+    }
+
+    fn depositions(&self) -> String {
+        format!("{}/deposit/depositions", self.base_url)
+    }
+
+    fn deposition(&self, id: u64) -> String {
+        format!("{}/{}", self.depositions(), id)
+    }
+
+    /// A cheap authenticated request, to see whether the token is accepted
+    pub fn verify_token(&self) -> Call {
+        let mut call = Call::new(Method::GET, self.depositions(), "checking token");
+        call.query.push(("size", "1".to_string()));
+        call
+    }
+
+    pub fn token_info(&self) -> Call {
+        let url = format!("{}/oauth/info", self.web_url());
+        Call::new(Method::GET, url, "checking token scopes")
+    }
+
+    /// A new deposition with `metadata`
+    pub fn create(&self, metadata: serde_json::Value, context: &str) -> Call {
+        Call::new(Method::POST, self.depositions(), context)
+            .json(serde_json::json!({ "metadata": metadata }))
+    }
+
+    pub fn get_deposition(&self, id: u64) -> Call {
+        Call::new(Method::GET, self.deposition(id), "fetching deposition")
+    }
+
+    pub fn delete_deposition(&self, id: u64) -> Call {
+        Call::new(Method::DELETE, self.deposition(id), "deleting deposition")
+    }
+
+    /// One page of the user's depositions, most recent first
+    pub fn list_depositions(&self, page: usize) -> Call {
+        let mut call = Call::new(Method::GET, self.depositions(), "listing depositions");
+        call.query = vec![
+            ("sort", "mostrecent".to_string()),
+            ("size", PAGE_SIZE.to_string()),
+            ("page", page.to_string()),
+        ];
+        call
+    }
+
+    /// `file_path` into a deposition's bucket, as `filename`
+    pub fn upload_file(&self, bucket_url: &str, file_path: &Path, filename: &str) -> Call {
+        let mut call = Call::new(
+            Method::PUT,
+            format!("{}/{}", bucket_url, filename),
+            "uploading file",
+        );
+        call.upload = Some(file_path.to_path_buf());
+        call
+    }
+
+    /// Replace a deposition's metadata with `body`
+    pub fn put_metadata(&self, id: u64, body: serde_json::Value, context: &str) -> Call {
+        Call::new(Method::PUT, self.deposition(id), context).json(body)
+    }
+
+    /// `publish`, `edit`, `discard` or `newversion`
+    pub fn action(&self, id: u64, action: &str) -> Call {
+        let url = format!("{}/actions/{}", self.deposition(id), action);
+        Call::new(Method::POST, url, format!("on {} action", action))
+    }
+
+    /// The record of `concept`, which redirects to its latest version
+    pub fn latest_record(&self, concept: &str) -> Call {
+        let url = format!("{}/records/{}", self.base_url, concept);
+        Call::new(Method::GET, url, "finding the latest version")
+    }
+
+    pub fn list_files(&self, id: u64) -> Call {
+        let url = format!("{}/files", self.deposition(id));
+        Call::new(Method::GET, url, "listing the new version's files")
+    }
+
+    pub fn delete_file(&self, id: u64, file_id: &str) -> Call {
+        let url = format!("{}/files/{}", self.deposition(id), file_id);
+        Call::new(Method::DELETE, url, "removing the previous version's files")
+    }
+}
+
+/// The answer's body as `T`, or the error for a failed call
+pub(super) fn read<T: DeserializeOwned>(
+    call: &Call,
+    answer: Answer,
+    what: &str,
+) -> Result<T, ZenodoError> {
+    done(call, &answer)?;
+    serde_json::from_slice(&answer.body).map_err(|source| ZenodoError::Parse {
+        what: what.to_string(),
+        source,
+    })
+}
+
+/// Nothing to read, as long as the call succeeded
+pub(super) fn done(call: &Call, answer: &Answer) -> Result<(), ZenodoError> {
+    if answer.status.is_success() {
+        Ok(())
+    } else {
+        Err(ZenodoError::Api {
+            status: answer.status,
+            context: call.context.clone(),
+            body: String::from_utf8_lossy(&answer.body).into_owned(),
+        })
+    }
+}
+
+/// A deposition, or `None` if it no longer exists
+pub(super) fn found(
+    call: &Call,
+    answer: Answer,
+) -> Result<Option<DepositionResponse>, ZenodoError> {
+    match answer.status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+        _ => read(call, answer, "deposition response").map(Some),
+    }
+}
+
+pub(super) fn token_accepted(
+    call: &Call,
+    answer: &Answer,
+    sandbox: bool,
+) -> Result<(), ZenodoError> {
+    match answer.status {
+        StatusCode::UNAUTHORIZED => Err(ZenodoError::TokenRejected),
+        StatusCode::FORBIDDEN => Err(ZenodoError::MissingScope {
+            scope: "deposit:write",
+            sandbox,
+        }),
+        _ => done(call, answer),
+    }
+}
+
+/// The scopes Zenodo lists for the token, if it lists them
+pub(super) fn token_scopes(answer: Answer) -> Result<Option<Vec<String>>, ZenodoError> {
+    match answer.status {
+        StatusCode::UNAUTHORIZED => Err(ZenodoError::TokenRejected),
+        s if s.is_success() => Ok(serde_json::from_slice::<TokenInfo>(&answer.body)
+            .ok()
+            .and_then(|i| i.scopes)),
+        _ => Ok(None),
+    }
+}
+
+/// The `(field, message)` pairs Zenodo rejected metadata for
+pub(super) fn validation_errors(
+    call: &Call,
+    answer: Answer,
+) -> Result<Vec<(String, String)>, ZenodoError> {
+    if answer.status == StatusCode::BAD_REQUEST {
+        let body: serde_json::Value = serde_json::from_slice(&answer.body).unwrap_or_default();
+        return Ok(parse_validation_errors(&body));
+    }
+    done(call, &answer).map(|()| Vec::new())
+}
+
+/// The ID of a record; a number on legacy Zenodo, a string on InvenioRDM
+pub(super) fn record_id(call: &Call, answer: Answer) -> Result<u64, ZenodoError> {
+    let record: serde_json::Value = read(call, answer, "record response")?;
+    record["id"]
+        .as_u64()
+        .or_else(|| record["id"].as_str().and_then(|id| id.parse().ok()))
+        .ok_or(ZenodoError::NewVersion { record: 0 })
+}
+
+/// The ID of the draft `newversion` made, from its parent's links
+pub(super) fn latest_draft(parent: &DepositionResponse, record: u64) -> Result<u64, ZenodoError> {
+    parent
+        .links
+        .latest_draft
+        .as_deref()
+        .and_then(|url| url.rsplit('/').next())
+        .and_then(|id| id.parse::<u64>().ok())
+        .ok_or(ZenodoError::NewVersion { record })
+}
+
+/// A new version's metadata: the previous version's, retitled and
+/// reversioned, without its DOI
+pub(super) fn new_version_metadata(
+    draft: DepositionResponse,
+    title: &str,
+    version: &str,
+    reserve_doi: bool,
+) -> serde_json::Value {
+    let mut metadata = draft.metadata.unwrap_or_else(|| serde_json::json!({}));
+    if let Some(fields) = metadata.as_object_mut() {
+        // The previous version's DOI is not this one's
+        fields.remove("doi");
+        fields.insert("title".to_string(), title.into());
+        fields.insert("version".to_string(), version.into());
+        if reserve_doi {
+            fields.insert("prereserve_doi".to_string(), true.into());
+        }
+    }
+    serde_json::json!({ "metadata": metadata })
+}
+
+/// The metadata of a new draft, titled and versioned so a later run (or a
+/// lost answer) finds it among the drafts
+pub(super) fn draft_metadata(title: &str, version: &str, reserve_doi: bool) -> serde_json::Value {
+    let mut metadata = serde_json::json!({
+        "title": title,
+        "version": version,
+        "upload_type": "software",
+    });
+    if reserve_doi {
+        metadata["prereserve_doi"] = true.into();
+    }
+    metadata
+}
@@ -0,0 +1,330 @@
+//! Async Zenodo client for use inside a tokio runtime, e.g. in a release bot
+//! serving many repositories. Mirrors the blocking [`super::ZenodoClient`]
+//! method for method and returns the same response and error types; both
+//! build their requests and read the answers in the same place.
+
+use super::api::{self, Answer, Api, Call, DepositionFile};
+use super::{find_release, has_scopes, http_error, load_token, DepositionResponse, FileResponse};
+use crate::config::NetworkConfig;
+use crate::error::{IoError, ZenodoError};
+use crate::http;
+use crate::metadata::zenodo::ZenodoDeposit;
+use bytes::Bytes;
+use reqwest::Client;
+use std::path::Path;
+
+pub struct ZenodoClient {
+    client: Client,
+    api: Api,
+    token: String,
+    network: NetworkConfig,
+}
+
+impl ZenodoClient {
+    /// Client for the stored token. The token is read (from the environment,
+    /// the OS keyring or the token file) on tokio's blocking pool.
+    pub async fn new(sandbox: bool, network: &NetworkConfig) -> Result<Self, ZenodoError> {
+        let token = tokio::task::spawn_blocking(move || load_token(sandbox))
+            .await
+            // Only a panic while reading it, which is passed on
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))?;
+        Self::with_token(sandbox, token, network)
+    }
+
+    /// Client for an explicit token, e.g. one held by the calling service
+    pub fn with_token(
+        sandbox: bool,
+        token: String,
+        network: &NetworkConfig,
+    ) -> Result<Self, ZenodoError> {
+        let client = http::async_client(network, None)?;
+        Ok(ZenodoClient {
+            client,
+            api: Api::new(sandbox),
+            token,
+            network: network.clone(),
+        })
+    }
+
+    /// Send `call`, retrying as the network settings allow, and read the answer
+    async fn execute(&self, call: &Call) -> Result<Answer, ZenodoError> {
+        // Read once and shared, not copied, by each attempt
+        let upload = match &call.upload {
+            Some(path) => Some(Bytes::from(
+                tokio::fs::read(path)
+                    .await
+                    .map_err(IoError::on("read", path))?,
+            )),
+            None => None,
+        };
+        let resp = http::send_with_retry_async(&self.network, || {
+            let request = self
+                .client
+                .request(call.method.clone(), &call.url)
+                .bearer_auth(&self.token)
+                .query(&call.query);
+            match (&call.json, &upload) {
+                (Some(json), _) => request.json(json),
+                (None, Some(data)) => request
+                    .header("Content-Type", "application/octet-stream")
+                    .body(data.clone()),
+                (None, None) => request,
+            }
+        })
+        .await
+        .map_err(http_error(&call.context))?;
+        let status = resp.status();
+        let body = resp.bytes().await.map_err(http_error(&call.context))?;
+        Ok(Answer { status, body })
+    }
+
+    /// Check that the token is accepted by making a cheap authenticated request
+    pub async fn verify_token(&self) -> Result<(), ZenodoError> {
+        let call = self.api.verify_token();
+        api::token_accepted(&call, &self.execute(&call).await?, self.api.sandbox())
+    }
+
+    /// Check the token's scopes as the blocking client does
+    pub async fn check_scopes(&self, need_actions: bool) -> Result<(), ZenodoError> {
+        let scopes = self
+            .execute(&self.api.token_info())
+            .await
+            .and_then(api::token_scopes);
+        match scopes {
+            Ok(Some(scopes)) => has_scopes(&scopes, need_actions, self.api.sandbox()),
+            Ok(None) => match self.verify_token().await {
+                Ok(()) => Ok(()),
+                Err(e @ ZenodoError::MissingScope { .. }) => Err(e),
+                Err(e) => Err(ZenodoError::TokenCheck(Box::new(e))),
+            },
+            Err(e) => Err(ZenodoError::TokenCheck(Box::new(e))),
+        }
+    }
+
+    /// Create a draft titled and versioned as given, so that a later run can
+    /// find it among the drafts
+    pub async fn create_deposition(
+        &self,
+        title: &str,
+        version: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let metadata = api::draft_metadata(title, version, false);
+        self.create(metadata, "creating deposition").await
+    }
+
+    /// Create a draft with a DOI reserved for it
+    pub async fn reserve_doi(
+        &self,
+        title: &str,
+        version: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let metadata = api::draft_metadata(title, version, true);
+        self.create(metadata, "reserving a DOI").await
+    }
+
+    /// Start the next version of the record whose `conceptrecid` is
+    /// `concept`, as the blocking client does
+    pub async fn new_version(
+        &self,
+        concept: &str,
+        title: &str,
+        version: &str,
+        reserve_doi: bool,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let call = self.api.latest_record(concept);
+        let latest = api::record_id(&call, self.execute(&call).await?)?;
+        let parent = self.action(latest, "newversion").await?;
+        let draft_id = api::latest_draft(&parent, latest)?;
+
+        let call = self.api.list_files(draft_id);
+        let files: Vec<DepositionFile> =
+            api::read(&call, self.execute(&call).await?, "files response")?;
+        for file in files {
+            let call = self.api.delete_file(draft_id, &file.id);
+            api::done(&call, &self.execute(&call).await?)?;
+        }
+
+        let draft = self
+            .get_deposition(draft_id)
+            .await?
+            .ok_or(ZenodoError::NewVersion { record: latest })?;
+        let body = api::new_version_metadata(draft, title, version, reserve_doi);
+        self.put_metadata(draft_id, body, "titling the new version")
+            .await
+    }
+
+    /// POST a new deposition; as in the blocking client, a create whose
+    /// answer was lost is looked for among the drafts before it is sent again
+    async fn create(
+        &self,
+        metadata: serde_json::Value,
+        context: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let title = metadata["title"].as_str().unwrap_or_default();
+        let version = metadata["version"].as_str().unwrap_or_default();
+        let call = self.api.create(metadata.clone(), context);
+        let mut attempt = 0;
+        loop {
+            let result = match self.execute(&call).await {
+                Ok(answer) => api::read(&call, answer, "deposition response"),
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) if e.outcome_unknown() && attempt < self.network.retries => {
+                    attempt += 1;
+                    tokio::time::sleep(http::lost_answer_delay(&self.network, attempt)).await;
+                    let depositions = self.list_depositions().await?;
+                    if let Some(draft) = find_release(&depositions, title, version) {
+                        if let Some(draft) = self.get_deposition(draft.id).await? {
+                            return Ok(draft);
+                        }
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetch a deposition by ID; `None` if it no longer exists
+    pub async fn get_deposition(
+        &self,
+        deposition_id: u64,
+    ) -> Result<Option<DepositionResponse>, ZenodoError> {
+        let call = self.api.get_deposition(deposition_id);
+        api::found(&call, self.execute(&call).await?)
+    }
+
+    /// Delete an unpublished deposition (Zenodo refuses for published ones)
+    pub async fn delete_deposition(&self, deposition_id: u64) -> Result<(), ZenodoError> {
+        let call = self.api.delete_deposition(deposition_id);
+        api::done(&call, &self.execute(&call).await?)
+    }
+
+    /// List all of the authenticated user's depositions, most recent first
+    pub async fn list_depositions(&self) -> Result<Vec<DepositionResponse>, ZenodoError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let call = self.api.list_depositions(page);
+            let batch: Vec<DepositionResponse> =
+                api::read(&call, self.execute(&call).await?, "depositions response")?;
+            let last = batch.len() < api::PAGE_SIZE;
+            all.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    /// Upload a file to a deposition's bucket
+    pub async fn upload_file(
+        &self,
+        bucket_url: &str,
+        file_path: &Path,
+        filename: &str,
+    ) -> Result<FileResponse, ZenodoError> {
+        let call = self.api.upload_file(bucket_url, file_path, filename);
+        api::read(&call, self.execute(&call).await?, "upload response")
+    }
+
+    /// Update deposition metadata
+    pub async fn update_metadata(
+        &self,
+        deposition_id: u64,
+        deposit: &ZenodoDeposit,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        self.put_metadata(
+            deposition_id,
+            serde_json::json!(deposit),
+            "updating metadata",
+        )
+        .await
+    }
+
+    async fn put_metadata(
+        &self,
+        deposition_id: u64,
+        body: serde_json::Value,
+        context: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let call = self.api.put_metadata(deposition_id, body, context);
+        api::read(&call, self.execute(&call).await?, "metadata response")
+    }
+
+    /// Publish the deposition (makes it permanent!). If the answer is lost,
+    /// the deposition is looked at before publishing is asked for again.
+    pub async fn publish(&self, deposition_id: u64) -> Result<DepositionResponse, ZenodoError> {
+        let mut attempt = 0;
+        loop {
+            match self.action(deposition_id, "publish").await {
+                Err(e) if e.outcome_unknown() && attempt < self.network.retries => {
+                    attempt += 1;
+                    tokio::time::sleep(http::lost_answer_delay(&self.network, attempt)).await;
+                    if let Some(published) = self
+                        .get_deposition(deposition_id)
+                        .await?
+                        .filter(|d| d.submitted)
+                    {
+                        return Ok(published);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Let Zenodo validate metadata on a throwaway draft, which is deleted
+    /// again. Returns the `(field, message)` pairs Zenodo rejected.
+    pub async fn validate_remote(
+        &self,
+        deposit: &ZenodoDeposit,
+    ) -> Result<Vec<(String, String)>, ZenodoError> {
+        // A title no release has, and a version only this check uses
+        let check = format!("check-{}", crate::state::now_utc());
+        let draft = self
+            .create_deposition("release-scholar metadata check", &check)
+            .await?;
+        let call =
+            self.api
+                .put_metadata(draft.id, serde_json::json!(deposit), "validating metadata");
+        let result = self.execute(&call).await;
+        let cleanup = self.delete_deposition(draft.id).await;
+
+        let errors = api::validation_errors(&call, result?)?;
+        cleanup.map_err(|e| ZenodoError::DraftLeftBehind {
+            draft: draft.id,
+            source: Box::new(e),
+        })?;
+        Ok(errors)
+    }
+
+    /// Unlock a published deposition so its metadata can be changed
+    pub async fn edit(&self, deposition_id: u64) -> Result<DepositionResponse, ZenodoError> {
+        self.action(deposition_id, "edit").await
+    }
+
+    /// Leave edit mode without saving changes
+    pub async fn discard_edit(
+        &self,
+        deposition_id: u64,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        self.action(deposition_id, "discard").await
+    }
+
+    async fn action(
+        &self,
+        deposition_id: u64,
+        action: &str,
+    ) -> Result<DepositionResponse, ZenodoError> {
+        let call = self.api.action(deposition_id, action);
+        api::read(
+            &call,
+            self.execute(&call).await?,
+            &format!("{} response", action),
+        )
+    }
+
+    pub fn base_web_url(&self) -> &str {
+        self.api.web_url()
+    }
+}